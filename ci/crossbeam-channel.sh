@@ -9,8 +9,13 @@ fi
 
 cargo check --bins --examples --tests
 cargo test -- --test-threads=1
+cargo test --features metrics -- --test-threads=1
+cargo test --features debug -- --test-threads=1
+cargo test --features deadlock-detection -- --test-threads=1
 
 if [[ "$TRAVIS_RUST_VERSION" == "nightly" ]]; then
+    cargo test --features async -- --test-threads=1
+
     cd benchmarks
     cargo check --bins
 fi
@@ -0,0 +1,550 @@
+//! Adapters that transform messages as they are sent or received.
+//!
+//! Adapters wrap a sender or a receiver and apply a function on every operation, so they don't
+//! need a forwarding thread and can still participate in select.
+
+use std::fmt;
+use std::ptr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use channel::{self, Receiver, Sender};
+use context::Context;
+use err::{RecvError, RecvTimeoutError, TryRecvError};
+use err::{SendError, SendTimeoutError, TrySendError};
+use select::{Operation, RecvHandle, SelectHandle, SendHandle, Token};
+
+/// Creates a receiver that converts messages from `receiver` with `f`.
+pub fn map<T, U, F>(receiver: Receiver<T>, f: F) -> MappedReceiver<T, U>
+where
+    F: Fn(T) -> U + Send + Sync + 'static,
+{
+    MappedReceiver {
+        receiver,
+        f: Arc::new(f),
+    }
+}
+
+/// Creates a receiver that drops messages from `receiver` not satisfying `pred`.
+pub fn filter<T, F>(receiver: Receiver<T>, pred: F) -> FilteredReceiver<T>
+where
+    F: Fn(&T) -> bool + Send + Sync + 'static,
+{
+    FilteredReceiver {
+        receiver,
+        pred: Arc::new(pred),
+    }
+}
+
+/// Creates a sender that converts messages with `f` before sending them into `sender`.
+pub fn contramap<T, U, F>(sender: Sender<U>, f: F) -> ContramappedSender<T, U>
+where
+    F: Fn(T) -> U + Send + Sync + 'static,
+{
+    ContramappedSender {
+        sender,
+        f: Arc::new(f),
+    }
+}
+
+/// A receiver that converts messages with a function as they are received.
+///
+/// Created by [`Receiver::map`].
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use crossbeam_channel::unbounded;
+///
+/// let (s1, r1) = unbounded::<String>();
+/// let (_s2, r2) = unbounded::<i32>();
+///
+/// let r1 = r1.map(|s| s.parse::<i32>().unwrap());
+/// s1.send("10".to_string()).unwrap();
+///
+/// select! {
+///     recv_from(r1) -> msg => assert_eq!(msg, Ok(10)),
+///     recv(r2) -> _ => panic!(),
+/// }
+/// # }
+/// ```
+///
+/// [`Receiver::map`]: struct.Receiver.html#method.map
+pub struct MappedReceiver<T, U> {
+    /// The underlying receiver.
+    receiver: Receiver<T>,
+
+    /// The function applied to received messages.
+    f: Arc<Fn(T) -> U + Send + Sync>,
+}
+
+impl<T, U> MappedReceiver<T, U> {
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// See [`Receiver::try_recv`] for details.
+    ///
+    /// [`Receiver::try_recv`]: struct.Receiver.html#method.try_recv
+    pub fn try_recv(&self) -> Result<U, TryRecvError> {
+        self.receiver.try_recv().map(&*self.f)
+    }
+
+    /// Blocks the current thread until a message is received or the channel is empty and
+    /// disconnected.
+    ///
+    /// See [`Receiver::recv`] for details.
+    ///
+    /// [`Receiver::recv`]: struct.Receiver.html#method.recv
+    pub fn recv(&self) -> Result<U, RecvError> {
+        self.receiver.recv().map(&*self.f)
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    ///
+    /// See [`Receiver::recv_timeout`] for details.
+    ///
+    /// [`Receiver::recv_timeout`]: struct.Receiver.html#method.recv_timeout
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<U, RecvTimeoutError> {
+        self.recv_deadline(Instant::now() + timeout)
+    }
+
+    /// Waits for a message to be received from the channel, but only until a given deadline.
+    ///
+    /// See [`Receiver::recv_deadline`] for details.
+    ///
+    /// [`Receiver::recv_deadline`]: struct.Receiver.html#method.recv_deadline
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<U, RecvTimeoutError> {
+        self.receiver.recv_deadline(deadline).map(&*self.f)
+    }
+
+    /// Returns a reference to the underlying receiver.
+    pub fn get_ref(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+
+    /// Returns the underlying receiver.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// let r = r.map(|n: i32| n * 2);
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(r.into_inner().recv(), Ok(1));
+    /// ```
+    pub fn into_inner(self) -> Receiver<T> {
+        self.receiver
+    }
+}
+
+impl<T, U> Clone for MappedReceiver<T, U> {
+    fn clone(&self) -> Self {
+        MappedReceiver {
+            receiver: self.receiver.clone(),
+            f: self.f.clone(),
+        }
+    }
+}
+
+impl<T, U> fmt::Debug for MappedReceiver<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("MappedReceiver { .. }")
+    }
+}
+
+impl<T, U> SelectHandle for MappedReceiver<T, U> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.receiver.try_select(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.receiver.deadline()
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.receiver.register(oper, cx)
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.receiver.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        self.receiver.accept(token, cx)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.receiver.is_ready()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.receiver.watch(oper, cx)
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.receiver.unwatch(oper);
+    }
+
+    #[cfg(feature = "metrics")]
+    fn waited(&self, dur: Duration) {
+        self.receiver.waited(dur);
+    }
+}
+
+impl<T, U> RecvHandle for MappedReceiver<T, U> {
+    type Msg = U;
+
+    unsafe fn read(&self, token: &mut Token) -> Result<U, ()> {
+        channel::read(&self.receiver, token).map(&*self.f)
+    }
+}
+
+/// A receiver that drops messages not satisfying a predicate.
+///
+/// Created by [`Receiver::filter`]. Messages are tested as they are received, and those that
+/// don't satisfy the predicate are dropped.
+///
+/// When used in [`Select`], the operation may be reported as ready by [`ready`] even though all
+/// messages in the channel are going to be dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::time::Duration;
+/// use crossbeam_channel::unbounded;
+///
+/// let (s, r) = unbounded();
+/// let r = r.filter(|n: &i32| *n > 0);
+///
+/// s.send(-1).unwrap();
+/// s.send(1).unwrap();
+///
+/// select! {
+///     recv_from(r) -> msg => assert_eq!(msg, Ok(1)),
+///     default(Duration::from_secs(1)) => panic!(),
+/// }
+/// # }
+/// ```
+///
+/// [`Receiver::filter`]: struct.Receiver.html#method.filter
+/// [`Select`]: struct.Select.html
+/// [`ready`]: struct.Select.html#method.ready
+pub struct FilteredReceiver<T> {
+    /// The underlying receiver.
+    receiver: Receiver<T>,
+
+    /// The predicate received messages are tested with.
+    pred: Arc<Fn(&T) -> bool + Send + Sync>,
+}
+
+impl<T> FilteredReceiver<T> {
+    /// Attempts to receive a message satisfying the predicate without blocking.
+    ///
+    /// Messages that don't satisfy the predicate are dropped until the channel becomes empty.
+    ///
+    /// See [`Receiver::try_recv`] for details.
+    ///
+    /// [`Receiver::try_recv`]: struct.Receiver.html#method.try_recv
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        loop {
+            let msg = self.receiver.try_recv()?;
+            if (self.pred)(&msg) {
+                return Ok(msg);
+            }
+        }
+    }
+
+    /// Blocks the current thread until a message satisfying the predicate is received or the
+    /// channel is empty and disconnected.
+    ///
+    /// See [`Receiver::recv`] for details.
+    ///
+    /// [`Receiver::recv`]: struct.Receiver.html#method.recv
+    pub fn recv(&self) -> Result<T, RecvError> {
+        loop {
+            let msg = self.receiver.recv()?;
+            if (self.pred)(&msg) {
+                return Ok(msg);
+            }
+        }
+    }
+
+    /// Waits for a message satisfying the predicate to be received from the channel, but only
+    /// for a limited time.
+    ///
+    /// See [`Receiver::recv_timeout`] for details.
+    ///
+    /// [`Receiver::recv_timeout`]: struct.Receiver.html#method.recv_timeout
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_deadline(Instant::now() + timeout)
+    }
+
+    /// Waits for a message satisfying the predicate to be received from the channel, but only
+    /// until a given deadline.
+    ///
+    /// See [`Receiver::recv_deadline`] for details.
+    ///
+    /// [`Receiver::recv_deadline`]: struct.Receiver.html#method.recv_deadline
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        loop {
+            let msg = self.receiver.recv_deadline(deadline)?;
+            if (self.pred)(&msg) {
+                return Ok(msg);
+            }
+        }
+    }
+
+    /// Returns a reference to the underlying receiver.
+    pub fn get_ref(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+
+    /// Returns the underlying receiver.
+    pub fn into_inner(self) -> Receiver<T> {
+        self.receiver
+    }
+
+    /// Reads the message an operation on the underlying receiver was selected for.
+    ///
+    /// Returns `true` and stores the message in the token if it satisfies the predicate or the
+    /// channel is disconnected.
+    unsafe fn complete(&self, token: &mut Token) -> bool {
+        match channel::read(&self.receiver, token) {
+            Ok(msg) => {
+                if (self.pred)(&msg) {
+                    token.boxed.msg = Box::into_raw(Box::new(msg)) as *mut u8;
+                    true
+                } else {
+                    false
+                }
+            }
+            Err(()) => {
+                token.boxed.msg = ptr::null_mut();
+                true
+            }
+        }
+    }
+}
+
+impl<T> Clone for FilteredReceiver<T> {
+    fn clone(&self) -> Self {
+        FilteredReceiver {
+            receiver: self.receiver.clone(),
+            pred: self.pred.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for FilteredReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("FilteredReceiver { .. }")
+    }
+}
+
+impl<T> SelectHandle for FilteredReceiver<T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        // Drop messages until one satisfies the predicate or the channel becomes empty.
+        while self.receiver.try_select(token) {
+            if unsafe { self.complete(token) } {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.receiver.deadline()
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.receiver.register(oper, cx)
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.receiver.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        self.receiver.accept(token, cx) && unsafe { self.complete(token) }
+    }
+
+    fn is_ready(&self) -> bool {
+        self.receiver.is_ready()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.receiver.watch(oper, cx)
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.receiver.unwatch(oper);
+    }
+
+    #[cfg(feature = "metrics")]
+    fn waited(&self, dur: Duration) {
+        self.receiver.waited(dur);
+    }
+}
+
+impl<T> RecvHandle for FilteredReceiver<T> {
+    type Msg = T;
+
+    unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if token.boxed.msg.is_null() {
+            Err(())
+        } else {
+            Ok(*Box::from_raw(token.boxed.msg as *mut T))
+        }
+    }
+}
+
+/// A sender that converts messages with a function before sending them.
+///
+/// Created by [`Sender::contramap`]. If a message cannot be sent, the error contains the
+/// converted message.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use crossbeam_channel::bounded;
+///
+/// let (s, r) = bounded::<String>(1);
+/// let s = s.contramap(|n: i32| n.to_string());
+///
+/// select! {
+///     send_to(s, 7) -> res => assert_eq!(res, Ok(())),
+/// }
+/// assert_eq!(r.recv(), Ok("7".to_string()));
+/// # }
+/// ```
+///
+/// [`Sender::contramap`]: struct.Sender.html#method.contramap
+pub struct ContramappedSender<T, U> {
+    /// The underlying sender.
+    sender: Sender<U>,
+
+    /// The function applied to sent messages.
+    f: Arc<Fn(T) -> U + Send + Sync>,
+}
+
+impl<T, U> ContramappedSender<T, U> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// See [`Sender::try_send`] for details.
+    ///
+    /// [`Sender::try_send`]: struct.Sender.html#method.try_send
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<U>> {
+        self.sender.try_send((self.f)(msg))
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// See [`Sender::send`] for details.
+    ///
+    /// [`Sender::send`]: struct.Sender.html#method.send
+    pub fn send(&self, msg: T) -> Result<(), SendError<U>> {
+        self.sender.send((self.f)(msg))
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    ///
+    /// See [`Sender::send_timeout`] for details.
+    ///
+    /// [`Sender::send_timeout`]: struct.Sender.html#method.send_timeout
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<U>> {
+        self.send_deadline(msg, Instant::now() + timeout)
+    }
+
+    /// Waits for a message to be sent into the channel, but only until a given deadline.
+    ///
+    /// See [`Sender::send_deadline`] for details.
+    ///
+    /// [`Sender::send_deadline`]: struct.Sender.html#method.send_deadline
+    pub fn send_deadline(&self, msg: T, deadline: Instant) -> Result<(), SendTimeoutError<U>> {
+        self.sender.send_deadline((self.f)(msg), deadline)
+    }
+
+    /// Returns a reference to the underlying sender.
+    pub fn get_ref(&self) -> &Sender<U> {
+        &self.sender
+    }
+
+    /// Returns the underlying sender.
+    pub fn into_inner(self) -> Sender<U> {
+        self.sender
+    }
+}
+
+impl<T, U> Clone for ContramappedSender<T, U> {
+    fn clone(&self) -> Self {
+        ContramappedSender {
+            sender: self.sender.clone(),
+            f: self.f.clone(),
+        }
+    }
+}
+
+impl<T, U> fmt::Debug for ContramappedSender<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ContramappedSender { .. }")
+    }
+}
+
+impl<T, U> SelectHandle for ContramappedSender<T, U> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.sender.try_select(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.sender.deadline()
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.sender.register(oper, cx)
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.sender.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        self.sender.accept(token, cx)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.sender.is_ready()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.sender.watch(oper, cx)
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.sender.unwatch(oper);
+    }
+
+    #[cfg(feature = "metrics")]
+    fn waited(&self, dur: Duration) {
+        self.sender.waited(dur);
+    }
+}
+
+impl<T, U> SendHandle for ContramappedSender<T, U> {
+    type Msg = T;
+    type Sent = U;
+
+    unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), U> {
+        channel::write(&self.sender, token, (self.f)(msg))
+    }
+}
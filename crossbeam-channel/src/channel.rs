@@ -1,17 +1,36 @@
 //! The channel interface.
 
+use std::collections::VecDeque;
 use std::fmt;
+#[cfg(feature = "async")]
+use std::future::Future;
 use std::iter::FusedIterator;
-use std::mem;
+use std::ops::Deref;
 use std::panic::{RefUnwindSafe, UnwindSafe};
-use std::sync::Arc;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::ptr;
+use std::sync::{Arc, Weak};
+#[cfg(feature = "async")]
+use std::task::{self, Poll};
 use std::time::{Duration, Instant};
 
+#[cfg(feature = "async")]
+use futures_core::Stream;
+
+use adapter::{self, ContramappedSender, FilteredReceiver, MappedReceiver};
+use clock::Clock;
 use context::Context;
 use counter;
-use err::{RecvError, RecvTimeoutError, SendError, SendTimeoutError, TryRecvError, TrySendError};
+use err::{
+    BroadcastRecvError, RecvError, RecvTimeoutError, SendError, SendTimeoutError, TryRecvError,
+    TrySendError,
+};
 use flavors;
-use select::{Operation, SelectHandle, Token};
+use flavors::tick::MissedTicks;
+#[cfg(feature = "metrics")]
+use metrics::Stats;
+use select::{Operation, RecvHandle, Select, SelectHandle, SendHandle, Token};
 
 /// Creates a channel of unbounded capacity.
 ///
@@ -41,7 +60,39 @@ use select::{Operation, SelectHandle, Token};
 /// println!("{}", r.recv().unwrap());
 /// ```
 pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
-    let (s, r) = counter::new(flavors::list::Channel::new());
+    new_unbounded(None)
+}
+
+/// Creates a channel of unbounded capacity, just like [`unbounded`], and gives it a name.
+///
+/// The name is only kept with the `debug` feature enabled, in which case it is returned by
+/// [`Sender::name`] and [`Receiver::name`], and shows up in the [`Debug`] output of senders and
+/// receivers and in the list of live channels returned by [`live_channels`]. Without the feature,
+/// the name is dropped and the channel is just like one created by [`unbounded`].
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::unbounded_named;
+///
+/// let (s, r) = unbounded_named::<i32>("events");
+/// s.send(1).unwrap();
+/// assert_eq!(r.recv(), Ok(1));
+/// ```
+///
+/// [`Sender::name`]: struct.Sender.html#method.name
+/// [`Receiver::name`]: struct.Receiver.html#method.name
+///
+/// [`unbounded`]: fn.unbounded.html
+/// [`Debug`]: https://doc.rust-lang.org/std/fmt/trait.Debug.html
+/// [`live_channels`]: fn.live_channels.html
+pub fn unbounded_named<T>(name: &str) -> (Sender<T>, Receiver<T>) {
+    new_unbounded(Some(name.to_string()))
+}
+
+/// Creates a channel of unbounded capacity named `name`.
+fn new_unbounded<T>(name: Option<String>) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::list::Channel::new(), name);
     let s = Sender {
         flavor: SenderFlavor::List(s),
     };
@@ -102,8 +153,40 @@ pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
 /// assert_eq!(r.recv(), Ok(1));
 /// ```
 pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    new_bounded(cap, None)
+}
+
+/// Creates a channel of bounded capacity, just like [`bounded`], and gives it a name.
+///
+/// The name is only kept with the `debug` feature enabled, in which case it is returned by
+/// [`Sender::name`] and [`Receiver::name`], and shows up in the [`Debug`] output of senders and
+/// receivers and in the list of live channels returned by [`live_channels`]. Without the feature,
+/// the name is dropped and the channel is just like one created by [`bounded`].
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::bounded_named;
+///
+/// let (s, r) = bounded_named::<i32>("jobs", 64);
+/// assert_eq!(s.capacity(), Some(64));
+/// assert_eq!(r.capacity(), Some(64));
+/// ```
+///
+/// [`Sender::name`]: struct.Sender.html#method.name
+/// [`Receiver::name`]: struct.Receiver.html#method.name
+///
+/// [`bounded`]: fn.bounded.html
+/// [`Debug`]: https://doc.rust-lang.org/std/fmt/trait.Debug.html
+/// [`live_channels`]: fn.live_channels.html
+pub fn bounded_named<T>(name: &str, cap: usize) -> (Sender<T>, Receiver<T>) {
+    new_bounded(cap, Some(name.to_string()))
+}
+
+/// Creates a channel of bounded capacity named `name`.
+fn new_bounded<T>(cap: usize, name: Option<String>) -> (Sender<T>, Receiver<T>) {
     if cap == 0 {
-        let (s, r) = counter::new(flavors::zero::Channel::new());
+        let (s, r) = counter::new(flavors::zero::Channel::new(), name);
         let s = Sender {
             flavor: SenderFlavor::Zero(s),
         };
@@ -112,7 +195,7 @@ pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
         };
         (s, r)
     } else {
-        let (s, r) = counter::new(flavors::array::Channel::with_capacity(cap));
+        let (s, r) = counter::new(flavors::array::Channel::with_capacity(cap), name);
         let s = Sender {
             flavor: SenderFlavor::Array(s),
         };
@@ -123,6 +206,244 @@ pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
     }
 }
 
+/// Creates a channel of bounded capacity that delivers every message to every receiver.
+///
+/// Each receiver has its own position in the channel and sees all messages sent after it was
+/// created, in the order they were sent. Cloning a receiver creates a new subscriber that starts
+/// at the same position as the original. Sending never blocks: if the channel is full, the
+/// oldest message is overwritten, even if some receivers haven't received it yet.
+///
+/// A receiver that falls behind by more than `cap` messages misses the overwritten ones and
+/// continues from the oldest message still in the channel. Receiving, iterating, and selecting
+/// skip over the missed messages, while [`try_recv_lagged`] reports their number with a
+/// [`BroadcastRecvError::Lagged`] error.
+///
+/// The channel is disconnected once all senders or all receivers are dropped.
+///
+/// # Panics
+///
+/// Panics if the capacity is zero.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{broadcast, BroadcastRecvError};
+///
+/// let (s, r1) = broadcast(2);
+/// let r2 = r1.clone();
+///
+/// s.send(1).unwrap();
+/// s.send(2).unwrap();
+/// assert_eq!(r1.recv(), Ok(1));
+/// assert_eq!(r2.recv(), Ok(1));
+///
+/// // The third message overwrites the first one, which `r2` has already received.
+/// s.send(3).unwrap();
+/// assert_eq!(r2.recv(), Ok(2));
+///
+/// // Now `r1` falls behind and misses the second message.
+/// s.send(4).unwrap();
+/// assert_eq!(r1.try_recv_lagged(), Err(BroadcastRecvError::Lagged(1)));
+/// assert_eq!(r1.try_recv(), Ok(3));
+///
+/// // Now `r2` falls behind too, and silently skips the third message.
+/// s.send(5).unwrap();
+/// assert_eq!(r2.recv(), Ok(4));
+/// ```
+///
+/// [`try_recv_lagged`]: struct.Receiver.html#method.try_recv_lagged
+/// [`BroadcastRecvError::Lagged`]: enum.BroadcastRecvError.html#variant.Lagged
+pub fn broadcast<T: Clone>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(
+        flavors::broadcast::Channel::with_capacity(cap, T::clone),
+        None,
+    );
+    let cursor = r.cursor();
+    let s = Sender {
+        flavor: SenderFlavor::Broadcast(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Broadcast(r, cursor),
+    };
+    (s, r)
+}
+
+/// Creates a channel that holds the latest value and notifies receivers when it changes.
+///
+/// The channel starts out holding `initial`. Each send replaces the current value, so sending
+/// never waits for receivers to catch up and a receiver only ever gets the latest value, skipping
+/// over the ones it didn't get to see. Each receiver remembers which value it has last seen:
+/// receiving or calling [`changed`] waits until a newer value is sent, while [`borrow`] looks at
+/// the current value without marking it as seen. A new receiver, including one created by cloning,
+/// has seen the value that is current at the time.
+///
+/// Senders can't replace the value while a reference returned by [`borrow`] is alive, so they
+/// wait until all such references are dropped.
+///
+/// A receiver's length is one if it hasn't seen the latest value yet and zero otherwise, while a
+/// sender's length is one until any receiver receives the latest value.
+///
+/// The channel is disconnected once all senders or all receivers are dropped. Receivers still get
+/// the last value if they haven't seen it yet.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{watch, TryRecvError};
+///
+/// let (s, r) = watch("idle");
+/// assert_eq!(*r.borrow().unwrap(), "idle");
+/// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+///
+/// s.send("starting").unwrap();
+/// s.send("running").unwrap();
+/// assert_eq!(r.recv(), Ok("running"));
+///
+/// thread::spawn(move || s.send("done").unwrap());
+///
+/// r.changed().unwrap();
+/// assert_eq!(*r.borrow().unwrap(), "done");
+/// ```
+///
+/// [`changed`]: struct.Receiver.html#method.changed
+/// [`borrow`]: struct.Receiver.html#method.borrow
+pub fn watch<T: Clone>(initial: T) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::watch::Channel::new(initial, T::clone), None);
+    let version = r.version();
+    let s = Sender {
+        flavor: SenderFlavor::Watch(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Watch(r, version),
+    };
+    (s, r)
+}
+
+/// Creates a channel of bounded capacity that delivers messages in order of their priority.
+///
+/// Messages sent with [`send_with_priority`] or its non-blocking and timed variants are received
+/// highest priority first. Messages of equal priority are received in the order they were sent.
+/// Other sending methods, including sends in [`Select`], use the lowest priority, zero.
+///
+/// Apart from the order of delivery, this channel behaves like one created with [`bounded`]: if
+/// it is full, send operations block until there is room for another message.
+///
+/// # Panics
+///
+/// Panics if the capacity is zero.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::priority_bounded;
+///
+/// let (s, r) = priority_bounded(10);
+///
+/// s.send_with_priority("bulk 1", 0).unwrap();
+/// s.send_with_priority("bulk 2", 0).unwrap();
+/// s.send_with_priority("control", 1).unwrap();
+///
+/// assert_eq!(r.recv(), Ok("control"));
+/// assert_eq!(r.recv(), Ok("bulk 1"));
+/// assert_eq!(r.recv(), Ok("bulk 2"));
+/// ```
+///
+/// [`send_with_priority`]: struct.Sender.html#method.send_with_priority
+/// [`Select`]: struct.Select.html
+/// [`bounded`]: fn.bounded.html
+pub fn priority_bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::priority::Channel::with_capacity(Some(cap)), None);
+    let s = Sender {
+        flavor: SenderFlavor::Priority(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Priority(r),
+    };
+    (s, r)
+}
+
+/// Creates a channel of unbounded capacity that delivers messages in order of their priority.
+///
+/// Messages sent with [`send_with_priority`] or [`try_send_with_priority`] are received highest
+/// priority first. Messages of equal priority are received in the order they were sent. Other
+/// sending methods, including sends in [`Select`], use the lowest priority, zero.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::priority_unbounded;
+///
+/// let (s, r) = priority_unbounded();
+///
+/// for i in 0..5 {
+///     s.send_with_priority(i, i % 2).unwrap();
+/// }
+///
+/// let v: Vec<_> = r.try_iter().collect();
+/// assert_eq!(v, [1, 3, 0, 2, 4]);
+/// ```
+///
+/// [`send_with_priority`]: struct.Sender.html#method.send_with_priority
+/// [`try_send_with_priority`]: struct.Sender.html#method.try_send_with_priority
+/// [`Select`]: struct.Select.html
+pub fn priority_unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::priority::Channel::with_capacity(None), None);
+    let s = Sender {
+        flavor: SenderFlavor::Priority(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Priority(r),
+    };
+    (s, r)
+}
+
+/// Creates a channel that delivers a single message.
+///
+/// The sender is consumed by sending the message, so the channel becomes disconnected as soon as
+/// the message is sent. The receiver side works like any other [`Receiver`], including in
+/// [`Select`], and reports disconnection once the message has been received or if the sender is
+/// dropped without sending.
+///
+/// This channel is meant for handing back a single result, like a response to a request. It
+/// takes much less memory than a channel created with [`bounded`] of capacity 1.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{oneshot, RecvError};
+///
+/// let (s, r) = oneshot();
+///
+/// thread::spawn(move || s.send(42).unwrap());
+///
+/// assert_eq!(r.recv(), Ok(42));
+/// assert_eq!(r.recv(), Err(RecvError));
+/// ```
+///
+/// Dropping the sender without sending disconnects the channel:
+///
+/// ```
+/// use crossbeam_channel::{oneshot, RecvError};
+///
+/// let (s, r) = oneshot::<i32>();
+/// drop(s);
+/// assert_eq!(r.recv(), Err(RecvError));
+/// ```
+///
+/// [`Receiver`]: struct.Receiver.html
+/// [`Select`]: struct.Select.html
+/// [`bounded`]: fn.bounded.html
+pub fn oneshot<T>() -> (OneshotSender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::oneshot::Channel::new(), None);
+    let s = OneshotSender { chan: s };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Oneshot(r),
+    };
+    (s, r)
+}
+
 /// Creates a receiver that delivers a message after a certain duration of time.
 ///
 /// The channel is bounded with capacity of 1 and never gets disconnected. Exactly one message will
@@ -174,10 +495,146 @@ pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
 /// ```
 pub fn after(duration: Duration) -> Receiver<Instant> {
     Receiver {
-        flavor: ReceiverFlavor::After(Arc::new(flavors::after::Channel::new(duration))),
+        flavor: ReceiverFlavor::After(TimeChannel::new(Arc::new(flavors::after::Channel::new(
+            duration,
+        )))),
+    }
+}
+
+/// Creates a receiver that delivers a message after a certain duration of time on `clock`.
+///
+/// This works just like [`after`], except that time is measured by `clock`, so the message is
+/// delivered once `clock` has advanced by `duration`. The message is the time on `clock` at which
+/// it is sent.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{after_with_clock, Clock};
+///
+/// let clock = Clock::manual();
+/// let start = clock.now();
+/// let r = after_with_clock(Duration::from_secs(3600), &clock);
+///
+/// let c = clock.clone();
+/// thread::spawn(move || c.advance(Duration::from_secs(3600)));
+///
+/// // The message arrives without waiting for an hour.
+/// assert_eq!(r.recv(), Ok(start + Duration::from_secs(3600)));
+/// ```
+///
+/// [`after`]: fn.after.html
+pub fn after_with_clock(duration: Duration, clock: &Clock) -> Receiver<Instant> {
+    Receiver {
+        flavor: ReceiverFlavor::After(TimeChannel::new(Arc::new(
+            flavors::after::Channel::with_clock(duration, clock.clone()),
+        ))),
     }
 }
 
+/// Creates a timer and a receiver that gets a message when the timer expires.
+///
+/// The channel is bounded with capacity of 1 and never gets disconnected. A message is sent into
+/// the channel once `duration` elapses, and the message is the instant at which it is sent. Unlike
+/// with [`after`], the [`Timer`] can postpone the message with [`reset`] or withdraw it with
+/// [`cancel`], and can be rearmed after the message has been received.
+///
+/// Like other timed channels and operations, timers are kept in a single timer wheel shared by the
+/// whole process, which wakes up receivers when their timers expire.
+///
+/// # Examples
+///
+/// Using a timer to detect inactivity:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{timer, unbounded};
+///
+/// let (s, r) = unbounded();
+/// let (idle, timeout) = timer(Duration::from_millis(500));
+///
+/// // Send three messages and then go quiet without disconnecting.
+/// let s2 = s.clone();
+/// thread::spawn(move || {
+///     for i in 0..3 {
+///         thread::sleep(Duration::from_millis(100));
+///         s2.send(i).unwrap();
+///     }
+/// });
+///
+/// let mut received = 0;
+/// loop {
+///     select! {
+///         recv(r) -> _ => {
+///             received += 1;
+///             idle.reset(Duration::from_millis(500));
+///         }
+///         recv(timeout) -> _ => break,
+///     }
+/// }
+/// assert_eq!(received, 3);
+/// # }
+/// ```
+///
+/// [`after`]: fn.after.html
+/// [`Timer`]: struct.Timer.html
+/// [`reset`]: struct.Timer.html#method.reset
+/// [`cancel`]: struct.Timer.html#method.cancel
+pub fn timer(duration: Duration) -> (Timer, Receiver<Instant>) {
+    let chan = Arc::new(flavors::timer::Channel::new(duration));
+    let t = Timer {
+        chan: Arc::downgrade(&chan),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Timer(TimeChannel::new(chan)),
+    };
+    (t, r)
+}
+
+/// Creates a timer and a receiver that gets a message when the timer expires on `clock`.
+///
+/// This works just like [`timer`], except that time is measured by `clock`, both for the initial
+/// `duration` and for [`reset`]. The message is the time on `clock` at which it is sent.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use crossbeam_channel::{timer_with_clock, Clock, TryRecvError};
+///
+/// let clock = Clock::manual();
+/// let start = clock.now();
+/// let (t, r) = timer_with_clock(Duration::from_secs(60), &clock);
+///
+/// clock.advance(Duration::from_secs(30));
+/// t.reset(Duration::from_secs(60));
+///
+/// clock.advance(Duration::from_secs(59));
+/// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+///
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(r.try_recv(), Ok(start + Duration::from_secs(90)));
+/// ```
+///
+/// [`timer`]: fn.timer.html
+/// [`reset`]: struct.Timer.html#method.reset
+pub fn timer_with_clock(duration: Duration, clock: &Clock) -> (Timer, Receiver<Instant>) {
+    let chan = Arc::new(flavors::timer::Channel::with_clock(duration, clock.clone()));
+    let t = Timer {
+        chan: Arc::downgrade(&chan),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Timer(TimeChannel::new(chan)),
+    };
+    (t, r)
+}
+
 /// Creates a receiver that never delivers messages.
 ///
 /// The channel is bounded with capacity of 0 and never gets disconnected.
@@ -278,7 +735,137 @@ pub fn never<T>() -> Receiver<T> {
 /// ```
 pub fn tick(duration: Duration) -> Receiver<Instant> {
     Receiver {
-        flavor: ReceiverFlavor::Tick(Arc::new(flavors::tick::Channel::new(duration))),
+        flavor: ReceiverFlavor::Tick(TimeChannel::new(Arc::new(flavors::tick::Channel::new(
+            duration,
+        )))),
+    }
+}
+
+/// Creates a receiver that delivers messages periodically on `clock`.
+///
+/// This works just like [`tick`], except that time is measured by `clock`. Each message is the
+/// time on `clock` at which it is sent.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use crossbeam_channel::{tick_with_clock, Clock, TryRecvError};
+///
+/// let clock = Clock::manual();
+/// let start = clock.now();
+/// let r = tick_with_clock(Duration::from_secs(1), &clock);
+///
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(r.try_recv(), Ok(start + Duration::from_secs(1)));
+/// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+///
+/// // The next message is delivered one second after the previous one was received.
+/// clock.advance(Duration::from_millis(1500));
+/// assert_eq!(r.try_recv(), Ok(start + Duration::from_secs(2)));
+/// ```
+///
+/// [`tick`]: fn.tick.html
+pub fn tick_with_clock(duration: Duration, clock: &Clock) -> Receiver<Instant> {
+    TickBuilder::new(duration).clock(clock).build()
+}
+
+/// Configures and creates channels that deliver messages periodically.
+///
+/// A channel created by [`tick`] delivers the first message one period after it is created, and
+/// handles missed ticks according to [`MissedTicks::Delay`]. This builder can change both, as
+/// well as the [`Clock`] measuring time.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use crossbeam_channel::{Clock, MissedTicks, TickBuilder, TryRecvError};
+///
+/// let clock = Clock::manual();
+/// let start = clock.now();
+///
+/// let r = TickBuilder::new(Duration::from_secs(10))
+///     .initial_delay(Duration::from_secs(1))
+///     .missed_ticks(MissedTicks::Skip)
+///     .clock(&clock)
+///     .build();
+///
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(r.try_recv(), Ok(start + Duration::from_secs(1)));
+///
+/// // The tick at 11 seconds is received late, so the one at 21 seconds is skipped.
+/// clock.advance(Duration::from_secs(25));
+/// assert_eq!(r.try_recv(), Ok(start + Duration::from_secs(11)));
+/// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+///
+/// clock.advance(Duration::from_secs(5));
+/// assert_eq!(r.try_recv(), Ok(start + Duration::from_secs(31)));
+/// ```
+///
+/// [`tick`]: fn.tick.html
+/// [`MissedTicks::Delay`]: enum.MissedTicks.html#variant.Delay
+/// [`Clock`]: struct.Clock.html
+#[derive(Clone, Debug)]
+pub struct TickBuilder {
+    /// The time interval in which messages get delivered.
+    period: Duration,
+
+    /// The delay before the first message, or `None` to wait for one period.
+    initial_delay: Option<Duration>,
+
+    /// What to do when ticks are missed.
+    missed: MissedTicks,
+
+    /// The clock measuring time for the channel.
+    clock: Clock,
+}
+
+impl TickBuilder {
+    /// Creates a builder for a channel that delivers messages in intervals of `period`.
+    pub fn new(period: Duration) -> TickBuilder {
+        TickBuilder {
+            period,
+            initial_delay: None,
+            missed: MissedTicks::default(),
+            clock: Clock::system(),
+        }
+    }
+
+    /// Sets the delay before the first message is delivered.
+    ///
+    /// By default, the first message is delivered after one period.
+    pub fn initial_delay(mut self, delay: Duration) -> TickBuilder {
+        self.initial_delay = Some(delay);
+        self
+    }
+
+    /// Sets what to do when ticks are missed.
+    ///
+    /// The default is [`MissedTicks::Delay`].
+    ///
+    /// [`MissedTicks::Delay`]: enum.MissedTicks.html#variant.Delay
+    pub fn missed_ticks(mut self, missed: MissedTicks) -> TickBuilder {
+        self.missed = missed;
+        self
+    }
+
+    /// Sets the clock measuring time for the channel.
+    ///
+    /// The default is the system clock.
+    pub fn clock(mut self, clock: &Clock) -> TickBuilder {
+        self.clock = clock.clone();
+        self
+    }
+
+    /// Creates the channel and returns its receiver.
+    pub fn build(self) -> Receiver<Instant> {
+        let delay = self.initial_delay.unwrap_or(self.period);
+        Receiver {
+            flavor: ReceiverFlavor::Tick(TimeChannel::new(Arc::new(
+                flavors::tick::Channel::with_schedule(delay, self.period, self.missed, self.clock),
+            ))),
+        }
     }
 }
 
@@ -310,11 +897,20 @@ enum SenderFlavor<T> {
     /// Bounded channel based on a preallocated array.
     Array(counter::Sender<flavors::array::Channel<T>>),
 
+    /// Channel that delivers messages in order of their priority.
+    Priority(counter::Sender<flavors::priority::Channel<T>>),
+
     /// Unbounded channel implemented as a linked list.
     List(counter::Sender<flavors::list::Channel<T>>),
 
     /// Zero-capacity channel.
     Zero(counter::Sender<flavors::zero::Channel<T>>),
+
+    /// Broadcast channel.
+    Broadcast(counter::Sender<flavors::broadcast::Channel<T>>),
+
+    /// Channel holding the latest value.
+    Watch(counter::Sender<flavors::watch::Channel<T>>),
 }
 
 unsafe impl<T: Send> Send for Sender<T> {}
@@ -346,10 +942,15 @@ impl<T> Sender<T> {
     /// assert_eq!(s.try_send(3), Err(TrySendError::Disconnected(3)));
     /// ```
     pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.participate();
+
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.try_send(msg),
+            SenderFlavor::Priority(chan) => chan.try_send(msg, 0),
             SenderFlavor::List(chan) => chan.try_send(msg),
             SenderFlavor::Zero(chan) => chan.try_send(msg),
+            SenderFlavor::Broadcast(chan) => chan.try_send(msg),
+            SenderFlavor::Watch(chan) => chan.try_send(msg),
         }
     }
 
@@ -382,10 +983,15 @@ impl<T> Sender<T> {
     /// assert_eq!(s.send(3), Err(SendError(3)));
     /// ```
     pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.participate();
+
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.send(msg, None),
+            SenderFlavor::Priority(chan) => chan.send(msg, 0, None),
             SenderFlavor::List(chan) => chan.send(msg, None),
             SenderFlavor::Zero(chan) => chan.send(msg, None),
+            SenderFlavor::Broadcast(chan) => chan.send(msg, None),
+            SenderFlavor::Watch(chan) => chan.send(msg, None),
         }
         .map_err(|err| match err {
             SendTimeoutError::Disconnected(msg) => SendError(msg),
@@ -431,15 +1037,467 @@ impl<T> Sender<T> {
     /// );
     /// ```
     pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
-        let deadline = Instant::now() + timeout;
+        self.send_deadline(msg, Instant::now() + timeout)
+    }
 
-        match &self.flavor {
-            SenderFlavor::Array(chan) => chan.send(msg, Some(deadline)),
-            SenderFlavor::List(chan) => chan.send(msg, Some(deadline)),
-            SenderFlavor::Zero(chan) => chan.send(msg, Some(deadline)),
+    /// Waits for a message to be sent into the channel, but only until a given deadline.
+    ///
+    /// If the channel is full and not disconnected, this call will block until the send operation
+    /// can proceed or the deadline is reached. If the channel becomes disconnected, this call will
+    /// wake up and return an error. The returned error contains the original message.
+    ///
+    /// Unlike [`send_timeout`], the same deadline can be passed to repeated calls, for example
+    /// when retrying in a loop, without extending the total waiting time.
+    ///
+    /// If called on a zero-capacity channel, this method will wait for a receive operation to
+    /// appear on the other side of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{bounded, SendTimeoutError};
+    ///
+    /// let (s, r) = bounded(0);
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     assert_eq!(r.recv(), Ok(2));
+    ///     drop(r);
+    /// });
+    ///
+    /// let now = Instant::now();
+    ///
+    /// assert_eq!(
+    ///     s.send_deadline(1, now + Duration::from_millis(500)),
+    ///     Err(SendTimeoutError::Timeout(1)),
+    /// );
+    /// assert_eq!(
+    ///     s.send_deadline(2, now + Duration::from_millis(1500)),
+    ///     Ok(()),
+    /// );
+    /// assert_eq!(
+    ///     s.send_deadline(3, now + Duration::from_millis(2000)),
+    ///     Err(SendTimeoutError::Disconnected(3)),
+    /// );
+    /// ```
+    ///
+    /// [`send_timeout`]: struct.Sender.html#method.send_timeout
+    pub fn send_deadline(&self, msg: T, deadline: Instant) -> Result<(), SendTimeoutError<T>> {
+        self.participate();
+
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Priority(chan) => chan.send(msg, 0, Some(deadline)),
+            SenderFlavor::List(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Zero(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Broadcast(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Watch(chan) => chan.send(msg, Some(deadline)),
+        }
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time measured by
+    /// `clock`.
+    ///
+    /// This works just like [`send_timeout`], except that the operation times out once `clock`
+    /// has advanced by `timeout`. If the operation can proceed when it times out, the message is
+    /// sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{bounded, Clock, SendTimeoutError};
+    ///
+    /// let clock = Clock::manual();
+    /// let (s, r) = bounded(1);
+    /// s.send(1).unwrap();
+    ///
+    /// // Advance the clock once the operation has started waiting.
+    /// let c = clock.clone();
+    /// let handle = thread::spawn(move || {
+    ///     c.wait_for_waiters(1);
+    ///     c.advance(Duration::from_secs(60));
+    /// });
+    ///
+    /// assert_eq!(
+    ///     s.send_timeout_with_clock(2, Duration::from_secs(60), &clock),
+    ///     Err(SendTimeoutError::Timeout(2)),
+    /// );
+    /// assert_eq!(r.len(), 1);
+    /// handle.join().unwrap();
+    /// ```
+    ///
+    /// [`send_timeout`]: struct.Sender.html#method.send_timeout
+    pub fn send_timeout_with_clock(
+        &self,
+        msg: T,
+        timeout: Duration,
+        clock: &Clock,
+    ) -> Result<(), SendTimeoutError<T>> {
+        if !clock.is_manual() {
+            return self.send_timeout(msg, timeout);
+        }
+
+        let msg = match self.try_send(msg) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Disconnected(msg)) => {
+                return Err(SendTimeoutError::Disconnected(msg))
+            }
+            Err(TrySendError::Full(msg)) => msg,
+        };
+
+        let timer = after_with_clock(timeout, clock);
+        let mut sel = Select::new();
+        let send = sel.send(self);
+        sel.recv(&timer);
+
+        let oper = sel.select();
+        if oper.index() == send {
+            return oper
+                .send(self, msg)
+                .map_err(|SendError(msg)| SendTimeoutError::Disconnected(msg));
+        }
+        let _ = oper.recv(&timer);
+
+        // Prefer sending the message if the channel became ready at the same time.
+        match self.try_send(msg) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Disconnected(msg)) => Err(SendTimeoutError::Disconnected(msg)),
+            Err(TrySendError::Full(msg)) => Err(SendTimeoutError::Timeout(msg)),
+        }
+    }
+
+    /// Blocks the current thread until a message is sent with the given priority or the channel
+    /// is disconnected.
+    ///
+    /// In channels created with [`priority_bounded`] or [`priority_unbounded`], messages with
+    /// higher priority are received first, while messages of equal priority are received in the
+    /// order they were sent. Other channels ignore the priority, and this method behaves just
+    /// like [`send`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::priority_unbounded;
+    ///
+    /// let (s, r) = priority_unbounded();
+    ///
+    /// s.send_with_priority(1, 0).unwrap();
+    /// s.send_with_priority(2, 5).unwrap();
+    /// s.send(3).unwrap();
+    ///
+    /// assert_eq!(r.recv(), Ok(2));
+    /// assert_eq!(r.recv(), Ok(1));
+    /// assert_eq!(r.recv(), Ok(3));
+    /// ```
+    ///
+    /// [`priority_bounded`]: fn.priority_bounded.html
+    /// [`priority_unbounded`]: fn.priority_unbounded.html
+    /// [`send`]: struct.Sender.html#method.send
+    pub fn send_with_priority(&self, msg: T, priority: u32) -> Result<(), SendError<T>> {
+        self.participate();
+
+        match &self.flavor {
+            SenderFlavor::Priority(chan) => {
+                chan.send(msg, priority, None).map_err(|err| match err {
+                    SendTimeoutError::Disconnected(msg) => SendError(msg),
+                    SendTimeoutError::Timeout(_) => unreachable!(),
+                })
+            }
+            _ => self.send(msg),
+        }
+    }
+
+    /// Attempts to send a message with the given priority into the channel without blocking.
+    ///
+    /// This works just like [`try_send`], except that channels created with [`priority_bounded`]
+    /// or [`priority_unbounded`] deliver the message according to its priority, as in
+    /// [`send_with_priority`]. Other channels ignore the priority.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{priority_bounded, TrySendError};
+    ///
+    /// let (s, r) = priority_bounded(2);
+    ///
+    /// assert_eq!(s.try_send_with_priority(1, 0), Ok(()));
+    /// assert_eq!(s.try_send_with_priority(2, 5), Ok(()));
+    /// assert_eq!(s.try_send_with_priority(3, 9), Err(TrySendError::Full(3)));
+    ///
+    /// assert_eq!(r.recv(), Ok(2));
+    /// assert_eq!(r.recv(), Ok(1));
+    /// ```
+    ///
+    /// [`try_send`]: struct.Sender.html#method.try_send
+    /// [`priority_bounded`]: fn.priority_bounded.html
+    /// [`priority_unbounded`]: fn.priority_unbounded.html
+    /// [`send_with_priority`]: struct.Sender.html#method.send_with_priority
+    pub fn try_send_with_priority(&self, msg: T, priority: u32) -> Result<(), TrySendError<T>> {
+        self.participate();
+
+        match &self.flavor {
+            SenderFlavor::Priority(chan) => chan.try_send(msg, priority),
+            _ => self.try_send(msg),
+        }
+    }
+
+    /// Waits for a message to be sent with the given priority into the channel, but only for a
+    /// limited time.
+    ///
+    /// This works just like [`send_timeout`], except that channels created with
+    /// [`priority_bounded`] or [`priority_unbounded`] deliver the message according to its
+    /// priority, as in [`send_with_priority`]. Other channels ignore the priority.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{priority_bounded, SendTimeoutError};
+    ///
+    /// let (s, r) = priority_bounded(1);
+    /// let timeout = Duration::from_millis(100);
+    ///
+    /// assert_eq!(s.send_timeout_with_priority(1, 0, timeout), Ok(()));
+    /// assert_eq!(
+    ///     s.send_timeout_with_priority(2, 5, timeout),
+    ///     Err(SendTimeoutError::Timeout(2)),
+    /// );
+    ///
+    /// assert_eq!(r.recv(), Ok(1));
+    /// assert_eq!(s.send_timeout_with_priority(2, 5, timeout), Ok(()));
+    /// ```
+    ///
+    /// [`send_timeout`]: struct.Sender.html#method.send_timeout
+    /// [`priority_bounded`]: fn.priority_bounded.html
+    /// [`priority_unbounded`]: fn.priority_unbounded.html
+    /// [`send_with_priority`]: struct.Sender.html#method.send_with_priority
+    pub fn send_timeout_with_priority(
+        &self,
+        msg: T,
+        priority: u32,
+        timeout: Duration,
+    ) -> Result<(), SendTimeoutError<T>> {
+        self.send_deadline_with_priority(msg, priority, Instant::now() + timeout)
+    }
+
+    /// Waits for a message to be sent with the given priority into the channel, but only until a
+    /// given deadline.
+    ///
+    /// This works just like [`send_deadline`], except that channels created with
+    /// [`priority_bounded`] or [`priority_unbounded`] deliver the message according to its
+    /// priority, as in [`send_with_priority`]. Other channels ignore the priority.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{priority_bounded, SendTimeoutError};
+    ///
+    /// let (s, r) = priority_bounded(1);
+    /// let deadline = Instant::now() + Duration::from_millis(100);
+    ///
+    /// assert_eq!(s.send_deadline_with_priority(1, 0, deadline), Ok(()));
+    /// assert_eq!(
+    ///     s.send_deadline_with_priority(2, 5, deadline),
+    ///     Err(SendTimeoutError::Timeout(2)),
+    /// );
+    /// assert_eq!(r.recv(), Ok(1));
+    /// ```
+    ///
+    /// [`send_deadline`]: struct.Sender.html#method.send_deadline
+    /// [`priority_bounded`]: fn.priority_bounded.html
+    /// [`priority_unbounded`]: fn.priority_unbounded.html
+    /// [`send_with_priority`]: struct.Sender.html#method.send_with_priority
+    pub fn send_deadline_with_priority(
+        &self,
+        msg: T,
+        priority: u32,
+        deadline: Instant,
+    ) -> Result<(), SendTimeoutError<T>> {
+        self.participate();
+
+        match &self.flavor {
+            SenderFlavor::Priority(chan) => chan.send(msg, priority, Some(deadline)),
+            _ => self.send_deadline(msg, deadline),
+        }
+    }
+
+    /// Sends a message into the channel without blocking, evicting the oldest message if the
+    /// channel is full.
+    ///
+    /// This turns a bounded channel into a sliding window over the most recent messages: instead
+    /// of waiting for room, the message is sent right away and the oldest message in the channel
+    /// is removed and returned. If the channel is not full, `None` is returned.
+    ///
+    /// How a full channel makes room depends on its flavor:
+    ///
+    /// * Channels created with [`bounded`] evict the oldest message.
+    /// * Channels created with [`priority_bounded`] evict the oldest of the messages with the
+    ///   lowest priority. The new message is sent with priority 0.
+    /// * A zero-capacity channel cannot hold messages, so if no receive operation is waiting on
+    ///   the other side, the message is returned back inside [`TrySendError::Full`].
+    /// * Unbounded channels are never full, while broadcast and watch channels already overwrite
+    ///   their oldest messages. None of them ever evict anything.
+    ///
+    /// If the channel is disconnected, the message is returned back inside
+    /// [`TrySendError::Disconnected`].
+    ///
+    /// If the oldest message is being inspected by [`peek_with`] or [`try_recv_if`], this call
+    /// waits until the inspection is done.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, TrySendError};
+    ///
+    /// let (s, r) = bounded(2);
+    ///
+    /// assert_eq!(s.force_send(1), Ok(None));
+    /// assert_eq!(s.force_send(2), Ok(None));
+    /// assert_eq!(s.force_send(3), Ok(Some(1)));
+    ///
+    /// assert_eq!(r.recv(), Ok(2));
+    /// assert_eq!(r.recv(), Ok(3));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.force_send(4), Err(TrySendError::Disconnected(4)));
+    /// ```
+    ///
+    /// [`bounded`]: fn.bounded.html
+    /// [`priority_bounded`]: fn.priority_bounded.html
+    /// [`TrySendError::Full`]: enum.TrySendError.html#variant.Full
+    /// [`TrySendError::Disconnected`]: enum.TrySendError.html#variant.Disconnected
+    /// [`peek_with`]: struct.Receiver.html#method.peek_with
+    /// [`try_recv_if`]: struct.Receiver.html#method.try_recv_if
+    pub fn force_send(&self, msg: T) -> Result<Option<T>, TrySendError<T>> {
+        self.participate();
+
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.force_send(msg).map_err(TrySendError::Disconnected),
+            SenderFlavor::Priority(chan) => chan.force_send(msg, 0),
+            SenderFlavor::Zero(chan) => chan.try_send(msg).map(|()| None),
+            SenderFlavor::List(_) | SenderFlavor::Broadcast(_) | SenderFlavor::Watch(_) => self
+                .send(msg)
+                .map(|()| None)
+                .map_err(|SendError(msg)| TrySendError::Disconnected(msg)),
         }
     }
 
+    /// Attempts to send a batch of messages into the channel without blocking.
+    ///
+    /// Messages are sent in order, as many as the channel can accept at the moment. If not all of
+    /// them could be sent, the remaining messages are returned back inside the error. Bounded and
+    /// unbounded channels reserve room for several messages at once and wake up receivers only
+    /// once per reservation, which makes this cheaper than sending the messages one by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, TrySendError};
+    ///
+    /// let (s, r) = bounded(3);
+    ///
+    /// assert_eq!(s.try_send_batch(vec![1, 2]), Ok(()));
+    /// assert_eq!(s.try_send_batch(vec![3, 4, 5]), Err(TrySendError::Full(vec![4, 5])));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.try_send_batch(vec![6]), Err(TrySendError::Disconnected(vec![6])));
+    /// ```
+    pub fn try_send_batch<I>(&self, msgs: I) -> Result<(), TrySendError<Vec<T>>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.participate();
+
+        let mut msgs = msgs.into_iter().collect::<VecDeque<T>>();
+
+        let res = match &self.flavor {
+            SenderFlavor::Array(chan) => chan.try_send_batch(&mut msgs),
+            SenderFlavor::List(chan) => chan.try_send_batch(&mut msgs),
+            _ => {
+                // Other flavors send the messages one by one.
+                let mut res = Ok(());
+                while let Some(msg) = msgs.pop_front() {
+                    if let Err(err) = self.try_send(msg) {
+                        let (msg, err) = match err {
+                            TrySendError::Full(msg) => (msg, TrySendError::Full(())),
+                            TrySendError::Disconnected(msg) => {
+                                (msg, TrySendError::Disconnected(()))
+                            }
+                        };
+                        msgs.push_front(msg);
+                        res = Err(err);
+                        break;
+                    }
+                }
+                res
+            }
+        };
+
+        res.map_err(|err| match err {
+            TrySendError::Full(()) => TrySendError::Full(Vec::from(msgs)),
+            TrySendError::Disconnected(()) => TrySendError::Disconnected(Vec::from(msgs)),
+        })
+    }
+
+    /// Blocks the current thread until a batch of messages is sent or the channel is
+    /// disconnected.
+    ///
+    /// Messages are sent in order. While the channel is full, this method waits for room to
+    /// become available. If the channel becomes disconnected, the messages that haven't been sent
+    /// yet are returned back inside the error. Bounded and unbounded channels reserve room for
+    /// several messages at once and wake up receivers only once per reservation, which makes
+    /// this cheaper than sending the messages one by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::bounded;
+    ///
+    /// let (s, r) = bounded(2);
+    ///
+    /// thread::spawn(move || {
+    ///     assert_eq!(r.iter().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+    /// });
+    ///
+    /// assert_eq!(s.send_batch(1..6), Ok(()));
+    /// ```
+    pub fn send_batch<I>(&self, msgs: I) -> Result<(), SendError<Vec<T>>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.participate();
+
+        let mut msgs = msgs.into_iter().collect::<VecDeque<T>>();
+
+        let res = match &self.flavor {
+            SenderFlavor::Array(chan) => chan.send_batch(&mut msgs, None),
+            SenderFlavor::List(chan) => chan.send_batch(&mut msgs, None),
+            _ => {
+                // Other flavors send the messages one by one.
+                let mut res = Ok(());
+                while let Some(msg) = msgs.pop_front() {
+                    if let Err(SendError(msg)) = self.send(msg) {
+                        msgs.push_front(msg);
+                        res = Err(SendTimeoutError::Disconnected(()));
+                        break;
+                    }
+                }
+                res
+            }
+        };
+
+        res.map_err(|err| match err {
+            SendTimeoutError::Disconnected(()) => SendError(Vec::from(msgs)),
+            SendTimeoutError::Timeout(()) => unreachable!(),
+        })
+    }
+
     /// Returns `true` if the channel is empty.
     ///
     /// Note: Zero-capacity channels are always empty.
@@ -458,8 +1516,11 @@ impl<T> Sender<T> {
     pub fn is_empty(&self) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.is_empty(),
+            SenderFlavor::Priority(chan) => chan.is_empty(),
             SenderFlavor::List(chan) => chan.is_empty(),
             SenderFlavor::Zero(chan) => chan.is_empty(),
+            SenderFlavor::Broadcast(chan) => chan.is_empty(),
+            SenderFlavor::Watch(chan) => chan.is_empty(),
         }
     }
 
@@ -481,8 +1542,11 @@ impl<T> Sender<T> {
     pub fn is_full(&self) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.is_full(),
+            SenderFlavor::Priority(chan) => chan.is_full(),
             SenderFlavor::List(chan) => chan.is_full(),
             SenderFlavor::Zero(chan) => chan.is_full(),
+            SenderFlavor::Broadcast(chan) => chan.is_full(),
+            SenderFlavor::Watch(chan) => chan.is_full(),
         }
     }
 
@@ -503,8 +1567,11 @@ impl<T> Sender<T> {
     pub fn len(&self) -> usize {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.len(),
+            SenderFlavor::Priority(chan) => chan.len(),
             SenderFlavor::List(chan) => chan.len(),
             SenderFlavor::Zero(chan) => chan.len(),
+            SenderFlavor::Broadcast(chan) => chan.len(),
+            SenderFlavor::Watch(chan) => chan.len(),
         }
     }
 
@@ -527,54 +1594,399 @@ impl<T> Sender<T> {
     pub fn capacity(&self) -> Option<usize> {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.capacity(),
+            SenderFlavor::Priority(chan) => chan.capacity(),
             SenderFlavor::List(chan) => chan.capacity(),
             SenderFlavor::Zero(chan) => chan.capacity(),
+            SenderFlavor::Broadcast(chan) => chan.capacity(),
+            SenderFlavor::Watch(chan) => chan.capacity(),
         }
     }
 
-    /// Returns `true` if senders belong to the same channel.
+    /// Changes the capacity of the channel.
+    ///
+    /// Only channels created by [`bounded`] with a positive capacity can be resized. For other
+    /// channels, this method does nothing and returns `false`.
+    ///
+    /// Raising the capacity wakes up senders blocked on a full channel. Lowering it never drops
+    /// messages already in the channel; instead, new sends block until receivers bring the number
+    /// of messages below the new capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cap` is zero.
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```
+    /// use crossbeam_channel::{bounded, unbounded, TrySendError};
+    ///
+    /// let (s, r) = bounded(1);
+    /// s.send(1).unwrap();
+    /// assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    ///
+    /// assert!(s.set_capacity(2));
+    /// assert_eq!(s.capacity(), Some(2));
+    /// s.send(2).unwrap();
+    ///
+    /// assert!(s.set_capacity(1));
+    /// assert_eq!(r.len(), 2);
+    /// r.recv().unwrap();
+    /// assert_eq!(s.try_send(3), Err(TrySendError::Full(3)));
+    ///
+    /// let (s, _r) = unbounded::<i32>();
+    /// assert!(!s.set_capacity(10));
+    /// ```
+    ///
+    /// [`bounded`]: fn.bounded.html
+    pub fn set_capacity(&self, cap: usize) -> bool {
+        assert!(cap > 0, "capacity must be positive");
+
+        match &self.flavor {
+            SenderFlavor::Array(chan) => {
+                chan.set_capacity(cap);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the number of senders associated with the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
     /// use crossbeam_channel::unbounded;
     ///
-    /// let (s, _) = unbounded::<usize>();
+    /// let (s, _r) = unbounded::<i32>();
+    /// assert_eq!(s.sender_count(), 1);
     ///
     /// let s2 = s.clone();
-    /// assert!(s.same_channel(&s2));
+    /// assert_eq!(s.sender_count(), 2);
     ///
-    /// let (s3, _) = unbounded();
-    /// assert!(!s.same_channel(&s3));
+    /// drop(s2);
+    /// assert_eq!(s.sender_count(), 1);
     /// ```
-    pub fn same_channel(&self, other: &Sender<T>) -> bool {
-        match (&self.flavor, &other.flavor) {
-            (SenderFlavor::Array(ref a), SenderFlavor::Array(ref b)) => a == b,
-            (SenderFlavor::List(ref a), SenderFlavor::List(ref b)) => a == b,
-            (SenderFlavor::Zero(ref a), SenderFlavor::Zero(ref b)) => a == b,
-            _ => false,
+    pub fn sender_count(&self) -> usize {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.sender_count(),
+            SenderFlavor::Priority(chan) => chan.sender_count(),
+            SenderFlavor::List(chan) => chan.sender_count(),
+            SenderFlavor::Zero(chan) => chan.sender_count(),
+            SenderFlavor::Broadcast(chan) => chan.sender_count(),
+            SenderFlavor::Watch(chan) => chan.sender_count(),
         }
     }
-}
 
-impl<T> Drop for Sender<T> {
-    fn drop(&mut self) {
-        unsafe {
-            match &self.flavor {
-                SenderFlavor::Array(chan) => chan.release(|c| c.disconnect()),
-                SenderFlavor::List(chan) => chan.release(|c| c.disconnect()),
-                SenderFlavor::Zero(chan) => chan.release(|c| c.disconnect()),
-            }
+    /// Returns the number of receivers associated with the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// assert_eq!(s.receiver_count(), 1);
+    ///
+    /// drop(r);
+    /// assert_eq!(s.receiver_count(), 0);
+    /// ```
+    pub fn receiver_count(&self) -> usize {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.receiver_count(),
+            SenderFlavor::Priority(chan) => chan.receiver_count(),
+            SenderFlavor::List(chan) => chan.receiver_count(),
+            SenderFlavor::Zero(chan) => chan.receiver_count(),
+            SenderFlavor::Broadcast(chan) => chan.receiver_count(),
+            SenderFlavor::Watch(chan) => chan.receiver_count(),
         }
     }
-}
 
-impl<T> Clone for Sender<T> {
+    /// Returns the name of the channel, if it has one.
+    ///
+    /// Channels are named when created with [`bounded_named`] or [`unbounded_named`].
+    ///
+    /// This method is only available with the `debug` feature enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, bounded_named};
+    ///
+    /// let (s, _) = bounded_named::<i32>("jobs", 1);
+    /// assert_eq!(s.name(), Some("jobs"));
+    ///
+    /// let (s, _) = bounded::<i32>(1);
+    /// assert_eq!(s.name(), None);
+    /// ```
+    ///
+    /// [`bounded_named`]: fn.bounded_named.html
+    /// [`unbounded_named`]: fn.unbounded_named.html
+    #[cfg(feature = "debug")]
+    pub fn name(&self) -> Option<&str> {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.name(),
+            SenderFlavor::Priority(chan) => chan.name(),
+            SenderFlavor::List(chan) => chan.name(),
+            SenderFlavor::Zero(chan) => chan.name(),
+            SenderFlavor::Broadcast(chan) => chan.name(),
+            SenderFlavor::Watch(chan) => chan.name(),
+        }
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    ///
+    /// A channel becomes disconnected when all senders or all receivers are dropped, or when it
+    /// gets closed with [`close`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// assert!(!s.is_disconnected());
+    ///
+    /// drop(r);
+    /// assert!(s.is_disconnected());
+    /// ```
+    ///
+    /// [`close`]: struct.Sender.html#method.close
+    pub fn is_disconnected(&self) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.is_disconnected(),
+            SenderFlavor::Priority(chan) => chan.is_disconnected(),
+            SenderFlavor::List(chan) => chan.is_disconnected(),
+            SenderFlavor::Zero(chan) => chan.is_disconnected(),
+            SenderFlavor::Broadcast(chan) => chan.is_disconnected(),
+            SenderFlavor::Watch(chan) => chan.is_disconnected(),
+        }
+    }
+
+    /// Returns a snapshot of statistics about the channel.
+    ///
+    /// This method is only available with the `metrics` feature enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// r.recv().unwrap();
+    ///
+    /// let stats = s.stats();
+    /// assert_eq!(stats.sends(), 2);
+    /// assert_eq!(stats.recvs(), 1);
+    /// assert_eq!(stats.max_len(), 2);
+    /// ```
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> Stats {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.stats(),
+            SenderFlavor::Priority(chan) => chan.stats(),
+            SenderFlavor::List(chan) => chan.stats(),
+            SenderFlavor::Zero(chan) => chan.stats(),
+            SenderFlavor::Broadcast(chan) => chan.stats(),
+            SenderFlavor::Watch(chan) => chan.stats(),
+        }
+    }
+
+    /// Disconnects the channel while senders and receivers are still alive.
+    ///
+    /// All blocked operations are woken up. Subsequent send operations fail, while messages
+    /// already in the channel can still be received. Receive operations fail only once the
+    /// channel becomes empty.
+    ///
+    /// Returns `true` if this call disconnected the channel, and `false` if it was already
+    /// disconnected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, RecvError, SendError};
+    ///
+    /// let (s, r) = unbounded();
+    /// let s2 = s.clone();
+    ///
+    /// s.send(1).unwrap();
+    /// assert!(s.close());
+    /// assert!(!s2.close());
+    ///
+    /// assert_eq!(s2.send(2), Err(SendError(2)));
+    /// assert_eq!(r.recv(), Ok(1));
+    /// assert_eq!(r.recv(), Err(RecvError));
+    /// ```
+    pub fn close(&self) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.disconnect(),
+            SenderFlavor::Priority(chan) => chan.disconnect(),
+            SenderFlavor::List(chan) => chan.disconnect(),
+            SenderFlavor::Zero(chan) => chan.disconnect(),
+            SenderFlavor::Broadcast(chan) => chan.disconnect(),
+            SenderFlavor::Watch(chan) => chan.disconnect(),
+        }
+    }
+
+    /// Returns `true` if senders belong to the same channel.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, _) = unbounded::<usize>();
+    ///
+    /// let s2 = s.clone();
+    /// assert!(s.same_channel(&s2));
+    ///
+    /// let (s3, _) = unbounded();
+    /// assert!(!s.same_channel(&s3));
+    /// ```
+    pub fn same_channel(&self, other: &Sender<T>) -> bool {
+        match (&self.flavor, &other.flavor) {
+            (SenderFlavor::Array(ref a), SenderFlavor::Array(ref b)) => a == b,
+            (SenderFlavor::Priority(ref a), SenderFlavor::Priority(ref b)) => a == b,
+            (SenderFlavor::List(ref a), SenderFlavor::List(ref b)) => a == b,
+            (SenderFlavor::Zero(ref a), SenderFlavor::Zero(ref b)) => a == b,
+            (SenderFlavor::Broadcast(ref a), SenderFlavor::Broadcast(ref b)) => a == b,
+            (SenderFlavor::Watch(ref a), SenderFlavor::Watch(ref b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Creates a weak sender that doesn't keep the channel connected.
+    ///
+    /// Weak senders don't count toward the number of senders, so the channel becomes
+    /// disconnected once all regular senders are dropped, regardless of how many weak senders
+    /// exist. A weak sender can be turned back into a regular one with [`upgrade`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, RecvError};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// let w = s.downgrade();
+    /// assert_eq!(s.sender_count(), 1);
+    ///
+    /// drop(s);
+    /// assert_eq!(r.recv(), Err(RecvError));
+    /// assert!(w.upgrade().is_none());
+    /// ```
+    ///
+    /// [`upgrade`]: struct.WeakSender.html#method.upgrade
+    pub fn downgrade(&self) -> WeakSender<T> {
+        let flavor = match &self.flavor {
+            SenderFlavor::Array(chan) => WeakSenderFlavor::Array(chan.downgrade()),
+            SenderFlavor::Priority(chan) => WeakSenderFlavor::Priority(chan.downgrade()),
+            SenderFlavor::List(chan) => WeakSenderFlavor::List(chan.downgrade()),
+            SenderFlavor::Zero(chan) => WeakSenderFlavor::Zero(chan.downgrade()),
+            SenderFlavor::Broadcast(chan) => WeakSenderFlavor::Broadcast(chan.downgrade()),
+            SenderFlavor::Watch(chan) => WeakSenderFlavor::Watch(chan.downgrade()),
+        };
+
+        WeakSender { flavor }
+    }
+
+    /// Turns the sender into one that accepts messages of a different type.
+    ///
+    /// Every message is converted with `f` right before it is sent into the channel. No extra
+    /// thread is involved, and the returned sender can still be used in [`Select`] and
+    /// [`select!`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<String>();
+    /// let s = s.contramap(|n: i32| n.to_string());
+    ///
+    /// s.send(42).unwrap();
+    /// assert_eq!(r.recv(), Ok("42".to_string()));
+    /// ```
+    ///
+    /// [`Select`]: struct.Select.html
+    /// [`select!`]: macro.select.html
+    pub fn contramap<U, F>(self, f: F) -> ContramappedSender<U, T>
+    where
+        F: Fn(U) -> T + Send + Sync + 'static,
+    {
+        adapter::contramap(self, f)
+    }
+
+    /// Returns a future that sends a message into the channel.
+    ///
+    /// The future resolves once the message is sent, or with an error if the channel is
+    /// disconnected. If the channel is full, the task waits for space instead of blocking the
+    /// current thread.
+    ///
+    /// If the future is dropped before it resolves, the message is dropped and is not sent.
+    ///
+    /// This method is only available with the `async` feature enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018,no_run
+    /// # async fn example() {
+    /// use crossbeam_channel::{bounded, SendError};
+    ///
+    /// let (s, r) = bounded(1);
+    ///
+    /// assert_eq!(s.send_async(1).await, Ok(()));
+    /// drop(r);
+    /// assert_eq!(s.send_async(2).await, Err(SendError(2)));
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn send_async(&self, msg: T) -> SendFuture<T> {
+        SendFuture {
+            sender: self,
+            msg: Some(msg),
+            waiting: None,
+        }
+    }
+
+    /// Records the current thread as a sender for deadlock detection.
+    #[inline]
+    fn participate(&self) {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.participate(),
+            SenderFlavor::Priority(chan) => chan.participate(),
+            SenderFlavor::List(chan) => chan.participate(),
+            SenderFlavor::Zero(chan) => chan.participate(),
+            SenderFlavor::Broadcast(chan) => chan.participate(),
+            SenderFlavor::Watch(chan) => chan.participate(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            match &self.flavor {
+                SenderFlavor::Array(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::Priority(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::List(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::Zero(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::Broadcast(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::Watch(chan) => chan.release(|c| c.disconnect()),
+            }
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
     fn clone(&self) -> Self {
         let flavor = match &self.flavor {
             SenderFlavor::Array(chan) => SenderFlavor::Array(chan.acquire()),
+            SenderFlavor::Priority(chan) => SenderFlavor::Priority(chan.acquire()),
             SenderFlavor::List(chan) => SenderFlavor::List(chan.acquire()),
             SenderFlavor::Zero(chan) => SenderFlavor::Zero(chan.acquire()),
+            SenderFlavor::Broadcast(chan) => SenderFlavor::Broadcast(chan.acquire()),
+            SenderFlavor::Watch(chan) => SenderFlavor::Watch(chan.acquire()),
         };
 
         Sender { flavor }
@@ -582,229 +1994,1099 @@ impl<T> Clone for Sender<T> {
 }
 
 impl<T> fmt::Debug for Sender<T> {
+    #[cfg(feature = "debug")]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "Sender {{ name: {:?}, .. }}", name),
+            None => f.pad("Sender { .. }"),
+        }
+    }
+
+    #[cfg(not(feature = "debug"))]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.pad("Sender { .. }")
     }
 }
 
-/// The receiving side of a channel.
+/// A sending side of a channel that doesn't keep the channel connected.
+///
+/// Weak senders are created with [`Sender::downgrade`]. They don't count toward the number of
+/// senders, so dropping all regular senders disconnects the channel even if weak senders still
+/// exist. Messages can be sent only after upgrading a weak sender back into a regular [`Sender`].
+///
+/// A weak sender keeps the channel's memory allocated, so messages left in the channel after all
+/// senders and receivers are dropped get dropped along with the last weak sender.
 ///
 /// # Examples
 ///
 /// ```
-/// use std::thread;
-/// use std::time::Duration;
 /// use crossbeam_channel::unbounded;
 ///
 /// let (s, r) = unbounded();
+/// let w = s.downgrade();
 ///
-/// thread::spawn(move || {
-///     s.send(1);
-///     thread::sleep(Duration::from_secs(1));
-///     s.send(2);
-/// });
+/// // The channel is still connected, so the weak sender can be upgraded.
+/// w.upgrade().unwrap().send(1).unwrap();
+/// assert_eq!(r.recv(), Ok(1));
 ///
-/// assert_eq!(r.recv(), Ok(1)); // Received immediately.
-/// assert_eq!(r.recv(), Ok(2)); // Received after 1 second.
+/// // Dropping the only regular sender disconnects the channel.
+/// drop(s);
+/// assert!(w.upgrade().is_none());
 /// ```
-pub struct Receiver<T> {
-    flavor: ReceiverFlavor<T>,
+///
+/// [`Sender::downgrade`]: struct.Sender.html#method.downgrade
+/// [`Sender`]: struct.Sender.html
+pub struct WeakSender<T> {
+    flavor: WeakSenderFlavor<T>,
 }
 
-/// Receiver flavors.
-enum ReceiverFlavor<T> {
+/// Weak sender flavors.
+enum WeakSenderFlavor<T> {
     /// Bounded channel based on a preallocated array.
-    Array(counter::Receiver<flavors::array::Channel<T>>),
+    Array(counter::WeakSender<flavors::array::Channel<T>>),
+
+    /// Channel that delivers messages in order of their priority.
+    Priority(counter::WeakSender<flavors::priority::Channel<T>>),
 
     /// Unbounded channel implemented as a linked list.
-    List(counter::Receiver<flavors::list::Channel<T>>),
+    List(counter::WeakSender<flavors::list::Channel<T>>),
 
     /// Zero-capacity channel.
-    Zero(counter::Receiver<flavors::zero::Channel<T>>),
-
-    /// The after flavor.
-    After(Arc<flavors::after::Channel>),
+    Zero(counter::WeakSender<flavors::zero::Channel<T>>),
 
-    /// The tick flavor.
-    Tick(Arc<flavors::tick::Channel>),
+    /// Broadcast channel.
+    Broadcast(counter::WeakSender<flavors::broadcast::Channel<T>>),
 
-    /// The never flavor.
-    Never(flavors::never::Channel<T>),
+    /// Channel holding the latest value.
+    Watch(counter::WeakSender<flavors::watch::Channel<T>>),
 }
 
-unsafe impl<T: Send> Send for Receiver<T> {}
-unsafe impl<T: Send> Sync for Receiver<T> {}
+unsafe impl<T: Send> Send for WeakSender<T> {}
+unsafe impl<T: Send> Sync for WeakSender<T> {}
 
-impl<T> UnwindSafe for Receiver<T> {}
-impl<T> RefUnwindSafe for Receiver<T> {}
+impl<T> UnwindSafe for WeakSender<T> {}
+impl<T> RefUnwindSafe for WeakSender<T> {}
 
-impl<T> Receiver<T> {
-    /// Attempts to receive a message from the channel without blocking.
-    ///
-    /// This method will either receive a message from the channel immediately or return an error
-    /// if the channel is empty.
+impl<T> WeakSender<T> {
+    /// Attempts to turn the weak sender into a regular sender.
     ///
-    /// If called on a zero-capacity channel, this method will receive a message only if there
-    /// happens to be a send operation on the other side of the channel at the same time.
+    /// Returns `None` if the channel is disconnected.
     ///
     /// # Examples
     ///
     /// ```
-    /// use crossbeam_channel::{unbounded, TryRecvError};
+    /// use crossbeam_channel::unbounded;
     ///
-    /// let (s, r) = unbounded();
-    /// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    /// let (s, r) = unbounded::<i32>();
+    /// let w = s.downgrade();
     ///
-    /// s.send(5).unwrap();
-    /// drop(s);
+    /// let s2 = w.upgrade().unwrap();
+    /// assert_eq!(s2.sender_count(), 2);
     ///
-    /// assert_eq!(r.try_recv(), Ok(5));
-    /// assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    /// drop(r);
+    /// assert!(w.upgrade().is_none());
     /// ```
-    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+    pub fn upgrade(&self) -> Option<Sender<T>> {
+        let flavor = match &self.flavor {
+            WeakSenderFlavor::Array(chan) => chan.upgrade().map(SenderFlavor::Array),
+            WeakSenderFlavor::Priority(chan) => chan.upgrade().map(SenderFlavor::Priority),
+            WeakSenderFlavor::List(chan) => chan.upgrade().map(SenderFlavor::List),
+            WeakSenderFlavor::Zero(chan) => chan.upgrade().map(SenderFlavor::Zero),
+            WeakSenderFlavor::Broadcast(chan) => chan.upgrade().map(SenderFlavor::Broadcast),
+            WeakSenderFlavor::Watch(chan) => chan.upgrade().map(SenderFlavor::Watch),
+        };
+
+        // The channel might have been disconnected from the receiving side or closed.
+        let s = Sender { flavor: flavor? };
+        if s.is_disconnected() {
+            None
+        } else {
+            Some(s)
+        }
+    }
+}
+
+impl<T> Drop for WeakSender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            match &self.flavor {
+                WeakSenderFlavor::Array(chan) => chan.release(),
+                WeakSenderFlavor::Priority(chan) => chan.release(),
+                WeakSenderFlavor::List(chan) => chan.release(),
+                WeakSenderFlavor::Zero(chan) => chan.release(),
+                WeakSenderFlavor::Broadcast(chan) => chan.release(),
+                WeakSenderFlavor::Watch(chan) => chan.release(),
+            }
+        }
+    }
+}
+
+impl<T> Clone for WeakSender<T> {
+    fn clone(&self) -> Self {
+        let flavor = match &self.flavor {
+            WeakSenderFlavor::Array(chan) => WeakSenderFlavor::Array(chan.acquire()),
+            WeakSenderFlavor::Priority(chan) => WeakSenderFlavor::Priority(chan.acquire()),
+            WeakSenderFlavor::List(chan) => WeakSenderFlavor::List(chan.acquire()),
+            WeakSenderFlavor::Zero(chan) => WeakSenderFlavor::Zero(chan.acquire()),
+            WeakSenderFlavor::Broadcast(chan) => WeakSenderFlavor::Broadcast(chan.acquire()),
+            WeakSenderFlavor::Watch(chan) => WeakSenderFlavor::Watch(chan.acquire()),
+        };
+
+        WeakSender { flavor }
+    }
+}
+
+impl<T> fmt::Debug for WeakSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("WeakSender { .. }")
+    }
+}
+
+/// The sending side of a channel created by [`oneshot`].
+///
+/// A oneshot sender cannot be cloned, and sending a message consumes it.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::oneshot;
+///
+/// let (s, r) = oneshot();
+///
+/// thread::spawn(move || {
+///     let result = 2 + 2;
+///     s.send(result).unwrap();
+/// });
+///
+/// assert_eq!(r.recv(), Ok(4));
+/// ```
+///
+/// [`oneshot`]: fn.oneshot.html
+pub struct OneshotSender<T> {
+    chan: counter::Sender<flavors::oneshot::Channel<T>>,
+}
+
+unsafe impl<T: Send> Send for OneshotSender<T> {}
+unsafe impl<T: Send> Sync for OneshotSender<T> {}
+
+impl<T> UnwindSafe for OneshotSender<T> {}
+impl<T> RefUnwindSafe for OneshotSender<T> {}
+
+impl<T> OneshotSender<T> {
+    /// Sends the message into the channel without blocking, consuming the sender.
+    ///
+    /// If all receivers have been dropped or the channel was closed, the message is returned back
+    /// inside the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{oneshot, SendError};
+    ///
+    /// let (s, r) = oneshot();
+    /// assert_eq!(s.send(1), Ok(()));
+    /// assert_eq!(r.recv(), Ok(1));
+    ///
+    /// let (s, r) = oneshot();
+    /// drop(r);
+    /// assert_eq!(s.send(2), Err(SendError(2)));
+    /// ```
+    pub fn send(self, msg: T) -> Result<(), SendError<T>> {
+        self.chan.participate();
+        self.chan.send(msg).map_err(SendError)
+    }
+
+    /// Returns `true` if the channel is disconnected, which means the message cannot be sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::oneshot;
+    ///
+    /// let (s, r) = oneshot::<i32>();
+    /// assert!(!s.is_disconnected());
+    ///
+    /// drop(r);
+    /// assert!(s.is_disconnected());
+    /// ```
+    pub fn is_disconnected(&self) -> bool {
+        self.chan.is_disconnected()
+    }
+}
+
+impl<T> Drop for OneshotSender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.chan.release(|c| c.disconnect());
+        }
+    }
+}
+
+impl<T> fmt::Debug for OneshotSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("OneshotSender { .. }")
+    }
+}
+
+/// A handle that controls the channel created by [`timer`].
+///
+/// The timer only holds a weak reference to the channel. Once all receivers are dropped, the timer
+/// is stopped and its methods have no effect.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use crossbeam_channel::{timer, TryRecvError};
+///
+/// let (t, r) = timer(Duration::from_secs(60));
+/// assert!(t.remaining().unwrap() <= Duration::from_secs(60));
+///
+/// t.cancel();
+/// assert_eq!(t.remaining(), None);
+///
+/// t.reset(Duration::from_millis(10));
+/// assert!(r.recv().is_ok());
+/// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+/// ```
+///
+/// [`timer`]: fn.timer.html
+#[derive(Clone)]
+pub struct Timer {
+    chan: Weak<flavors::timer::Channel>,
+}
+
+impl UnwindSafe for Timer {}
+impl RefUnwindSafe for Timer {}
+
+impl Timer {
+    /// Restarts the timer so that the message is delivered after `duration`.
+    ///
+    /// This works whether the timer is running, cancelled, or has already expired. A message that
+    /// has been delivered but not received yet is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::timer;
+    ///
+    /// let start = Instant::now();
+    /// let (t, r) = timer(Duration::from_millis(100));
+    ///
+    /// thread::sleep(Duration::from_millis(50));
+    /// t.reset(Duration::from_millis(100));
+    ///
+    /// // The message is postponed.
+    /// assert!(r.recv().unwrap() >= start + Duration::from_millis(150));
+    /// ```
+    pub fn reset(&self, duration: Duration) {
+        if let Some(chan) = self.chan.upgrade() {
+            chan.reset(duration);
+        }
+    }
+
+    /// Stops the timer so that no message is delivered until it is reset.
+    ///
+    /// A message that has been delivered but not received yet is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{timer, RecvTimeoutError};
+    ///
+    /// let (t, r) = timer(Duration::from_millis(10));
+    /// t.cancel();
+    ///
+    /// assert_eq!(
+    ///     r.recv_timeout(Duration::from_millis(100)),
+    ///     Err(RecvTimeoutError::Timeout),
+    /// );
+    /// ```
+    pub fn cancel(&self) {
+        if let Some(chan) = self.chan.upgrade() {
+            chan.cancel();
+        }
+    }
+
+    /// Returns the time left until the message is delivered.
+    ///
+    /// If the timer has been cancelled, or its message has been received, `None` is returned. An
+    /// expired timer whose message is still in the channel has zero time left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::timer;
+    ///
+    /// let (t, r) = timer(Duration::from_millis(10));
+    /// assert!(t.remaining().unwrap() <= Duration::from_millis(10));
+    ///
+    /// thread::sleep(Duration::from_millis(20));
+    /// assert_eq!(t.remaining(), Some(Duration::from_secs(0)));
+    ///
+    /// r.recv().unwrap();
+    /// assert_eq!(t.remaining(), None);
+    /// ```
+    pub fn remaining(&self) -> Option<Duration> {
+        self.chan.upgrade().and_then(|chan| chan.remaining())
+    }
+}
+
+impl fmt::Debug for Timer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Timer { .. }")
+    }
+}
+
+/// The receiving side of a channel.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::unbounded;
+///
+/// let (s, r) = unbounded();
+///
+/// thread::spawn(move || {
+///     s.send(1);
+///     thread::sleep(Duration::from_secs(1));
+///     s.send(2);
+/// });
+///
+/// assert_eq!(r.recv(), Ok(1)); // Received immediately.
+/// assert_eq!(r.recv(), Ok(2)); // Received after 1 second.
+/// ```
+pub struct Receiver<T> {
+    flavor: ReceiverFlavor<T>,
+}
+
+/// Receiver flavors.
+enum ReceiverFlavor<T> {
+    /// Bounded channel based on a preallocated array.
+    Array(counter::Receiver<flavors::array::Channel<T>>),
+
+    /// Channel that delivers messages in order of their priority.
+    Priority(counter::Receiver<flavors::priority::Channel<T>>),
+
+    /// Unbounded channel implemented as a linked list.
+    List(counter::Receiver<flavors::list::Channel<T>>),
+
+    /// Zero-capacity channel.
+    Zero(counter::Receiver<flavors::zero::Channel<T>>),
+
+    /// Broadcast channel, along with the position of this receiver.
+    Broadcast(
+        counter::Receiver<flavors::broadcast::Channel<T>>,
+        flavors::broadcast::Cursor,
+    ),
+
+    /// Channel holding the latest value, along with the version this receiver has last seen.
+    Watch(
+        counter::Receiver<flavors::watch::Channel<T>>,
+        flavors::watch::Version,
+    ),
+
+    /// Channel that delivers a single message.
+    Oneshot(counter::Receiver<flavors::oneshot::Channel<T>>),
+
+    /// The after flavor.
+    After(TimeChannel<flavors::after::Channel, T>),
+
+    /// The tick flavor.
+    Tick(TimeChannel<flavors::tick::Channel, T>),
+
+    /// The timer flavor.
+    Timer(TimeChannel<flavors::timer::Channel, T>),
+
+    /// The never flavor.
+    Never(flavors::never::Channel<T>),
+}
+
+/// A channel whose messages are instants, as seen by a `Receiver<T>`.
+///
+/// Such channels are only ever created with `T` being `Instant`, which the identity function
+/// stored in `as_msg` proves, so messages can be handed out without casting.
+struct TimeChannel<C, T> {
+    /// The channel.
+    chan: Arc<C>,
+
+    /// Converts a reference to an instant into a reference to a message.
+    as_msg: fn(&Instant) -> &T,
+}
+
+impl<C> TimeChannel<C, Instant> {
+    /// Wraps a channel that delivers instants.
+    fn new(chan: Arc<C>) -> Self {
+        fn identity(instant: &Instant) -> &Instant {
+            instant
+        }
+
+        TimeChannel {
+            chan,
+            as_msg: identity,
+        }
+    }
+}
+
+impl<C, T> TimeChannel<C, T> {
+    /// Converts a received instant into a message.
+    fn msg(&self, instant: Instant) -> T {
+        // `as_msg` returns the reference it is given and instants are `Copy`, so the message can
+        // be read out of it.
+        unsafe { ptr::read((self.as_msg)(&instant)) }
+    }
+}
+
+impl<C, T> Deref for TimeChannel<C, T> {
+    type Target = Arc<C>;
+
+    fn deref(&self) -> &Arc<C> {
+        &self.chan
+    }
+}
+
+impl<C, T> Clone for TimeChannel<C, T> {
+    fn clone(&self) -> Self {
+        TimeChannel {
+            chan: self.chan.clone(),
+            as_msg: self.as_msg,
+        }
+    }
+}
+
+unsafe impl<T: Send> Send for Receiver<T> {}
+unsafe impl<T: Send> Sync for Receiver<T> {}
+
+impl<T> UnwindSafe for Receiver<T> {}
+impl<T> RefUnwindSafe for Receiver<T> {}
+
+impl<T> Receiver<T> {
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// This method will either receive a message from the channel immediately or return an error
+    /// if the channel is empty.
+    ///
+    /// If called on a zero-capacity channel, this method will receive a message only if there
+    /// happens to be a send operation on the other side of the channel at the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, TryRecvError};
+    ///
+    /// let (s, r) = unbounded();
+    /// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// s.send(5).unwrap();
+    /// drop(s);
+    ///
+    /// assert_eq!(r.try_recv(), Ok(5));
+    /// assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    /// ```
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.participate();
+
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.try_recv(),
+            ReceiverFlavor::Priority(chan) => chan.try_recv(),
+            ReceiverFlavor::List(chan) => chan.try_recv(),
+            ReceiverFlavor::Zero(chan) => chan.try_recv(),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.try_recv(cursor),
+            ReceiverFlavor::Watch(chan, version) => chan.try_recv(version),
+            ReceiverFlavor::Oneshot(chan) => chan.try_recv(),
+            ReceiverFlavor::After(chan) => chan.try_recv().map(|i| chan.msg(i)),
+            ReceiverFlavor::Tick(chan) => chan.try_recv().map(|i| chan.msg(i)),
+            ReceiverFlavor::Timer(chan) => chan.try_recv().map(|i| chan.msg(i)),
+            ReceiverFlavor::Never(chan) => chan.try_recv(),
+        }
+    }
+
+    /// Attempts to receive a message from the channel without blocking, reporting messages missed
+    /// by a lagging receiver.
+    ///
+    /// This works just like [`try_recv`], except when called on a receiver of a broadcast channel
+    /// that has fallen behind. Instead of skipping over the missed messages, it returns
+    /// [`BroadcastRecvError::Lagged`] with their number, and the next call receives the oldest
+    /// message still in the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{broadcast, BroadcastRecvError};
+    ///
+    /// let (s, r) = broadcast(2);
+    /// assert_eq!(r.try_recv_lagged(), Err(BroadcastRecvError::Empty));
+    ///
+    /// for i in 0..5 {
+    ///     s.send(i).unwrap();
+    /// }
+    ///
+    /// assert_eq!(r.try_recv_lagged(), Err(BroadcastRecvError::Lagged(3)));
+    /// assert_eq!(r.try_recv_lagged(), Ok(3));
+    /// assert_eq!(r.try_recv_lagged(), Ok(4));
+    /// ```
+    ///
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    /// [`BroadcastRecvError::Lagged`]: enum.BroadcastRecvError.html#variant.Lagged
+    pub fn try_recv_lagged(&self) -> Result<T, BroadcastRecvError> {
+        match &self.flavor {
+            ReceiverFlavor::Broadcast(chan, cursor) => {
+                self.participate();
+                chan.try_recv_lagged(cursor)
+            }
+            _ => self.try_recv().map_err(BroadcastRecvError::from),
+        }
+    }
+
+    /// Blocks the current thread until a message is received or the channel is empty and
+    /// disconnected.
+    ///
+    /// If the channel is empty and not disconnected, this call will block until the receive
+    /// operation can proceed. If the channel is empty and becomes disconnected, this call will
+    /// wake up and return an error.
+    ///
+    /// If called on a zero-capacity channel, this method will wait for a send operation to appear
+    /// on the other side of the channel.
+    ///
+    /// A receiver of a broadcast channel that has fallen behind skips over the missed messages.
+    /// Use [`try_recv_lagged`] to be told about them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, RecvError};
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     s.send(5).unwrap();
+    ///     drop(s);
+    /// });
+    ///
+    /// assert_eq!(r.recv(), Ok(5));
+    /// assert_eq!(r.recv(), Err(RecvError));
+    /// ```
+    ///
+    /// [`try_recv_lagged`]: struct.Receiver.html#method.try_recv_lagged
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.participate();
+
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.recv(None),
+            ReceiverFlavor::Priority(chan) => chan.recv(None),
+            ReceiverFlavor::List(chan) => chan.recv(None),
+            ReceiverFlavor::Zero(chan) => chan.recv(None),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.recv(cursor, None),
+            ReceiverFlavor::Watch(chan, version) => chan.recv(version, None),
+            ReceiverFlavor::Oneshot(chan) => chan.recv(None),
+            ReceiverFlavor::After(chan) => chan.recv(None).map(|i| chan.msg(i)),
+            ReceiverFlavor::Tick(chan) => chan.recv(None).map(|i| chan.msg(i)),
+            ReceiverFlavor::Timer(chan) => chan.recv(None).map(|i| chan.msg(i)),
+            ReceiverFlavor::Never(chan) => chan.recv(None),
+        }
+        .map_err(|_| RecvError)
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    ///
+    /// If the channel is empty and not disconnected, this call will block until the receive
+    /// operation can proceed or the operation times out. If the channel is empty and becomes
+    /// disconnected, this call will wake up and return an error.
+    ///
+    /// If called on a zero-capacity channel, this method will wait for a send operation to appear
+    /// on the other side of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, RecvTimeoutError};
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     s.send(5).unwrap();
+    ///     drop(s);
+    /// });
+    ///
+    /// assert_eq!(
+    ///     r.recv_timeout(Duration::from_millis(500)),
+    ///     Err(RecvTimeoutError::Timeout),
+    /// );
+    /// assert_eq!(
+    ///     r.recv_timeout(Duration::from_secs(1)),
+    ///     Ok(5),
+    /// );
+    /// assert_eq!(
+    ///     r.recv_timeout(Duration::from_secs(1)),
+    ///     Err(RecvTimeoutError::Disconnected),
+    /// );
+    /// ```
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_deadline(Instant::now() + timeout)
+    }
+
+    /// Waits for a message to be received from the channel, but only until a given deadline.
+    ///
+    /// If the channel is empty and not disconnected, this call will block until the receive
+    /// operation can proceed or the deadline is reached. If the channel is empty and becomes
+    /// disconnected, this call will wake up and return an error.
+    ///
+    /// Unlike [`recv_timeout`], the same deadline can be passed to repeated calls, for example
+    /// when receiving several messages in a loop, without extending the total waiting time.
+    ///
+    /// If called on a zero-capacity channel, this method will wait for a send operation to appear
+    /// on the other side of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{unbounded, RecvTimeoutError};
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     for i in 0..3 {
+    ///         thread::sleep(Duration::from_millis(400));
+    ///         s.send(i).unwrap();
+    ///     }
+    /// });
+    ///
+    /// // Only the first two messages arrive within one second.
+    /// let deadline = Instant::now() + Duration::from_secs(1);
+    /// assert_eq!(r.recv_deadline(deadline), Ok(0));
+    /// assert_eq!(r.recv_deadline(deadline), Ok(1));
+    /// assert_eq!(r.recv_deadline(deadline), Err(RecvTimeoutError::Timeout));
+    /// ```
+    ///
+    /// [`recv_timeout`]: struct.Receiver.html#method.recv_timeout
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        self.participate();
+
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Priority(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::List(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Zero(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.recv(cursor, Some(deadline)),
+            ReceiverFlavor::Watch(chan, version) => chan.recv(version, Some(deadline)),
+            ReceiverFlavor::Oneshot(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::After(chan) => chan.recv(Some(deadline)).map(|i| chan.msg(i)),
+            ReceiverFlavor::Tick(chan) => chan.recv(Some(deadline)).map(|i| chan.msg(i)),
+            ReceiverFlavor::Timer(chan) => chan.recv(Some(deadline)).map(|i| chan.msg(i)),
+            ReceiverFlavor::Never(chan) => chan.recv(Some(deadline)),
+        }
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time measured
+    /// by `clock`.
+    ///
+    /// This works just like [`recv_timeout`], except that the operation times out once `clock`
+    /// has advanced by `timeout`. If a message is available when the operation times out, it is
+    /// received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Clock, RecvTimeoutError};
+    ///
+    /// let clock = Clock::manual();
+    /// let (s, r) = unbounded::<i32>();
+    ///
+    /// // Advance the clock once the operation has started waiting.
+    /// let c = clock.clone();
+    /// let handle = thread::spawn(move || {
+    ///     c.wait_for_waiters(1);
+    ///     c.advance(Duration::from_secs(60));
+    /// });
+    ///
+    /// assert_eq!(
+    ///     r.recv_timeout_with_clock(Duration::from_secs(60), &clock),
+    ///     Err(RecvTimeoutError::Timeout),
+    /// );
+    /// handle.join().unwrap();
+    /// # drop(s);
+    /// ```
+    ///
+    /// [`recv_timeout`]: struct.Receiver.html#method.recv_timeout
+    pub fn recv_timeout_with_clock(
+        &self,
+        timeout: Duration,
+        clock: &Clock,
+    ) -> Result<T, RecvTimeoutError> {
+        if !clock.is_manual() {
+            return self.recv_timeout(timeout);
+        }
+
+        match self.try_recv() {
+            Ok(msg) => return Ok(msg),
+            Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        let timer = after_with_clock(timeout, clock);
+        let mut sel = Select::new();
+        let recv = sel.recv(self);
+        sel.recv(&timer);
+
+        let oper = sel.select();
+        if oper.index() == recv {
+            return oper.recv(self).map_err(|_| RecvTimeoutError::Disconnected);
+        }
+        let _ = oper.recv(&timer);
+
+        // Prefer receiving a message if one arrived at the same time.
+        match self.try_recv() {
+            Ok(msg) => Ok(msg),
+            Err(TryRecvError::Disconnected) => Err(RecvTimeoutError::Disconnected),
+            Err(TryRecvError::Empty) => Err(RecvTimeoutError::Timeout),
+        }
+    }
+
+    /// Attempts to receive up to `max` messages from the channel without blocking.
+    ///
+    /// Received messages are appended to `buf` and their number is returned. If the channel is
+    /// empty, or empty and disconnected, this call fails just like [`try_recv`]. Bounded and
+    /// unbounded channels claim several messages at once and wake up senders only once per
+    /// claim, which makes this cheaper than receiving the messages one by one.
+    ///
+    /// If `max` is zero, no messages are received and `Ok(0)` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, TryRecvError};
+    ///
+    /// let (s, r) = unbounded();
+    /// let mut buf = Vec::new();
+    ///
+    /// s.send_batch(vec![1, 2, 3]).unwrap();
+    ///
+    /// assert_eq!(r.try_recv_batch(&mut buf, 2), Ok(2));
+    /// assert_eq!(r.try_recv_batch(&mut buf, 2), Ok(1));
+    /// assert_eq!(buf, [1, 2, 3]);
+    ///
+    /// assert_eq!(r.try_recv_batch(&mut buf, 2), Err(TryRecvError::Empty));
+    /// drop(s);
+    /// assert_eq!(r.try_recv_batch(&mut buf, 2), Err(TryRecvError::Disconnected));
+    /// ```
+    ///
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    pub fn try_recv_batch(&self, buf: &mut Vec<T>, max: usize) -> Result<usize, TryRecvError> {
+        self.participate();
+
+        if max == 0 {
+            return Ok(0);
+        }
+
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.try_recv_batch(buf, max),
+            ReceiverFlavor::List(chan) => chan.try_recv_batch(buf, max),
+            _ => {
+                // Other flavors receive the messages one by one.
+                buf.push(self.try_recv()?);
+                Ok(1 + self.try_recv_rest(buf, max - 1))
+            }
+        }
+    }
+
+    /// Blocks the current thread until at least one message is received or the channel is
+    /// disconnected, then receives up to `max` messages.
+    ///
+    /// Received messages are appended to `buf` and their number is returned. If the channel is
+    /// empty and disconnected, this call fails just like [`recv`]. Bounded and unbounded channels
+    /// claim several messages at once and wake up senders only once per claim, which makes this
+    /// cheaper than receiving the messages one by one.
+    ///
+    /// If `max` is zero, this method doesn't block and returns `Ok(0)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{unbounded, RecvError};
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     s.send_batch(0..10).unwrap();
+    /// });
+    ///
+    /// let mut buf = Vec::new();
+    /// while r.recv_batch(&mut buf, 4).is_ok() {}
+    /// assert_eq!(buf, (0..10).collect::<Vec<_>>());
+    ///
+    /// assert_eq!(r.recv_batch(&mut buf, 4), Err(RecvError));
+    /// ```
+    ///
+    /// [`recv`]: struct.Receiver.html#method.recv
+    pub fn recv_batch(&self, buf: &mut Vec<T>, max: usize) -> Result<usize, RecvError> {
+        self.participate();
+
+        if max == 0 {
+            return Ok(0);
+        }
+
+        let res = match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.recv_batch(buf, max, None),
+            ReceiverFlavor::List(chan) => chan.recv_batch(buf, max, None),
+            _ => {
+                // Other flavors receive the messages one by one.
+                buf.push(self.recv()?);
+                return Ok(1 + self.try_recv_rest(buf, max - 1));
+            }
+        };
+
+        res.map_err(|_| RecvError)
+    }
+
+    /// Receives up to `max` messages into `buf` without blocking, one by one.
+    ///
+    /// Returns the number of received messages.
+    fn try_recv_rest(&self, buf: &mut Vec<T>, max: usize) -> usize {
+        let mut count = 0;
+        while count < max {
+            match self.try_recv() {
+                Ok(msg) => {
+                    buf.push(msg);
+                    count += 1;
+                }
+                Err(_) => break,
+            }
+        }
+        count
+    }
+
+    /// Calls `f` on the message that would be received next, without receiving it.
+    ///
+    /// If there is no message, this call fails just like [`try_recv`]. While `f` runs, the message
+    /// stays in the channel and other receivers wait until `f` returns before receiving from the
+    /// same channel, so `f` should be short. Senders are not blocked, except on broadcast and
+    /// priority channels.
+    ///
+    /// For the same reason, `f` must not receive from the same channel, which would deadlock. On
+    /// bounded and unbounded channels, debug builds detect this and panic instead.
+    ///
+    /// Zero-capacity and [`never`] channels never have a message to inspect. A receiver of a
+    /// broadcast channel that has fallen behind skips over the missed messages, just like
+    /// [`try_recv`] does.
+    ///
+    /// To wait for a message on one of several receivers before inspecting it, use
+    /// [`Select::peek`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, TryRecvError};
+    ///
+    /// let (s, r) = unbounded();
+    /// assert_eq!(r.peek_with(|x: &i32| *x), Err(TryRecvError::Empty));
+    ///
+    /// s.send(5).unwrap();
+    /// assert_eq!(r.peek_with(|x| *x * 2), Ok(10));
+    /// assert_eq!(r.try_recv(), Ok(5));
+    /// ```
+    ///
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    /// [`never`]: fn.never.html
+    /// [`Select::peek`]: struct.Select.html#method.peek
+    pub fn peek_with<F, R>(&self, f: F) -> Result<R, TryRecvError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.participate();
+
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.peek_with(f),
+            ReceiverFlavor::Priority(chan) => chan.peek_with(f),
+            ReceiverFlavor::List(chan) => chan.peek_with(f),
+            ReceiverFlavor::Zero(chan) => {
+                if chan.is_disconnected() {
+                    Err(TryRecvError::Disconnected)
+                } else {
+                    Err(TryRecvError::Empty)
+                }
+            }
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.peek_with(cursor, f),
+            ReceiverFlavor::Watch(chan, version) => chan.peek_with(version, f),
+            ReceiverFlavor::Oneshot(chan) => chan.peek_with(f),
+            ReceiverFlavor::After(chan) => {
+                let msg = chan.peek()?;
+                Ok(f((chan.as_msg)(&msg)))
+            }
+            ReceiverFlavor::Tick(chan) => {
+                let msg = chan.peek()?;
+                Ok(f((chan.as_msg)(&msg)))
+            }
+            ReceiverFlavor::Timer(chan) => {
+                let msg = chan.peek()?;
+                Ok(f((chan.as_msg)(&msg)))
+            }
+            ReceiverFlavor::Never(_) => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Receives the message that would be received next, but only if it satisfies `pred`.
+    ///
+    /// Returns `Ok(Some(msg))` if the message was received, or `Ok(None)` if `pred` returned
+    /// `false` and the message was left in the channel. If there is no message, this call fails
+    /// just like [`try_recv`].
+    ///
+    /// The check and the receive happen atomically: other receivers wait while `pred` runs, so no
+    /// other receiver can take the inspected message in between, and a rejected message stays at
+    /// the front of the channel. Receivers of a broadcast channel each see their own copy of the
+    /// message, so they don't wait for each other. Since other receivers wait, `pred` must not
+    /// receive from the same channel, which would deadlock. On bounded and unbounded channels,
+    /// debug builds detect this and panic instead.
+    ///
+    /// To wait for a suitable message on one of several receivers, use [`Select::peek`] and
+    /// complete the operation with [`SelectedOperation::recv_if`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    ///
+    /// assert_eq!(r.try_recv_if(|x| x % 2 == 0), Ok(None));
+    /// assert_eq!(r.try_recv_if(|x| x % 2 == 1), Ok(Some(1)));
+    /// assert_eq!(r.try_recv_if(|x| x % 2 == 0), Ok(Some(2)));
+    /// ```
+    ///
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    /// [`Select::peek`]: struct.Select.html#method.peek
+    /// [`SelectedOperation::recv_if`]: struct.SelectedOperation.html#method.recv_if
+    pub fn try_recv_if<F>(&self, pred: F) -> Result<Option<T>, TryRecvError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        self.participate();
+
         match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.try_recv(),
-            ReceiverFlavor::List(chan) => chan.try_recv(),
-            ReceiverFlavor::Zero(chan) => chan.try_recv(),
-            ReceiverFlavor::After(chan) => {
-                let msg = chan.try_recv();
-                unsafe {
-                    mem::transmute_copy::<Result<Instant, TryRecvError>, Result<T, TryRecvError>>(
-                        &msg,
-                    )
+            ReceiverFlavor::Array(chan) => chan.try_recv_if(pred),
+            ReceiverFlavor::Priority(chan) => chan.try_recv_if(pred),
+            ReceiverFlavor::List(chan) => chan.try_recv_if(pred),
+            ReceiverFlavor::Zero(chan) => {
+                if chan.is_disconnected() {
+                    Err(TryRecvError::Disconnected)
+                } else {
+                    Err(TryRecvError::Empty)
                 }
             }
-            ReceiverFlavor::Tick(chan) => {
-                let msg = chan.try_recv();
-                unsafe {
-                    mem::transmute_copy::<Result<Instant, TryRecvError>, Result<T, TryRecvError>>(
-                        &msg,
-                    )
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.try_recv_if(cursor, pred),
+            ReceiverFlavor::Watch(chan, version) => chan.try_recv_if(version, pred),
+            ReceiverFlavor::Oneshot(chan) => chan.try_recv_if(pred),
+            ReceiverFlavor::After(_) | ReceiverFlavor::Tick(_) | ReceiverFlavor::Timer(_) => {
+                // Timer messages are created on demand, so inspect the message first and then try
+                // receiving it. If another receiver takes it in between, this fails with `Empty`.
+                if self.peek_with(pred)? {
+                    self.try_recv().map(Some)
+                } else {
+                    Ok(None)
                 }
             }
-            ReceiverFlavor::Never(chan) => chan.try_recv(),
+            ReceiverFlavor::Never(_) => Err(TryRecvError::Empty),
         }
     }
 
-    /// Blocks the current thread until a message is received or the channel is empty and
-    /// disconnected.
-    ///
-    /// If the channel is empty and not disconnected, this call will block until the receive
-    /// operation can proceed. If the channel is empty and becomes disconnected, this call will
-    /// wake up and return an error.
+    /// Returns a reference to the latest value in a channel created with [`watch`].
     ///
-    /// If called on a zero-capacity channel, this method will wait for a send operation to appear
-    /// on the other side of the channel.
+    /// The value is not marked as seen, and senders block until the reference is dropped, so it
+    /// should be held only briefly. Returns `None` for channels of other kinds.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::thread;
-    /// use std::time::Duration;
-    /// use crossbeam_channel::{unbounded, RecvError};
-    ///
-    /// let (s, r) = unbounded();
+    /// use crossbeam_channel::{unbounded, watch};
     ///
-    /// thread::spawn(move || {
-    ///     thread::sleep(Duration::from_secs(1));
-    ///     s.send(5).unwrap();
-    ///     drop(s);
-    /// });
+    /// let (s, r) = watch(1);
+    /// s.send(2).unwrap();
+    /// assert_eq!(*r.borrow().unwrap(), 2);
+    /// assert_eq!(r.recv(), Ok(2));
     ///
-    /// assert_eq!(r.recv(), Ok(5));
-    /// assert_eq!(r.recv(), Err(RecvError));
+    /// let (_s, r) = unbounded::<i32>();
+    /// assert!(r.borrow().is_none());
     /// ```
-    pub fn recv(&self) -> Result<T, RecvError> {
+    ///
+    /// [`watch`]: fn.watch.html
+    pub fn borrow(&self) -> Option<WatchRef<T>> {
         match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.recv(None),
-            ReceiverFlavor::List(chan) => chan.recv(None),
-            ReceiverFlavor::Zero(chan) => chan.recv(None),
-            ReceiverFlavor::After(chan) => {
-                let msg = chan.recv(None);
-                unsafe {
-                    mem::transmute_copy::<
-                        Result<Instant, RecvTimeoutError>,
-                        Result<T, RecvTimeoutError>,
-                    >(&msg)
-                }
-            }
-            ReceiverFlavor::Tick(chan) => {
-                let msg = chan.recv(None);
-                unsafe {
-                    mem::transmute_copy::<
-                        Result<Instant, RecvTimeoutError>,
-                        Result<T, RecvTimeoutError>,
-                    >(&msg)
-                }
-            }
-            ReceiverFlavor::Never(chan) => chan.recv(None),
+            ReceiverFlavor::Watch(chan, _) => Some(WatchRef {
+                inner: chan.borrow(),
+            }),
+            _ => None,
         }
-        .map_err(|_| RecvError)
     }
 
-    /// Waits for a message to be received from the channel, but only for a limited time.
+    /// Blocks the current thread until the channel has a message for this receiver, without
+    /// receiving it.
     ///
-    /// If the channel is empty and not disconnected, this call will block until the receive
-    /// operation can proceed or the operation times out. If the channel is empty and becomes
-    /// disconnected, this call will wake up and return an error.
+    /// In a channel created with [`watch`], this waits until a value this receiver hasn't seen
+    /// yet is sent, and marks that value as seen. The value can then be inspected with
+    /// [`borrow`]. In channels of other kinds, this waits until a message can be received.
     ///
-    /// If called on a zero-capacity channel, this method will wait for a send operation to appear
-    /// on the other side of the channel.
+    /// If there are no new messages and the channel is disconnected, this call will wake up and
+    /// return an error.
     ///
     /// # Examples
     ///
     /// ```
     /// use std::thread;
     /// use std::time::Duration;
-    /// use crossbeam_channel::{unbounded, RecvTimeoutError};
+    /// use crossbeam_channel::{watch, RecvError};
     ///
-    /// let (s, r) = unbounded();
+    /// let (s, r) = watch(0);
     ///
     /// thread::spawn(move || {
-    ///     thread::sleep(Duration::from_secs(1));
-    ///     s.send(5).unwrap();
-    ///     drop(s);
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     s.send(1).unwrap();
     /// });
     ///
-    /// assert_eq!(
-    ///     r.recv_timeout(Duration::from_millis(500)),
-    ///     Err(RecvTimeoutError::Timeout),
-    /// );
-    /// assert_eq!(
-    ///     r.recv_timeout(Duration::from_secs(1)),
-    ///     Ok(5),
-    /// );
-    /// assert_eq!(
-    ///     r.recv_timeout(Duration::from_secs(1)),
-    ///     Err(RecvTimeoutError::Disconnected),
-    /// );
+    /// assert_eq!(r.changed(), Ok(()));
+    /// assert_eq!(*r.borrow().unwrap(), 1);
+    /// assert_eq!(r.changed(), Err(RecvError));
     /// ```
-    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
-        let deadline = Instant::now() + timeout;
+    ///
+    /// [`watch`]: fn.watch.html
+    /// [`borrow`]: struct.Receiver.html#method.borrow
+    pub fn changed(&self) -> Result<(), RecvError> {
+        self.participate();
 
         match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.recv(Some(deadline)),
-            ReceiverFlavor::List(chan) => chan.recv(Some(deadline)),
-            ReceiverFlavor::Zero(chan) => chan.recv(Some(deadline)),
-            ReceiverFlavor::After(chan) => {
-                let msg = chan.recv(Some(deadline));
-                unsafe {
-                    mem::transmute_copy::<
-                        Result<Instant, RecvTimeoutError>,
-                        Result<T, RecvTimeoutError>,
-                    >(&msg)
-                }
+            ReceiverFlavor::Watch(chan, version) => {
+                chan.changed(version, None).map_err(|_| RecvError)
             }
-            ReceiverFlavor::Tick(chan) => {
-                let msg = chan.recv(Some(deadline));
-                unsafe {
-                    mem::transmute_copy::<
-                        Result<Instant, RecvTimeoutError>,
-                        Result<T, RecvTimeoutError>,
-                    >(&msg)
+            _ => {
+                let mut sel = Select::new();
+                sel.recv(self);
+                sel.ready();
+
+                if self.is_empty() && self.is_disconnected() {
+                    Err(RecvError)
+                } else {
+                    Ok(())
                 }
             }
-            ReceiverFlavor::Never(chan) => chan.recv(Some(deadline)),
         }
     }
 
@@ -826,10 +3108,15 @@ impl<T> Receiver<T> {
     pub fn is_empty(&self) -> bool {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.is_empty(),
+            ReceiverFlavor::Priority(chan) => chan.is_empty(),
             ReceiverFlavor::List(chan) => chan.is_empty(),
             ReceiverFlavor::Zero(chan) => chan.is_empty(),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.pending(cursor) == 0,
+            ReceiverFlavor::Watch(chan, version) => chan.pending(version) == 0,
+            ReceiverFlavor::Oneshot(chan) => chan.is_empty(),
             ReceiverFlavor::After(chan) => chan.is_empty(),
             ReceiverFlavor::Tick(chan) => chan.is_empty(),
+            ReceiverFlavor::Timer(chan) => chan.is_empty(),
             ReceiverFlavor::Never(chan) => chan.is_empty(),
         }
     }
@@ -851,64 +3138,441 @@ impl<T> Receiver<T> {
     /// ```
     pub fn is_full(&self) -> bool {
         match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.is_full(),
-            ReceiverFlavor::List(chan) => chan.is_full(),
-            ReceiverFlavor::Zero(chan) => chan.is_full(),
-            ReceiverFlavor::After(chan) => chan.is_full(),
-            ReceiverFlavor::Tick(chan) => chan.is_full(),
-            ReceiverFlavor::Never(chan) => chan.is_full(),
+            ReceiverFlavor::Array(chan) => chan.is_full(),
+            ReceiverFlavor::Priority(chan) => chan.is_full(),
+            ReceiverFlavor::List(chan) => chan.is_full(),
+            ReceiverFlavor::Zero(chan) => chan.is_full(),
+            ReceiverFlavor::Broadcast(chan, cursor) => {
+                chan.pending(cursor) == chan.capacity().unwrap()
+            }
+            ReceiverFlavor::Watch(chan, version) => chan.pending(version) == 1,
+            ReceiverFlavor::Oneshot(chan) => chan.is_full(),
+            ReceiverFlavor::After(chan) => chan.is_full(),
+            ReceiverFlavor::Tick(chan) => chan.is_full(),
+            ReceiverFlavor::Timer(chan) => chan.is_full(),
+            ReceiverFlavor::Never(chan) => chan.is_full(),
+        }
+    }
+
+    /// Returns the number of messages in the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// assert_eq!(r.len(), 0);
+    ///
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// assert_eq!(r.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.len(),
+            ReceiverFlavor::Priority(chan) => chan.len(),
+            ReceiverFlavor::List(chan) => chan.len(),
+            ReceiverFlavor::Zero(chan) => chan.len(),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.pending(cursor),
+            ReceiverFlavor::Watch(chan, version) => chan.pending(version),
+            ReceiverFlavor::Oneshot(chan) => chan.len(),
+            ReceiverFlavor::After(chan) => chan.len(),
+            ReceiverFlavor::Tick(chan) => chan.len(),
+            ReceiverFlavor::Timer(chan) => chan.len(),
+            ReceiverFlavor::Never(chan) => chan.len(),
+        }
+    }
+
+    /// If the channel is bounded, returns its capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, unbounded};
+    ///
+    /// let (_, r) = unbounded::<i32>();
+    /// assert_eq!(r.capacity(), None);
+    ///
+    /// let (_, r) = bounded::<i32>(5);
+    /// assert_eq!(r.capacity(), Some(5));
+    ///
+    /// let (_, r) = bounded::<i32>(0);
+    /// assert_eq!(r.capacity(), Some(0));
+    /// ```
+    pub fn capacity(&self) -> Option<usize> {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.capacity(),
+            ReceiverFlavor::Priority(chan) => chan.capacity(),
+            ReceiverFlavor::List(chan) => chan.capacity(),
+            ReceiverFlavor::Zero(chan) => chan.capacity(),
+            ReceiverFlavor::Broadcast(chan, _) => chan.capacity(),
+            ReceiverFlavor::Watch(chan, _) => chan.capacity(),
+            ReceiverFlavor::Oneshot(chan) => chan.capacity(),
+            ReceiverFlavor::After(chan) => chan.capacity(),
+            ReceiverFlavor::Tick(chan) => chan.capacity(),
+            ReceiverFlavor::Timer(chan) => chan.capacity(),
+            ReceiverFlavor::Never(chan) => chan.capacity(),
+        }
+    }
+
+    /// Changes the capacity of the channel.
+    ///
+    /// Only channels created by [`bounded`] with a positive capacity can be resized. For other
+    /// channels, this method does nothing and returns `false`.
+    ///
+    /// Raising the capacity wakes up senders blocked on a full channel. Lowering it never drops
+    /// messages already in the channel; instead, new sends block until receivers bring the number
+    /// of messages below the new capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cap` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, unbounded, TrySendError};
+    ///
+    /// let (s, r) = bounded(1);
+    /// s.send(1).unwrap();
+    /// assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    ///
+    /// assert!(r.set_capacity(2));
+    /// assert_eq!(s.capacity(), Some(2));
+    /// s.send(2).unwrap();
+    ///
+    /// assert!(r.set_capacity(1));
+    /// assert_eq!(r.len(), 2);
+    /// r.recv().unwrap();
+    /// assert_eq!(s.try_send(3), Err(TrySendError::Full(3)));
+    ///
+    /// let (_s, r) = unbounded::<i32>();
+    /// assert!(!r.set_capacity(10));
+    /// ```
+    ///
+    /// [`bounded`]: fn.bounded.html
+    pub fn set_capacity(&self, cap: usize) -> bool {
+        assert!(cap > 0, "capacity must be positive");
+
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => {
+                chan.set_capacity(cap);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Restarts the schedule of a channel created by [`tick`] or [`TickBuilder`].
+    ///
+    /// The next message is delivered one period from now, and a message that is already due is
+    /// discarded. For other channels, this method does nothing and returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{tick_with_clock, Clock, TryRecvError};
+    ///
+    /// let clock = Clock::manual();
+    /// let r = tick_with_clock(Duration::from_secs(10), &clock);
+    ///
+    /// clock.advance(Duration::from_secs(9));
+    /// assert!(r.reset());
+    ///
+    /// clock.advance(Duration::from_secs(9));
+    /// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    /// clock.advance(Duration::from_secs(1));
+    /// assert!(r.try_recv().is_ok());
+    /// ```
+    ///
+    /// [`tick`]: fn.tick.html
+    /// [`TickBuilder`]: struct.TickBuilder.html
+    pub fn reset(&self) -> bool {
+        match &self.flavor {
+            ReceiverFlavor::Tick(chan) => {
+                chan.reset();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Changes the interval in which a channel created by [`tick`] or [`TickBuilder`] delivers
+    /// messages.
+    ///
+    /// The next message is delivered one new period from now, unless it is already due earlier.
+    /// Later messages follow the new period. For other channels, this method does nothing and
+    /// returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{tick_with_clock, Clock};
+    ///
+    /// let clock = Clock::manual();
+    /// let start = clock.now();
+    /// let r = tick_with_clock(Duration::from_secs(60), &clock);
+    ///
+    /// assert!(r.set_period(Duration::from_secs(1)));
+    /// clock.advance(Duration::from_secs(1));
+    /// assert_eq!(r.try_recv(), Ok(start + Duration::from_secs(1)));
+    /// clock.advance(Duration::from_secs(1));
+    /// assert_eq!(r.try_recv(), Ok(start + Duration::from_secs(2)));
+    /// ```
+    ///
+    /// [`tick`]: fn.tick.html
+    /// [`TickBuilder`]: struct.TickBuilder.html
+    pub fn set_period(&self, period: Duration) -> bool {
+        match &self.flavor {
+            ReceiverFlavor::Tick(chan) => {
+                chan.set_period(period);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the number of senders associated with the channel.
+    ///
+    /// Channels created by [`after`], [`tick`], and [`never`] have no senders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// assert_eq!(r.sender_count(), 1);
+    ///
+    /// drop(s);
+    /// assert_eq!(r.sender_count(), 0);
+    /// ```
+    ///
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
+    pub fn sender_count(&self) -> usize {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.sender_count(),
+            ReceiverFlavor::Priority(chan) => chan.sender_count(),
+            ReceiverFlavor::List(chan) => chan.sender_count(),
+            ReceiverFlavor::Zero(chan) => chan.sender_count(),
+            ReceiverFlavor::Broadcast(chan, _) => chan.sender_count(),
+            ReceiverFlavor::Watch(chan, _) => chan.sender_count(),
+            ReceiverFlavor::Oneshot(chan) => chan.sender_count(),
+            ReceiverFlavor::After(_) => 0,
+            ReceiverFlavor::Tick(_) => 0,
+            ReceiverFlavor::Timer(_) => 0,
+            ReceiverFlavor::Never(_) => 0,
+        }
+    }
+
+    /// Returns the number of receivers associated with the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (_s, r) = unbounded::<i32>();
+    /// assert_eq!(r.receiver_count(), 1);
+    ///
+    /// let r2 = r.clone();
+    /// assert_eq!(r.receiver_count(), 2);
+    ///
+    /// drop(r2);
+    /// assert_eq!(r.receiver_count(), 1);
+    /// ```
+    pub fn receiver_count(&self) -> usize {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.receiver_count(),
+            ReceiverFlavor::Priority(chan) => chan.receiver_count(),
+            ReceiverFlavor::List(chan) => chan.receiver_count(),
+            ReceiverFlavor::Zero(chan) => chan.receiver_count(),
+            ReceiverFlavor::Broadcast(chan, _) => chan.receiver_count(),
+            ReceiverFlavor::Watch(chan, _) => chan.receiver_count(),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver_count(),
+            ReceiverFlavor::After(chan) => Arc::strong_count(chan),
+            ReceiverFlavor::Tick(chan) => Arc::strong_count(chan),
+            ReceiverFlavor::Timer(chan) => Arc::strong_count(chan),
+            ReceiverFlavor::Never(_) => 1,
+        }
+    }
+
+    /// Returns the name of the channel, if it has one.
+    ///
+    /// Channels are named when created with [`bounded_named`] or [`unbounded_named`].
+    ///
+    /// This method is only available with the `debug` feature enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{never, unbounded_named};
+    ///
+    /// let (_, r) = unbounded_named::<i32>("events");
+    /// assert_eq!(r.name(), Some("events"));
+    ///
+    /// let r = never::<i32>();
+    /// assert_eq!(r.name(), None);
+    /// ```
+    ///
+    /// [`bounded_named`]: fn.bounded_named.html
+    /// [`unbounded_named`]: fn.unbounded_named.html
+    #[cfg(feature = "debug")]
+    pub fn name(&self) -> Option<&str> {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.name(),
+            ReceiverFlavor::Priority(chan) => chan.name(),
+            ReceiverFlavor::List(chan) => chan.name(),
+            ReceiverFlavor::Zero(chan) => chan.name(),
+            ReceiverFlavor::Broadcast(chan, _) => chan.name(),
+            ReceiverFlavor::Watch(chan, _) => chan.name(),
+            ReceiverFlavor::Oneshot(chan) => chan.name(),
+            ReceiverFlavor::After(_) => None,
+            ReceiverFlavor::Tick(_) => None,
+            ReceiverFlavor::Timer(_) => None,
+            ReceiverFlavor::Never(_) => None,
+        }
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    ///
+    /// A channel becomes disconnected when all senders or all receivers are dropped, or when it
+    /// gets closed with [`close`]. Channels created by [`after`], [`tick`], and [`never`] are
+    /// never disconnected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// assert!(!r.is_disconnected());
+    ///
+    /// drop(s);
+    /// assert!(r.is_disconnected());
+    /// ```
+    ///
+    /// [`close`]: struct.Receiver.html#method.close
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
+    pub fn is_disconnected(&self) -> bool {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.is_disconnected(),
+            ReceiverFlavor::Priority(chan) => chan.is_disconnected(),
+            ReceiverFlavor::List(chan) => chan.is_disconnected(),
+            ReceiverFlavor::Zero(chan) => chan.is_disconnected(),
+            ReceiverFlavor::Broadcast(chan, _) => chan.is_disconnected(),
+            ReceiverFlavor::Watch(chan, _) => chan.is_disconnected(),
+            ReceiverFlavor::Oneshot(chan) => chan.is_disconnected(),
+            ReceiverFlavor::After(_) => false,
+            ReceiverFlavor::Tick(_) => false,
+            ReceiverFlavor::Timer(_) => false,
+            ReceiverFlavor::Never(_) => false,
         }
     }
 
-    /// Returns the number of messages in the channel.
+    /// Returns a snapshot of statistics about the channel.
+    ///
+    /// Channels created by [`after`], [`tick`], [`timer`], and [`never`] don't record statistics,
+    /// so their snapshots are always empty.
+    ///
+    /// This method is only available with the `metrics` feature enabled.
     ///
     /// # Examples
     ///
     /// ```
-    /// use crossbeam_channel::unbounded;
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::bounded;
     ///
-    /// let (s, r) = unbounded();
-    /// assert_eq!(r.len(), 0);
+    /// let (s, r) = bounded(0);
     ///
-    /// s.send(1).unwrap();
-    /// s.send(2).unwrap();
-    /// assert_eq!(r.len(), 2);
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     s.send(1).unwrap();
+    /// });
+    ///
+    /// // Wait for the message.
+    /// r.recv().unwrap();
+    ///
+    /// let stats = r.stats();
+    /// assert_eq!(stats.recvs(), 1);
+    /// assert!(stats.recv_wait() > Duration::from_millis(0));
     /// ```
-    pub fn len(&self) -> usize {
+    ///
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`timer`]: fn.timer.html
+    /// [`never`]: fn.never.html
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> Stats {
         match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.len(),
-            ReceiverFlavor::List(chan) => chan.len(),
-            ReceiverFlavor::Zero(chan) => chan.len(),
-            ReceiverFlavor::After(chan) => chan.len(),
-            ReceiverFlavor::Tick(chan) => chan.len(),
-            ReceiverFlavor::Never(chan) => chan.len(),
+            ReceiverFlavor::Array(chan) => chan.stats(),
+            ReceiverFlavor::Priority(chan) => chan.stats(),
+            ReceiverFlavor::List(chan) => chan.stats(),
+            ReceiverFlavor::Zero(chan) => chan.stats(),
+            ReceiverFlavor::Broadcast(chan, _) => chan.stats(),
+            ReceiverFlavor::Watch(chan, _) => chan.stats(),
+            ReceiverFlavor::Oneshot(chan) => chan.stats(),
+            ReceiverFlavor::After(_) => Stats::default(),
+            ReceiverFlavor::Tick(_) => Stats::default(),
+            ReceiverFlavor::Timer(_) => Stats::default(),
+            ReceiverFlavor::Never(_) => Stats::default(),
         }
     }
 
-    /// If the channel is bounded, returns its capacity.
+    /// Disconnects the channel while senders and receivers are still alive.
+    ///
+    /// All blocked operations are woken up. Subsequent send operations fail, while messages
+    /// already in the channel can still be received. Receive operations fail only once the
+    /// channel becomes empty.
+    ///
+    /// Returns `true` if this call disconnected the channel, and `false` if it was already
+    /// disconnected. Channels created by [`after`], [`tick`], and [`never`] cannot be closed, so
+    /// this method always returns `false` for them.
     ///
     /// # Examples
     ///
     /// ```
-    /// use crossbeam_channel::{bounded, unbounded};
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{bounded, RecvError};
     ///
-    /// let (_, r) = unbounded::<i32>();
-    /// assert_eq!(r.capacity(), None);
+    /// let (s, r) = bounded::<i32>(0);
+    /// let r2 = r.clone();
     ///
-    /// let (_, r) = bounded::<i32>(5);
-    /// assert_eq!(r.capacity(), Some(5));
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     r2.close();
+    /// });
     ///
-    /// let (_, r) = bounded::<i32>(0);
-    /// assert_eq!(r.capacity(), Some(0));
+    /// // The blocked receive operation is woken up even though a sender is still alive.
+    /// assert_eq!(r.recv(), Err(RecvError));
+    /// assert!(s.is_disconnected());
     /// ```
-    pub fn capacity(&self) -> Option<usize> {
+    ///
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
+    pub fn close(&self) -> bool {
         match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.capacity(),
-            ReceiverFlavor::List(chan) => chan.capacity(),
-            ReceiverFlavor::Zero(chan) => chan.capacity(),
-            ReceiverFlavor::After(chan) => chan.capacity(),
-            ReceiverFlavor::Tick(chan) => chan.capacity(),
-            ReceiverFlavor::Never(chan) => chan.capacity(),
+            ReceiverFlavor::Array(chan) => chan.disconnect(),
+            ReceiverFlavor::Priority(chan) => chan.disconnect(),
+            ReceiverFlavor::List(chan) => chan.disconnect(),
+            ReceiverFlavor::Zero(chan) => chan.disconnect(),
+            ReceiverFlavor::Broadcast(chan, _) => chan.disconnect(),
+            ReceiverFlavor::Watch(chan, _) => chan.disconnect(),
+            ReceiverFlavor::Oneshot(chan) => chan.disconnect(),
+            ReceiverFlavor::After(_) => false,
+            ReceiverFlavor::Tick(_) => false,
+            ReceiverFlavor::Timer(_) => false,
+            ReceiverFlavor::Never(_) => false,
         }
     }
 
@@ -999,14 +3663,146 @@ impl<T> Receiver<T> {
     pub fn same_channel(&self, other: &Receiver<T>) -> bool {
         match (&self.flavor, &other.flavor) {
             (ReceiverFlavor::Array(a), ReceiverFlavor::Array(b)) => a == b,
+            (ReceiverFlavor::Priority(a), ReceiverFlavor::Priority(b)) => a == b,
             (ReceiverFlavor::List(a), ReceiverFlavor::List(b)) => a == b,
             (ReceiverFlavor::Zero(a), ReceiverFlavor::Zero(b)) => a == b,
+            (ReceiverFlavor::Broadcast(a, _), ReceiverFlavor::Broadcast(b, _)) => a == b,
+            (ReceiverFlavor::Watch(a, _), ReceiverFlavor::Watch(b, _)) => a == b,
+            (ReceiverFlavor::Oneshot(a), ReceiverFlavor::Oneshot(b)) => a == b,
             (ReceiverFlavor::After(a), ReceiverFlavor::After(b)) => Arc::ptr_eq(a, b),
             (ReceiverFlavor::Tick(a), ReceiverFlavor::Tick(b)) => Arc::ptr_eq(a, b),
+            (ReceiverFlavor::Timer(a), ReceiverFlavor::Timer(b)) => Arc::ptr_eq(a, b),
             (ReceiverFlavor::Never(_), ReceiverFlavor::Never(_)) => true,
             _ => false,
         }
     }
+
+    /// Turns the receiver into one that yields messages of a different type.
+    ///
+    /// Every message is converted with `f` right after it is received from the channel. No extra
+    /// thread is involved, and the returned receiver can still be used in [`Select`] and
+    /// [`select!`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// let r = r.map(|line: &str| line.len());
+    ///
+    /// s.send("hello").unwrap();
+    /// assert_eq!(r.recv(), Ok(5));
+    /// ```
+    ///
+    /// [`Select`]: struct.Select.html
+    /// [`select!`]: macro.select.html
+    pub fn map<U, F>(self, f: F) -> MappedReceiver<T, U>
+    where
+        F: Fn(T) -> U + Send + Sync + 'static,
+    {
+        adapter::map(self, f)
+    }
+
+    /// Turns the receiver into one that only yields messages satisfying a predicate.
+    ///
+    /// Messages for which `pred` returns `false` are received from the channel and dropped. No
+    /// extra thread is involved, and the returned receiver can still be used in [`Select`] and
+    /// [`select!`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, TryRecvError};
+    ///
+    /// let (s, r) = unbounded();
+    /// let r = r.filter(|n: &i32| n % 2 == 0);
+    ///
+    /// for i in 1..5 {
+    ///     s.send(i).unwrap();
+    /// }
+    /// assert_eq!(r.recv(), Ok(2));
+    /// assert_eq!(r.recv(), Ok(4));
+    /// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    /// ```
+    ///
+    /// [`Select`]: struct.Select.html
+    /// [`select!`]: macro.select.html
+    pub fn filter<F>(self, pred: F) -> FilteredReceiver<T>
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        adapter::filter(self, pred)
+    }
+
+    /// Returns a future that receives a message from the channel.
+    ///
+    /// The future resolves with the next message, or with an error if the channel is empty and
+    /// disconnected. If the channel is empty, the task waits for a message instead of blocking
+    /// the current thread.
+    ///
+    /// Dropping the future before it resolves never loses a message.
+    ///
+    /// This method is only available with the `async` feature enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018,no_run
+    /// # async fn example() {
+    /// use std::thread;
+    /// use crossbeam_channel::{unbounded, RecvError};
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     s.send(5).unwrap();
+    ///     drop(s);
+    /// });
+    ///
+    /// assert_eq!(r.recv_async().await, Ok(5));
+    /// assert_eq!(r.recv_async().await, Err(RecvError));
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn recv_async(&self) -> RecvFuture<T> {
+        RecvFuture {
+            receiver: self,
+            waiting: None,
+        }
+    }
+
+    /// Converts the receiver into a [`Stream`] of messages.
+    ///
+    /// The stream ends when the channel becomes empty and disconnected.
+    ///
+    /// This method is only available with the `async` feature enabled.
+    ///
+    /// [`Stream`]: https://docs.rs/futures-core/0.3/futures_core/stream/trait.Stream.html
+    #[cfg(feature = "async")]
+    pub fn into_stream(self) -> RecvStream<T> {
+        RecvStream {
+            receiver: self,
+            waiting: None,
+        }
+    }
+
+    /// Records the current thread as a receiver for deadlock detection.
+    #[inline]
+    fn participate(&self) {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.participate(),
+            ReceiverFlavor::Priority(chan) => chan.participate(),
+            ReceiverFlavor::List(chan) => chan.participate(),
+            ReceiverFlavor::Zero(chan) => chan.participate(),
+            ReceiverFlavor::Broadcast(chan, _) => chan.participate(),
+            ReceiverFlavor::Watch(chan, _) => chan.participate(),
+            ReceiverFlavor::Oneshot(chan) => chan.participate(),
+            ReceiverFlavor::After(_) => {}
+            ReceiverFlavor::Tick(_) => {}
+            ReceiverFlavor::Timer(_) => {}
+            ReceiverFlavor::Never(_) => {}
+        }
+    }
 }
 
 impl<T> Drop for Receiver<T> {
@@ -1014,10 +3810,15 @@ impl<T> Drop for Receiver<T> {
         unsafe {
             match &self.flavor {
                 ReceiverFlavor::Array(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Priority(chan) => chan.release(|c| c.disconnect()),
                 ReceiverFlavor::List(chan) => chan.release(|c| c.disconnect()),
                 ReceiverFlavor::Zero(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Broadcast(chan, _) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Watch(chan, _) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Oneshot(chan) => chan.release(|c| c.disconnect()),
                 ReceiverFlavor::After(_) => {}
                 ReceiverFlavor::Tick(_) => {}
+                ReceiverFlavor::Timer(_) => {}
                 ReceiverFlavor::Never(_) => {}
             }
         }
@@ -1028,10 +3829,19 @@ impl<T> Clone for Receiver<T> {
     fn clone(&self) -> Self {
         let flavor = match &self.flavor {
             ReceiverFlavor::Array(chan) => ReceiverFlavor::Array(chan.acquire()),
+            ReceiverFlavor::Priority(chan) => ReceiverFlavor::Priority(chan.acquire()),
             ReceiverFlavor::List(chan) => ReceiverFlavor::List(chan.acquire()),
             ReceiverFlavor::Zero(chan) => ReceiverFlavor::Zero(chan.acquire()),
+            ReceiverFlavor::Broadcast(chan, cursor) => {
+                ReceiverFlavor::Broadcast(chan.acquire(), cursor.clone())
+            }
+            ReceiverFlavor::Watch(chan, version) => {
+                ReceiverFlavor::Watch(chan.acquire(), version.clone())
+            }
+            ReceiverFlavor::Oneshot(chan) => ReceiverFlavor::Oneshot(chan.acquire()),
             ReceiverFlavor::After(chan) => ReceiverFlavor::After(chan.clone()),
             ReceiverFlavor::Tick(chan) => ReceiverFlavor::Tick(chan.clone()),
+            ReceiverFlavor::Timer(chan) => ReceiverFlavor::Timer(chan.clone()),
             ReceiverFlavor::Never(_) => ReceiverFlavor::Never(flavors::never::Channel::new()),
         };
 
@@ -1040,6 +3850,15 @@ impl<T> Clone for Receiver<T> {
 }
 
 impl<T> fmt::Debug for Receiver<T> {
+    #[cfg(feature = "debug")]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "Receiver {{ name: {:?}, .. }}", name),
+            None => f.pad("Receiver { .. }"),
+        }
+    }
+
+    #[cfg(not(feature = "debug"))]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.pad("Receiver { .. }")
     }
@@ -1211,12 +4030,276 @@ impl<T> fmt::Debug for IntoIter<T> {
     }
 }
 
+/// A reference to the latest value in a channel created with [`watch`].
+///
+/// This reference is created by [`Receiver::borrow`]. Senders can't replace the value while it is
+/// alive.
+///
+/// [`watch`]: fn.watch.html
+/// [`Receiver::borrow`]: struct.Receiver.html#method.borrow
+pub struct WatchRef<'a, T: 'a> {
+    inner: flavors::watch::Ref<'a, T>,
+}
+
+impl<'a, T> Deref for WatchRef<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for WatchRef<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// The registration of an asynchronous task waiting on a channel operation.
+///
+/// It is boxed so that its address, which identifies the operation, stays the same while the
+/// future that owns it moves around.
+#[cfg(feature = "async")]
+struct Waiting {
+    /// The context that wakes up the task.
+    cx: Context,
+}
+
+#[cfg(feature = "async")]
+impl Waiting {
+    /// Creates a registration for the task that owns `waker`.
+    fn new(waker: &task::Waker) -> Box<Waiting> {
+        Box::new(Waiting {
+            cx: Context::for_task(waker),
+        })
+    }
+
+    /// Returns the operation identified by this registration.
+    fn oper(&mut self) -> Operation {
+        Operation::hook(self)
+    }
+}
+
+/// A future that sends a message into a channel.
+///
+/// This future is created by [`Sender::send_async`].
+///
+/// [`Sender::send_async`]: struct.Sender.html#method.send_async
+#[cfg(feature = "async")]
+#[must_use = "futures do nothing unless polled"]
+pub struct SendFuture<'a, T: 'a> {
+    sender: &'a Sender<T>,
+    msg: Option<T>,
+    waiting: Option<Box<Waiting>>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> SendFuture<'a, T> {
+    /// Cancels the current registration, if there is one.
+    ///
+    /// Returns `false` if a receiver has already taken the message.
+    fn unregister(&mut self) -> bool {
+        if let Some(mut waiting) = self.waiting.take() {
+            let oper = waiting.oper();
+            match &self.sender.flavor {
+                SenderFlavor::Zero(chan) => match chan.unregister_message(oper) {
+                    Some(msg) => self.msg = Some(msg),
+                    None => return false,
+                },
+                _ => self.sender.unwatch(oper),
+            }
+        }
+        true
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> Unpin for SendFuture<'a, T> {}
+
+#[cfg(feature = "async")]
+impl<'a, T> Future for SendFuture<'a, T> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        loop {
+            if !this.unregister() {
+                return Poll::Ready(Ok(()));
+            }
+
+            let msg = this
+                .msg
+                .take()
+                .expect("`SendFuture` polled after completion");
+
+            let msg = match this.sender.try_send(msg) {
+                Ok(()) => return Poll::Ready(Ok(())),
+                Err(TrySendError::Disconnected(msg)) => return Poll::Ready(Err(SendError(msg))),
+                Err(TrySendError::Full(msg)) => msg,
+            };
+
+            let mut waiting = Waiting::new(cx.waker());
+            let oper = waiting.oper();
+
+            match &this.sender.flavor {
+                // A zero-capacity channel has no room to wait for, so the message itself is
+                // registered and handed over to the receiver that selects it.
+                SenderFlavor::Zero(chan) => match chan.send_or_register(msg, oper, &waiting.cx) {
+                    Ok(true) => return Poll::Ready(Ok(())),
+                    Ok(false) => {
+                        this.waiting = Some(waiting);
+                        return Poll::Pending;
+                    }
+                    Err(msg) => return Poll::Ready(Err(SendError(msg))),
+                },
+                _ => {
+                    this.msg = Some(msg);
+                    let is_ready = this.sender.watch(oper, &waiting.cx);
+                    this.waiting = Some(waiting);
+
+                    if !is_ready {
+                        return Poll::Pending;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> Drop for SendFuture<'a, T> {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> fmt::Debug for SendFuture<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("SendFuture { .. }")
+    }
+}
+
+/// Polls for a message, registering the task to be woken up if there is none.
+#[cfg(feature = "async")]
+fn poll_recv<T>(
+    receiver: &Receiver<T>,
+    waiting: &mut Option<Box<Waiting>>,
+    cx: &mut task::Context,
+) -> Poll<Result<T, RecvError>> {
+    loop {
+        if let Some(mut w) = waiting.take() {
+            receiver.unwatch(w.oper());
+        }
+
+        match receiver.try_recv() {
+            Ok(msg) => return Poll::Ready(Ok(msg)),
+            Err(TryRecvError::Disconnected) => return Poll::Ready(Err(RecvError)),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        let mut w = Waiting::new(cx.waker());
+        let is_ready = receiver.watch(w.oper(), &w.cx);
+        *waiting = Some(w);
+
+        if !is_ready {
+            return Poll::Pending;
+        }
+    }
+}
+
+/// A future that receives a message from a channel.
+///
+/// This future is created by [`Receiver::recv_async`].
+///
+/// [`Receiver::recv_async`]: struct.Receiver.html#method.recv_async
+#[cfg(feature = "async")]
+#[must_use = "futures do nothing unless polled"]
+pub struct RecvFuture<'a, T: 'a> {
+    receiver: &'a Receiver<T>,
+    waiting: Option<Box<Waiting>>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> Unpin for RecvFuture<'a, T> {}
+
+#[cfg(feature = "async")]
+impl<'a, T> Future for RecvFuture<'a, T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+        poll_recv(this.receiver, &mut this.waiting, cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> Drop for RecvFuture<'a, T> {
+    fn drop(&mut self) {
+        if let Some(mut waiting) = self.waiting.take() {
+            self.receiver.unwatch(waiting.oper());
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> fmt::Debug for RecvFuture<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("RecvFuture { .. }")
+    }
+}
+
+/// A stream of messages received from a channel.
+///
+/// This stream is created by [`Receiver::into_stream`].
+///
+/// [`Receiver::into_stream`]: struct.Receiver.html#method.into_stream
+#[cfg(feature = "async")]
+#[must_use = "streams do nothing unless polled"]
+pub struct RecvStream<T> {
+    receiver: Receiver<T>,
+    waiting: Option<Box<Waiting>>,
+}
+
+#[cfg(feature = "async")]
+impl<T> Unpin for RecvStream<T> {}
+
+#[cfg(feature = "async")]
+impl<T> Stream for RecvStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context) -> Poll<Option<T>> {
+        let this = &mut *self;
+        poll_recv(&this.receiver, &mut this.waiting, cx).map(Result::ok)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> Drop for RecvStream<T> {
+    fn drop(&mut self) {
+        if let Some(mut waiting) = self.waiting.take() {
+            self.receiver.unwatch(waiting.oper());
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> fmt::Debug for RecvStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("RecvStream { .. }")
+    }
+}
+
 impl<T> SelectHandle for Sender<T> {
     fn try_select(&self, token: &mut Token) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().try_select(token),
+            SenderFlavor::Priority(chan) => chan.sender().try_select(token),
             SenderFlavor::List(chan) => chan.sender().try_select(token),
             SenderFlavor::Zero(chan) => chan.sender().try_select(token),
+            SenderFlavor::Broadcast(chan) => chan.sender().try_select(token),
+            SenderFlavor::Watch(chan) => chan.sender().try_select(token),
         }
     }
 
@@ -1225,50 +4308,82 @@ impl<T> SelectHandle for Sender<T> {
     }
 
     fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.participate();
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Priority(chan) => chan.sender().register(oper, cx),
             SenderFlavor::List(chan) => chan.sender().register(oper, cx),
             SenderFlavor::Zero(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Broadcast(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Watch(chan) => chan.sender().register(oper, cx),
         }
     }
 
     fn unregister(&self, oper: Operation) {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().unregister(oper),
+            SenderFlavor::Priority(chan) => chan.sender().unregister(oper),
             SenderFlavor::List(chan) => chan.sender().unregister(oper),
             SenderFlavor::Zero(chan) => chan.sender().unregister(oper),
+            SenderFlavor::Broadcast(chan) => chan.sender().unregister(oper),
+            SenderFlavor::Watch(chan) => chan.sender().unregister(oper),
         }
     }
 
     fn accept(&self, token: &mut Token, cx: &Context) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Priority(chan) => chan.sender().accept(token, cx),
             SenderFlavor::List(chan) => chan.sender().accept(token, cx),
             SenderFlavor::Zero(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Broadcast(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Watch(chan) => chan.sender().accept(token, cx),
         }
     }
 
     fn is_ready(&self) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().is_ready(),
+            SenderFlavor::Priority(chan) => chan.sender().is_ready(),
             SenderFlavor::List(chan) => chan.sender().is_ready(),
             SenderFlavor::Zero(chan) => chan.sender().is_ready(),
+            SenderFlavor::Broadcast(chan) => chan.sender().is_ready(),
+            SenderFlavor::Watch(chan) => chan.sender().is_ready(),
         }
     }
 
     fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.participate();
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().watch(oper, cx),
+            SenderFlavor::Priority(chan) => chan.sender().watch(oper, cx),
             SenderFlavor::List(chan) => chan.sender().watch(oper, cx),
             SenderFlavor::Zero(chan) => chan.sender().watch(oper, cx),
+            SenderFlavor::Broadcast(chan) => chan.sender().watch(oper, cx),
+            SenderFlavor::Watch(chan) => chan.sender().watch(oper, cx),
         }
     }
 
     fn unwatch(&self, oper: Operation) {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().unwatch(oper),
+            SenderFlavor::Priority(chan) => chan.sender().unwatch(oper),
             SenderFlavor::List(chan) => chan.sender().unwatch(oper),
             SenderFlavor::Zero(chan) => chan.sender().unwatch(oper),
+            SenderFlavor::Broadcast(chan) => chan.sender().unwatch(oper),
+            SenderFlavor::Watch(chan) => chan.sender().unwatch(oper),
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn waited(&self, dur: Duration) {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.metrics().waited_to_send(dur),
+            SenderFlavor::Priority(chan) => chan.metrics().waited_to_send(dur),
+            SenderFlavor::List(chan) => chan.metrics().waited_to_send(dur),
+            SenderFlavor::Zero(chan) => chan.metrics().waited_to_send(dur),
+            SenderFlavor::Broadcast(chan) => chan.metrics().waited_to_send(dur),
+            SenderFlavor::Watch(chan) => chan.metrics().waited_to_send(dur),
         }
     }
 }
@@ -1277,10 +4392,15 @@ impl<T> SelectHandle for Receiver<T> {
     fn try_select(&self, token: &mut Token) -> bool {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Priority(chan) => chan.receiver().try_select(token),
             ReceiverFlavor::List(chan) => chan.receiver().try_select(token),
             ReceiverFlavor::Zero(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.receiver(cursor).try_select(token),
+            ReceiverFlavor::Watch(chan, version) => chan.receiver(version).try_select(token),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().try_select(token),
             ReceiverFlavor::After(chan) => chan.try_select(token),
             ReceiverFlavor::Tick(chan) => chan.try_select(token),
+            ReceiverFlavor::Timer(chan) => chan.try_select(token),
             ReceiverFlavor::Never(chan) => chan.try_select(token),
         }
     }
@@ -1288,21 +4408,32 @@ impl<T> SelectHandle for Receiver<T> {
     fn deadline(&self) -> Option<Instant> {
         match &self.flavor {
             ReceiverFlavor::Array(_) => None,
+            ReceiverFlavor::Priority(_) => None,
             ReceiverFlavor::List(_) => None,
             ReceiverFlavor::Zero(_) => None,
+            ReceiverFlavor::Broadcast(..) => None,
+            ReceiverFlavor::Watch(..) => None,
+            ReceiverFlavor::Oneshot(_) => None,
             ReceiverFlavor::After(chan) => chan.deadline(),
             ReceiverFlavor::Tick(chan) => chan.deadline(),
+            ReceiverFlavor::Timer(chan) => chan.deadline(),
             ReceiverFlavor::Never(chan) => chan.deadline(),
         }
     }
 
     fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.participate();
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Priority(chan) => chan.receiver().register(oper, cx),
             ReceiverFlavor::List(chan) => chan.receiver().register(oper, cx),
             ReceiverFlavor::Zero(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.receiver(cursor).register(oper, cx),
+            ReceiverFlavor::Watch(chan, version) => chan.receiver(version).register(oper, cx),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().register(oper, cx),
             ReceiverFlavor::After(chan) => chan.register(oper, cx),
             ReceiverFlavor::Tick(chan) => chan.register(oper, cx),
+            ReceiverFlavor::Timer(chan) => chan.register(oper, cx),
             ReceiverFlavor::Never(chan) => chan.register(oper, cx),
         }
     }
@@ -1310,10 +4441,15 @@ impl<T> SelectHandle for Receiver<T> {
     fn unregister(&self, oper: Operation) {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Priority(chan) => chan.receiver().unregister(oper),
             ReceiverFlavor::List(chan) => chan.receiver().unregister(oper),
             ReceiverFlavor::Zero(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.receiver(cursor).unregister(oper),
+            ReceiverFlavor::Watch(chan, version) => chan.receiver(version).unregister(oper),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().unregister(oper),
             ReceiverFlavor::After(chan) => chan.unregister(oper),
             ReceiverFlavor::Tick(chan) => chan.unregister(oper),
+            ReceiverFlavor::Timer(chan) => chan.unregister(oper),
             ReceiverFlavor::Never(chan) => chan.unregister(oper),
         }
     }
@@ -1321,10 +4457,15 @@ impl<T> SelectHandle for Receiver<T> {
     fn accept(&self, token: &mut Token, cx: &Context) -> bool {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Priority(chan) => chan.receiver().accept(token, cx),
             ReceiverFlavor::List(chan) => chan.receiver().accept(token, cx),
             ReceiverFlavor::Zero(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.receiver(cursor).accept(token, cx),
+            ReceiverFlavor::Watch(chan, version) => chan.receiver(version).accept(token, cx),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().accept(token, cx),
             ReceiverFlavor::After(chan) => chan.accept(token, cx),
             ReceiverFlavor::Tick(chan) => chan.accept(token, cx),
+            ReceiverFlavor::Timer(chan) => chan.accept(token, cx),
             ReceiverFlavor::Never(chan) => chan.accept(token, cx),
         }
     }
@@ -1332,21 +4473,32 @@ impl<T> SelectHandle for Receiver<T> {
     fn is_ready(&self) -> bool {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().is_ready(),
+            ReceiverFlavor::Priority(chan) => chan.receiver().is_ready(),
             ReceiverFlavor::List(chan) => chan.receiver().is_ready(),
             ReceiverFlavor::Zero(chan) => chan.receiver().is_ready(),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.receiver(cursor).is_ready(),
+            ReceiverFlavor::Watch(chan, version) => chan.receiver(version).is_ready(),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().is_ready(),
             ReceiverFlavor::After(chan) => chan.is_ready(),
             ReceiverFlavor::Tick(chan) => chan.is_ready(),
+            ReceiverFlavor::Timer(chan) => chan.is_ready(),
             ReceiverFlavor::Never(chan) => chan.is_ready(),
         }
     }
 
     fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.participate();
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().watch(oper, cx),
+            ReceiverFlavor::Priority(chan) => chan.receiver().watch(oper, cx),
             ReceiverFlavor::List(chan) => chan.receiver().watch(oper, cx),
             ReceiverFlavor::Zero(chan) => chan.receiver().watch(oper, cx),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.receiver(cursor).watch(oper, cx),
+            ReceiverFlavor::Watch(chan, version) => chan.receiver(version).watch(oper, cx),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().watch(oper, cx),
             ReceiverFlavor::After(chan) => chan.watch(oper, cx),
             ReceiverFlavor::Tick(chan) => chan.watch(oper, cx),
+            ReceiverFlavor::Timer(chan) => chan.watch(oper, cx),
             ReceiverFlavor::Never(chan) => chan.watch(oper, cx),
         }
     }
@@ -1354,36 +4506,121 @@ impl<T> SelectHandle for Receiver<T> {
     fn unwatch(&self, oper: Operation) {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().unwatch(oper),
+            ReceiverFlavor::Priority(chan) => chan.receiver().unwatch(oper),
             ReceiverFlavor::List(chan) => chan.receiver().unwatch(oper),
             ReceiverFlavor::Zero(chan) => chan.receiver().unwatch(oper),
+            ReceiverFlavor::Broadcast(chan, cursor) => chan.receiver(cursor).unwatch(oper),
+            ReceiverFlavor::Watch(chan, version) => chan.receiver(version).unwatch(oper),
+            ReceiverFlavor::Oneshot(chan) => chan.receiver().unwatch(oper),
             ReceiverFlavor::After(chan) => chan.unwatch(oper),
             ReceiverFlavor::Tick(chan) => chan.unwatch(oper),
+            ReceiverFlavor::Timer(chan) => chan.unwatch(oper),
             ReceiverFlavor::Never(chan) => chan.unwatch(oper),
         }
     }
+
+    #[cfg(feature = "metrics")]
+    fn waited(&self, dur: Duration) {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.metrics().waited_to_recv(dur),
+            ReceiverFlavor::Priority(chan) => chan.metrics().waited_to_recv(dur),
+            ReceiverFlavor::List(chan) => chan.metrics().waited_to_recv(dur),
+            ReceiverFlavor::Zero(chan) => chan.metrics().waited_to_recv(dur),
+            ReceiverFlavor::Broadcast(chan, _) => chan.metrics().waited_to_recv(dur),
+            ReceiverFlavor::Watch(chan, _) => chan.metrics().waited_to_recv(dur),
+            ReceiverFlavor::Oneshot(chan) => chan.metrics().waited_to_recv(dur),
+            ReceiverFlavor::After(_) => {}
+            ReceiverFlavor::Tick(_) => {}
+            ReceiverFlavor::Timer(_) => {}
+            ReceiverFlavor::Never(_) => {}
+        }
+    }
+}
+
+impl<T> SendHandle for Sender<T> {
+    type Msg = T;
+    type Sent = T;
+
+    unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        write(self, token, msg)
+    }
+}
+
+impl<T> RecvHandle for Receiver<T> {
+    type Msg = T;
+
+    unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        read(self, token)
+    }
+}
+
+/// Returns `true` if a selected receive operation is completed with a packet from the sender.
+///
+/// Such operations are accepted by waiting for the packet rather than by selecting again.
+pub fn expects_packet<T>(r: &Receiver<T>) -> bool {
+    match &r.flavor {
+        ReceiverFlavor::Zero(_) => true,
+        _ => false,
+    }
 }
 
 /// Writes a message into the channel.
 pub unsafe fn write<T>(s: &Sender<T>, token: &mut Token, msg: T) -> Result<(), T> {
+    s.participate();
     match &s.flavor {
         SenderFlavor::Array(chan) => chan.write(token, msg),
+        SenderFlavor::Priority(chan) => chan.write(token, msg),
         SenderFlavor::List(chan) => chan.write(token, msg),
         SenderFlavor::Zero(chan) => chan.write(token, msg),
+        SenderFlavor::Broadcast(chan) => chan.write(token, msg),
+        SenderFlavor::Watch(chan) => chan.write(token, msg),
     }
 }
 
 /// Reads a message from the channel.
 pub unsafe fn read<T>(r: &Receiver<T>, token: &mut Token) -> Result<T, ()> {
+    r.participate();
     match &r.flavor {
         ReceiverFlavor::Array(chan) => chan.read(token),
+        ReceiverFlavor::Priority(chan) => chan.read(token),
         ReceiverFlavor::List(chan) => chan.read(token),
         ReceiverFlavor::Zero(chan) => chan.read(token),
-        ReceiverFlavor::After(chan) => {
-            mem::transmute_copy::<Result<Instant, ()>, Result<T, ()>>(&chan.read(token))
-        }
-        ReceiverFlavor::Tick(chan) => {
-            mem::transmute_copy::<Result<Instant, ()>, Result<T, ()>>(&chan.read(token))
-        }
+        ReceiverFlavor::Broadcast(chan, _) => chan.read(token),
+        ReceiverFlavor::Watch(chan, _) => chan.read(token),
+        ReceiverFlavor::Oneshot(chan) => chan.read(token),
+        ReceiverFlavor::After(chan) => chan.read(token).map(|i| chan.msg(i)),
+        ReceiverFlavor::Tick(chan) => chan.read(token).map(|i| chan.msg(i)),
+        ReceiverFlavor::Timer(chan) => chan.read(token).map(|i| chan.msg(i)),
         ReceiverFlavor::Never(chan) => chan.read(token),
     }
 }
+
+/// Attempts to select a conditional receive operation.
+///
+/// The message that would be received next is not locked, but bounded and unbounded channels
+/// remember where it was found so that `read_if` receives only that message.
+pub fn start_peek<T>(r: &Receiver<T>, token: &mut Token) -> bool {
+    match &r.flavor {
+        ReceiverFlavor::Array(chan) => chan.start_peek(token),
+        ReceiverFlavor::List(chan) => chan.start_peek(token),
+        _ => r.peek_with(|_| ()) != Err(TryRecvError::Empty),
+    }
+}
+
+/// Receives the message selected by `start_peek` only if it satisfies `pred`.
+pub unsafe fn read_if<T, F>(
+    r: &Receiver<T>,
+    token: &mut Token,
+    pred: F,
+) -> Result<Option<T>, TryRecvError>
+where
+    F: FnOnce(&T) -> bool,
+{
+    r.participate();
+    match &r.flavor {
+        ReceiverFlavor::Array(chan) => chan.read_if(token, pred),
+        ReceiverFlavor::List(chan) => chan.read_if(token, pred),
+        // Another receiver may have taken the message in the meantime.
+        _ => r.try_recv_if(pred),
+    }
+}
@@ -0,0 +1,205 @@
+//! Clocks that measure time for timer channels.
+
+use std::fmt;
+use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossbeam_utils::Backoff;
+
+use utils::Spinlock;
+use waker::SyncWaker;
+
+/// A source of time for channels created by [`after_with_clock`], [`tick_with_clock`], and
+/// [`timer_with_clock`].
+///
+/// The system clock follows real time. A manual clock stands still until it is moved forward with
+/// [`advance`], which makes timer channels deterministic and lets tests run without sleeping.
+/// Threads blocked on a timer channel driven by a manual clock, including in [`Select`], are woken
+/// up when the clock is advanced past the time they are waiting for.
+///
+/// Cloning a clock creates a new handle to the same clock.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use crossbeam_channel::{after_with_clock, Clock, TryRecvError};
+///
+/// let clock = Clock::manual();
+/// let start = clock.now();
+/// let r = after_with_clock(Duration::from_secs(60), &clock);
+///
+/// clock.advance(Duration::from_secs(59));
+/// assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+///
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(r.try_recv(), Ok(start + Duration::from_secs(60)));
+/// ```
+///
+/// [`after_with_clock`]: fn.after_with_clock.html
+/// [`tick_with_clock`]: fn.tick_with_clock.html
+/// [`timer_with_clock`]: fn.timer_with_clock.html
+/// [`advance`]: struct.Clock.html#method.advance
+/// [`Select`]: struct.Select.html
+#[derive(Clone)]
+pub struct Clock {
+    /// The state of a manual clock, or `None` for the system clock.
+    manual: Option<Arc<Manual>>,
+}
+
+impl UnwindSafe for Clock {}
+impl RefUnwindSafe for Clock {}
+
+/// A clock that only moves forward when advanced.
+struct Manual {
+    /// The current time.
+    now: Spinlock<Instant>,
+
+    /// Threads waiting for the clock to advance.
+    waker: SyncWaker,
+}
+
+impl Clock {
+    /// Returns the system clock, which follows real time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Instant;
+    /// use crossbeam_channel::Clock;
+    ///
+    /// let clock = Clock::system();
+    /// assert!(clock.now() <= Instant::now());
+    /// ```
+    pub fn system() -> Clock {
+        Clock { manual: None }
+    }
+
+    /// Creates a manual clock, which starts at the current time and only moves forward when
+    /// advanced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::Clock;
+    ///
+    /// let clock = Clock::manual();
+    /// let start = clock.now();
+    ///
+    /// thread::sleep(Duration::from_millis(10));
+    /// assert_eq!(clock.now(), start);
+    /// ```
+    pub fn manual() -> Clock {
+        Clock {
+            manual: Some(Arc::new(Manual {
+                now: Spinlock::new(Instant::now()),
+                waker: SyncWaker::new(),
+            })),
+        }
+    }
+
+    /// Returns the current time according to this clock.
+    pub fn now(&self) -> Instant {
+        match &self.manual {
+            None => Instant::now(),
+            Some(manual) => *manual.now.lock(),
+        }
+    }
+
+    /// Moves a manual clock forward by `dur` and wakes up threads waiting on it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is the system clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::Clock;
+    ///
+    /// let clock = Clock::manual();
+    /// let start = clock.now();
+    ///
+    /// clock.advance(Duration::from_secs(5));
+    /// assert_eq!(clock.now(), start + Duration::from_secs(5));
+    /// ```
+    pub fn advance(&self, dur: Duration) {
+        let manual = self
+            .manual
+            .as_ref()
+            .expect("the system clock can't be advanced");
+
+        {
+            let mut now = manual.now.lock();
+            *now += dur;
+        }
+        manual.waker.notify_all();
+    }
+
+    /// Blocks until at least `n` operations are waiting for a manual clock to advance.
+    ///
+    /// An operation is waiting once it is blocked on a timer channel driven by this clock, or on
+    /// a timeout measured by it. This is useful for advancing the clock only after another thread
+    /// has started waiting, without guessing how long that takes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is the system clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{after_with_clock, Clock};
+    ///
+    /// let clock = Clock::manual();
+    /// let r = after_with_clock(Duration::from_secs(60), &clock);
+    ///
+    /// let c = clock.clone();
+    /// let handle = thread::spawn(move || {
+    ///     c.wait_for_waiters(1);
+    ///     c.advance(Duration::from_secs(60));
+    /// });
+    ///
+    /// assert!(r.recv().is_ok());
+    /// handle.join().unwrap();
+    /// ```
+    pub fn wait_for_waiters(&self, n: usize) {
+        let manual = self
+            .manual
+            .as_ref()
+            .expect("the system clock has no waiters");
+
+        let backoff = Backoff::new();
+        while manual.waker.waiting() < n {
+            backoff.snooze();
+        }
+    }
+
+    /// Returns `true` if this is a manual clock.
+    pub fn is_manual(&self) -> bool {
+        self.manual.is_some()
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Clock {
+        Clock::system()
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Clock { .. }")
+    }
+}
+
+/// Returns the waker notified whenever a manual clock advances, or `None` for the system clock.
+pub fn waker(clock: &Clock) -> Option<&SyncWaker> {
+    clock.manual.as_ref().map(|manual| &manual.waker)
+}
@@ -3,12 +3,16 @@
 use std::cell::Cell;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+#[cfg(feature = "async")]
+use std::task::Waker;
 use std::thread::{self, Thread, ThreadId};
 use std::time::Instant;
 
 use crossbeam_utils::Backoff;
 
+use deadlock::WaitState;
 use select::Selected;
+use wheel;
 
 /// Thread-local context used in select.
 #[derive(Debug, Clone)]
@@ -25,11 +29,25 @@ struct Inner {
     /// A slot into which another thread may store a pointer to its `Packet`.
     packet: AtomicUsize,
 
-    /// Thread handle.
-    thread: Thread,
+    /// The owner of this context.
+    owner: Owner,
 
-    /// Thread id.
-    thread_id: ThreadId,
+    /// `true` if watched operations stay registered after being notified.
+    persistent: bool,
+
+    /// What the owner is waiting for, used for deadlock detection.
+    wait: WaitState,
+}
+
+/// The owner of a context, which gets woken up when an operation is selected.
+#[derive(Debug)]
+enum Owner {
+    /// A thread, along with its id.
+    Thread(Thread, ThreadId),
+
+    /// An asynchronous task.
+    #[cfg(feature = "async")]
+    Task(Waker),
 }
 
 impl Context {
@@ -66,23 +84,70 @@ impl Context {
     /// Creates a new `Context`.
     #[cold]
     fn new() -> Context {
+        let thread = thread::current();
+        let thread_id = thread.id();
+
+        Context {
+            inner: Arc::new(Inner {
+                select: AtomicUsize::new(Selected::Waiting.into()),
+                packet: AtomicUsize::new(0),
+                owner: Owner::Thread(thread, thread_id),
+                persistent: false,
+                wait: WaitState::new(),
+            }),
+        }
+    }
+
+    /// Creates a new `Context` whose watched operations stay registered after being notified.
+    ///
+    /// Such a context is owned by the current thread and can be reused for waiting any number of
+    /// times by calling `reset` before each wait. Its operations must be explicitly unwatched.
+    #[cold]
+    pub fn persistent() -> Context {
+        let thread = thread::current();
+        let thread_id = thread.id();
+
+        Context {
+            inner: Arc::new(Inner {
+                select: AtomicUsize::new(Selected::Waiting.into()),
+                packet: AtomicUsize::new(0),
+                owner: Owner::Thread(thread, thread_id),
+                persistent: true,
+                wait: WaitState::new(),
+            }),
+        }
+    }
+
+    /// Creates a new `Context` owned by the asynchronous task `waker` belongs to.
+    ///
+    /// Such a context must not be used with `wait_until`. Instead, the task gets woken up when an
+    /// operation is selected and should check the selected operation the next time it is polled.
+    #[cfg(feature = "async")]
+    #[cold]
+    pub fn for_task(waker: &Waker) -> Context {
         Context {
             inner: Arc::new(Inner {
                 select: AtomicUsize::new(Selected::Waiting.into()),
                 packet: AtomicUsize::new(0),
-                thread: thread::current(),
-                thread_id: thread::current().id(),
+                owner: Owner::Task(waker.clone()),
+                persistent: false,
+                wait: WaitState::new(),
             }),
         }
     }
 
     /// Resets `select` and `packet`.
     #[inline]
-    fn reset(&self) {
+    pub fn reset(&self) {
         self.inner
             .select
             .store(Selected::Waiting.into(), Ordering::Release);
         self.inner.packet.store(0, Ordering::Release);
+
+        // Operations of a persistent context stay registered, so it keeps waiting for them.
+        if !self.inner.persistent {
+            self.inner.wait.track();
+        }
     }
 
     /// Attempts to select an operation.
@@ -102,6 +167,12 @@ impl Context {
             .map_err(|e| e.into())
     }
 
+    /// Returns `true` if watched operations stay registered after being notified.
+    #[inline]
+    pub fn is_persistent(&self) -> bool {
+        self.inner.persistent
+    }
+
     /// Returns the selected operation.
     #[inline]
     pub fn selected(&self) -> Selected {
@@ -118,6 +189,12 @@ impl Context {
         }
     }
 
+    /// Returns `true` if a packet has been provided.
+    #[inline]
+    pub fn has_packet(&self) -> bool {
+        self.inner.packet.load(Ordering::Acquire) != 0
+    }
+
     /// Waits until a packet is provided and returns it.
     #[inline]
     pub fn wait_packet(&self) -> usize {
@@ -151,41 +228,90 @@ impl Context {
             }
         }
 
-        loop {
+        // Without a deadline, the current thread might be blocked forever.
+        self.inner.wait.enter(deadline.is_none());
+
+        // If there's a deadline, let the timer wheel unpark the current thread once it's reached.
+        let timer = match deadline {
+            Some(end) if Instant::now() < end => {
+                let cx = self.clone();
+                Some(wheel::schedule(end, Box::new(move || cx.unpark())))
+            }
+            _ => None,
+        };
+
+        let sel = loop {
             // Check whether an operation has been selected.
             let sel = Selected::from(self.inner.select.load(Ordering::Acquire));
             if sel != Selected::Waiting {
-                return sel;
+                break sel;
             }
 
-            // If there's a deadline, park the current thread until the deadline is reached.
             if let Some(end) = deadline {
                 let now = Instant::now();
 
-                if now < end {
-                    thread::park_timeout(end - now);
-                } else {
+                if now >= end {
                     // The deadline has been reached. Try aborting select.
-                    return match self.try_select(Selected::Aborted) {
+                    break match self.try_select(Selected::Aborted) {
                         Ok(()) => Selected::Aborted,
                         Err(s) => s,
                     };
                 }
+
+                // The timer fires early if the wheel can't start its thread, so don't rely on it
+                // once it has fired.
+                match &timer {
+                    Some(key) if key.is_pending() => thread::park(),
+                    _ => thread::park_timeout(end - now),
+                }
             } else {
                 thread::park();
             }
+        };
+
+        self.inner.wait.leave();
+
+        if let Some(key) = timer {
+            wheel::cancel(&key);
         }
+        sel
     }
 
-    /// Unparks the thread this context belongs to.
+    /// Unparks the thread or wakes up the task this context belongs to.
     #[inline]
     pub fn unpark(&self) {
-        self.inner.thread.unpark();
+        match &self.inner.owner {
+            Owner::Thread(thread, _) => thread.unpark(),
+            #[cfg(feature = "async")]
+            Owner::Task(waker) => waker.wake_by_ref(),
+        }
+    }
+
+    /// Marks the context as waiting for operations that are not on channels.
+    ///
+    /// Deadlock detection can't tell whether such operations will ever complete, so it never
+    /// considers the owner of the context blocked.
+    #[inline]
+    pub fn untrack(&self) {
+        self.inner.wait.untrack();
+    }
+
+    /// Returns `true` if the owner is waiting without a deadline and only for channels, and no
+    /// operation has been selected yet.
+    #[cfg(feature = "deadlock-detection")]
+    pub fn is_blocked_forever(&self) -> bool {
+        self.inner.wait.is_blocked_forever() && self.selected() == Selected::Waiting
     }
 
     /// Returns the id of the thread this context belongs to.
+    ///
+    /// Returns `None` if the context belongs to an asynchronous task rather than a thread.
     #[inline]
-    pub fn thread_id(&self) -> ThreadId {
-        self.inner.thread_id
+    pub fn thread_id(&self) -> Option<ThreadId> {
+        match &self.inner.owner {
+            Owner::Thread(_, thread_id) => Some(*thread_id),
+            #[cfg(feature = "async")]
+            Owner::Task(_) => None,
+        }
     }
 }
@@ -5,6 +5,11 @@ use std::ops;
 use std::process;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
+use deadlock::Participants;
+#[cfg(feature = "debug")]
+use debug;
+use debug::Inspect;
+
 /// Reference counter internals.
 struct Counter<C> {
     /// The number of senders associated with the channel.
@@ -16,23 +21,80 @@ struct Counter<C> {
     /// Set to `true` if the last sender or the last receiver reference deallocates the channel.
     destroy: AtomicBool,
 
+    /// The number of weak sender references, plus one held jointly by all sender and receiver
+    /// references.
+    weak: AtomicUsize,
+
+    /// The name of the channel.
+    #[cfg(feature = "debug")]
+    name: Option<String>,
+
+    /// Threads that have used the channel.
+    participants: Participants,
+
     /// The internal channel.
     chan: C,
 }
 
-/// Wraps a channel into the reference counter.
-pub fn new<C>(chan: C) -> (Sender<C>, Receiver<C>) {
+/// Wraps a channel named `name` into the reference counter.
+///
+/// The name is only kept with the `debug` feature enabled.
+pub fn new<C: Inspect>(chan: C, name: Option<String>) -> (Sender<C>, Receiver<C>) {
+    #[cfg(not(feature = "debug"))]
+    drop(name);
+
     let counter = Box::into_raw(Box::new(Counter {
         senders: AtomicUsize::new(1),
         receivers: AtomicUsize::new(1),
         destroy: AtomicBool::new(false),
+        weak: AtomicUsize::new(1),
+        #[cfg(feature = "debug")]
+        name,
+        participants: Participants::new(),
         chan,
     }));
+
+    #[cfg(feature = "debug")]
+    unsafe {
+        debug::register(counter as *const u8, snapshot::<C>);
+    }
+
     let s = Sender { counter };
     let r = Receiver { counter };
     (s, r)
 }
 
+/// Releases a weak reference.
+///
+/// The channel is deallocated if this was the last reference of any kind.
+unsafe fn release_weak<C>(counter: *mut Counter<C>) {
+    if (*counter).weak.fetch_sub(1, Ordering::AcqRel) == 1 {
+        #[cfg(feature = "debug")]
+        debug::unregister(counter as *const u8);
+
+        drop(Box::from_raw(counter));
+    }
+}
+
+/// Takes a snapshot of the channel inside the counter at address `counter`.
+#[cfg(feature = "debug")]
+unsafe fn snapshot<C: Inspect>(counter: *const u8) -> debug::Snapshot {
+    let counter = &*(counter as *const Counter<C>);
+    let blocked = counter.chan.blocked();
+
+    debug::Snapshot {
+        info: debug::describe(
+            &counter.chan,
+            counter.name.as_ref().map(|s| s.as_str()),
+            &blocked,
+        ),
+        #[cfg(feature = "deadlock-detection")]
+        blocked,
+        #[cfg(feature = "deadlock-detection")]
+        participants: counter.participants.get(),
+    }
+}
+
 /// The sending side.
 pub struct Sender<C> {
     counter: *mut Counter<C>,
@@ -64,14 +126,54 @@ impl<C> Sender<C> {
     ///
     /// Function `disconnect` will be called if this is the last sender reference.
     pub unsafe fn release<F: FnOnce(&C) -> bool>(&self, disconnect: F) {
+        self.counter().participants.release_sender(false);
+
         if self.counter().senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // Nobody is left on this side, so forget the threads that have used it.
+            self.counter().participants.release_sender(true);
             disconnect(&self.counter().chan);
 
             if self.counter().destroy.swap(true, Ordering::AcqRel) {
-                drop(Box::from_raw(self.counter));
+                release_weak(self.counter);
             }
         }
     }
+
+    /// Records the current thread as a sender for deadlock detection.
+    #[inline]
+    pub fn participate(&self) {
+        self.counter().participants.sender();
+    }
+
+    /// Returns the current number of sender references.
+    pub fn sender_count(&self) -> usize {
+        self.counter().senders.load(Ordering::SeqCst)
+    }
+
+    /// Returns the current number of receiver references.
+    pub fn receiver_count(&self) -> usize {
+        self.counter().receivers.load(Ordering::SeqCst)
+    }
+
+    /// Returns the name of the channel.
+    #[cfg(feature = "debug")]
+    pub fn name(&self) -> Option<&str> {
+        self.counter().name.as_ref().map(|s| s.as_str())
+    }
+
+    /// Creates a weak sender reference.
+    pub fn downgrade(&self) -> WeakSender<C> {
+        let count = self.counter().weak.fetch_add(1, Ordering::Relaxed);
+
+        // Same as in `acquire`, abort if the count becomes very large.
+        if count > isize::MAX as usize {
+            process::abort();
+        }
+
+        WeakSender {
+            counter: self.counter,
+        }
+    }
 }
 
 impl<C> ops::Deref for Sender<C> {
@@ -119,14 +221,40 @@ impl<C> Receiver<C> {
     ///
     /// Function `disconnect` will be called if this is the last receiver reference.
     pub unsafe fn release<F: FnOnce(&C) -> bool>(&self, disconnect: F) {
+        self.counter().participants.release_receiver(false);
+
         if self.counter().receivers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // Nobody is left on this side, so forget the threads that have used it.
+            self.counter().participants.release_receiver(true);
             disconnect(&self.counter().chan);
 
             if self.counter().destroy.swap(true, Ordering::AcqRel) {
-                drop(Box::from_raw(self.counter));
+                release_weak(self.counter);
             }
         }
     }
+
+    /// Records the current thread as a receiver for deadlock detection.
+    #[inline]
+    pub fn participate(&self) {
+        self.counter().participants.receiver();
+    }
+
+    /// Returns the current number of sender references.
+    pub fn sender_count(&self) -> usize {
+        self.counter().senders.load(Ordering::SeqCst)
+    }
+
+    /// Returns the current number of receiver references.
+    pub fn receiver_count(&self) -> usize {
+        self.counter().receivers.load(Ordering::SeqCst)
+    }
+
+    /// Returns the name of the channel.
+    #[cfg(feature = "debug")]
+    pub fn name(&self) -> Option<&str> {
+        self.counter().name.as_ref().map(|s| s.as_str())
+    }
 }
 
 impl<C> ops::Deref for Receiver<C> {
@@ -142,3 +270,67 @@ impl<C> PartialEq for Receiver<C> {
         self.counter == other.counter
     }
 }
+
+/// The sending side that doesn't keep the channel connected.
+pub struct WeakSender<C> {
+    counter: *mut Counter<C>,
+}
+
+impl<C> WeakSender<C> {
+    /// Returns the internal `Counter`.
+    fn counter(&self) -> &Counter<C> {
+        unsafe { &*self.counter }
+    }
+
+    /// Acquires another weak sender reference.
+    pub fn acquire(&self) -> WeakSender<C> {
+        let count = self.counter().weak.fetch_add(1, Ordering::Relaxed);
+
+        // Same as in `Sender::acquire`, abort if the count becomes very large.
+        if count > isize::MAX as usize {
+            process::abort();
+        }
+
+        WeakSender {
+            counter: self.counter,
+        }
+    }
+
+    /// Attempts to acquire a sender reference.
+    ///
+    /// Fails if all sender references have already been released.
+    pub fn upgrade(&self) -> Option<Sender<C>> {
+        let mut count = self.counter().senders.load(Ordering::Relaxed);
+
+        loop {
+            // Once the last sender is gone, the channel is disconnected for good.
+            if count == 0 {
+                return None;
+            }
+
+            // Same as in `Sender::acquire`, abort if the count becomes very large.
+            if count > isize::MAX as usize {
+                process::abort();
+            }
+
+            match self.counter().senders.compare_exchange_weak(
+                count,
+                count + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    return Some(Sender {
+                        counter: self.counter,
+                    })
+                }
+                Err(c) => count = c,
+            }
+        }
+    }
+
+    /// Releases the weak sender reference.
+    pub unsafe fn release(&self) {
+        release_weak(self.counter);
+    }
+}
@@ -0,0 +1,708 @@
+//! Detection of deadlocks between threads blocked on channels.
+//!
+//! With the `deadlock-detection` feature enabled, every channel remembers which threads use its
+//! sending and receiving side, and every context remembers whether its thread is waiting
+//! without a deadline. Without the feature, `Participants` and `WaitState` are empty types whose
+//! methods do nothing.
+
+#[cfg(feature = "deadlock-detection")]
+use std::cell::RefCell;
+#[cfg(feature = "deadlock-detection")]
+use std::collections::VecDeque;
+#[cfg(feature = "deadlock-detection")]
+use std::fmt;
+#[cfg(feature = "deadlock-detection")]
+use std::sync::atomic::Ordering;
+#[cfg(feature = "deadlock-detection")]
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+#[cfg(feature = "deadlock-detection")]
+use std::sync::Arc;
+#[cfg(feature = "deadlock-detection")]
+use std::thread::{self, JoinHandle, ThreadId};
+#[cfg(feature = "deadlock-detection")]
+use std::time::Duration;
+
+#[cfg(feature = "deadlock-detection")]
+use debug::{self, ChannelInfo};
+#[cfg(feature = "deadlock-detection")]
+use utils::Spinlock;
+
+/// A group of threads that are blocked on channels forever.
+///
+/// Deadlocks are found by [`find_deadlocks`] and reported by a [`Watchdog`].
+///
+/// [`find_deadlocks`]: fn.find_deadlocks.html
+/// [`Watchdog`]: struct.Watchdog.html
+#[cfg(feature = "deadlock-detection")]
+#[derive(Clone, Debug)]
+pub struct Deadlock {
+    /// The deadlocked threads.
+    threads: Vec<ThreadId>,
+
+    /// The channels the deadlocked threads are blocked on.
+    channels: Vec<ChannelInfo>,
+}
+
+#[cfg(feature = "deadlock-detection")]
+impl Deadlock {
+    /// Returns the deadlocked threads.
+    pub fn threads(&self) -> &[ThreadId] {
+        &self.threads
+    }
+
+    /// Returns the channels the deadlocked threads are blocked on.
+    pub fn channels(&self) -> &[ChannelInfo] {
+        &self.channels
+    }
+}
+
+#[cfg(feature = "deadlock-detection")]
+impl fmt::Display for Deadlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "deadlock between threads {:?} on channels:",
+            self.threads
+        )?;
+        for chan in &self.channels {
+            write!(f, "\n  {}", chan)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the groups of threads that are currently deadlocked on channels.
+///
+/// This function is only available with the `deadlock-detection` feature enabled. It builds a
+/// wait-for graph from the threads blocked without a deadline in send, receive, and select
+/// operations, and from the threads that use each side of a channel. A thread uses a side from its
+/// first send, receive, or select operation on it until it drops a handle to that side or exits.
+/// A blocked thread can make progress if one of the channels it waits for is used from the other
+/// side by a thread that is not blocked, or that can itself make progress. Threads that can't make
+/// progress are deadlocked, and every returned [`Deadlock`] is a group of them connected by
+/// channels.
+///
+/// The detection is conservative and only reports threads that are certainly stuck, as long as
+/// all threads that will ever use a channel have already used it once. In particular:
+///
+/// * A thread waiting with a timeout, or for a [`SourceReceiver`], is never deadlocked.
+/// * A thread waiting for a channel whose other side nobody else uses is never deadlocked, since
+///   the other side might still be used by a thread that hasn't gotten to it.
+///
+/// A thread that drops one of several handles to the same side doesn't count as its user again
+/// until its next operation on that side.
+///
+/// The result is a snapshot of a moment in time, and threads that are just being woken up can be
+/// reported by mistake. The [`Watchdog`] reports a deadlock only once it has been found twice in
+/// a row.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{bounded_named, find_deadlocks};
+///
+/// let (s1, r1) = bounded_named::<i32>("first", 0);
+/// let (s2, r2) = bounded_named::<i32>("second", 0);
+/// let (r1_, r2_) = (r1.clone(), r2.clone());
+///
+/// // Each thread uses both channels once, and then sends before receiving.
+/// let t1 = thread::spawn(move || {
+///     s1.send(1).unwrap();
+///     r2.recv().unwrap();
+///     s1.send(1).unwrap();
+/// });
+/// let t2 = thread::spawn(move || {
+///     r1.recv().unwrap();
+///     s2.send(2).unwrap();
+///     s2.send(2).unwrap();
+/// });
+///
+/// // The threads take a moment to block, so search a few times.
+/// let mut deadlock = None;
+/// for _ in 0..500 {
+///     deadlock = find_deadlocks()
+///         .into_iter()
+///         .find(|d| d.threads().contains(&t1.thread().id()));
+///     if deadlock.is_some() {
+///         break;
+///     }
+///     thread::sleep(Duration::from_millis(10));
+/// }
+/// let deadlock = deadlock.unwrap();
+/// assert!(deadlock.threads().contains(&t2.thread().id()));
+/// println!("{}", deadlock);
+///
+/// // Break the deadlock from the main thread.
+/// r1_.recv().unwrap();
+/// r2_.recv().unwrap();
+/// # t1.join().unwrap();
+/// # t2.join().unwrap();
+/// ```
+///
+/// [`Deadlock`]: struct.Deadlock.html
+/// [`SourceReceiver`]: struct.SourceReceiver.html
+/// [`Watchdog`]: struct.Watchdog.html
+#[cfg(feature = "deadlock-detection")]
+pub fn find_deadlocks() -> Vec<Deadlock> {
+    let snapshots = debug::snapshots();
+
+    // Collect threads blocked forever, along with the operations they wait for. An operation is
+    // identified by the index of its channel and `true` for sending or `false` for receiving.
+    let mut threads: Vec<ThreadId> = Vec::new();
+    let mut waits: Vec<Vec<(usize, bool)>> = Vec::new();
+
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        let senders = snapshot.blocked.0.iter().map(|cx| (cx, true));
+        let receivers = snapshot.blocked.1.iter().map(|cx| (cx, false));
+
+        for (cx, is_send) in senders.chain(receivers) {
+            let id = match cx.thread_id() {
+                Some(id) if cx.is_blocked_forever() => id,
+                _ => continue,
+            };
+
+            let i = match threads.iter().position(|t| *t == id) {
+                Some(i) => i,
+                None => {
+                    threads.push(id);
+                    waits.push(Vec::new());
+                    threads.len() - 1
+                }
+            };
+            waits[i].push((index, is_send));
+        }
+    }
+
+    // Returns the threads that can complete an operation from the other side, excluding `id`.
+    let counterparts = |id: ThreadId, (index, is_send): (usize, bool)| {
+        let participants = &snapshots[index].participants;
+        let others = if is_send {
+            &participants.1
+        } else {
+            &participants.0
+        };
+        others
+            .iter()
+            .cloned()
+            .filter(|t| *t != id)
+            .collect::<Vec<_>>()
+    };
+
+    // Find the blocked threads that can make progress, until there are no more of them.
+    let mut live = vec![false; threads.len()];
+    loop {
+        let mut changed = false;
+
+        for i in 0..threads.len() {
+            if live[i] {
+                continue;
+            }
+
+            let can_progress = waits[i].iter().any(|&oper| {
+                let others = counterparts(threads[i], oper);
+                others.is_empty()
+                    || others
+                        .iter()
+                        .any(|t| match threads.iter().position(|b| b == t) {
+                            None => true,
+                            Some(j) => live[j],
+                        })
+            });
+
+            if can_progress {
+                live[i] = true;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    // Split the deadlocked threads into groups connected by the channels they wait for.
+    let mut group = vec![None; threads.len()];
+    let mut deadlocks = Vec::new();
+
+    for start in 0..threads.len() {
+        if live[start] || group[start].is_some() {
+            continue;
+        }
+
+        let mut members = Vec::new();
+        let mut channels: Vec<usize> = Vec::new();
+        let mut stack = vec![start];
+        group[start] = Some(deadlocks.len());
+
+        while let Some(i) = stack.pop() {
+            members.push(i);
+
+            for &oper in &waits[i] {
+                if !channels.contains(&oper.0) {
+                    channels.push(oper.0);
+                }
+
+                for t in counterparts(threads[i], oper) {
+                    if let Some(j) = threads.iter().position(|b| *b == t) {
+                        if !live[j] && group[j].is_none() {
+                            group[j] = Some(deadlocks.len());
+                            stack.push(j);
+                        }
+                    }
+                }
+            }
+        }
+
+        members.sort();
+        channels.sort();
+        deadlocks.push(Deadlock {
+            threads: members.into_iter().map(|i| threads[i]).collect(),
+            channels: channels
+                .into_iter()
+                .map(|index| snapshots[index].info.clone())
+                .collect(),
+        });
+    }
+
+    deadlocks
+}
+
+/// A thread that periodically looks for deadlocks.
+///
+/// This type is only available with the `deadlock-detection` feature enabled. The watchdog calls
+/// [`find_deadlocks`] once every period and reports each deadlock that has been found in two
+/// consecutive checks, so that threads that are just being woken up are not reported. The same
+/// group of threads is reported only once.
+///
+/// By default, a deadlock is reported by printing the deadlocked threads and channels to the
+/// standard error, after which the watchdog keeps looking for other deadlocks. A different
+/// reaction, such as logging or aborting the process, can be set with [`on_deadlock`]. If that
+/// function panics, the watchdog thread stops.
+///
+/// # Examples
+///
+/// ```
+/// use std::process;
+/// use std::time::Duration;
+/// use crossbeam_channel::Watchdog;
+///
+/// let watchdog = Watchdog::new(Duration::from_secs(1))
+///     .on_deadlock(|deadlock| {
+///         eprintln!("{}", deadlock);
+///         process::abort();
+///     })
+///     .spawn();
+///
+/// // ...
+///
+/// watchdog.stop();
+/// ```
+///
+/// [`find_deadlocks`]: fn.find_deadlocks.html
+/// [`on_deadlock`]: struct.Watchdog.html#method.on_deadlock
+#[cfg(feature = "deadlock-detection")]
+pub struct Watchdog {
+    /// The time between two checks.
+    period: Duration,
+
+    /// Reports a deadlock.
+    handler: Box<FnMut(&Deadlock) + Send>,
+}
+
+#[cfg(feature = "deadlock-detection")]
+impl Watchdog {
+    /// Creates a watchdog that looks for deadlocks once every `period`.
+    pub fn new(period: Duration) -> Watchdog {
+        Watchdog {
+            period,
+            handler: Box::new(|deadlock| eprintln!("{}", deadlock)),
+        }
+    }
+
+    /// Sets the function called with every found deadlock.
+    ///
+    /// The default function prints a description of the deadlock to the standard error.
+    pub fn on_deadlock<F>(mut self, handler: F) -> Watchdog
+    where
+        F: FnMut(&Deadlock) + Send + 'static,
+    {
+        self.handler = Box::new(handler);
+        self
+    }
+
+    /// Spawns the watchdog thread.
+    ///
+    /// The watchdog runs until it is stopped with [`WatchdogHandle::stop`]. Dropping the returned
+    /// handle detaches the watchdog, which then runs for as long as the process does.
+    ///
+    /// [`WatchdogHandle::stop`]: struct.WatchdogHandle.html#method.stop
+    pub fn spawn(self) -> WatchdogHandle {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+            thread::Builder::new()
+                .name("crossbeam-channel-watchdog".to_string())
+                .spawn(move || self.run(&stop))
+                .expect("failed to spawn the watchdog thread")
+        };
+        WatchdogHandle { stop, thread }
+    }
+
+    /// Checks for deadlocks until `stop` is set.
+    fn run(mut self, stop: &AtomicBool) {
+        // Deadlocks found in the previous check, and deadlocks that have already been reported.
+        let mut previous: Vec<Deadlock> = Vec::new();
+        let mut reported: Vec<Deadlock> = Vec::new();
+
+        loop {
+            thread::park_timeout(self.period);
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let found = find_deadlocks();
+            reported.retain(|d| found.iter().any(|f| same_threads(d, f)));
+
+            for deadlock in &found {
+                if previous.iter().any(|d| same_threads(d, deadlock))
+                    && !reported.iter().any(|d| same_threads(d, deadlock))
+                {
+                    (self.handler)(deadlock);
+                    reported.push(deadlock.clone());
+                }
+            }
+
+            previous = found;
+        }
+    }
+}
+
+#[cfg(feature = "deadlock-detection")]
+impl fmt::Debug for Watchdog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Watchdog")
+            .field("period", &self.period)
+            .finish()
+    }
+}
+
+/// Returns `true` if both deadlocks consist of the same threads.
+#[cfg(feature = "deadlock-detection")]
+fn same_threads(a: &Deadlock, b: &Deadlock) -> bool {
+    a.threads.len() == b.threads.len() && a.threads.iter().all(|t| b.threads.contains(t))
+}
+
+/// A handle to a running [`Watchdog`].
+///
+/// [`Watchdog`]: struct.Watchdog.html
+#[cfg(feature = "deadlock-detection")]
+#[derive(Debug)]
+pub struct WatchdogHandle {
+    /// Set to `true` when the watchdog should stop.
+    stop: Arc<AtomicBool>,
+
+    /// The watchdog thread.
+    thread: JoinHandle<()>,
+}
+
+#[cfg(feature = "deadlock-detection")]
+impl WatchdogHandle {
+    /// Stops the watchdog and waits for its thread to finish.
+    pub fn stop(self) {
+        self.stop.store(true, Ordering::SeqCst);
+        self.thread.thread().unpark();
+        let _ = self.thread.join();
+    }
+}
+
+/// The number of channel sides a thread remembers having been recorded as a user of.
+#[cfg(feature = "deadlock-detection")]
+const RECENT: usize = 16;
+
+/// The id of the next set of participants.
+///
+/// Unlike addresses of channels, ids are never reused, so a thread can't mistake a new channel for
+/// one it has already been recorded in.
+#[cfg(feature = "deadlock-detection")]
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A thread that uses channels.
+#[cfg(feature = "deadlock-detection")]
+struct Participant {
+    /// The id of the thread.
+    id: ThreadId,
+
+    /// Set to `false` when the thread exits.
+    alive: AtomicBool,
+}
+
+/// The current thread as a participant.
+#[cfg(feature = "deadlock-detection")]
+struct Local {
+    /// The current thread, shared with the channels it has been recorded in.
+    participant: Arc<Participant>,
+
+    /// Ids of the participant sets the thread has recently been recorded in, along with `true` for
+    /// the sending side and `false` for the receiving side.
+    recent: RefCell<VecDeque<(usize, bool)>>,
+}
+
+#[cfg(feature = "deadlock-detection")]
+impl Drop for Local {
+    fn drop(&mut self) {
+        self.participant.alive.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(feature = "deadlock-detection")]
+thread_local! {
+    /// The current thread as a participant.
+    static LOCAL: Local = Local {
+        participant: Arc::new(Participant {
+            id: thread::current().id(),
+            alive: AtomicBool::new(true),
+        }),
+        recent: RefCell::new(VecDeque::with_capacity(RECENT)),
+    };
+}
+
+/// Threads that use the sending and receiving side of a channel.
+///
+/// A thread is recorded when it sends, receives, or selects, and forgotten when it exits or drops a
+/// handle to that side. Once the last handle to a side is dropped, all of its threads are
+/// forgotten.
+#[cfg(feature = "deadlock-detection")]
+pub struct Participants {
+    /// The id of this set.
+    id: usize,
+
+    /// Threads using the sending side.
+    senders: Spinlock<Vec<Arc<Participant>>>,
+
+    /// Threads using the receiving side.
+    receivers: Spinlock<Vec<Arc<Participant>>>,
+}
+
+#[cfg(feature = "deadlock-detection")]
+impl Participants {
+    /// Creates an empty set of participants.
+    #[inline]
+    pub fn new() -> Participants {
+        Participants {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            senders: Spinlock::new(Vec::new()),
+            receivers: Spinlock::new(Vec::new()),
+        }
+    }
+
+    /// Records the current thread as a sender.
+    #[inline]
+    pub fn sender(&self) {
+        self.record(true);
+    }
+
+    /// Records the current thread as a receiver.
+    #[inline]
+    pub fn receiver(&self) {
+        self.record(false);
+    }
+
+    /// Forgets the current thread as a sender, or all senders if `last` is `true`.
+    pub fn release_sender(&self, last: bool) {
+        self.release(true, last);
+    }
+
+    /// Forgets the current thread as a receiver, or all receivers if `last` is `true`.
+    pub fn release_receiver(&self, last: bool) {
+        self.release(false, last);
+    }
+
+    /// Records the current thread as a user of one side.
+    ///
+    /// The thread remembers the sides it has recently been recorded in, so repeated operations on
+    /// the same channels don't take the lock.
+    #[inline]
+    fn record(&self, is_send: bool) {
+        let _ = LOCAL.try_with(|local| {
+            if !local.recent.borrow().contains(&(self.id, is_send)) {
+                self.insert(local, is_send);
+            }
+        });
+    }
+
+    /// Adds the current thread to one side and remembers that it has been recorded.
+    #[cold]
+    fn insert(&self, local: &Local, is_send: bool) {
+        {
+            let mut side = self.side(is_send).lock();
+
+            // While the lock is held anyway, forget threads that have exited.
+            side.retain(|p| p.alive.load(Ordering::SeqCst));
+            if !side.iter().any(|p| Arc::ptr_eq(p, &local.participant)) {
+                side.push(local.participant.clone());
+            }
+        }
+
+        let mut recent = local.recent.borrow_mut();
+        if recent.len() == RECENT {
+            recent.pop_front();
+        }
+        recent.push_back((self.id, is_send));
+    }
+
+    /// Removes the current thread, or all threads if `last` is `true`, from one side.
+    fn release(&self, is_send: bool, last: bool) {
+        let participant = LOCAL
+            .try_with(|local| {
+                local
+                    .recent
+                    .borrow_mut()
+                    .retain(|&entry| entry != (self.id, is_send));
+                local.participant.clone()
+            })
+            .ok();
+
+        let mut side = self.side(is_send).lock();
+        if last {
+            side.clear();
+        } else if let Some(participant) = participant {
+            side.retain(|p| !Arc::ptr_eq(p, &participant));
+        }
+    }
+
+    /// Returns the threads using the sending and receiving side, respectively.
+    pub fn get(&self) -> (Vec<ThreadId>, Vec<ThreadId>) {
+        (self.live(true), self.live(false))
+    }
+
+    /// Forgets threads that have exited from one side and returns the rest.
+    fn live(&self, is_send: bool) -> Vec<ThreadId> {
+        let mut side = self.side(is_send).lock();
+        side.retain(|p| p.alive.load(Ordering::SeqCst));
+        side.iter().map(|p| p.id).collect()
+    }
+
+    /// Returns the threads using the sending side if `is_send` is `true`, or the receiving side
+    /// otherwise.
+    fn side(&self, is_send: bool) -> &Spinlock<Vec<Arc<Participant>>> {
+        if is_send {
+            &self.senders
+        } else {
+            &self.receivers
+        }
+    }
+}
+
+/// Threads that use a channel, which are not recorded.
+#[cfg(not(feature = "deadlock-detection"))]
+pub struct Participants;
+
+#[cfg(not(feature = "deadlock-detection"))]
+impl Participants {
+    /// Creates an empty set of participants.
+    #[inline]
+    pub fn new() -> Participants {
+        Participants
+    }
+
+    /// Does nothing.
+    #[inline]
+    pub fn sender(&self) {}
+
+    /// Does nothing.
+    #[inline]
+    pub fn receiver(&self) {}
+
+    /// Does nothing.
+    #[inline]
+    pub fn release_sender(&self, _last: bool) {}
+
+    /// Does nothing.
+    #[inline]
+    pub fn release_receiver(&self, _last: bool) {}
+}
+
+/// What the owner of a context is waiting for.
+#[cfg(feature = "deadlock-detection")]
+#[derive(Debug)]
+pub struct WaitState {
+    /// Set to `true` while the owner is waiting without a deadline.
+    forever: AtomicBool,
+
+    /// Set to `true` if the owner waits for operations that are not on channels.
+    untracked: AtomicBool,
+}
+
+#[cfg(feature = "deadlock-detection")]
+impl WaitState {
+    /// Creates a state of not waiting.
+    #[inline]
+    pub fn new() -> WaitState {
+        WaitState {
+            forever: AtomicBool::new(false),
+            untracked: AtomicBool::new(false),
+        }
+    }
+
+    /// Records that the owner starts waiting, with or without a deadline.
+    #[inline]
+    pub fn enter(&self, forever: bool) {
+        self.forever.store(forever, Ordering::SeqCst);
+    }
+
+    /// Records that the owner stops waiting.
+    #[inline]
+    pub fn leave(&self) {
+        self.forever.store(false, Ordering::SeqCst);
+    }
+
+    /// Records that the owner waits for operations that are not on channels.
+    #[inline]
+    pub fn untrack(&self) {
+        self.untracked.store(true, Ordering::SeqCst);
+    }
+
+    /// Forgets about operations that are not on channels.
+    #[inline]
+    pub fn track(&self) {
+        self.untracked.store(false, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if the owner is waiting without a deadline, and only for channels.
+    #[inline]
+    pub fn is_blocked_forever(&self) -> bool {
+        self.forever.load(Ordering::SeqCst) && !self.untracked.load(Ordering::SeqCst)
+    }
+}
+
+/// What the owner of a context is waiting for, which is not recorded.
+#[cfg(not(feature = "deadlock-detection"))]
+#[derive(Debug)]
+pub struct WaitState;
+
+#[cfg(not(feature = "deadlock-detection"))]
+impl WaitState {
+    /// Creates a state of not waiting.
+    #[inline]
+    pub fn new() -> WaitState {
+        WaitState
+    }
+
+    /// Does nothing.
+    #[inline]
+    pub fn enter(&self, _forever: bool) {}
+
+    /// Does nothing.
+    #[inline]
+    pub fn leave(&self) {}
+
+    /// Does nothing.
+    #[inline]
+    pub fn untrack(&self) {}
+
+    /// Does nothing.
+    #[inline]
+    pub fn track(&self) {}
+}
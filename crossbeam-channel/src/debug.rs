@@ -0,0 +1,302 @@
+//! A registry of live channels, used for debugging.
+//!
+//! With the `debug` feature enabled, every channel is added to a global registry when it is
+//! created and removed right before it is deallocated. Without the feature, `Inspect` is an empty
+//! trait implemented by every type and nothing is ever registered.
+
+#[cfg(feature = "debug")]
+use std::collections::HashMap;
+#[cfg(feature = "debug")]
+use std::fmt;
+#[cfg(feature = "debug")]
+use std::ptr;
+#[cfg(feature = "debug")]
+use std::sync::atomic::{AtomicPtr, Ordering};
+#[cfg(feature = "debug")]
+use std::sync::Mutex;
+#[cfg(feature = "debug")]
+use std::thread::ThreadId;
+
+#[cfg(feature = "debug")]
+use context::Context;
+
+/// Information about a live channel.
+///
+/// A list of all live channels is returned by [`live_channels`].
+///
+/// [`live_channels`]: fn.live_channels.html
+#[cfg(feature = "debug")]
+#[derive(Clone, Debug)]
+pub struct ChannelInfo {
+    /// The name of the channel, if it has one.
+    name: Option<String>,
+
+    /// The flavor of the channel.
+    flavor: &'static str,
+
+    /// The number of messages in the channel.
+    len: usize,
+
+    /// Threads waiting in send operations.
+    blocked_senders: Vec<ThreadId>,
+
+    /// Threads waiting in receive operations.
+    blocked_receivers: Vec<ThreadId>,
+}
+
+#[cfg(feature = "debug")]
+impl ChannelInfo {
+    /// Returns the name of the channel, if it has one.
+    ///
+    /// Channels are named when created with [`bounded_named`] or [`unbounded_named`].
+    ///
+    /// [`bounded_named`]: fn.bounded_named.html
+    /// [`unbounded_named`]: fn.unbounded_named.html
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|s| s.as_str())
+    }
+
+    /// Returns the flavor of the channel.
+    ///
+    /// The flavor is one of `"array"`, `"list"`, `"zero"`, `"priority"`, `"broadcast"`,
+    /// `"watch"`, and `"oneshot"`. Channels created by [`bounded`] with a positive capacity are
+    /// `"array"` channels, and those with zero capacity are `"zero"` channels. Channels created by
+    /// [`unbounded`] are `"list"` channels.
+    ///
+    /// [`bounded`]: fn.bounded.html
+    /// [`unbounded`]: fn.unbounded.html
+    pub fn flavor(&self) -> &'static str {
+        self.flavor
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the threads waiting for the channel in send operations.
+    ///
+    /// Threads waiting in [`Select`], [`select!`], and [`Selector`] are included. A thread is
+    /// listed once for every operation it waits in.
+    ///
+    /// [`Select`]: struct.Select.html
+    /// [`select!`]: macro.select.html
+    /// [`Selector`]: struct.Selector.html
+    pub fn blocked_senders(&self) -> &[ThreadId] {
+        &self.blocked_senders
+    }
+
+    /// Returns the threads waiting for the channel in receive operations.
+    ///
+    /// Threads waiting in [`Select`], [`select!`], and [`Selector`] are included. A thread is
+    /// listed once for every operation it waits in.
+    ///
+    /// [`Select`]: struct.Select.html
+    /// [`select!`]: macro.select.html
+    /// [`Selector`]: struct.Selector.html
+    pub fn blocked_receivers(&self) -> &[ThreadId] {
+        &self.blocked_receivers
+    }
+}
+
+#[cfg(feature = "debug")]
+impl fmt::Display for ChannelInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            Some(ref name) => write!(f, "{:?}", name)?,
+            None => write!(f, "<unnamed>")?,
+        }
+        write!(
+            f,
+            " ({}, len {}): blocked senders {:?}, blocked receivers {:?}",
+            self.flavor, self.len, self.blocked_senders, self.blocked_receivers,
+        )
+    }
+}
+
+/// Returns information about all live channels.
+///
+/// Channels are listed in order of creation. Channels created by [`after`], [`tick`], [`timer`],
+/// and [`never`] are not listed.
+///
+/// This function is only available with the `debug` feature enabled. It is meant for finding out
+/// which threads are blocked on which channels, for example when a program hangs.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{bounded_named, live_channels};
+///
+/// let (s, r) = bounded_named::<i32>("jobs", 1);
+/// s.send(1).unwrap();
+///
+/// // Block a thread on the full channel.
+/// let t = thread::spawn(move || s.send(2).unwrap());
+/// thread::sleep(Duration::from_millis(100));
+///
+/// let channels = live_channels();
+/// let jobs = channels.iter().find(|c| c.name() == Some("jobs")).unwrap();
+/// assert_eq!(jobs.flavor(), "array");
+/// assert_eq!(jobs.len(), 1);
+/// assert_eq!(jobs.blocked_senders(), &[t.thread().id()]);
+///
+/// // Print all channels.
+/// for c in &channels {
+///     println!("{}", c);
+/// }
+/// # r.recv().unwrap();
+/// # t.join().unwrap();
+/// ```
+///
+/// [`after`]: fn.after.html
+/// [`tick`]: fn.tick.html
+/// [`timer`]: fn.timer.html
+/// [`never`]: fn.never.html
+#[cfg(feature = "debug")]
+pub fn live_channels() -> Vec<ChannelInfo> {
+    snapshots().into_iter().map(|s| s.info).collect()
+}
+
+/// A channel that can be described for debugging.
+#[cfg(feature = "debug")]
+pub trait Inspect {
+    /// Returns the flavor of the channel.
+    fn flavor(&self) -> &'static str;
+
+    /// Returns the current number of messages inside the channel.
+    fn len(&self) -> usize;
+
+    /// Returns the contexts waiting in send and receive operations, respectively.
+    fn blocked(&self) -> (Vec<Context>, Vec<Context>);
+}
+
+/// A channel that can be described for debugging, which is implemented by every type.
+#[cfg(not(feature = "debug"))]
+pub trait Inspect {}
+
+#[cfg(not(feature = "debug"))]
+impl<C> Inspect for C {}
+
+/// A snapshot of a live channel.
+#[cfg(feature = "debug")]
+pub struct Snapshot {
+    /// Information about the channel.
+    pub info: ChannelInfo,
+
+    /// Contexts waiting in send and receive operations, respectively.
+    #[cfg(feature = "deadlock-detection")]
+    pub blocked: (Vec<Context>, Vec<Context>),
+
+    /// Threads that have used the sending and receiving side, respectively.
+    #[cfg(feature = "deadlock-detection")]
+    pub participants: (Vec<ThreadId>, Vec<ThreadId>),
+}
+
+/// Describes channel `chan` named `name`, given the contexts returned by `Inspect::blocked`.
+#[cfg(feature = "debug")]
+pub fn describe<C: Inspect>(
+    chan: &C,
+    name: Option<&str>,
+    blocked: &(Vec<Context>, Vec<Context>),
+) -> ChannelInfo {
+    let threads = |contexts: &[Context]| -> Vec<ThreadId> {
+        contexts.iter().filter_map(|cx| cx.thread_id()).collect()
+    };
+
+    ChannelInfo {
+        name: name.map(|s| s.to_string()),
+        flavor: chan.flavor(),
+        len: chan.len(),
+        blocked_senders: threads(&blocked.0),
+        blocked_receivers: threads(&blocked.1),
+    }
+}
+
+/// Takes snapshots of all live channels, in order of creation.
+#[cfg(feature = "debug")]
+pub fn snapshots() -> Vec<Snapshot> {
+    let registry = registry().lock().unwrap();
+    let mut entries: Vec<_> = registry.entries.iter().collect();
+    entries.sort_by_key(|&(_, e)| e.index);
+    entries
+        .into_iter()
+        .map(|(&chan, e)| unsafe { (e.snapshot)(chan as *const u8) })
+        .collect()
+}
+
+/// A registered channel.
+#[cfg(feature = "debug")]
+struct Entry {
+    /// The number of channels registered before this one.
+    index: u64,
+
+    /// Takes a snapshot of the channel at the given address.
+    snapshot: unsafe fn(*const u8) -> Snapshot,
+}
+
+/// Registered channels.
+#[cfg(feature = "debug")]
+struct Registry {
+    /// Entries keyed by the addresses of their channels.
+    entries: HashMap<usize, Entry>,
+
+    /// The number of channels registered so far.
+    registered: u64,
+}
+
+/// The global registry of live channels.
+#[cfg(feature = "debug")]
+static REGISTRY: AtomicPtr<Mutex<Registry>> = AtomicPtr::new(ptr::null_mut());
+
+/// Returns the global registry, creating it if needed.
+#[cfg(feature = "debug")]
+fn registry() -> &'static Mutex<Registry> {
+    let mut ptr = REGISTRY.load(Ordering::Acquire);
+
+    if ptr.is_null() {
+        let registry = Box::into_raw(Box::new(Mutex::new(Registry {
+            entries: HashMap::new(),
+            registered: 0,
+        })));
+
+        match REGISTRY.compare_exchange(ptr, registry, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => ptr = registry,
+            Err(current) => {
+                // Another thread has created the registry first.
+                drop(unsafe { Box::from_raw(registry) });
+                ptr = current;
+            }
+        }
+    }
+
+    unsafe { &*ptr }
+}
+
+/// Adds the channel at address `chan` to the registry.
+///
+/// The channel must stay alive until it is unregistered.
+#[cfg(feature = "debug")]
+pub unsafe fn register(chan: *const u8, snapshot: unsafe fn(*const u8) -> Snapshot) {
+    let mut registry = registry().lock().unwrap();
+    let index = registry.registered;
+    registry.registered += 1;
+    registry
+        .entries
+        .insert(chan as usize, Entry { index, snapshot });
+}
+
+/// Removes the channel at address `chan` from the registry.
+///
+/// Once this function returns, no snapshot of the channel is being taken anymore, so it can be
+/// deallocated.
+#[cfg(feature = "debug")]
+pub fn unregister(chan: *const u8) {
+    registry().lock().unwrap().entries.remove(&(chan as usize));
+}
@@ -83,6 +83,27 @@ pub enum RecvTimeoutError {
     Disconnected,
 }
 
+/// An error returned from the [`try_recv_lagged`] method.
+///
+/// [`try_recv_lagged`]: struct.Receiver.html#method.try_recv_lagged
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BroadcastRecvError {
+    /// A message could not be received because the channel is empty.
+    ///
+    /// If this is a zero-capacity channel, then the error indicates that there was no sender
+    /// available to send a message at the time.
+    Empty,
+
+    /// The message could not be received because the channel is empty and disconnected.
+    Disconnected,
+
+    /// The receiver of a broadcast channel fell behind and missed the given number of messages.
+    ///
+    /// The missed messages were overwritten by newer ones. The receiver now points to the oldest
+    /// message still in the channel, so the next call will receive it.
+    Lagged(u64),
+}
+
 /// An error returned from the [`try_select`] method.
 ///
 /// Failed because none of the channel operations were ready.
@@ -418,6 +439,67 @@ impl RecvTimeoutError {
     }
 }
 
+impl fmt::Display for BroadcastRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BroadcastRecvError::Empty => "receiving on an empty channel".fmt(f),
+            BroadcastRecvError::Disconnected => {
+                "receiving on an empty and disconnected channel".fmt(f)
+            }
+            BroadcastRecvError::Lagged(n) => write!(f, "receiver lagged behind by {} messages", n),
+        }
+    }
+}
+
+impl error::Error for BroadcastRecvError {
+    fn description(&self) -> &str {
+        match *self {
+            BroadcastRecvError::Empty => "receiving on an empty channel",
+            BroadcastRecvError::Disconnected => "receiving on an empty and disconnected channel",
+            BroadcastRecvError::Lagged(_) => "receiver lagged behind",
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        None
+    }
+}
+
+impl From<TryRecvError> for BroadcastRecvError {
+    fn from(err: TryRecvError) -> BroadcastRecvError {
+        match err {
+            TryRecvError::Empty => BroadcastRecvError::Empty,
+            TryRecvError::Disconnected => BroadcastRecvError::Disconnected,
+        }
+    }
+}
+
+impl BroadcastRecvError {
+    /// Returns `true` if the receive operation failed because the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            BroadcastRecvError::Empty => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the receive operation failed because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            BroadcastRecvError::Disconnected => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the receiver missed messages because it fell behind.
+    pub fn is_lagged(&self) -> bool {
+        match self {
+            BroadcastRecvError::Lagged(_) => true,
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Display for TrySelectError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         "all operations in select would block".fmt(f)
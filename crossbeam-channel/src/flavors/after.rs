@@ -3,13 +3,14 @@
 //! Messages cannot be sent into this kind of channel; they are materialized on demand.
 
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread;
 use std::time::{Duration, Instant};
 
+use clock::{self, Clock};
 use context::Context;
 use err::{RecvTimeoutError, TryRecvError};
-use select::{Operation, SelectHandle, Token};
+use select::{Operation, SelectHandle, Selected, Token};
 use utils;
+use wheel::Alarm;
 
 /// Result of a receive operation.
 pub type AfterToken = Option<Instant>;
@@ -21,15 +22,29 @@ pub struct Channel {
 
     /// `true` if the message has been received.
     received: AtomicBool,
+
+    /// The clock measuring time for the channel.
+    clock: Clock,
+
+    /// Wakes up receivers when the message is delivered, unless the clock does that.
+    alarm: Alarm,
 }
 
 impl Channel {
     /// Creates a channel that delivers a message after a certain duration of time.
     #[inline]
     pub fn new(dur: Duration) -> Self {
+        Channel::with_clock(dur, Clock::system())
+    }
+
+    /// Creates a channel that delivers a message after a certain duration of time on `clock`.
+    #[inline]
+    pub fn with_clock(dur: Duration, clock: Clock) -> Self {
         Channel {
-            delivery_time: Instant::now() + dur,
+            delivery_time: clock.now() + dur,
             received: AtomicBool::new(false),
+            clock,
+            alarm: Alarm::new(),
         }
     }
 
@@ -42,7 +57,7 @@ impl Channel {
             return Err(TryRecvError::Empty);
         }
 
-        if Instant::now() < self.delivery_time {
+        if self.clock.now() < self.delivery_time {
             // The message was not delivered yet.
             return Err(TryRecvError::Empty);
         }
@@ -57,45 +72,53 @@ impl Channel {
         }
     }
 
-    /// Receives a message from the channel.
+    /// Returns the message without receiving it, if it has been delivered.
     #[inline]
-    pub fn recv(&self, deadline: Option<Instant>) -> Result<Instant, RecvTimeoutError> {
-        // We use relaxed ordering because this is just an optional optimistic check.
-        if self.received.load(Ordering::Relaxed) {
-            // The message has already been received.
-            utils::sleep_until(deadline);
-            return Err(RecvTimeoutError::Timeout);
+    pub fn peek(&self) -> Result<Instant, TryRecvError> {
+        if self.is_empty() {
+            Err(TryRecvError::Empty)
+        } else {
+            Ok(self.delivery_time)
         }
+    }
 
-        // Wait until the message is received or the deadline is reached.
+    /// Receives a message from the channel.
+    #[inline]
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<Instant, RecvTimeoutError> {
+        let token = &mut Token::default();
         loop {
-            let now = Instant::now();
+            if let Ok(msg) = self.try_recv() {
+                return Ok(msg);
+            }
 
-            // Check if we can receive the next message.
-            if now >= self.delivery_time {
-                break;
+            if self.received.load(Ordering::SeqCst) {
+                // The message has already been received, so wait until the deadline.
+                utils::sleep_until(deadline);
+                return Err(RecvTimeoutError::Timeout);
             }
 
-            // Check if the deadline has been reached.
+            // Check if the operation deadline has been reached.
             if let Some(d) = deadline {
-                if now >= d {
+                if Instant::now() >= d {
                     return Err(RecvTimeoutError::Timeout);
                 }
-
-                thread::sleep(self.delivery_time.min(d) - now);
-            } else {
-                thread::sleep(self.delivery_time - now);
             }
-        }
 
-        // Try receiving the message if it is still available.
-        if !self.received.swap(true, Ordering::SeqCst) {
-            // Success! Return the message, which is the instant at which it was delivered.
-            Ok(self.delivery_time)
-        } else {
-            // The message was already received. Block forever.
-            utils::sleep_until(None);
-            unreachable!()
+            Context::with(|cx| {
+                // Prepare for blocking until the message is delivered.
+                let oper = Operation::hook(token);
+                if self.register(oper, cx) {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    _ => self.unregister(oper),
+                }
+            });
         }
     }
 
@@ -114,7 +137,7 @@ impl Channel {
         }
 
         // If the delivery time hasn't been reached yet, the channel is empty.
-        if Instant::now() < self.delivery_time {
+        if self.clock.now() < self.delivery_time {
             return true;
         }
 
@@ -164,21 +187,27 @@ impl SelectHandle for Channel {
 
     #[inline]
     fn deadline(&self) -> Option<Instant> {
-        // We use relaxed ordering because this is just an optional optimistic check.
-        if self.received.load(Ordering::Relaxed) {
-            None
-        } else {
-            Some(self.delivery_time)
-        }
+        // Registered operations are woken up by the timer wheel or the manual clock instead.
+        None
     }
 
     #[inline]
-    fn register(&self, _oper: Operation, _cx: &Context) -> bool {
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        match clock::waker(&self.clock) {
+            Some(waker) => waker.register(oper, cx),
+            None => {
+                self.alarm.waker().register(oper, cx);
+                self.alarm.arm(self.delivery_time);
+            }
+        }
         self.is_ready()
     }
 
     #[inline]
-    fn unregister(&self, _oper: Operation) {}
+    fn unregister(&self, oper: Operation) {
+        let waker = clock::waker(&self.clock).unwrap_or_else(|| self.alarm.waker());
+        waker.unregister(oper);
+    }
 
     #[inline]
     fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
@@ -191,10 +220,20 @@ impl SelectHandle for Channel {
     }
 
     #[inline]
-    fn watch(&self, _oper: Operation, _cx: &Context) -> bool {
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        match clock::waker(&self.clock) {
+            Some(waker) => waker.watch(oper, cx),
+            None => {
+                self.alarm.waker().watch(oper, cx);
+                self.alarm.arm(self.delivery_time);
+            }
+        }
         self.is_ready()
     }
 
     #[inline]
-    fn unwatch(&self, _oper: Operation) {}
+    fn unwatch(&self, oper: Operation) {
+        let waker = clock::waker(&self.clock).unwrap_or_else(|| self.alarm.waker());
+        waker.unwatch(oper);
+    }
 }
@@ -1,6 +1,6 @@
 //! Bounded channel based on a preallocated array.
 //!
-//! This flavor has a fixed, positive capacity.
+//! This flavor has a positive capacity, which can be changed at runtime.
 //!
 //! The implementation is based on Dmitry Vyukov's bounded MPMC queue.
 //!
@@ -14,6 +14,8 @@
 //!   - http://www.1024cores.net/home/code-license
 
 use std::cell::UnsafeCell;
+use std::cmp;
+use std::collections::VecDeque;
 use std::marker::PhantomData;
 use std::mem;
 use std::ptr;
@@ -21,10 +23,17 @@ use std::sync::atomic::{self, AtomicUsize, Ordering};
 use std::time::Instant;
 
 use crossbeam_utils::{Backoff, CachePadded};
+use epoch::{self, Atomic, Guard, Owned, Shared};
 
 use context::Context;
+#[cfg(feature = "debug")]
+use debug::Inspect;
 use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use metrics::Metrics;
+#[cfg(feature = "metrics")]
+use metrics::Stats;
 use select::{Operation, SelectHandle, Selected, Token};
+use utils::{self, Spinlock};
 use waker::SyncWaker;
 
 /// A slot in a channel.
@@ -56,53 +65,53 @@ impl Default for ArrayToken {
     }
 }
 
-/// Bounded channel based on a preallocated array.
-pub struct Channel<T> {
-    /// The head of the channel.
+/// A bounded queue of fixed capacity.
+///
+/// A channel starts with a single queue. Changing the capacity closes the queue that accepts new
+/// messages and links a new one after it.
+struct Queue<T> {
+    /// The head of the queue.
     ///
     /// This value is a "stamp" consisting of an index into the buffer, a mark bit, and a lap, but
     /// packed into a single `usize`. The lower bits represent the index, while the upper bits
-    /// represent the lap. The mark bit in the head is always zero.
+    /// represent the lap. The mark bit in the head is set while a receiver inspects the message at
+    /// the head without receiving it, which prevents other receivers from moving the head.
     ///
-    /// Messages are popped from the head of the channel.
+    /// Messages are popped from the head of the queue.
     head: CachePadded<AtomicUsize>,
 
-    /// The tail of the channel.
+    /// The tail of the queue.
     ///
     /// This value is a "stamp" consisting of an index into the buffer, a mark bit, and a lap, but
     /// packed into a single `usize`. The lower bits represent the index, while the upper bits
-    /// represent the lap. The mark bit indicates that the channel is disconnected.
+    /// represent the lap. The mark bit indicates that the queue is closed, either because the
+    /// channel is disconnected or because the queue has been replaced.
     ///
-    /// Messages are pushed into the tail of the channel.
+    /// Messages are pushed into the tail of the queue.
     tail: CachePadded<AtomicUsize>,
 
     /// The buffer holding slots.
     buffer: *mut Slot<T>,
 
-    /// The channel capacity.
+    /// The queue capacity.
     cap: usize,
 
     /// A stamp with the value of `{ lap: 1, mark: 0, index: 0 }`.
     one_lap: usize,
 
-    /// If this bit is set in the tail, that means the channel is disconnected.
+    /// If this bit is set in the tail, that means the queue is closed.
     mark_bit: usize,
 
-    /// Senders waiting while the channel is full.
-    senders: SyncWaker,
-
-    /// Receivers waiting while the channel is empty and not disconnected.
-    receivers: SyncWaker,
+    /// The queue that replaced this one, or null.
+    next: Atomic<Queue<T>>,
 
-    /// Indicates that dropping a `Channel<T>` may drop values of type `T`.
+    /// Indicates that dropping a `Queue<T>` may drop values of type `T`.
     _marker: PhantomData<T>,
 }
 
-impl<T> Channel<T> {
-    /// Creates a bounded channel of capacity `cap`.
-    pub fn with_capacity(cap: usize) -> Self {
-        assert!(cap > 0, "capacity must be positive");
-
+impl<T> Queue<T> {
+    /// Creates a queue of capacity `cap`.
+    fn with_capacity(cap: usize) -> Self {
         // Compute constants `mark_bit` and `one_lap`.
         let mark_bit = (cap + 1).next_power_of_two();
         let one_lap = mark_bit * 2;
@@ -129,36 +138,48 @@ impl<T> Channel<T> {
             }
         }
 
-        Channel {
+        Queue {
             buffer,
             cap,
             one_lap,
             mark_bit,
             head: CachePadded::new(AtomicUsize::new(head)),
             tail: CachePadded::new(AtomicUsize::new(tail)),
-            senders: SyncWaker::new(),
-            receivers: SyncWaker::new(),
+            next: Atomic::null(),
             _marker: PhantomData,
         }
     }
 
-    /// Returns a receiver handle to the channel.
-    pub fn receiver(&self) -> Receiver<T> {
-        Receiver(self)
+    /// Returns the slot that the head or tail `pos` points to.
+    #[inline]
+    unsafe fn slot(&self, pos: usize) -> &Slot<T> {
+        &*self.buffer.add(pos & (self.mark_bit - 1))
     }
 
-    /// Returns a sender handle to the channel.
-    pub fn sender(&self) -> Sender<T> {
-        Sender(self)
+    /// Returns the head or tail that follows `pos`.
+    #[inline]
+    fn next_pos(&self, pos: usize) -> usize {
+        let index = pos & (self.mark_bit - 1);
+        let lap = pos & !(self.one_lap - 1);
+
+        if index + 1 < self.cap {
+            pos + 1
+        } else {
+            lap.wrapping_add(self.one_lap)
+        }
     }
 
     /// Attempts to reserve a slot for sending a message.
+    ///
+    /// If the queue is closed, the token is left without a slot. This is the fast path of every
+    /// send, so it's inlined into all of its callers.
+    #[inline(always)]
     fn start_send(&self, token: &mut Token) -> bool {
         let backoff = Backoff::new();
         let mut tail = self.tail.load(Ordering::Relaxed);
 
         loop {
-            // Check if the channel is disconnected.
+            // Check if the queue is closed.
             if tail & self.mark_bit != 0 {
                 token.array.slot = ptr::null();
                 token.array.stamp = 0;
@@ -205,11 +226,11 @@ impl<T> Channel<T> {
                 }
             } else if stamp.wrapping_add(self.one_lap) == tail + 1 {
                 atomic::fence(Ordering::SeqCst);
-                let head = self.head.load(Ordering::Relaxed);
+                let head = self.head.load(Ordering::Relaxed) & !self.mark_bit;
 
                 // If the head lags one lap behind the tail as well...
                 if head.wrapping_add(self.one_lap) == tail {
-                    // ...then the channel is full.
+                    // ...then the queue is full.
                     return false;
                 }
 
@@ -223,25 +244,11 @@ impl<T> Channel<T> {
         }
     }
 
-    /// Writes a message into the channel.
-    pub unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
-        // If there is no slot, the channel is disconnected.
-        if token.array.slot.is_null() {
-            return Err(msg);
-        }
-
-        let slot: &Slot<T> = &*(token.array.slot as *const Slot<T>);
-
-        // Write the message into the slot and update the stamp.
-        slot.msg.get().write(msg);
-        slot.stamp.store(token.array.stamp, Ordering::Release);
-
-        // Wake a sleeping receiver.
-        self.receivers.notify();
-        Ok(())
-    }
-
     /// Attempts to reserve a slot for receiving a message.
+    ///
+    /// If the queue is closed and empty, the token is left without a slot. This is the fast path of
+    /// every receive, so it's inlined into all of its callers.
+    #[inline(always)]
     fn start_recv(&self, token: &mut Token) -> bool {
         let backoff = Backoff::new();
         let mut head = self.head.load(Ordering::Relaxed);
@@ -255,7 +262,7 @@ impl<T> Channel<T> {
             let slot = unsafe { &*self.buffer.add(index) };
             let stamp = slot.stamp.load(Ordering::Acquire);
 
-            // If the the stamp is ahead of the head by 1, we may attempt to pop.
+            // If the stamp is ahead of the head by 1, we may attempt to pop.
             if head + 1 == stamp {
                 let new = if index + 1 < self.cap {
                     // Same lap, incremented index.
@@ -289,9 +296,9 @@ impl<T> Channel<T> {
                 atomic::fence(Ordering::SeqCst);
                 let tail = self.tail.load(Ordering::Relaxed);
 
-                // If the tail equals the head, that means the channel is empty.
+                // If the tail equals the head, that means the queue is empty.
                 if (tail & !self.mark_bit) == head {
-                    // If the channel is disconnected...
+                    // If the queue is closed...
                     if tail & self.mark_bit != 0 {
                         // ...then receive an error.
                         token.array.slot = ptr::null();
@@ -307,227 +314,383 @@ impl<T> Channel<T> {
                 head = self.head.load(Ordering::Relaxed);
             } else {
                 // Snooze because we need to wait for the stamp to get updated.
+                utils::check_head_wait(&self.head);
                 backoff.snooze();
                 head = self.head.load(Ordering::Relaxed);
             }
         }
     }
 
-    /// Reads a message from the channel.
-    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
-        if token.array.slot.is_null() {
-            // The channel is disconnected.
-            return Err(());
-        }
-
-        let slot: &Slot<T> = &*(token.array.slot as *const Slot<T>);
-
-        // Read the message from the slot and update the stamp.
-        let msg = slot.msg.get().read();
-        slot.stamp.store(token.array.stamp, Ordering::Release);
-
-        // Wake a sleeping sender.
-        self.senders.notify();
-        Ok(msg)
-    }
-
-    /// Attempts to send a message into the channel.
-    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
-        let token = &mut Token::default();
-        if self.start_send(token) {
-            unsafe { self.write(token, msg).map_err(TrySendError::Disconnected) }
-        } else {
-            Err(TrySendError::Full(msg))
-        }
-    }
+    /// Attempts to reserve up to `max` consecutive slots for sending messages.
+    ///
+    /// On success, returns the tail at which the reserved slots begin and their number. Zero
+    /// reserved slots means the queue is full. Returns `None` if the queue is closed.
+    fn start_send_batch(&self, max: usize) -> Option<(usize, usize)> {
+        let backoff = Backoff::new();
 
-    /// Sends a message into the channel.
-    pub fn send(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
-        let token = &mut Token::default();
         loop {
-            // Try sending a message several times.
-            let backoff = Backoff::new();
-            loop {
-                if self.start_send(token) {
-                    let res = unsafe { self.write(token, msg) };
-                    return res.map_err(SendTimeoutError::Disconnected);
-                }
+            let tail = self.tail.load(Ordering::Relaxed);
 
-                if backoff.is_completed() {
+            // Check if the queue is closed.
+            if tail & self.mark_bit != 0 {
+                return None;
+            }
+
+            // Count the consecutive slots that are ready for writing, starting at the tail.
+            let mut count = 0;
+            let mut new_tail = tail;
+            while count < max {
+                let slot = unsafe { self.slot(new_tail) };
+                if slot.stamp.load(Ordering::Acquire) != new_tail {
                     break;
-                } else {
-                    backoff.snooze();
                 }
+                new_tail = self.next_pos(new_tail);
+                count += 1;
             }
 
-            if let Some(d) = deadline {
-                if Instant::now() >= d {
-                    return Err(SendTimeoutError::Timeout(msg));
+            if count > 0 {
+                // Try moving the tail past all the counted slots at once.
+                match self.tail.compare_exchange_weak(
+                    tail,
+                    new_tail,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => return Some((tail, count)),
+                    Err(_) => backoff.spin(),
                 }
+                continue;
             }
 
-            Context::with(|cx| {
-                // Prepare for blocking until a receiver wakes us up.
-                let oper = Operation::hook(token);
-                self.senders.register(oper, cx);
-
-                // Has the channel become ready just now?
-                if !self.is_full() || self.is_disconnected() {
-                    let _ = cx.try_select(Selected::Aborted);
-                }
+            let slot = unsafe { self.slot(tail) };
+            let stamp = slot.stamp.load(Ordering::Acquire);
 
-                // Block the current thread.
-                let sel = cx.wait_until(deadline);
+            if stamp.wrapping_add(self.one_lap) == tail + 1 {
+                atomic::fence(Ordering::SeqCst);
+                let head = self.head.load(Ordering::Relaxed) & !self.mark_bit;
 
-                match sel {
-                    Selected::Waiting => unreachable!(),
-                    Selected::Aborted | Selected::Disconnected => {
-                        self.senders.unregister(oper).unwrap();
-                    }
-                    Selected::Operation(_) => {}
+                // If the head lags one lap behind the tail as well...
+                if head.wrapping_add(self.one_lap) == tail {
+                    // ...then the queue is full.
+                    return Some((tail, 0));
                 }
-            });
-        }
-    }
-
-    /// Attempts to receive a message without blocking.
-    pub fn try_recv(&self) -> Result<T, TryRecvError> {
-        let token = &mut Token::default();
 
-        if self.start_recv(token) {
-            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
-        } else {
-            Err(TryRecvError::Empty)
+                backoff.spin();
+            } else {
+                // Snooze because we need to wait for the stamp to get updated.
+                backoff.snooze();
+            }
         }
     }
 
-    /// Receives a message from the channel.
-    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
-        let token = &mut Token::default();
-        loop {
-            // Try receiving a message several times.
-            let backoff = Backoff::new();
-            loop {
-                if self.start_recv(token) {
-                    let res = unsafe { self.read(token) };
-                    return res.map_err(|_| RecvTimeoutError::Disconnected);
-                }
+    /// Attempts to reserve up to `max` consecutive slots for receiving messages.
+    ///
+    /// On success, returns the head at which the reserved slots begin and their number. Zero
+    /// reserved slots means the queue is empty. Returns `None` if the queue is closed and empty.
+    fn start_recv_batch(&self, max: usize) -> Option<(usize, usize)> {
+        let backoff = Backoff::new();
 
-                if backoff.is_completed() {
+        loop {
+            let head = self.head.load(Ordering::Relaxed);
+
+            // Count the consecutive slots that hold messages, starting at the head.
+            let mut count = 0;
+            let mut new_head = head;
+            while count < max {
+                let slot = unsafe { self.slot(new_head) };
+                if slot.stamp.load(Ordering::Acquire) != new_head + 1 {
                     break;
-                } else {
-                    backoff.snooze();
                 }
+                new_head = self.next_pos(new_head);
+                count += 1;
             }
 
-            if let Some(d) = deadline {
-                if Instant::now() >= d {
-                    return Err(RecvTimeoutError::Timeout);
+            if count > 0 {
+                // Try moving the head past all the counted slots at once.
+                match self.head.compare_exchange_weak(
+                    head,
+                    new_head,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => return Some((head, count)),
+                    Err(_) => backoff.spin(),
                 }
+                continue;
             }
 
-            Context::with(|cx| {
-                // Prepare for blocking until a sender wakes us up.
-                let oper = Operation::hook(token);
-                self.receivers.register(oper, cx);
-
-                // Has the channel become ready just now?
-                if !self.is_empty() || self.is_disconnected() {
-                    let _ = cx.try_select(Selected::Aborted);
-                }
+            let slot = unsafe { self.slot(head) };
+            let stamp = slot.stamp.load(Ordering::Acquire);
 
-                // Block the current thread.
-                let sel = cx.wait_until(deadline);
+            if stamp == head {
+                atomic::fence(Ordering::SeqCst);
+                let tail = self.tail.load(Ordering::Relaxed);
 
-                match sel {
-                    Selected::Waiting => unreachable!(),
-                    Selected::Aborted | Selected::Disconnected => {
-                        self.receivers.unregister(oper).unwrap();
-                        // If the channel was disconnected, we still have to check for remaining
-                        // messages.
+                // If the tail equals the head, that means the queue is empty.
+                if (tail & !self.mark_bit) == head {
+                    if tail & self.mark_bit != 0 {
+                        return None;
+                    } else {
+                        return Some((head, 0));
                     }
-                    Selected::Operation(_) => {}
                 }
-            });
+
+                backoff.spin();
+            } else {
+                // Snooze because we need to wait for the stamp to get updated.
+                utils::check_head_wait(&self.head);
+                backoff.snooze();
+            }
         }
     }
 
-    /// Returns the current number of messages inside the channel.
-    pub fn len(&self) -> usize {
-        loop {
-            // Load the tail, then load the head.
-            let tail = self.tail.load(Ordering::SeqCst);
-            let head = self.head.load(Ordering::SeqCst);
+    /// Locks the head so that the message at the head can be inspected without receiving it.
+    ///
+    /// Other receivers wait until the head is unlocked. Returns the unlocked value of the head, or
+    /// an error if the queue is empty. `Disconnected` means the queue is closed and empty.
+    fn lock_head(&self) -> Result<usize, TryRecvError> {
+        let backoff = Backoff::new();
 
-            // If the tail didn't change, we've got consistent values to work with.
-            if self.tail.load(Ordering::SeqCst) == tail {
-                let hix = head & (self.mark_bit - 1);
-                let tix = tail & (self.mark_bit - 1);
+        loop {
+            let head = self.head.load(Ordering::Relaxed);
 
-                return if hix < tix {
-                    tix - hix
-                } else if hix > tix {
-                    self.cap - hix + tix
-                } else if (tail & !self.mark_bit) == head {
-                    0
-                } else {
-                    self.cap
-                };
+            // If another receiver has locked the head, wait until it's done.
+            if head & self.mark_bit != 0 {
+                utils::check_head_wait(&self.head);
+                backoff.snooze();
+                continue;
             }
-        }
-    }
 
-    /// Returns the capacity of the channel.
-    pub fn capacity(&self) -> Option<usize> {
-        Some(self.cap)
-    }
+            let slot = unsafe { self.slot(head) };
+            let stamp = slot.stamp.load(Ordering::Acquire);
 
-    /// Disconnects the channel and wakes up all blocked senders and receivers.
-    ///
-    /// Returns `true` if this call disconnected the channel.
-    pub fn disconnect(&self) -> bool {
-        let tail = self.tail.fetch_or(self.mark_bit, Ordering::SeqCst);
+            // If the stamp is ahead of the head by 1, there is a message we may lock.
+            if head + 1 == stamp {
+                match self.head.compare_exchange_weak(
+                    head,
+                    head | self.mark_bit,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => return Ok(head),
+                    Err(_) => backoff.spin(),
+                }
+            } else if stamp == head {
+                atomic::fence(Ordering::SeqCst);
+                let tail = self.tail.load(Ordering::Relaxed);
 
-        if tail & self.mark_bit == 0 {
-            self.senders.disconnect();
-            self.receivers.disconnect();
-            true
-        } else {
-            false
+                // If the tail equals the head, that means the queue is empty.
+                if (tail & !self.mark_bit) == head {
+                    if tail & self.mark_bit != 0 {
+                        return Err(TryRecvError::Disconnected);
+                    } else {
+                        return Err(TryRecvError::Empty);
+                    }
+                }
+
+                backoff.spin();
+            } else {
+                // Snooze because we need to wait for the stamp to get updated.
+                backoff.snooze();
+            }
         }
     }
 
-    /// Returns `true` if the channel is disconnected.
-    pub fn is_disconnected(&self) -> bool {
-        self.tail.load(Ordering::SeqCst) & self.mark_bit != 0
-    }
+    /// Returns the head if there is a message at it, without locking the head.
+    ///
+    /// Returns an error if the queue is empty. `Disconnected` means the queue is closed and empty.
+    fn find_head(&self) -> Result<usize, TryRecvError> {
+        let backoff = Backoff::new();
 
-    /// Returns `true` if the channel is empty.
-    pub fn is_empty(&self) -> bool {
-        let head = self.head.load(Ordering::SeqCst);
+        loop {
+            let head = self.head.load(Ordering::Relaxed);
+
+            // If another receiver has locked the head, there is a message at it.
+            if head & self.mark_bit != 0 {
+                return Ok(head & !self.mark_bit);
+            }
+
+            let slot = unsafe { self.slot(head) };
+            let stamp = slot.stamp.load(Ordering::Acquire);
+
+            // If the stamp is ahead of the head by 1, there is a message.
+            if head + 1 == stamp {
+                return Ok(head);
+            } else if stamp == head {
+                atomic::fence(Ordering::SeqCst);
+                let tail = self.tail.load(Ordering::Relaxed);
+
+                // If the tail equals the head, that means the queue is empty.
+                if (tail & !self.mark_bit) == head {
+                    if tail & self.mark_bit != 0 {
+                        return Err(TryRecvError::Disconnected);
+                    } else {
+                        return Err(TryRecvError::Empty);
+                    }
+                }
+
+                backoff.spin();
+            } else {
+                // Snooze because we need to wait for the stamp to get updated.
+                backoff.snooze();
+            }
+        }
+    }
+
+    /// Pushes a message into the queue, evicting the oldest message if the queue is full.
+    ///
+    /// Returns the evicted message, if any, or gives the message back if the queue is closed.
+    fn force_push(&self, msg: T) -> Result<Option<T>, T> {
+        let backoff = Backoff::new();
+        let token = &mut Token::default();
+
+        loop {
+            if self.start_send(token) {
+                if token.array.slot.is_null() {
+                    return Err(msg);
+                }
+
+                // Write the message into the slot and update the stamp.
+                let slot = unsafe { &*(token.array.slot as *const Slot<T>) };
+                unsafe { slot.msg.get().write(msg) };
+                slot.stamp.store(token.array.stamp, Ordering::Release);
+                return Ok(None);
+            }
+
+            // Lock the head so that no receiver can take the oldest message away from us.
+            let head = match self.lock_head() {
+                Ok(head) => head,
+                Err(_) => {
+                    // The queue is empty so it's not full anymore.
+                    backoff.spin();
+                    continue;
+                }
+            };
+            let mut lock = HeadLock::new(&self.head, head);
+
+            let tail = self.tail.load(Ordering::SeqCst);
+            if tail & self.mark_bit != 0 {
+                return Err(msg);
+            }
+
+            // If a message has been received in the meantime, try sending again.
+            if head.wrapping_add(self.one_lap) != tail {
+                drop(lock);
+                backoff.spin();
+                continue;
+            }
+
+            // Try moving the tail. This fails if the queue got closed in the meantime.
+            if self
+                .tail
+                .compare_exchange(
+                    tail,
+                    self.next_pos(tail),
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                )
+                .is_err()
+            {
+                drop(lock);
+                backoff.spin();
+                continue;
+            }
+
+            // The queue is full, so the slot at the tail holds the oldest message. Swap it with the
+            // new message, which becomes the newest one.
+            let slot = unsafe { self.slot(tail) };
+            let old = unsafe { slot.msg.get().replace(msg) };
+            slot.stamp.store(tail + 1, Ordering::Release);
+
+            // Unlock the head by moving it to the next slot.
+            lock.value = self.next_pos(head);
+            return Ok(Some(old));
+        }
+    }
+
+    /// Returns the current number of messages inside the queue.
+    fn len(&self) -> usize {
+        loop {
+            // Load the tail, then load the head.
+            let tail = self.tail.load(Ordering::SeqCst);
+            let head = self.head.load(Ordering::SeqCst) & !self.mark_bit;
+
+            // If the tail didn't change, we've got consistent values to work with.
+            if self.tail.load(Ordering::SeqCst) == tail {
+                let hix = head & (self.mark_bit - 1);
+                let tix = tail & (self.mark_bit - 1);
+
+                return if hix < tix {
+                    tix - hix
+                } else if hix > tix {
+                    self.cap - hix + tix
+                } else if (tail & !self.mark_bit) == head {
+                    0
+                } else {
+                    self.cap
+                };
+            }
+        }
+    }
+
+    /// Returns `true` if the queue is closed.
+    fn is_closed(&self) -> bool {
+        self.tail.load(Ordering::SeqCst) & self.mark_bit != 0
+    }
+
+    /// Returns `true` if the queue is empty.
+    fn is_empty(&self) -> bool {
+        let head = self.head.load(Ordering::SeqCst) & !self.mark_bit;
         let tail = self.tail.load(Ordering::SeqCst);
 
         // Is the tail equal to the head?
         //
         // Note: If the head changes just before we load the tail, that means there was a moment
-        // when the channel was not empty, so it is safe to just return `false`.
+        // when the queue was not empty, so it is safe to just return `false`.
         (tail & !self.mark_bit) == head
     }
 
-    /// Returns `true` if the channel is full.
-    pub fn is_full(&self) -> bool {
+    /// Returns `true` if the queue is full.
+    fn is_full(&self) -> bool {
         let tail = self.tail.load(Ordering::SeqCst);
-        let head = self.head.load(Ordering::SeqCst);
+        let head = self.head.load(Ordering::SeqCst) & !self.mark_bit;
 
         // Is the head lagging one lap behind tail?
         //
         // Note: If the tail changes just before we load the head, that means there was a moment
-        // when the channel was not full, so it is safe to just return `false`.
+        // when the queue was not full, so it is safe to just return `false`.
         head.wrapping_add(self.one_lap) == tail & !self.mark_bit
     }
+
+    /// Returns the queue that replaced this one, if any.
+    ///
+    /// The replacement is linked before the queue is closed, so it's always found after the queue
+    /// has been seen closed.
+    fn next<'g>(&self, guard: &'g Guard) -> Option<&'g Queue<T>> {
+        atomic::fence(Ordering::Acquire);
+        unsafe { self.next.load(Ordering::Acquire, guard).as_ref() }
+    }
+
+    /// Returns `true` if the queue has been replaced.
+    #[inline]
+    fn is_replaced(&self) -> bool {
+        // Only the pointer is compared, so there is no need to protect it.
+        unsafe { self.next(epoch::unprotected()).is_some() }
+    }
+
+    /// Returns `true` if no operation is still reading from or writing into the queue.
+    ///
+    /// This only makes sense for a queue that has been closed and then emptied, in which case
+    /// every slot gets stamped with its own index once the receiver reading from it is done.
+    fn is_quiescent(&self) -> bool {
+        (0..self.cap).all(|i| {
+            let slot = unsafe { &*self.buffer.add(i) };
+            slot.stamp.load(Ordering::Acquire) & (self.mark_bit - 1) == i
+        })
+    }
 }
 
-impl<T> Drop for Channel<T> {
+impl<T> Drop for Queue<T> {
     fn drop(&mut self) {
         // Get the index of the head.
         let hix = self.head.load(Ordering::Relaxed) & (self.mark_bit - 1);
@@ -553,6 +716,1125 @@ impl<T> Drop for Channel<T> {
     }
 }
 
+/// Bounded channel based on a preallocated array.
+pub struct Channel<T> {
+    /// The queue the channel was created with.
+    ///
+    /// Until the capacity is changed for the first time, it is the only queue and every operation
+    /// goes straight to it.
+    queue: Queue<T>,
+
+    /// The oldest queue that may still hold messages, or null if that is the original queue.
+    ///
+    /// Receivers take messages from this queue, and move on to the next one once it's closed and
+    /// empty.
+    front: Atomic<Queue<T>>,
+
+    /// Queues that have been emptied but may still be read from by receivers.
+    ///
+    /// Locking this list also serializes changes of the capacity with disconnection, except for
+    /// the first change, which `set_capacity` and `disconnect` resolve without the lock.
+    retired: Spinlock<Vec<Owned<Queue<T>>>>,
+
+    /// Serializes senders while older queues still hold messages.
+    ///
+    /// Messages in older queues count towards the capacity of the newest one, so checking for
+    /// room and reserving a slot must happen at once.
+    draining: Spinlock<()>,
+
+    /// Senders waiting while the channel is full.
+    senders: SyncWaker,
+
+    /// Receivers waiting while the channel is empty and not disconnected.
+    receivers: SyncWaker,
+
+    /// Statistics about operations on the channel.
+    metrics: Metrics,
+
+    /// Indicates that dropping a `Channel<T>` may drop values of type `T`.
+    _marker: PhantomData<T>,
+}
+
+impl<T> Channel<T> {
+    /// Creates a bounded channel of capacity `cap`.
+    pub fn with_capacity(cap: usize) -> Self {
+        assert!(cap > 0, "capacity must be positive");
+
+        Channel {
+            queue: Queue::with_capacity(cap),
+            front: Atomic::null(),
+            retired: Spinlock::new(Vec::new()),
+            draining: Spinlock::new(()),
+            senders: SyncWaker::new(),
+            receivers: SyncWaker::new(),
+            metrics: Metrics::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub fn receiver(&self) -> Receiver<T> {
+        Receiver(self)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub fn sender(&self) -> Sender<T> {
+        Sender(self)
+    }
+
+    /// Returns the oldest queue that may still hold messages.
+    fn front<'g>(&'g self, guard: &'g Guard) -> &'g Queue<T> {
+        match unsafe { self.front.load(Ordering::Acquire, guard).as_ref() } {
+            Some(queue) => queue,
+            None => &self.queue,
+        }
+    }
+
+    /// Returns the oldest queue that may still hold messages, first moving the front past queues
+    /// that have been replaced and emptied.
+    ///
+    /// Senders call this so that they stop counting messages in older queues as soon as there are
+    /// none left, without waiting for a receiver to move the front.
+    fn live_front<'g>(&'g self, guard: &'g Guard) -> &'g Queue<T> {
+        let mut front = self.front(guard);
+        while let Some(next) = front.next(guard) {
+            // The queue is linked to its replacement before it's closed, so check both.
+            if !front.is_closed() || !front.is_empty() {
+                break;
+            }
+            self.advance(front, next, guard);
+            front = self.front(guard);
+        }
+        front
+    }
+
+    /// Returns the newest queue, which accepts new messages, following the queues from `front`.
+    fn back<'g>(&self, front: &'g Queue<T>, guard: &'g Guard) -> &'g Queue<T> {
+        let mut queue = front;
+        while let Some(next) = queue.next(guard) {
+            queue = next;
+        }
+        queue
+    }
+
+    /// Returns the number of messages in `front` and all queues after it.
+    fn len_from(&self, front: &Queue<T>, guard: &Guard) -> usize {
+        let mut len = front.len();
+        let mut queue = front;
+        while let Some(next) = queue.next(guard) {
+            len += next.len();
+            queue = next;
+        }
+        len
+    }
+
+    /// Moves the front from `queue`, which is closed and empty, to the queue that replaced it.
+    #[cold]
+    fn advance(&self, queue: &Queue<T>, next: &Queue<T>, guard: &Guard) {
+        let current = if ptr::eq(queue, &self.queue) {
+            Shared::null()
+        } else {
+            Shared::from(queue as *const Queue<T>)
+        };
+
+        if self
+            .front
+            .compare_and_set(
+                current,
+                Shared::from(next as *const Queue<T>),
+                Ordering::AcqRel,
+                guard,
+            )
+            .is_ok()
+            && !current.is_null()
+        {
+            // Receivers may still be reading from the queue, so it can't be destroyed just yet.
+            let mut retired = self.retired.lock();
+            retired.push(unsafe { current.into_owned() });
+            Channel::collect(&mut retired, guard);
+        }
+    }
+
+    /// Destroys retired queues that are no longer read from.
+    fn collect(retired: &mut Vec<Owned<Queue<T>>>, guard: &Guard) {
+        let mut i = 0;
+        while i < retired.len() {
+            if retired[i].is_quiescent() {
+                let queue = retired.swap_remove(i);
+                unsafe { guard.defer_destroy(queue.into_shared(guard)) };
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// Attempts to reserve a slot for sending a message.
+    fn start_send(&self, token: &mut Token) -> bool {
+        if !self.queue.start_send(token) {
+            return false;
+        }
+
+        // If the original queue is closed, the capacity may have been changed.
+        if token.array.slot.is_null() {
+            self.start_send_replaced(token)
+        } else {
+            true
+        }
+    }
+
+    /// Attempts to reserve a slot for sending a message after the capacity has been changed.
+    ///
+    /// Kept out of line so that the fast path stays small.
+    #[inline(never)]
+    fn start_send_replaced(&self, token: &mut Token) -> bool {
+        // If the queue hasn't been replaced, the channel is disconnected.
+        if !self.queue.is_replaced() {
+            return true;
+        }
+
+        let guard = &epoch::pin();
+
+        loop {
+            let front = self.live_front(guard);
+            let back = self.back(front, guard);
+
+            // Messages sent before the capacity was changed count towards the new capacity. Once
+            // older queues are empty, they stay empty, so only the newest queue limits the rest.
+            if !ptr::eq(front, back) {
+                let _draining = self.draining.lock();
+                if self.len_from(front, guard) >= back.cap || !back.start_send(token) {
+                    return false;
+                }
+            } else if !back.start_send(token) {
+                return false;
+            }
+
+            // If the queue has just been replaced, try the next one.
+            if token.array.slot.is_null() && back.next(guard).is_some() {
+                continue;
+            }
+            return true;
+        }
+    }
+
+    /// Writes a message into the channel.
+    pub unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        // If there is no slot, the channel is disconnected.
+        if token.array.slot.is_null() {
+            return Err(msg);
+        }
+
+        let slot: &Slot<T> = &*(token.array.slot as *const Slot<T>);
+
+        // Write the message into the slot and update the stamp.
+        slot.msg.get().write(msg);
+        slot.stamp.store(token.array.stamp, Ordering::Release);
+        self.metrics.sent(1, || self.len());
+
+        // Wake a sleeping receiver.
+        self.receivers.notify();
+        Ok(())
+    }
+
+    /// Attempts to reserve a slot for receiving a message.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        if !self.queue.start_recv(token) {
+            return false;
+        }
+
+        // If the original queue is closed and empty, the capacity may have been changed.
+        if token.array.slot.is_null() {
+            self.start_recv_replaced(token)
+        } else {
+            true
+        }
+    }
+
+    /// Attempts to reserve a slot for receiving a message after the capacity has been changed.
+    ///
+    /// Kept out of line so that the fast path stays small.
+    #[inline(never)]
+    fn start_recv_replaced(&self, token: &mut Token) -> bool {
+        // If the queue hasn't been replaced, the channel is disconnected.
+        if !self.queue.is_replaced() {
+            return true;
+        }
+
+        let guard = &epoch::pin();
+
+        loop {
+            let front = self.front(guard);
+            if !front.start_recv(token) {
+                return false;
+            }
+            if !token.array.slot.is_null() {
+                return true;
+            }
+
+            // The queue is closed and empty. If it has been replaced, move on to the next one.
+            match front.next(guard) {
+                Some(next) => self.advance(front, next, guard),
+                None => return true,
+            }
+        }
+    }
+
+    /// Reads a message from the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if token.array.slot.is_null() {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        let slot: &Slot<T> = &*(token.array.slot as *const Slot<T>);
+
+        // Read the message from the slot and update the stamp.
+        let msg = slot.msg.get().read();
+        slot.stamp.store(token.array.stamp, Ordering::Release);
+        self.metrics.received(1);
+
+        // Wake a sleeping sender.
+        self.senders.notify();
+        Ok(msg)
+    }
+
+    /// Writes messages from the front of `msgs` into slots of `queue` reserved by
+    /// `start_send_batch`.
+    unsafe fn write_batch(
+        &self,
+        queue: &Queue<T>,
+        tail: usize,
+        count: usize,
+        msgs: &mut VecDeque<T>,
+    ) {
+        let mut pos = tail;
+        for _ in 0..count {
+            let slot = queue.slot(pos);
+
+            // Write the message into the slot and update the stamp.
+            slot.msg.get().write(msgs.pop_front().unwrap());
+            slot.stamp.store(pos + 1, Ordering::Release);
+
+            pos = queue.next_pos(pos);
+        }
+        self.metrics.sent(count, || self.len());
+
+        // Wake as many sleeping receivers as there are new messages.
+        self.receivers.notify_many(count);
+    }
+
+    /// Reads messages from slots of `queue` reserved by `start_recv_batch` into `buf`.
+    unsafe fn read_batch(&self, queue: &Queue<T>, head: usize, count: usize, buf: &mut Vec<T>) {
+        buf.reserve(count);
+
+        let mut pos = head;
+        for _ in 0..count {
+            let slot = queue.slot(pos);
+
+            // Read the message from the slot and update the stamp.
+            buf.push(slot.msg.get().read());
+            slot.stamp
+                .store(pos.wrapping_add(queue.one_lap), Ordering::Release);
+
+            pos = queue.next_pos(pos);
+        }
+        self.metrics.received(count);
+
+        // Wake as many sleeping senders as there are freed slots.
+        self.senders.notify_many(count);
+    }
+
+    /// Finds the head of the front queue with `head` and calls `f` with the queue and the unlocked
+    /// value of its head.
+    ///
+    /// The head is found with either `Queue::lock_head` or `Queue::find_head`.
+    fn with_head<H, F, R>(&self, head: H, f: F) -> Result<R, TryRecvError>
+    where
+        H: Fn(&Queue<T>) -> Result<usize, TryRecvError>,
+        F: FnOnce(&Queue<T>, usize) -> R,
+    {
+        match head(&self.queue) {
+            Ok(h) => Ok(f(&self.queue, h)),
+            Err(TryRecvError::Disconnected) if self.queue.is_replaced() => {
+                self.with_head_replaced(head, f)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Same as `with_head`, but called after the capacity has been changed.
+    fn with_head_replaced<H, F, R>(&self, head: H, f: F) -> Result<R, TryRecvError>
+    where
+        H: Fn(&Queue<T>) -> Result<usize, TryRecvError>,
+        F: FnOnce(&Queue<T>, usize) -> R,
+    {
+        let guard = &epoch::pin();
+
+        loop {
+            let front = self.front(guard);
+            match head(front) {
+                Ok(h) => return Ok(f(front, h)),
+                Err(TryRecvError::Disconnected) => match front.next(guard) {
+                    Some(next) => self.advance(front, next, guard),
+                    None => return Err(TryRecvError::Disconnected),
+                },
+                Err(TryRecvError::Empty) => return Err(TryRecvError::Empty),
+            }
+        }
+    }
+
+    /// Calls `f` on the message at the head without receiving it.
+    pub fn peek_with<F, R>(&self, f: F) -> Result<R, TryRecvError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.with_head(Queue::lock_head, |queue, head| {
+            let _lock = HeadLock::new(&queue.head, head);
+
+            let slot = unsafe { queue.slot(head) };
+            f(unsafe { &*slot.msg.get() })
+        })
+    }
+
+    /// Receives the message at the head only if it satisfies `pred`.
+    pub fn try_recv_if<F>(&self, pred: F) -> Result<Option<T>, TryRecvError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        self.with_head(Queue::lock_head, |queue, head| {
+            self.recv_locked(queue, HeadLock::new(&queue.head, head), pred)
+        })
+    }
+
+    /// Attempts to select a conditional receive.
+    ///
+    /// The head is not locked. Its queue and value are remembered so that `read_if` can check
+    /// whether the message is still there.
+    pub fn start_peek(&self, token: &mut Token) -> bool {
+        let res = self.with_head(Queue::find_head, |queue, head| {
+            token.array.slot = queue as *const Queue<T> as *const u8;
+            token.array.stamp = head;
+        });
+
+        match res {
+            Ok(()) => true,
+            Err(TryRecvError::Disconnected) => {
+                token.array.slot = ptr::null();
+                token.array.stamp = 0;
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+        }
+    }
+
+    /// Receives the message selected by `start_peek` only if it satisfies `pred`.
+    ///
+    /// Fails with `Empty` if another receiver has taken the message in the meantime.
+    pub unsafe fn read_if<F>(&self, token: &mut Token, pred: F) -> Result<Option<T>, TryRecvError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        // If there is no queue, the channel is disconnected.
+        if token.array.slot.is_null() {
+            return Err(TryRecvError::Disconnected);
+        }
+
+        self.with_head(Queue::lock_head, |queue, head| {
+            let lock = HeadLock::new(&queue.head, head);
+
+            // The queue in the token is only compared by address, since it may have been
+            // destroyed in the meantime.
+            if queue as *const Queue<T> as *const u8 != token.array.slot
+                || head != token.array.stamp
+            {
+                return Err(TryRecvError::Empty);
+            }
+            Ok(self.recv_locked(queue, lock, pred))
+        })
+        .and_then(|res| res)
+    }
+
+    /// Receives the message at the locked head of `queue` only if it satisfies `pred`, and then
+    /// unlocks the head.
+    fn recv_locked<F>(&self, queue: &Queue<T>, mut lock: HeadLock, pred: F) -> Option<T>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        let head = lock.value;
+        let slot = unsafe { queue.slot(head) };
+        if !pred(unsafe { &*slot.msg.get() }) {
+            return None;
+        }
+
+        // Read the message from the slot and update the stamp.
+        let msg = unsafe { slot.msg.get().read() };
+        slot.stamp
+            .store(head.wrapping_add(queue.one_lap), Ordering::Release);
+
+        // Unlock the head by moving it to the next slot.
+        lock.value = queue.next_pos(head);
+        drop(lock);
+
+        self.metrics.received(1);
+
+        // Wake a sleeping sender.
+        self.senders.notify();
+        Some(msg)
+    }
+
+    /// Changes the capacity of the channel.
+    ///
+    /// The queue accepting new messages is closed and replaced with a new queue of capacity `cap`.
+    /// Messages already in the channel stay where they are and are received before any message
+    /// sent after the change. Until then, they count towards the new capacity.
+    ///
+    /// This never waits for other operations to complete.
+    pub fn set_capacity(&self, cap: usize) {
+        assert!(cap > 0, "capacity must be positive");
+
+        let guard = &epoch::pin();
+        let mut retired = self.retired.lock();
+        let back = self.back(self.front(guard), guard);
+
+        // A disconnected channel stays as it is, and so does one that already has this capacity.
+        if back.is_closed() || back.cap == cap {
+            return;
+        }
+
+        // Link the new queue before closing the old one so that anyone who sees the old queue
+        // closed also finds its replacement.
+        back.next
+            .store(Owned::new(Queue::with_capacity(cap)), Ordering::Release);
+        let tail = back.tail.fetch_or(back.mark_bit, Ordering::SeqCst);
+
+        // The original queue is closed without locking the list of retired queues if the capacity
+        // has never been changed. If that happened just now, close the new queue as well.
+        if tail & back.mark_bit != 0 {
+            let next = back.next(guard).unwrap();
+            next.tail.fetch_or(next.mark_bit, Ordering::SeqCst);
+        }
+
+        Channel::collect(&mut retired, guard);
+        drop(retired);
+
+        // Wake all sleeping senders so that they move on to the new queue.
+        self.senders.notify_all();
+    }
+
+    /// Attempts to send a message into the channel.
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        let token = &mut Token::default();
+        if self.start_send(token) {
+            unsafe { self.write(token, msg).map_err(TrySendError::Disconnected) }
+        } else {
+            Err(TrySendError::Full(msg))
+        }
+    }
+
+    /// Sends a message into the channel.
+    pub fn send(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        let token = &mut Token::default();
+        loop {
+            // Try sending a message several times.
+            let backoff = Backoff::new();
+            loop {
+                if self.start_send(token) {
+                    let res = unsafe { self.write(token, msg) };
+                    return res.map_err(SendTimeoutError::Disconnected);
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(SendTimeoutError::Timeout(msg));
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a receiver wakes us up.
+                let oper = Operation::hook(token);
+                self.senders.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if !self.is_full() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = self.metrics.send_wait(|| cx.wait_until(deadline));
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.senders.unregister(oper).unwrap();
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Sends a message into the channel, evicting the oldest message if the channel is full.
+    ///
+    /// Returns the evicted message, if any.
+    pub fn force_send(&self, msg: T) -> Result<Option<T>, T> {
+        let res = match self.queue.force_push(msg) {
+            Err(msg) => {
+                if self.queue.is_replaced() {
+                    self.force_send_replaced(msg)
+                } else {
+                    Err(msg)
+                }
+            }
+            res => res,
+        };
+
+        if res.is_ok() {
+            self.metrics.sent(1, || self.len());
+
+            // Wake a sleeping receiver.
+            self.receivers.notify();
+        }
+        res
+    }
+
+    /// Same as `force_send`, but called after the capacity has been changed.
+    fn force_send_replaced(&self, mut msg: T) -> Result<Option<T>, T> {
+        let guard = &epoch::pin();
+        let backoff = Backoff::new();
+        let token = &mut Token::default();
+
+        loop {
+            let front = self.live_front(guard);
+            let back = self.back(front, guard);
+
+            // If all messages are in the newest queue, evict from it.
+            if ptr::eq(front, back) {
+                match back.force_push(msg) {
+                    Err(m) => {
+                        if back.next(guard).is_none() {
+                            return Err(m);
+                        }
+                        msg = m;
+                        continue;
+                    }
+                    res => return res,
+                }
+            }
+
+            // If there is room, send without evicting anything.
+            let reserved = {
+                let _draining = self.draining.lock();
+                self.len_from(front, guard) < back.cap && back.start_send(token)
+            };
+            if reserved {
+                if token.array.slot.is_null() {
+                    if back.next(guard).is_none() {
+                        return Err(msg);
+                    }
+                    continue;
+                }
+
+                let slot = unsafe { &*(token.array.slot as *const Slot<T>) };
+                unsafe { slot.msg.get().write(msg) };
+                slot.stamp.store(token.array.stamp, Ordering::Release);
+                return Ok(None);
+            }
+
+            // Otherwise, the oldest message is at the head of the front queue. Lock the head so
+            // that no receiver can take it away from us.
+            let head = match front.lock_head() {
+                Ok(head) => head,
+                Err(TryRecvError::Disconnected) => {
+                    if let Some(next) = front.next(guard) {
+                        self.advance(front, next, guard);
+                    }
+                    continue;
+                }
+                Err(TryRecvError::Empty) => {
+                    backoff.spin();
+                    continue;
+                }
+            };
+            let mut lock = HeadLock::new(&front.head, head);
+
+            // Reserve a slot for the new message.
+            if !back.start_send(token) {
+                // The newest queue is full, which can happen after the capacity was lowered. The
+                // new message can only go into that queue, so evict the oldest message in it.
+                drop(lock);
+                match back.force_push(msg) {
+                    Err(m) => {
+                        if back.next(guard).is_none() {
+                            return Err(m);
+                        }
+                        msg = m;
+                        continue;
+                    }
+                    res => return res,
+                }
+            }
+            if token.array.slot.is_null() {
+                if back.next(guard).is_none() {
+                    return Err(msg);
+                }
+                continue;
+            }
+
+            // Write the new message, then take the oldest message out of its slot.
+            let slot = unsafe { &*(token.array.slot as *const Slot<T>) };
+            unsafe { slot.msg.get().write(msg) };
+            slot.stamp.store(token.array.stamp, Ordering::Release);
+
+            let slot = unsafe { front.slot(head) };
+            let old = unsafe { slot.msg.get().read() };
+            slot.stamp
+                .store(head.wrapping_add(front.one_lap), Ordering::Release);
+
+            // Unlock the head by moving it to the next slot.
+            lock.value = front.next_pos(head);
+            return Ok(Some(old));
+        }
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let token = &mut Token::default();
+
+        if self.start_recv(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            // Try receiving a message several times.
+            let backoff = Backoff::new();
+            loop {
+                if self.start_recv(token) {
+                    let res = unsafe { self.read(token) };
+                    return res.map_err(|_| RecvTimeoutError::Disconnected);
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a sender wakes us up.
+                let oper = Operation::hook(token);
+                self.receivers.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if !self.is_empty() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = self.metrics.recv_wait(|| cx.wait_until(deadline));
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.receivers.unregister(oper).unwrap();
+                        // If the channel was disconnected, we still have to check for remaining
+                        // messages.
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Attempts to send messages from the front of `msgs` without blocking.
+    ///
+    /// Sent messages are removed from `msgs`. Fails if not all of them could be sent.
+    pub fn try_send_batch(&self, msgs: &mut VecDeque<T>) -> Result<(), TrySendError<()>> {
+        while !msgs.is_empty() {
+            match self.queue.start_send_batch(msgs.len()) {
+                None => {
+                    if self.queue.is_replaced() {
+                        return self.try_send_batch_replaced(msgs);
+                    }
+                    return Err(TrySendError::Disconnected(()));
+                }
+                Some((_, 0)) => return Err(TrySendError::Full(())),
+                Some((tail, count)) => unsafe { self.write_batch(&self.queue, tail, count, msgs) },
+            }
+        }
+        Ok(())
+    }
+
+    /// Same as `try_send_batch`, but called after the capacity has been changed.
+    fn try_send_batch_replaced(&self, msgs: &mut VecDeque<T>) -> Result<(), TrySendError<()>> {
+        let guard = &epoch::pin();
+
+        while !msgs.is_empty() {
+            let front = self.live_front(guard);
+            let back = self.back(front, guard);
+
+            // Messages sent before the capacity was changed count towards the new capacity.
+            let reserved = if ptr::eq(front, back) {
+                back.start_send_batch(msgs.len())
+            } else {
+                let _draining = self.draining.lock();
+                let len = self.len_from(front, guard);
+                if len >= back.cap {
+                    return Err(TrySendError::Full(()));
+                }
+                back.start_send_batch(cmp::min(msgs.len(), back.cap - len))
+            };
+
+            match reserved {
+                None => {
+                    // If the queue has just been replaced, try the next one.
+                    if back.next(guard).is_none() {
+                        return Err(TrySendError::Disconnected(()));
+                    }
+                }
+                Some((_, 0)) => return Err(TrySendError::Full(())),
+                Some((tail, count)) => unsafe { self.write_batch(back, tail, count, msgs) },
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends all messages in `msgs`, blocking while the channel is full.
+    ///
+    /// Sent messages are removed from `msgs`.
+    pub fn send_batch(
+        &self,
+        msgs: &mut VecDeque<T>,
+        deadline: Option<Instant>,
+    ) -> Result<(), SendTimeoutError<()>> {
+        let token = &mut Token::default();
+        loop {
+            // Try sending the messages several times.
+            let backoff = Backoff::new();
+            loop {
+                match self.try_send_batch(msgs) {
+                    Ok(()) => return Ok(()),
+                    Err(TrySendError::Disconnected(())) => {
+                        return Err(SendTimeoutError::Disconnected(()))
+                    }
+                    Err(TrySendError::Full(())) => {}
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(SendTimeoutError::Timeout(()));
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a receiver wakes us up.
+                let oper = Operation::hook(token);
+                self.senders.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if !self.is_full() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = self.metrics.send_wait(|| cx.wait_until(deadline));
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.senders.unregister(oper).unwrap();
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Attempts to receive up to `max` messages into `buf` without blocking.
+    pub fn try_recv_batch(&self, buf: &mut Vec<T>, max: usize) -> Result<usize, TryRecvError> {
+        match self.queue.start_recv_batch(max) {
+            None => {
+                if self.queue.is_replaced() {
+                    self.try_recv_batch_replaced(buf, max)
+                } else {
+                    Err(TryRecvError::Disconnected)
+                }
+            }
+            Some((_, 0)) => Err(TryRecvError::Empty),
+            Some((head, count)) => {
+                unsafe { self.read_batch(&self.queue, head, count, buf) };
+                Ok(count)
+            }
+        }
+    }
+
+    /// Same as `try_recv_batch`, but called after the capacity has been changed.
+    fn try_recv_batch_replaced(&self, buf: &mut Vec<T>, max: usize) -> Result<usize, TryRecvError> {
+        let guard = &epoch::pin();
+
+        loop {
+            let front = self.front(guard);
+            match front.start_recv_batch(max) {
+                None => match front.next(guard) {
+                    Some(next) => self.advance(front, next, guard),
+                    None => return Err(TryRecvError::Disconnected),
+                },
+                Some((_, 0)) => return Err(TryRecvError::Empty),
+                Some((head, count)) => {
+                    unsafe { self.read_batch(front, head, count, buf) };
+                    return Ok(count);
+                }
+            }
+        }
+    }
+
+    /// Receives up to `max` messages into `buf`, blocking until at least one is available.
+    pub fn recv_batch(
+        &self,
+        buf: &mut Vec<T>,
+        max: usize,
+        deadline: Option<Instant>,
+    ) -> Result<usize, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            // Try receiving messages several times.
+            let backoff = Backoff::new();
+            loop {
+                match self.try_recv_batch(buf, max) {
+                    Ok(count) => return Ok(count),
+                    Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                    Err(_) => {}
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a sender wakes us up.
+                let oper = Operation::hook(token);
+                self.receivers.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if !self.is_empty() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = self.metrics.recv_wait(|| cx.wait_until(deadline));
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.receivers.unregister(oper).unwrap();
+                        // If the channel was disconnected, we still have to check for remaining
+                        // messages.
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Returns the current number of messages inside the channel.
+    pub fn len(&self) -> usize {
+        if self.queue.is_replaced() {
+            let guard = &epoch::pin();
+            self.len_from(self.front(guard), guard)
+        } else {
+            self.queue.len()
+        }
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        if self.queue.is_replaced() {
+            let guard = &epoch::pin();
+            Some(self.back(self.front(guard), guard).cap)
+        } else {
+            Some(self.queue.cap)
+        }
+    }
+
+    /// Disconnects the channel and wakes up all blocked senders and receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        // If the capacity has never been changed, close the original queue.
+        if !self.queue.is_replaced() {
+            let tail = self
+                .queue
+                .tail
+                .fetch_or(self.queue.mark_bit, Ordering::SeqCst);
+            if tail & self.queue.mark_bit == 0 {
+                self.senders.disconnect();
+                self.receivers.disconnect();
+                return true;
+            }
+
+            // The queue has been closed either by disconnection or by replacing it.
+            if !self.queue.is_replaced() {
+                return false;
+            }
+        }
+
+        let guard = &epoch::pin();
+
+        // Make sure no queue gets linked after the newest one is closed.
+        let _retired = self.retired.lock();
+        let back = self.back(self.front(guard), guard);
+        let tail = back.tail.fetch_or(back.mark_bit, Ordering::SeqCst);
+
+        if tail & back.mark_bit == 0 {
+            self.senders.disconnect();
+            self.receivers.disconnect();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        if self.queue.is_replaced() {
+            let guard = &epoch::pin();
+            let back = self.back(self.front(guard), guard);
+            back.is_closed() && back.next(guard).is_none()
+        } else {
+            self.queue.is_closed() && !self.queue.is_replaced()
+        }
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        if self.queue.is_replaced() {
+            let guard = &epoch::pin();
+            self.len_from(self.front(guard), guard) == 0
+        } else {
+            self.queue.is_empty()
+        }
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        if self.queue.is_replaced() {
+            let guard = &epoch::pin();
+            let front = self.front(guard);
+            self.len_from(front, guard) >= self.back(front, guard).cap
+        } else {
+            self.queue.is_full()
+        }
+    }
+
+    /// Returns a snapshot of statistics about the channel.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> Stats {
+        self.metrics
+            .stats(self.senders.waiting(), self.receivers.waiting())
+    }
+
+    /// Returns the statistics recorded by the channel.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+#[cfg(feature = "debug")]
+impl<T> Inspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "array"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn blocked(&self) -> (Vec<Context>, Vec<Context>) {
+        (self.senders.contexts(), self.receivers.contexts())
+    }
+}
+
+impl<T> Drop for Channel<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = epoch::unprotected();
+
+            // Destroy the queues from the front onwards. Queues before the front have been retired,
+            // except for the original one, which is dropped along with the channel.
+            let mut queue = self.front.load(Ordering::Relaxed, guard);
+            if queue.is_null() {
+                queue = self.queue.next.load(Ordering::Relaxed, guard);
+            }
+
+            while !queue.is_null() {
+                let next = queue.deref().next.load(Ordering::Relaxed, guard);
+                drop(queue.into_owned());
+                queue = next;
+            }
+        }
+    }
+}
+
+/// Unlocks the head of a queue when dropped, even if inspecting the message panics.
+struct HeadLock<'a> {
+    /// The head of the queue.
+    head: &'a AtomicUsize,
+
+    /// The value the head is set to when unlocked.
+    value: usize,
+}
+
+impl<'a> HeadLock<'a> {
+    /// Takes over a head that has just been locked.
+    fn new(head: &'a AtomicUsize, value: usize) -> HeadLock<'a> {
+        utils::set_head_locked(head, true);
+        HeadLock { head, value }
+    }
+}
+
+impl<'a> Drop for HeadLock<'a> {
+    fn drop(&mut self) {
+        utils::set_head_locked(self.head, false);
+        self.head.store(self.value, Ordering::SeqCst);
+    }
+}
+
 /// Receiver handle to a channel.
 pub struct Receiver<'a, T: 'a>(&'a Channel<T>);
 
@@ -0,0 +1,519 @@
+//! Broadcast channel that delivers every message to every receiver.
+//!
+//! This flavor has a fixed, positive capacity.
+//!
+//! Messages are kept in a ring buffer shared by all receivers, and each receiver has its own
+//! cursor pointing into it. Sending never blocks: if the buffer is full, the oldest message gets
+//! overwritten. Receivers whose cursor still points to an overwritten message have fallen behind
+//! and skip over the messages they missed, unless they ask to be told about them.
+
+use std::collections::VecDeque;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use context::Context;
+#[cfg(feature = "debug")]
+use debug::Inspect;
+use err::{BroadcastRecvError, RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use metrics::Metrics;
+#[cfg(feature = "metrics")]
+use metrics::Stats;
+use select::{Operation, SelectHandle, Selected, Token};
+use utils::Spinlock;
+use waker::SyncWaker;
+
+/// The position of a receiver in the channel.
+#[derive(Debug)]
+pub struct Cursor {
+    /// Sequence number of the next message to receive.
+    ///
+    /// This value is only modified while the channel is locked.
+    pos: AtomicUsize,
+}
+
+impl Clone for Cursor {
+    fn clone(&self) -> Cursor {
+        Cursor {
+            pos: AtomicUsize::new(self.pos.load(Ordering::SeqCst)),
+        }
+    }
+}
+
+/// The result of an attempt to receive a message.
+enum TryRecv<T> {
+    /// A message was received.
+    Message(T),
+
+    /// There are no new messages.
+    Empty,
+
+    /// There are no new messages and the channel is disconnected.
+    Disconnected,
+
+    /// The receiver fell behind and missed the given number of messages.
+    Lagged(usize),
+}
+
+/// Inner representation of a broadcast channel.
+struct Inner<T> {
+    /// Messages in the channel, from the oldest to the newest.
+    buffer: VecDeque<T>,
+
+    /// Sequence number of the next message to be sent.
+    tail: usize,
+
+    /// Equals `true` when the channel is disconnected.
+    is_disconnected: bool,
+}
+
+impl<T> Inner<T> {
+    /// Returns the sequence number of the oldest message in the channel.
+    fn head(&self) -> usize {
+        self.tail.wrapping_sub(self.buffer.len())
+    }
+}
+
+/// Broadcast channel.
+pub struct Channel<T> {
+    /// Inner representation of the channel.
+    inner: Spinlock<Inner<T>>,
+
+    /// The channel capacity.
+    cap: usize,
+
+    /// Clones a message for a receiver.
+    clone: fn(&T) -> T,
+
+    /// Receivers waiting for the next message.
+    receivers: SyncWaker,
+
+    /// Statistics about operations on the channel.
+    metrics: Metrics,
+}
+
+impl<T> Channel<T> {
+    /// Creates a broadcast channel of capacity `cap`, using `clone` to copy messages.
+    pub fn with_capacity(cap: usize, clone: fn(&T) -> T) -> Self {
+        assert!(cap > 0, "capacity must be positive");
+
+        Channel {
+            inner: Spinlock::new(Inner {
+                buffer: VecDeque::with_capacity(cap),
+                tail: 0,
+                is_disconnected: false,
+            }),
+            cap,
+            clone,
+            receivers: SyncWaker::new(),
+            metrics: Metrics::new(),
+        }
+    }
+
+    /// Returns a cursor pointing past the newest message in the channel.
+    pub fn cursor(&self) -> Cursor {
+        let inner = self.inner.lock();
+        Cursor {
+            pos: AtomicUsize::new(inner.tail),
+        }
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub fn receiver<'a>(&'a self, cursor: &'a Cursor) -> Receiver<'a, T> {
+        Receiver(self, cursor)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub fn sender(&self) -> Sender<T> {
+        Sender(self)
+    }
+
+    /// Attempts to reserve a slot for sending a message.
+    ///
+    /// Sending never blocks, so this always succeeds.
+    fn start_send(&self, _token: &mut Token) -> bool {
+        true
+    }
+
+    /// Pushes a message into the channel, overwriting the oldest one if the channel is full.
+    fn push(&self, msg: T) -> Result<(), T> {
+        let evicted = {
+            let mut inner = self.inner.lock();
+
+            if inner.is_disconnected {
+                return Err(msg);
+            }
+
+            let evicted = if inner.buffer.len() == self.cap {
+                inner.buffer.pop_front()
+            } else {
+                None
+            };
+
+            inner.buffer.push_back(msg);
+            inner.tail = inner.tail.wrapping_add(1);
+            self.metrics.sent(1, || inner.buffer.len());
+            evicted
+        };
+
+        // Drop the overwritten message outside the lock.
+        drop(evicted);
+
+        // Wake all sleeping receivers.
+        self.receivers.notify_all();
+        Ok(())
+    }
+
+    /// Attempts to receive the next message for `cursor`.
+    ///
+    /// If the receiver has fallen behind, the cursor is moved to the oldest message. The missed
+    /// messages are then skipped silently if `skip_lagged` is `true`, or reported otherwise.
+    fn pop(&self, cursor: &Cursor, skip_lagged: bool) -> TryRecv<T> {
+        let inner = self.inner.lock();
+        let len = inner.buffer.len();
+        let mut pos = cursor.pos.load(Ordering::SeqCst);
+
+        // Check if some of the messages for this receiver have been overwritten.
+        let missed = inner.head().wrapping_sub(pos);
+        if missed != 0 && missed <= inner.tail.wrapping_sub(pos) {
+            pos = inner.head();
+            cursor.pos.store(pos, Ordering::SeqCst);
+
+            if !skip_lagged {
+                return TryRecv::Lagged(missed);
+            }
+        }
+
+        if pos == inner.tail {
+            return if inner.is_disconnected {
+                TryRecv::Disconnected
+            } else {
+                TryRecv::Empty
+            };
+        }
+
+        // Clone the message while holding the lock so that it doesn't get overwritten and
+        // isn't accessed by multiple threads at the same time.
+        let msg = (self.clone)(&inner.buffer[len - inner.tail.wrapping_sub(pos)]);
+        cursor.pos.store(pos.wrapping_add(1), Ordering::SeqCst);
+        self.metrics.received(1);
+        TryRecv::Message(msg)
+    }
+
+    /// Returns the sequence number of the next message for `cursor`.
+    ///
+    /// If the receiver has fallen behind, the cursor is moved to the oldest message and the missed
+    /// messages are skipped.
+    fn next_pos(&self, inner: &Inner<T>, cursor: &Cursor) -> Result<usize, TryRecvError> {
+        let mut pos = cursor.pos.load(Ordering::SeqCst);
+
+        // Check if some of the messages for this receiver have been overwritten.
+        let missed = inner.head().wrapping_sub(pos);
+        if missed != 0 && missed <= inner.tail.wrapping_sub(pos) {
+            pos = inner.head();
+            cursor.pos.store(pos, Ordering::SeqCst);
+        }
+
+        if pos == inner.tail {
+            if inner.is_disconnected {
+                Err(TryRecvError::Disconnected)
+            } else {
+                Err(TryRecvError::Empty)
+            }
+        } else {
+            Ok(pos)
+        }
+    }
+
+    /// Calls `f` on the next message for `cursor` without receiving it.
+    pub fn peek_with<F, R>(&self, cursor: &Cursor, f: F) -> Result<R, TryRecvError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let inner = self.inner.lock();
+        let pos = self.next_pos(&inner, cursor)?;
+        let len = inner.buffer.len();
+        Ok(f(&inner.buffer[len - inner.tail.wrapping_sub(pos)]))
+    }
+
+    /// Receives the next message for `cursor` only if it satisfies `pred`.
+    pub fn try_recv_if<F>(&self, cursor: &Cursor, pred: F) -> Result<Option<T>, TryRecvError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        let inner = self.inner.lock();
+        let pos = self.next_pos(&inner, cursor)?;
+        let len = inner.buffer.len();
+        let msg = &inner.buffer[len - inner.tail.wrapping_sub(pos)];
+
+        if !pred(msg) {
+            return Ok(None);
+        }
+
+        let msg = (self.clone)(msg);
+        cursor.pos.store(pos.wrapping_add(1), Ordering::SeqCst);
+        self.metrics.received(1);
+        Ok(Some(msg))
+    }
+
+    /// Attempts to reserve a message for receiving.
+    fn start_recv(&self, cursor: &Cursor, token: &mut Token) -> bool {
+        match self.pop(cursor, true) {
+            TryRecv::Message(msg) => {
+                token.boxed.msg = Box::into_raw(Box::new(msg)) as *mut u8;
+                true
+            }
+            TryRecv::Disconnected => {
+                token.boxed.msg = ptr::null_mut();
+                true
+            }
+            TryRecv::Empty => false,
+            TryRecv::Lagged(_) => unreachable!(),
+        }
+    }
+
+    /// Reads a message from the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if token.boxed.msg.is_null() {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        let msg = Box::from_raw(token.boxed.msg as *mut T);
+        Ok(*msg)
+    }
+
+    /// Writes a message into the channel.
+    pub unsafe fn write(&self, _token: &mut Token, msg: T) -> Result<(), T> {
+        self.push(msg)
+    }
+
+    /// Attempts to send a message into the channel.
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.push(msg).map_err(TrySendError::Disconnected)
+    }
+
+    /// Sends a message into the channel.
+    ///
+    /// Sending never blocks, so the deadline is ignored.
+    pub fn send(&self, msg: T, _deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        self.push(msg).map_err(SendTimeoutError::Disconnected)
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self, cursor: &Cursor) -> Result<T, TryRecvError> {
+        match self.pop(cursor, true) {
+            TryRecv::Message(msg) => Ok(msg),
+            TryRecv::Empty => Err(TryRecvError::Empty),
+            TryRecv::Disconnected => Err(TryRecvError::Disconnected),
+            TryRecv::Lagged(_) => unreachable!(),
+        }
+    }
+
+    /// Attempts to receive a message without blocking, reporting missed messages.
+    pub fn try_recv_lagged(&self, cursor: &Cursor) -> Result<T, BroadcastRecvError> {
+        match self.pop(cursor, false) {
+            TryRecv::Message(msg) => Ok(msg),
+            TryRecv::Empty => Err(BroadcastRecvError::Empty),
+            TryRecv::Disconnected => Err(BroadcastRecvError::Disconnected),
+            TryRecv::Lagged(n) => Err(BroadcastRecvError::Lagged(n as u64)),
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(&self, cursor: &Cursor, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            match self.pop(cursor, true) {
+                TryRecv::Message(msg) => return Ok(msg),
+                TryRecv::Disconnected => return Err(RecvTimeoutError::Disconnected),
+                TryRecv::Empty => {}
+                TryRecv::Lagged(_) => unreachable!(),
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a sender wakes us up.
+                let oper = Operation::hook(token);
+                self.receivers.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if self.pending(cursor) > 0 || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = self.metrics.recv_wait(|| cx.wait_until(deadline));
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.receivers.unregister(oper).unwrap();
+                        // If the channel was disconnected, we still have to check for remaining
+                        // messages.
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Returns the number of messages inside the channel.
+    pub fn len(&self) -> usize {
+        self.inner.lock().buffer.len()
+    }
+
+    /// Returns the number of messages `cursor` has yet to receive.
+    pub fn pending(&self, cursor: &Cursor) -> usize {
+        let inner = self.inner.lock();
+        let pos = cursor.pos.load(Ordering::SeqCst);
+        let len = inner.buffer.len();
+        let pending = inner.tail.wrapping_sub(pos);
+
+        if pending > len {
+            len
+        } else {
+            pending
+        }
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        Some(self.cap)
+    }
+
+    /// Disconnects the channel and wakes up all blocked receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        let mut inner = self.inner.lock();
+
+        if !inner.is_disconnected {
+            inner.is_disconnected = true;
+            self.receivers.disconnect();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.lock().is_disconnected
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the channel is full, in which case the next message overwrites the
+    /// oldest one.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.cap
+    }
+
+    /// Returns a snapshot of statistics about the channel.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> Stats {
+        self.metrics.stats(0, self.receivers.waiting())
+    }
+
+    /// Returns the statistics recorded by the channel.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+#[cfg(feature = "debug")]
+impl<T> Inspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "broadcast"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn blocked(&self) -> (Vec<Context>, Vec<Context>) {
+        (Vec::new(), self.receivers.contexts())
+    }
+}
+
+/// Receiver handle to a channel.
+pub struct Receiver<'a, T: 'a>(&'a Channel<T>, &'a Cursor);
+
+/// Sender handle to a channel.
+pub struct Sender<'a, T: 'a>(&'a Channel<T>);
+
+impl<'a, T> SelectHandle for Receiver<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(self.1, token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.0.pending(self.1) > 0 || self.0.is_disconnected()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.receivers.unwatch(oper);
+    }
+}
+
+impl<'a, T> SelectHandle for Sender<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_send(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    fn unregister(&self, _oper: Operation) {}
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn watch(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    fn unwatch(&self, _oper: Operation) {}
+}
@@ -1,6 +1,8 @@
 //! Unbounded channel implemented as a linked list.
 
 use std::cell::UnsafeCell;
+use std::cmp;
+use std::collections::VecDeque;
 use std::marker::PhantomData;
 use std::mem::{self, ManuallyDrop};
 use std::ptr;
@@ -10,8 +12,14 @@ use std::time::Instant;
 use crossbeam_utils::{Backoff, CachePadded};
 
 use context::Context;
+#[cfg(feature = "debug")]
+use debug::Inspect;
 use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use metrics::Metrics;
+#[cfg(feature = "metrics")]
+use metrics::Stats;
 use select::{Operation, SelectHandle, Selected, Token};
+use utils;
 use waker::SyncWaker;
 
 // TODO(stjepang): Once we bump the minimum required Rust version to 1.28 or newer, re-apply the
@@ -33,11 +41,14 @@ const LAP: usize = 32;
 // The maximum number of messages a block can hold.
 const BLOCK_CAP: usize = LAP - 1;
 // How many lower bits are reserved for metadata.
-const SHIFT: usize = 1;
+const SHIFT: usize = 2;
 // Has two different purposes:
 // * If set in head, indicates that the block is not the last one.
 // * If set in tail, indicates that the channel is disconnected.
 const MARK_BIT: usize = 1;
+// If set in head, indicates that a receiver is inspecting the message at the head without
+// receiving it, so the head must not move.
+const LOCK_BIT: usize = 2;
 
 /// A slot in a block.
 struct Slot<T> {
@@ -155,6 +166,9 @@ pub struct Channel<T> {
     /// Receivers waiting while the channel is empty and not disconnected.
     receivers: SyncWaker,
 
+    /// Statistics about operations on the channel.
+    metrics: Metrics,
+
     /// Indicates that dropping a `Channel<T>` may drop messages of type `T`.
     _marker: PhantomData<T>,
 }
@@ -172,6 +186,7 @@ impl<T> Channel<T> {
                 index: AtomicUsize::new(0),
             }),
             receivers: SyncWaker::new(),
+            metrics: Metrics::new(),
             _marker: PhantomData,
         }
     }
@@ -282,6 +297,7 @@ impl<T> Channel<T> {
         let slot = (*block).slots.get_unchecked(offset);
         slot.msg.get().write(ManuallyDrop::new(msg));
         slot.state.fetch_or(WRITE, Ordering::Release);
+        self.metrics.sent(1, || self.len());
 
         // Wake a sleeping receiver.
         self.receivers.notify();
@@ -298,8 +314,10 @@ impl<T> Channel<T> {
             // Calculate the offset of the index into the block.
             let offset = (head >> SHIFT) % LAP;
 
-            // If we reached the end of the block, wait until the next one is installed.
-            if offset == BLOCK_CAP {
+            // If we reached the end of the block, wait until the next one is installed. If another
+            // receiver has locked the head, wait until it's done.
+            if offset == BLOCK_CAP || head & LOCK_BIT != 0 {
+                utils::check_head_wait(&self.head.index);
                 backoff.snooze();
                 head = self.head.index.load(Ordering::Acquire);
                 block = self.head.block.load(Ordering::Acquire);
@@ -396,9 +414,477 @@ impl<T> Channel<T> {
             Block::destroy(block, offset + 1);
         }
 
+        self.metrics.received(1);
         Ok(msg)
     }
 
+    /// Attempts to reserve up to `max` consecutive slots for sending messages.
+    ///
+    /// The reserved slots never span more than one block. On success, returns the block, the
+    /// offset of the first reserved slot, and the number of reserved slots. Returns `None` if the
+    /// channel is disconnected.
+    fn start_send_batch(&self, max: usize) -> Option<(*mut Block<T>, usize, usize)> {
+        let backoff = Backoff::new();
+        let mut tail = self.tail.index.load(Ordering::Acquire);
+        let mut block = self.tail.block.load(Ordering::Acquire);
+        let mut next_block = None;
+
+        loop {
+            // Check if the channel is disconnected.
+            if tail & MARK_BIT != 0 {
+                return None;
+            }
+
+            // Calculate the offset of the index into the block.
+            let offset = (tail >> SHIFT) % LAP;
+
+            // If we reached the end of the block, wait until the next one is installed.
+            if offset == BLOCK_CAP {
+                backoff.snooze();
+                tail = self.tail.index.load(Ordering::Acquire);
+                block = self.tail.block.load(Ordering::Acquire);
+                continue;
+            }
+
+            // Reserve no more slots than there are left in the block.
+            let count = cmp::min(max, BLOCK_CAP - offset);
+
+            // If we're going to have to install the next block, allocate it in advance in order to
+            // make the wait for other threads as short as possible.
+            if offset + count == BLOCK_CAP && next_block.is_none() {
+                next_block = Some(Box::new(Block::<T>::new()));
+            }
+
+            // If this is the first message to be sent into the channel, we need to allocate the
+            // first block and install it.
+            if block.is_null() {
+                let new = Box::into_raw(Box::new(Block::<T>::new()));
+
+                if self
+                    .tail
+                    .block
+                    .compare_and_swap(block, new, Ordering::Release)
+                    == block
+                {
+                    self.head.block.store(new, Ordering::Release);
+                    block = new;
+                } else {
+                    next_block = unsafe { Some(Box::from_raw(new)) };
+                    tail = self.tail.index.load(Ordering::Acquire);
+                    block = self.tail.block.load(Ordering::Acquire);
+                    continue;
+                }
+            }
+
+            let new_tail = tail + (count << SHIFT);
+
+            // Try advancing the tail forward.
+            match self.tail.index.compare_exchange_weak(
+                tail,
+                new_tail,
+                Ordering::SeqCst,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => unsafe {
+                    // If we've reached the end of the block, install the next one.
+                    if offset + count == BLOCK_CAP {
+                        let next_block = Box::into_raw(next_block.unwrap());
+                        self.tail.block.store(next_block, Ordering::Release);
+                        self.tail.index.fetch_add(1 << SHIFT, Ordering::Release);
+                        (*block).next.store(next_block, Ordering::Release);
+                    }
+
+                    return Some((block, offset, count));
+                },
+                Err(t) => {
+                    tail = t;
+                    block = self.tail.block.load(Ordering::Acquire);
+                    backoff.spin();
+                }
+            }
+        }
+    }
+
+    /// Writes messages from the front of `msgs` into slots reserved by `start_send_batch`.
+    unsafe fn write_batch(
+        &self,
+        block: *mut Block<T>,
+        offset: usize,
+        count: usize,
+        msgs: &mut VecDeque<T>,
+    ) {
+        for i in offset..offset + count {
+            let slot = (*block).slots.get_unchecked(i);
+            slot.msg
+                .get()
+                .write(ManuallyDrop::new(msgs.pop_front().unwrap()));
+            slot.state.fetch_or(WRITE, Ordering::Release);
+        }
+        self.metrics.sent(count, || self.len());
+
+        // Wake as many sleeping receivers as there are new messages.
+        self.receivers.notify_many(count);
+    }
+
+    /// Attempts to reserve up to `max` consecutive slots for receiving messages.
+    ///
+    /// The reserved slots never span more than one block. On success, returns the block, the
+    /// offset of the first reserved slot, and the number of reserved slots. Zero reserved slots
+    /// means the channel is empty. Returns `None` if the channel is empty and disconnected.
+    fn start_recv_batch(&self, max: usize) -> Option<(*mut Block<T>, usize, usize)> {
+        let backoff = Backoff::new();
+        let mut head = self.head.index.load(Ordering::Acquire);
+        let mut block = self.head.block.load(Ordering::Acquire);
+
+        loop {
+            // Calculate the offset of the index into the block.
+            let offset = (head >> SHIFT) % LAP;
+
+            // If we reached the end of the block, wait until the next one is installed. If another
+            // receiver has locked the head, wait until it's done.
+            if offset == BLOCK_CAP || head & LOCK_BIT != 0 {
+                utils::check_head_wait(&self.head.index);
+                backoff.snooze();
+                head = self.head.index.load(Ordering::Acquire);
+                block = self.head.block.load(Ordering::Acquire);
+                continue;
+            }
+
+            // If this is not the last block, all of its remaining slots have been reserved.
+            let mut available = BLOCK_CAP - offset;
+            let mut mark = 0;
+
+            if head & MARK_BIT == 0 {
+                atomic::fence(Ordering::SeqCst);
+                let tail = self.tail.index.load(Ordering::Relaxed);
+
+                // If the tail equals the head, that means the channel is empty.
+                if head >> SHIFT == tail >> SHIFT {
+                    if tail & MARK_BIT != 0 {
+                        return None;
+                    } else {
+                        return Some((ptr::null_mut(), 0, 0));
+                    }
+                }
+
+                if (head >> SHIFT) / LAP != (tail >> SHIFT) / LAP {
+                    // If head and tail are not in the same block, set `MARK_BIT` in head.
+                    mark = MARK_BIT;
+                } else {
+                    // Otherwise, only the slots up to the tail have been reserved.
+                    available = (tail >> SHIFT) - (head >> SHIFT);
+                }
+            }
+
+            // The block can be null here only if the first message is being sent into the channel.
+            // In that case, just wait until it gets initialized.
+            if block.is_null() {
+                backoff.snooze();
+                head = self.head.index.load(Ordering::Acquire);
+                block = self.head.block.load(Ordering::Acquire);
+                continue;
+            }
+
+            let count = cmp::min(max, available);
+            let new_head = (head + (count << SHIFT)) | mark;
+
+            // Try moving the head index forward.
+            match self.head.index.compare_exchange_weak(
+                head,
+                new_head,
+                Ordering::SeqCst,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => unsafe {
+                    // If we've reached the end of the block, move to the next one.
+                    if offset + count == BLOCK_CAP {
+                        let next = (*block).wait_next();
+                        let mut next_index = (new_head & !MARK_BIT).wrapping_add(1 << SHIFT);
+                        if !(*next).next.load(Ordering::Relaxed).is_null() {
+                            next_index |= MARK_BIT;
+                        }
+
+                        self.head.block.store(next, Ordering::Release);
+                        self.head.index.store(next_index, Ordering::Release);
+                    }
+
+                    return Some((block, offset, count));
+                },
+                Err(h) => {
+                    head = h;
+                    block = self.head.block.load(Ordering::Acquire);
+                    backoff.spin();
+                }
+            }
+        }
+    }
+
+    /// Reads messages from slots reserved by `start_recv_batch` into `buf`.
+    unsafe fn read_batch(
+        &self,
+        block: *mut Block<T>,
+        offset: usize,
+        count: usize,
+        buf: &mut Vec<T>,
+    ) {
+        buf.reserve(count);
+
+        for i in offset..offset + count {
+            // Read the message.
+            let slot = (*block).slots.get_unchecked(i);
+            slot.wait_write();
+            let m = slot.msg.get().read();
+            buf.push(ManuallyDrop::into_inner(m));
+
+            // Destroy the block if we've reached the end, or if another thread wanted to destroy
+            // but couldn't because we were busy reading from the slot. Since the slots are read in
+            // order, the block is not destroyed before our last slot has been read.
+            if i + 1 == BLOCK_CAP {
+                Block::destroy(block, 0);
+            } else if slot.state.fetch_or(READ, Ordering::AcqRel) & DESTROY != 0 {
+                Block::destroy(block, i + 1);
+            }
+        }
+
+        self.metrics.received(count);
+    }
+
+    /// Locks the head so that the message at the head can be inspected without receiving it.
+    ///
+    /// Other receivers wait until the head is unlocked. Returns the unlocked head index and its
+    /// block, or an error if the channel is empty.
+    fn lock_head(&self) -> Result<(usize, *mut Block<T>), TryRecvError> {
+        let backoff = Backoff::new();
+        let mut head = self.head.index.load(Ordering::Acquire);
+        let mut block = self.head.block.load(Ordering::Acquire);
+
+        loop {
+            // Calculate the offset of the index into the block.
+            let offset = (head >> SHIFT) % LAP;
+
+            // If we reached the end of the block, wait until the next one is installed. If another
+            // receiver has locked the head, wait until it's done.
+            if offset == BLOCK_CAP || head & LOCK_BIT != 0 {
+                utils::check_head_wait(&self.head.index);
+                backoff.snooze();
+                head = self.head.index.load(Ordering::Acquire);
+                block = self.head.block.load(Ordering::Acquire);
+                continue;
+            }
+
+            if head & MARK_BIT == 0 {
+                atomic::fence(Ordering::SeqCst);
+                let tail = self.tail.index.load(Ordering::Relaxed);
+
+                // If the tail equals the head, that means the channel is empty.
+                if head >> SHIFT == tail >> SHIFT {
+                    if tail & MARK_BIT != 0 {
+                        return Err(TryRecvError::Disconnected);
+                    } else {
+                        return Err(TryRecvError::Empty);
+                    }
+                }
+            }
+
+            // The block can be null here only if the first message is being sent into the channel.
+            // In that case, just wait until it gets initialized.
+            if block.is_null() {
+                backoff.snooze();
+                head = self.head.index.load(Ordering::Acquire);
+                block = self.head.block.load(Ordering::Acquire);
+                continue;
+            }
+
+            // Try locking the head. While it's locked, the block cannot be destroyed because the
+            // slot at the head hasn't been read.
+            match self.head.index.compare_exchange_weak(
+                head,
+                head | LOCK_BIT,
+                Ordering::SeqCst,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Ok((head, block)),
+                Err(h) => {
+                    head = h;
+                    block = self.head.block.load(Ordering::Acquire);
+                    backoff.spin();
+                }
+            }
+        }
+    }
+
+    /// Returns the head index and its block if there is a message at the head, without locking
+    /// the head.
+    ///
+    /// Returns an error if the channel is empty.
+    fn find_head(&self) -> Result<(usize, *mut Block<T>), TryRecvError> {
+        let backoff = Backoff::new();
+
+        loop {
+            let head = self.head.index.load(Ordering::Acquire);
+            let block = self.head.block.load(Ordering::Acquire);
+
+            // If another receiver has locked the head, there is a message at it.
+            if head & LOCK_BIT != 0 {
+                return Ok((head & !LOCK_BIT, block));
+            }
+
+            // If we reached the end of the block, wait until the next one is installed.
+            if (head >> SHIFT) % LAP == BLOCK_CAP {
+                backoff.snooze();
+                continue;
+            }
+
+            if head & MARK_BIT == 0 {
+                atomic::fence(Ordering::SeqCst);
+                let tail = self.tail.index.load(Ordering::Relaxed);
+
+                // If the tail equals the head, that means the channel is empty.
+                if head >> SHIFT == tail >> SHIFT {
+                    if tail & MARK_BIT != 0 {
+                        return Err(TryRecvError::Disconnected);
+                    } else {
+                        return Err(TryRecvError::Empty);
+                    }
+                }
+            }
+
+            // The block can be null here only if the first message is being sent into the channel.
+            // In that case, just wait until it gets initialized.
+            if block.is_null() {
+                backoff.snooze();
+                continue;
+            }
+
+            return Ok((head, block));
+        }
+    }
+
+    /// Calls `f` on the message at the head without receiving it.
+    pub fn peek_with<F, R>(&self, f: F) -> Result<R, TryRecvError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let (head, block) = self.lock_head()?;
+        let _lock = HeadLock::new(&self.head.index, head);
+
+        unsafe {
+            let slot = (*block).slots.get_unchecked((head >> SHIFT) % LAP);
+            slot.wait_write();
+            Ok(f(&*slot.msg.get()))
+        }
+    }
+
+    /// Receives the message at the head only if it satisfies `pred`.
+    pub fn try_recv_if<F>(&self, pred: F) -> Result<Option<T>, TryRecvError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        let (head, block) = self.lock_head()?;
+        let lock = HeadLock::new(&self.head.index, head);
+        Ok(unsafe { self.recv_locked(lock, block, pred) })
+    }
+
+    /// Attempts to select a conditional receive.
+    ///
+    /// The head is not locked. Its index is remembered so that `read_if` can check whether the
+    /// message is still there.
+    pub fn start_peek(&self, token: &mut Token) -> bool {
+        match self.find_head() {
+            Ok((head, block)) => {
+                token.list.block = block as *const u8;
+                token.list.offset = head;
+                true
+            }
+            Err(TryRecvError::Disconnected) => {
+                token.list.block = ptr::null();
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+        }
+    }
+
+    /// Receives the message selected by `start_peek` only if it satisfies `pred`.
+    ///
+    /// Fails with `Empty` if another receiver has taken the message in the meantime.
+    pub unsafe fn read_if<F>(&self, token: &mut Token, pred: F) -> Result<Option<T>, TryRecvError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        // If there is no block, the channel is disconnected. Otherwise, the block in the token is
+        // never dereferenced, since it may have been destroyed in the meantime.
+        if token.list.block.is_null() {
+            return Err(TryRecvError::Disconnected);
+        }
+
+        let (head, block) = self.lock_head()?;
+        let lock = HeadLock::new(&self.head.index, head);
+
+        // The offset field holds the head index the message was found at.
+        if head >> SHIFT != token.list.offset >> SHIFT {
+            return Err(TryRecvError::Empty);
+        }
+        Ok(self.recv_locked(lock, block, pred))
+    }
+
+    /// Receives the message at the locked head in `block` only if it satisfies `pred`, and then
+    /// unlocks the head.
+    unsafe fn recv_locked<F>(&self, lock: HeadLock, block: *mut Block<T>, pred: F) -> Option<T>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        let head = lock.value;
+        let offset = (head >> SHIFT) % LAP;
+        let slot = (*block).slots.get_unchecked(offset);
+        slot.wait_write();
+        if !pred(&*slot.msg.get()) {
+            return None;
+        }
+
+        // Keep the head locked until it is moved forward below.
+        mem::forget(lock);
+
+        // Read the message.
+        let msg = ManuallyDrop::into_inner(slot.msg.get().read());
+
+        // Move the head forward, which also unlocks it, just like in `start_recv`.
+        let mut new_head = head + (1 << SHIFT);
+        if new_head & MARK_BIT == 0 {
+            atomic::fence(Ordering::SeqCst);
+            let tail = self.tail.index.load(Ordering::Relaxed);
+
+            // If head and tail are not in the same block, set `MARK_BIT` in head.
+            if (head >> SHIFT) / LAP != (tail >> SHIFT) / LAP {
+                new_head |= MARK_BIT;
+            }
+        }
+        utils::set_head_locked(&self.head.index, false);
+        self.head.index.store(new_head, Ordering::SeqCst);
+
+        // If we've reached the end of the block, move to the next one.
+        if offset + 1 == BLOCK_CAP {
+            let next = (*block).wait_next();
+            let mut next_index = (new_head & !MARK_BIT).wrapping_add(1 << SHIFT);
+            if !(*next).next.load(Ordering::Relaxed).is_null() {
+                next_index |= MARK_BIT;
+            }
+
+            self.head.block.store(next, Ordering::Release);
+            self.head.index.store(next_index, Ordering::Release);
+        }
+
+        // Destroy the block if we've reached the end, or if another thread wanted to destroy
+        // but couldn't because we were busy reading from the slot.
+        if offset + 1 == BLOCK_CAP {
+            Block::destroy(block, 0);
+        } else if slot.state.fetch_or(READ, Ordering::AcqRel) & DESTROY != 0 {
+            Block::destroy(block, offset + 1);
+        }
+
+        self.metrics.received(1);
+        Some(msg)
+    }
+
     /// Attempts to send a message into the channel.
     pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
         self.send(msg, None).map_err(|err| match err {
@@ -465,7 +951,105 @@ impl<T> Channel<T> {
                 }
 
                 // Block the current thread.
-                let sel = cx.wait_until(deadline);
+                let sel = self.metrics.recv_wait(|| cx.wait_until(deadline));
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.receivers.unregister(oper).unwrap();
+                        // If the channel was disconnected, we still have to check for remaining
+                        // messages.
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Attempts to send messages from the front of `msgs` without blocking.
+    ///
+    /// Sent messages are removed from `msgs`. Fails only if the channel is disconnected.
+    pub fn try_send_batch(&self, msgs: &mut VecDeque<T>) -> Result<(), TrySendError<()>> {
+        self.send_batch(msgs, None).map_err(|err| match err {
+            SendTimeoutError::Disconnected(()) => TrySendError::Disconnected(()),
+            SendTimeoutError::Timeout(_) => unreachable!(),
+        })
+    }
+
+    /// Sends all messages in `msgs`.
+    ///
+    /// Sent messages are removed from `msgs`.
+    pub fn send_batch(
+        &self,
+        msgs: &mut VecDeque<T>,
+        _deadline: Option<Instant>,
+    ) -> Result<(), SendTimeoutError<()>> {
+        while !msgs.is_empty() {
+            match self.start_send_batch(msgs.len()) {
+                None => return Err(SendTimeoutError::Disconnected(())),
+                Some((block, offset, count)) => unsafe {
+                    self.write_batch(block, offset, count, msgs)
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Attempts to receive up to `max` messages into `buf` without blocking.
+    pub fn try_recv_batch(&self, buf: &mut Vec<T>, max: usize) -> Result<usize, TryRecvError> {
+        match self.start_recv_batch(max) {
+            None => Err(TryRecvError::Disconnected),
+            Some((_, _, 0)) => Err(TryRecvError::Empty),
+            Some((block, offset, count)) => {
+                unsafe { self.read_batch(block, offset, count, buf) };
+                Ok(count)
+            }
+        }
+    }
+
+    /// Receives up to `max` messages into `buf`, blocking until at least one is available.
+    pub fn recv_batch(
+        &self,
+        buf: &mut Vec<T>,
+        max: usize,
+        deadline: Option<Instant>,
+    ) -> Result<usize, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            // Try receiving messages several times.
+            let backoff = Backoff::new();
+            loop {
+                match self.try_recv_batch(buf, max) {
+                    Ok(count) => return Ok(count),
+                    Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                    Err(_) => {}
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            // Prepare for blocking until a sender wakes us up.
+            Context::with(|cx| {
+                let oper = Operation::hook(token);
+                self.receivers.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if !self.is_empty() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = self.metrics.recv_wait(|| cx.wait_until(deadline));
 
                 match sel {
                     Selected::Waiting => unreachable!(),
@@ -552,6 +1136,33 @@ impl<T> Channel<T> {
     pub fn is_full(&self) -> bool {
         false
     }
+
+    /// Returns a snapshot of statistics about the channel.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> Stats {
+        self.metrics.stats(0, self.receivers.waiting())
+    }
+
+    /// Returns the statistics recorded by the channel.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+#[cfg(feature = "debug")]
+impl<T> Inspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "list"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn blocked(&self) -> (Vec<Context>, Vec<Context>) {
+        (Vec::new(), self.receivers.contexts())
+    }
 }
 
 impl<T> Drop for Channel<T> {
@@ -591,6 +1202,30 @@ impl<T> Drop for Channel<T> {
     }
 }
 
+/// Unlocks the head of a channel when dropped, even if inspecting the message panics.
+struct HeadLock<'a> {
+    /// The head index of the channel.
+    index: &'a AtomicUsize,
+
+    /// The value the head index is set to when unlocked.
+    value: usize,
+}
+
+impl<'a> HeadLock<'a> {
+    /// Takes over a head index that has just been locked.
+    fn new(index: &'a AtomicUsize, value: usize) -> HeadLock<'a> {
+        utils::set_head_locked(index, true);
+        HeadLock { index, value }
+    }
+}
+
+impl<'a> Drop for HeadLock<'a> {
+    fn drop(&mut self) {
+        utils::set_head_locked(self.index, false);
+        self.index.store(self.value, Ordering::SeqCst);
+    }
+}
+
 /// Receiver handle to a channel.
 pub struct Receiver<'a, T: 'a>(&'a Channel<T>);
 
@@ -1,17 +1,27 @@
 //! Channel flavors.
 //!
-//! There are six flavors:
+//! There are eleven flavors:
 //!
 //! 1. `after` - Channel that delivers a message after a certain amount of time.
 //! 2. `array` - Bounded channel based on a preallocated array.
-//! 3. `list` - Unbounded channel implemented as a linked list.
-//! 4. `never` - Channel that never delivers messages.
-//! 5. `tick` - Channel that delivers messages periodically.
-//! 6. `zero` - Zero-capacity channel.
+//! 3. `broadcast` - Bounded channel that delivers every message to every receiver.
+//! 4. `list` - Unbounded channel implemented as a linked list.
+//! 5. `never` - Channel that never delivers messages.
+//! 6. `oneshot` - Channel that delivers a single message.
+//! 7. `priority` - Channel that delivers messages in order of their priority.
+//! 8. `tick` - Channel that delivers messages periodically.
+//! 9. `timer` - Channel that delivers a message when a resettable timer expires.
+//! 10. `watch` - Channel that holds the latest value and notifies receivers when it changes.
+//! 11. `zero` - Zero-capacity channel.
 
 pub mod after;
 pub mod array;
+pub mod broadcast;
 pub mod list;
 pub mod never;
+pub mod oneshot;
+pub mod priority;
 pub mod tick;
+pub mod timer;
+pub mod watch;
 pub mod zero;
@@ -0,0 +1,370 @@
+//! Channel that delivers a single message.
+//!
+//! The channel has a single slot and a state machine tracking its contents:
+//!
+//! * `EMPTY` - no message has been sent yet and the sender is alive.
+//! * `FULL` - the message is in the slot.
+//! * `LOCKED` - the message is in the slot and a receiver is inspecting it.
+//! * `DISCONNECTED` - the message has been received, or it will never be sent.
+//!
+//! A channel only ever moves from `EMPTY` to another state, and never comes back to it.
+
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crossbeam_utils::Backoff;
+
+use context::Context;
+#[cfg(feature = "debug")]
+use debug::Inspect;
+use err::{RecvTimeoutError, TryRecvError};
+use metrics::Metrics;
+#[cfg(feature = "metrics")]
+use metrics::Stats;
+use select::{Operation, SelectHandle, Selected, Token};
+use waker::SyncWaker;
+
+/// No message has been sent yet.
+const EMPTY: usize = 0;
+
+/// The message is waiting to be received.
+const FULL: usize = 1;
+
+/// The message is being inspected by a receiver.
+const LOCKED: usize = 2;
+
+/// The message has been received or will never be sent.
+const DISCONNECTED: usize = 3;
+
+/// Equals `true` if a receive operation has claimed the message.
+pub type OneshotToken = bool;
+
+/// Channel that delivers a single message.
+pub struct Channel<T> {
+    /// The state of the slot.
+    state: AtomicUsize,
+
+    /// The message.
+    msg: UnsafeCell<Option<T>>,
+
+    /// Receivers waiting for the message.
+    receivers: SyncWaker,
+
+    /// Statistics about operations on the channel.
+    metrics: Metrics,
+}
+
+impl<T> Channel<T> {
+    /// Creates a new oneshot channel.
+    pub fn new() -> Self {
+        Channel {
+            state: AtomicUsize::new(EMPTY),
+            msg: UnsafeCell::new(None),
+            receivers: SyncWaker::new(),
+            metrics: Metrics::new(),
+        }
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub fn receiver(&self) -> Receiver<T> {
+        Receiver(self)
+    }
+
+    /// Sends the message into the channel.
+    ///
+    /// Must be called at most once, by the only sender.
+    pub fn send(&self, msg: T) -> Result<(), T> {
+        // Receivers don't touch the slot until the state becomes `FULL`.
+        unsafe {
+            *self.msg.get() = Some(msg);
+        }
+
+        if self
+            .state
+            .compare_exchange(EMPTY, FULL, Ordering::SeqCst, Ordering::Relaxed)
+            .is_err()
+        {
+            // All receivers have been dropped or the channel was closed.
+            return Err(unsafe { (*self.msg.get()).take().unwrap() });
+        }
+        self.metrics.sent(1, || 1);
+
+        // The sender is gone now, so wake up all receivers.
+        self.receivers.disconnect();
+        Ok(())
+    }
+
+    /// Attempts to claim the message for receiving.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        let backoff = Backoff::new();
+
+        loop {
+            match self.state.load(Ordering::SeqCst) {
+                EMPTY => return false,
+                FULL => {
+                    if self
+                        .state
+                        .compare_exchange_weak(
+                            FULL,
+                            DISCONNECTED,
+                            Ordering::SeqCst,
+                            Ordering::Relaxed,
+                        )
+                        .is_ok()
+                    {
+                        token.oneshot = true;
+                        return true;
+                    }
+                    backoff.spin();
+                }
+                LOCKED => {
+                    // Wait until another receiver is done inspecting the message.
+                    backoff.snooze();
+                }
+                _ => {
+                    token.oneshot = false;
+                    return true;
+                }
+            }
+        }
+    }
+
+    /// Reads the message from the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if token.oneshot {
+            self.metrics.received(1);
+            Ok((*self.msg.get()).take().unwrap())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Attempts to receive the message without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let token = &mut Token::default();
+
+        if self.start_recv(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives the message from the channel.
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            if self.start_recv(token) {
+                let res = unsafe { self.read(token) };
+                return res.map_err(|_| RecvTimeoutError::Disconnected);
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until the sender wakes us up.
+                let oper = Operation::hook(token);
+                self.receivers.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if self.state.load(Ordering::SeqCst) != EMPTY {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = self.metrics.recv_wait(|| cx.wait_until(deadline));
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.receivers.unregister(oper).unwrap();
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Locks the message so that it can be inspected without receiving it.
+    fn lock(&self) -> Result<SlotLock<T>, TryRecvError> {
+        let backoff = Backoff::new();
+
+        loop {
+            match self.state.load(Ordering::SeqCst) {
+                EMPTY => return Err(TryRecvError::Empty),
+                FULL => {
+                    if self
+                        .state
+                        .compare_exchange_weak(FULL, LOCKED, Ordering::SeqCst, Ordering::Relaxed)
+                        .is_ok()
+                    {
+                        return Ok(SlotLock {
+                            chan: self,
+                            state: FULL,
+                        });
+                    }
+                    backoff.spin();
+                }
+                LOCKED => backoff.snooze(),
+                _ => return Err(TryRecvError::Disconnected),
+            }
+        }
+    }
+
+    /// Calls `f` on the message without receiving it.
+    pub fn peek_with<F, R>(&self, f: F) -> Result<R, TryRecvError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let _lock = self.lock()?;
+        let msg = unsafe { (*self.msg.get()).as_ref().unwrap() };
+        Ok(f(msg))
+    }
+
+    /// Receives the message only if it satisfies `pred`.
+    pub fn try_recv_if<F>(&self, pred: F) -> Result<Option<T>, TryRecvError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        let mut lock = self.lock()?;
+        if !pred(unsafe { (*self.msg.get()).as_ref().unwrap() }) {
+            return Ok(None);
+        }
+
+        lock.state = DISCONNECTED;
+        self.metrics.received(1);
+        Ok(unsafe { (*self.msg.get()).take() })
+    }
+
+    /// Returns the current number of messages inside the channel.
+    pub fn len(&self) -> usize {
+        match self.state.load(Ordering::SeqCst) {
+            FULL | LOCKED => 1,
+            _ => 0,
+        }
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        Some(1)
+    }
+
+    /// Disconnects the channel unless the message has already been sent.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        if self
+            .state
+            .compare_exchange(EMPTY, DISCONNECTED, Ordering::SeqCst, Ordering::Relaxed)
+            .is_ok()
+        {
+            self.receivers.disconnect();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    ///
+    /// Once the message is sent, the channel is disconnected because the sender is gone.
+    pub fn is_disconnected(&self) -> bool {
+        self.state.load(Ordering::SeqCst) != EMPTY
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.len() == 1
+    }
+
+    /// Returns a snapshot of statistics about the channel.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> Stats {
+        self.metrics.stats(0, self.receivers.waiting())
+    }
+
+    /// Returns the statistics recorded by the channel.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+#[cfg(feature = "debug")]
+impl<T> Inspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "oneshot"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn blocked(&self) -> (Vec<Context>, Vec<Context>) {
+        (Vec::new(), self.receivers.contexts())
+    }
+}
+
+/// Unlocks the message when dropped, even if inspecting the message panics.
+struct SlotLock<'a, T: 'a> {
+    /// The channel.
+    chan: &'a Channel<T>,
+
+    /// The state the channel is set to when unlocked.
+    state: usize,
+}
+
+impl<'a, T> Drop for SlotLock<'a, T> {
+    fn drop(&mut self) {
+        self.chan.state.store(self.state, Ordering::SeqCst);
+    }
+}
+
+/// Receiver handle to a channel.
+pub struct Receiver<'a, T: 'a>(&'a Channel<T>);
+
+impl<'a, T> SelectHandle for Receiver<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.0.state.load(Ordering::SeqCst) != EMPTY
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.receivers.unwatch(oper);
+    }
+}
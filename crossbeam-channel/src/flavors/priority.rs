@@ -0,0 +1,567 @@
+//! Channel that delivers messages in order of their priority.
+//!
+//! This flavor can be either bounded or unbounded.
+//!
+//! Messages are kept in a binary heap ordered by priority. Messages of equal priority are ordered
+//! by a sequence number assigned on send, so they are delivered in FIFO order.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ptr;
+use std::time::Instant;
+
+use context::Context;
+#[cfg(feature = "debug")]
+use debug::Inspect;
+use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use metrics::Metrics;
+#[cfg(feature = "metrics")]
+use metrics::Stats;
+use select::{Operation, SelectHandle, Selected, Token};
+use utils::Spinlock;
+use waker::SyncWaker;
+
+/// The token type for the priority flavor.
+///
+/// Received messages are handed over in the shared `BoxedToken`.
+#[derive(Debug, Default)]
+pub struct PriorityToken {
+    /// Equals `true` if a slot was reserved for a send operation.
+    reserved: bool,
+}
+
+/// A message along with its position in the delivery order.
+struct Entry<T> {
+    /// The priority of the message.
+    priority: u32,
+
+    /// The sequence number assigned when the message was sent.
+    seq: u64,
+
+    /// The message.
+    msg: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Entry<T>) -> bool {
+        self.priority == other.priority && self.seq == other.seq
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Entry<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    fn cmp(&self, other: &Entry<T>) -> Ordering {
+        // Higher priorities come first, and then earlier messages come first.
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Inner representation of a priority channel.
+struct Inner<T> {
+    /// Messages in the channel.
+    heap: BinaryHeap<Entry<T>>,
+
+    /// The sequence number for the next message.
+    seq: u64,
+
+    /// The number of slots reserved by selected send operations that haven't written yet.
+    reserved: usize,
+
+    /// Equals `true` when the channel is disconnected.
+    is_disconnected: bool,
+}
+
+/// Channel that delivers messages in order of their priority.
+pub struct Channel<T> {
+    /// Inner representation of the channel.
+    inner: Spinlock<Inner<T>>,
+
+    /// The channel capacity, or `None` if the channel is unbounded.
+    cap: Option<usize>,
+
+    /// Senders waiting while the channel is full.
+    senders: SyncWaker,
+
+    /// Receivers waiting while the channel is empty and not disconnected.
+    receivers: SyncWaker,
+
+    /// Statistics about operations on the channel.
+    metrics: Metrics,
+}
+
+impl<T> Channel<T> {
+    /// Creates a priority channel of capacity `cap`, or an unbounded one if `cap` is `None`.
+    pub fn with_capacity(cap: Option<usize>) -> Self {
+        if let Some(cap) = cap {
+            assert!(cap > 0, "capacity must be positive");
+        }
+
+        Channel {
+            inner: Spinlock::new(Inner {
+                heap: BinaryHeap::new(),
+                seq: 0,
+                reserved: 0,
+                is_disconnected: false,
+            }),
+            cap,
+            senders: SyncWaker::new(),
+            receivers: SyncWaker::new(),
+            metrics: Metrics::new(),
+        }
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub fn receiver(&self) -> Receiver<T> {
+        Receiver(self)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub fn sender(&self) -> Sender<T> {
+        Sender(self)
+    }
+
+    /// Returns `true` if there is no room for another message.
+    fn is_full_locked(&self, inner: &Inner<T>) -> bool {
+        match self.cap {
+            Some(cap) => inner.heap.len() + inner.reserved >= cap,
+            None => false,
+        }
+    }
+
+    /// Pushes a message into the channel.
+    fn push(&self, inner: &mut Inner<T>, msg: T, priority: u32) {
+        let seq = inner.seq;
+        inner.seq += 1;
+        inner.heap.push(Entry { priority, seq, msg });
+    }
+
+    /// Attempts to reserve a slot for sending a message.
+    fn start_send(&self, token: &mut Token) -> bool {
+        let mut inner = self.inner.lock();
+
+        if inner.is_disconnected {
+            token.priority.reserved = false;
+            true
+        } else if self.is_full_locked(&inner) {
+            false
+        } else {
+            inner.reserved += 1;
+            token.priority.reserved = true;
+            true
+        }
+    }
+
+    /// Writes a message into the channel.
+    pub unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        // If there is no reserved slot, the channel is disconnected.
+        if !token.priority.reserved {
+            return Err(msg);
+        }
+
+        {
+            let mut inner = self.inner.lock();
+            inner.reserved -= 1;
+            self.push(&mut inner, msg, 0);
+            self.metrics.sent(1, || inner.heap.len());
+        }
+
+        // Wake a sleeping receiver.
+        self.receivers.notify();
+        Ok(())
+    }
+
+    /// Attempts to reserve a message for receiving.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        match self.pop() {
+            Ok(msg) => {
+                token.boxed.msg = Box::into_raw(Box::new(msg)) as *mut u8;
+                true
+            }
+            Err(TryRecvError::Disconnected) => {
+                token.boxed.msg = ptr::null_mut();
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Reads a message from the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if token.boxed.msg.is_null() {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        let msg = Box::from_raw(token.boxed.msg as *mut T);
+        Ok(*msg)
+    }
+
+    /// Pops the message with the highest priority.
+    fn pop(&self) -> Result<T, TryRecvError> {
+        let entry = {
+            let mut inner = self.inner.lock();
+
+            match inner.heap.pop() {
+                Some(entry) => entry,
+                None if inner.is_disconnected => return Err(TryRecvError::Disconnected),
+                None => return Err(TryRecvError::Empty),
+            }
+        };
+        self.metrics.received(1);
+
+        // Wake a sleeping sender.
+        self.senders.notify();
+        Ok(entry.msg)
+    }
+
+    /// Calls `f` on the message that would be received next without receiving it.
+    pub fn peek_with<F, R>(&self, f: F) -> Result<R, TryRecvError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let inner = self.inner.lock();
+
+        match inner.heap.peek() {
+            Some(entry) => Ok(f(&entry.msg)),
+            None if inner.is_disconnected => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Receives the message that would be received next only if it satisfies `pred`.
+    pub fn try_recv_if<F>(&self, pred: F) -> Result<Option<T>, TryRecvError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        let entry = {
+            let mut inner = self.inner.lock();
+
+            match inner.heap.peek() {
+                Some(entry) => {
+                    if !pred(&entry.msg) {
+                        return Ok(None);
+                    }
+                }
+                None if inner.is_disconnected => return Err(TryRecvError::Disconnected),
+                None => return Err(TryRecvError::Empty),
+            }
+            inner.heap.pop().unwrap()
+        };
+        self.metrics.received(1);
+
+        // Wake a sleeping sender.
+        self.senders.notify();
+        Ok(Some(entry.msg))
+    }
+
+    /// Attempts to send a message into the channel.
+    pub fn try_send(&self, msg: T, priority: u32) -> Result<(), TrySendError<T>> {
+        {
+            let mut inner = self.inner.lock();
+
+            if inner.is_disconnected {
+                return Err(TrySendError::Disconnected(msg));
+            }
+            if self.is_full_locked(&inner) {
+                return Err(TrySendError::Full(msg));
+            }
+
+            self.push(&mut inner, msg, priority);
+            self.metrics.sent(1, || inner.heap.len());
+        }
+
+        // Wake a sleeping receiver.
+        self.receivers.notify();
+        Ok(())
+    }
+
+    /// Sends a message into the channel, evicting a message with the lowest priority if the
+    /// channel is full.
+    ///
+    /// Of the messages with the lowest priority, the oldest one is evicted and returned.
+    pub fn force_send(&self, msg: T, priority: u32) -> Result<Option<T>, TrySendError<T>> {
+        let evicted = {
+            let mut inner = self.inner.lock();
+
+            if inner.is_disconnected {
+                return Err(TrySendError::Disconnected(msg));
+            }
+
+            let evicted = if self.is_full_locked(&inner) {
+                // Slots reserved by selected send operations can't be evicted.
+                if inner.heap.is_empty() {
+                    return Err(TrySendError::Full(msg));
+                }
+
+                let mut entries = inner.heap.drain().collect::<Vec<_>>();
+                let index = (0..entries.len())
+                    .min_by_key(|&i| (entries[i].priority, entries[i].seq))
+                    .unwrap();
+                let entry = entries.swap_remove(index);
+                inner.heap.extend(entries);
+                Some(entry.msg)
+            } else {
+                None
+            };
+
+            self.push(&mut inner, msg, priority);
+            self.metrics.sent(1, || inner.heap.len());
+            evicted
+        };
+
+        // Wake a sleeping receiver.
+        self.receivers.notify();
+        Ok(evicted)
+    }
+
+    /// Sends a message into the channel.
+    pub fn send(
+        &self,
+        mut msg: T,
+        priority: u32,
+        deadline: Option<Instant>,
+    ) -> Result<(), SendTimeoutError<T>> {
+        let token = &mut Token::default();
+        loop {
+            match self.try_send(msg, priority) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Disconnected(m)) => {
+                    return Err(SendTimeoutError::Disconnected(m))
+                }
+                Err(TrySendError::Full(m)) => msg = m,
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(SendTimeoutError::Timeout(msg));
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a receiver wakes us up.
+                let oper = Operation::hook(token);
+                self.senders.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if !self.is_full() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = self.metrics.send_wait(|| cx.wait_until(deadline));
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.senders.unregister(oper).unwrap();
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.pop()
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            match self.pop() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(_) => {}
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a sender wakes us up.
+                let oper = Operation::hook(token);
+                self.receivers.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if !self.is_empty() || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = self.metrics.recv_wait(|| cx.wait_until(deadline));
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.receivers.unregister(oper).unwrap();
+                        // If the channel was disconnected, we still have to check for remaining
+                        // messages.
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Returns the current number of messages inside the channel.
+    pub fn len(&self) -> usize {
+        self.inner.lock().heap.len()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        self.cap
+    }
+
+    /// Disconnects the channel and wakes up all blocked senders and receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        let mut inner = self.inner.lock();
+
+        if !inner.is_disconnected {
+            inner.is_disconnected = true;
+            self.senders.disconnect();
+            self.receivers.disconnect();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.lock().is_disconnected
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().heap.is_empty()
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        let inner = self.inner.lock();
+        self.is_full_locked(&inner)
+    }
+
+    /// Returns a snapshot of statistics about the channel.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> Stats {
+        self.metrics
+            .stats(self.senders.waiting(), self.receivers.waiting())
+    }
+
+    /// Returns the statistics recorded by the channel.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+#[cfg(feature = "debug")]
+impl<T> Inspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "priority"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn blocked(&self) -> (Vec<Context>, Vec<Context>) {
+        (self.senders.contexts(), self.receivers.contexts())
+    }
+}
+
+/// Receiver handle to a channel.
+pub struct Receiver<'a, T: 'a>(&'a Channel<T>);
+
+/// Sender handle to a channel.
+pub struct Sender<'a, T: 'a>(&'a Channel<T>);
+
+impl<'a, T> SelectHandle for Receiver<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        !self.0.is_empty() || self.0.is_disconnected()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.receivers.unwatch(oper);
+    }
+}
+
+impl<'a, T> SelectHandle for Sender<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_send(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.senders.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.senders.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        !self.0.is_full() || self.0.is_disconnected()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.senders.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.senders.unwatch(oper);
+    }
+}
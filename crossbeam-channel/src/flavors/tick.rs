@@ -2,34 +2,128 @@
 //!
 //! Messages cannot be sent into this kind of channel; they are materialized on demand.
 
-use std::thread;
 use std::time::{Duration, Instant};
 
 use crossbeam_utils::atomic::AtomicCell;
 
+use clock::{self, Clock};
 use context::Context;
 use err::{RecvTimeoutError, TryRecvError};
-use select::{Operation, SelectHandle, Token};
+use select::{Operation, SelectHandle, Selected, Token};
+use wheel::Alarm;
 
 /// Result of a receive operation.
 pub type TickToken = Option<Instant>;
 
-/// Channel that delivers messages periodically.
-pub struct Channel {
+/// What a tick channel does when messages are received late and some ticks are missed.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use crossbeam_channel::{Clock, MissedTicks, TickBuilder};
+///
+/// let clock = Clock::manual();
+/// let start = clock.now();
+/// let r = TickBuilder::new(Duration::from_secs(1))
+///     .missed_ticks(MissedTicks::Burst)
+///     .clock(&clock)
+///     .build();
+///
+/// // Every missed tick is delivered.
+/// clock.advance(Duration::from_secs(3));
+/// assert_eq!(r.try_iter().count(), 3);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissedTicks {
+    /// Delivers every missed tick, one after another, until the channel catches up with the
+    /// original schedule.
+    Burst,
+
+    /// Delivers a single message for all missed ticks and schedules the next one a full period
+    /// after it is received.
+    ///
+    /// This is the default.
+    Delay,
+
+    /// Delivers a single message for all missed ticks and schedules the next one on the original
+    /// schedule.
+    Skip,
+}
+
+impl Default for MissedTicks {
+    fn default() -> MissedTicks {
+        MissedTicks::Delay
+    }
+}
+
+/// The schedule of a tick channel.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Schedule {
     /// The instant at which the next message will be delivered.
-    delivery_time: AtomicCell<Instant>,
+    delivery_time: Instant,
 
     /// The time interval in which messages get delivered.
-    duration: Duration,
+    period: Duration,
+}
+
+/// Channel that delivers messages periodically.
+pub struct Channel {
+    /// The schedule of the channel.
+    schedule: AtomicCell<Schedule>,
+
+    /// What to do when ticks are missed.
+    missed: MissedTicks,
+
+    /// The clock measuring time for the channel.
+    clock: Clock,
+
+    /// Wakes up receivers when the next message is due or the schedule changes.
+    alarm: Alarm,
 }
 
 impl Channel {
     /// Creates a channel that delivers messages periodically.
     #[inline]
     pub fn new(dur: Duration) -> Self {
+        Channel::with_schedule(dur, dur, MissedTicks::Delay, Clock::system())
+    }
+
+    /// Creates a channel that delivers the first message after `delay` and then every `period` on
+    /// `clock`.
+    #[inline]
+    pub fn with_schedule(
+        delay: Duration,
+        period: Duration,
+        missed: MissedTicks,
+        clock: Clock,
+    ) -> Self {
         Channel {
-            delivery_time: AtomicCell::new(Instant::now() + dur),
-            duration: dur,
+            schedule: AtomicCell::new(Schedule {
+                delivery_time: clock.now() + delay,
+                period,
+            }),
+            missed,
+            clock,
+            alarm: Alarm::new(),
+        }
+    }
+
+    /// Returns the instant at which the message after the one due with `schedule` is delivered.
+    fn next_delivery(&self, schedule: Schedule, now: Instant) -> Instant {
+        match self.missed {
+            MissedTicks::Burst => schedule.delivery_time + schedule.period,
+            MissedTicks::Delay => now + schedule.period,
+            MissedTicks::Skip => {
+                let period = nanos(schedule.period);
+                if period == 0 {
+                    return now;
+                }
+
+                // Skip over all ticks that are already due.
+                let missed = nanos(now - schedule.delivery_time) / period;
+                schedule.delivery_time + from_nanos((missed + 1) * period)
+            }
         }
     }
 
@@ -37,55 +131,72 @@ impl Channel {
     #[inline]
     pub fn try_recv(&self) -> Result<Instant, TryRecvError> {
         loop {
-            let now = Instant::now();
-            let delivery_time = self.delivery_time.load();
+            let now = self.clock.now();
+            let schedule = self.schedule.load();
 
-            if now < delivery_time {
+            if now < schedule.delivery_time {
                 return Err(TryRecvError::Empty);
             }
 
-            if self
-                .delivery_time
-                .compare_exchange(delivery_time, now + self.duration)
-                .is_ok()
-            {
-                return Ok(delivery_time);
+            let next = Schedule {
+                delivery_time: self.next_delivery(schedule, now),
+                period: schedule.period,
+            };
+
+            if self.schedule.compare_exchange(schedule, next).is_ok() {
+                // Other receivers might be waiting for the message that has just been received.
+                self.alarm.waker().notify_all();
+                self.rearm();
+                return Ok(schedule.delivery_time);
             }
         }
     }
 
+    /// Returns the next message without receiving it, if it has been delivered.
+    #[inline]
+    pub fn peek(&self) -> Result<Instant, TryRecvError> {
+        let delivery_time = self.schedule.load().delivery_time;
+
+        if self.clock.now() < delivery_time {
+            Err(TryRecvError::Empty)
+        } else {
+            Ok(delivery_time)
+        }
+    }
+
     /// Receives a message from the channel.
     #[inline]
     pub fn recv(&self, deadline: Option<Instant>) -> Result<Instant, RecvTimeoutError> {
+        let token = &mut Token::default();
         loop {
-            // Compute the time to sleep until the next message or the deadline.
-            let offset = {
-                let delivery_time = self.delivery_time.load();
-                let now = Instant::now();
-
-                // Check if we can receive the next message.
-                if now >= delivery_time
-                    && self
-                        .delivery_time
-                        .compare_exchange(delivery_time, now + self.duration)
-                        .is_ok()
-                {
-                    return Ok(delivery_time);
-                }
+            if let Ok(msg) = self.try_recv() {
+                return Ok(msg);
+            }
 
-                // Check if the operation deadline has been reached.
-                if let Some(d) = deadline {
-                    if now >= d {
-                        return Err(RecvTimeoutError::Timeout);
-                    }
+            // Check if the operation deadline has been reached.
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
 
-                    delivery_time.min(d) - now
-                } else {
-                    delivery_time - now
+            Context::with(|cx| {
+                // Prepare for blocking until the schedule changes or the clock advances.
+                let oper = Operation::hook(token);
+                if self.register(oper, cx) {
+                    let _ = cx.try_select(Selected::Aborted);
                 }
-            };
 
-            thread::sleep(offset);
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    // Even if the operation was selected by one waker, it may still be registered
+                    // with the other one.
+                    _ => self.unregister(oper),
+                }
+            });
         }
     }
 
@@ -95,10 +206,54 @@ impl Channel {
         token.tick.ok_or(())
     }
 
+    /// Schedules the next message one period from now, discarding a message that is due.
+    pub fn reset(&self) {
+        loop {
+            let schedule = self.schedule.load();
+            let next = Schedule {
+                delivery_time: self.clock.now() + schedule.period,
+                period: schedule.period,
+            };
+
+            if self.schedule.compare_exchange(schedule, next).is_ok() {
+                break;
+            }
+        }
+        self.alarm.waker().notify_all();
+        self.rearm();
+    }
+
+    /// Changes the time interval in which messages get delivered.
+    ///
+    /// The next message is delivered one new period from now, unless it is due earlier.
+    pub fn set_period(&self, period: Duration) {
+        loop {
+            let schedule = self.schedule.load();
+            let next = Schedule {
+                delivery_time: schedule.delivery_time.min(self.clock.now() + period),
+                period,
+            };
+
+            if self.schedule.compare_exchange(schedule, next).is_ok() {
+                break;
+            }
+        }
+        self.alarm.waker().notify_all();
+        self.rearm();
+    }
+
+    /// Sets the alarm for the next message if operations that stay registered after being
+    /// notified are still watching the channel.
+    fn rearm(&self) {
+        if clock::waker(&self.clock).is_none() && !self.alarm.waker().is_empty() {
+            self.alarm.arm(self.schedule.load().delivery_time);
+        }
+    }
+
     /// Returns `true` if the channel is empty.
     #[inline]
     pub fn is_empty(&self) -> bool {
-        Instant::now() < self.delivery_time.load()
+        self.clock.now() < self.schedule.load().delivery_time
     }
 
     /// Returns `true` if the channel is full.
@@ -124,6 +279,16 @@ impl Channel {
     }
 }
 
+/// Returns the number of nanoseconds in a duration.
+fn nanos(dur: Duration) -> u64 {
+    dur.as_secs() * 1_000_000_000 + u64::from(dur.subsec_nanos())
+}
+
+/// Creates a duration from a number of nanoseconds.
+fn from_nanos(nanos: u64) -> Duration {
+    Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
+}
+
 impl SelectHandle for Channel {
     #[inline]
     fn try_select(&self, token: &mut Token) -> bool {
@@ -142,16 +307,27 @@ impl SelectHandle for Channel {
 
     #[inline]
     fn deadline(&self) -> Option<Instant> {
-        Some(self.delivery_time.load())
+        // Registered operations are woken up by the timer wheel or the manual clock instead.
+        None
     }
 
     #[inline]
-    fn register(&self, _oper: Operation, _cx: &Context) -> bool {
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.alarm.waker().register(oper, cx);
+        match clock::waker(&self.clock) {
+            Some(waker) => waker.register(oper, cx),
+            None => self.alarm.arm(self.schedule.load().delivery_time),
+        }
         self.is_ready()
     }
 
     #[inline]
-    fn unregister(&self, _oper: Operation) {}
+    fn unregister(&self, oper: Operation) {
+        self.alarm.waker().unregister(oper);
+        if let Some(waker) = clock::waker(&self.clock) {
+            waker.unregister(oper);
+        }
+    }
 
     #[inline]
     fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
@@ -164,10 +340,20 @@ impl SelectHandle for Channel {
     }
 
     #[inline]
-    fn watch(&self, _oper: Operation, _cx: &Context) -> bool {
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.alarm.waker().watch(oper, cx);
+        match clock::waker(&self.clock) {
+            Some(waker) => waker.watch(oper, cx),
+            None => self.alarm.arm(self.schedule.load().delivery_time),
+        }
         self.is_ready()
     }
 
     #[inline]
-    fn unwatch(&self, _oper: Operation) {}
+    fn unwatch(&self, oper: Operation) {
+        self.alarm.waker().unwatch(oper);
+        if let Some(waker) = clock::waker(&self.clock) {
+            waker.unwatch(oper);
+        }
+    }
 }
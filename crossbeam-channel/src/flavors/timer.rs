@@ -0,0 +1,260 @@
+//! Channel that delivers a message when a timer expires.
+//!
+//! Unlike the `after` flavor, the timer can be reset and cancelled. Messages cannot be sent into
+//! this kind of channel; they are materialized on demand.
+
+use std::time::{Duration, Instant};
+
+use clock::{self, Clock};
+use context::Context;
+use err::{RecvTimeoutError, TryRecvError};
+use select::{Operation, SelectHandle, Selected, Token};
+use utils::Spinlock;
+use wheel::Alarm;
+
+/// Channel that delivers a message when a timer expires.
+pub struct Channel {
+    /// The instant at which the message will be delivered, or `None` if the timer is inactive.
+    delivery_time: Spinlock<Option<Instant>>,
+
+    /// The clock measuring time for the channel.
+    clock: Clock,
+
+    /// Wakes up receivers when the message is delivered, unless the clock does that.
+    alarm: Alarm,
+}
+
+impl Channel {
+    /// Creates a channel that delivers a message after a certain duration of time.
+    #[inline]
+    pub fn new(dur: Duration) -> Self {
+        Channel::with_clock(dur, Clock::system())
+    }
+
+    /// Creates a channel that delivers a message after a certain duration of time on `clock`.
+    #[inline]
+    pub fn with_clock(dur: Duration, clock: Clock) -> Self {
+        Channel {
+            delivery_time: Spinlock::new(Some(clock.now() + dur)),
+            clock,
+            alarm: Alarm::new(),
+        }
+    }
+
+    /// Restarts the timer so that the message is delivered after `dur`.
+    ///
+    /// A message that has been delivered but not received yet is discarded.
+    pub fn reset(&self, dur: Duration) {
+        let delivery_time = self.clock.now() + dur;
+        let mut current = self.delivery_time.lock();
+        *current = Some(delivery_time);
+
+        // The alarm is only set while receivers are waiting, so it doesn't need to change
+        // otherwise. Resetting is cheap even when it happens after every bit of activity.
+        match clock::waker(&self.clock) {
+            Some(waker) => {
+                if delivery_time <= self.clock.now() {
+                    waker.notify_all();
+                }
+            }
+            None => {
+                if !self.alarm.waker().is_empty() {
+                    self.alarm.arm(delivery_time);
+                }
+            }
+        }
+    }
+
+    /// Stops the timer so that no message is delivered until it is reset.
+    pub fn cancel(&self) {
+        let mut current = self.delivery_time.lock();
+        *current = None;
+        self.alarm.disarm();
+    }
+
+    /// Sets the alarm for the delivery time, if the timer is active.
+    fn arm(&self) {
+        // Hold the lock so that a concurrent reset can't be overwritten with a stale setting.
+        let delivery_time = self.delivery_time.lock();
+        match *delivery_time {
+            Some(d) => self.alarm.arm(d),
+            None => self.alarm.disarm(),
+        }
+    }
+
+    /// Returns the time left until the message is delivered, or `None` if the timer is inactive.
+    pub fn remaining(&self) -> Option<Duration> {
+        let delivery_time = *self.delivery_time.lock();
+        let now = self.clock.now();
+
+        delivery_time.map(|d| {
+            if now < d {
+                d - now
+            } else {
+                Duration::from_secs(0)
+            }
+        })
+    }
+
+    /// Attempts to receive a message without blocking.
+    #[inline]
+    pub fn try_recv(&self) -> Result<Instant, TryRecvError> {
+        let mut delivery_time = self.delivery_time.lock();
+
+        match *delivery_time {
+            Some(d) if self.clock.now() >= d => {
+                // The timer stays inactive until it is reset.
+                *delivery_time = None;
+                Ok(d)
+            }
+            _ => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Returns the message without receiving it, if it has been delivered.
+    #[inline]
+    pub fn peek(&self) -> Result<Instant, TryRecvError> {
+        match *self.delivery_time.lock() {
+            Some(d) if self.clock.now() >= d => Ok(d),
+            _ => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Receives a message from the channel.
+    #[inline]
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<Instant, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            if let Ok(msg) = self.try_recv() {
+                return Ok(msg);
+            }
+
+            // Check if the operation deadline has been reached.
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until the timer expires.
+                let oper = Operation::hook(token);
+                if self.register(oper, cx) {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    _ => self.unregister(oper),
+                }
+            });
+        }
+    }
+
+    /// Reads a message from the channel.
+    #[inline]
+    pub unsafe fn read(&self, token: &mut Token) -> Result<Instant, ()> {
+        // A timer delivers the same kind of message as the `after` flavor, so it shares its token.
+        token.after.ok_or(())
+    }
+
+    /// Returns `true` if the channel is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.peek().is_err()
+    }
+
+    /// Returns `true` if the channel is full.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        !self.is_empty()
+    }
+
+    /// Returns the number of messages in the channel.
+    #[inline]
+    pub fn len(&self) -> usize {
+        if self.is_empty() {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Returns the capacity of the channel.
+    #[inline]
+    pub fn capacity(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+impl SelectHandle for Channel {
+    #[inline]
+    fn try_select(&self, token: &mut Token) -> bool {
+        match self.try_recv() {
+            Ok(msg) => {
+                token.after = Some(msg);
+                true
+            }
+            Err(TryRecvError::Disconnected) => {
+                token.after = None;
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+        }
+    }
+
+    #[inline]
+    fn deadline(&self) -> Option<Instant> {
+        // Registered operations are woken up by the timer wheel or the manual clock instead.
+        None
+    }
+
+    #[inline]
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        match clock::waker(&self.clock) {
+            Some(waker) => waker.register(oper, cx),
+            None => {
+                self.alarm.waker().register(oper, cx);
+                self.arm();
+            }
+        }
+        self.is_ready()
+    }
+
+    #[inline]
+    fn unregister(&self, oper: Operation) {
+        let waker = clock::waker(&self.clock).unwrap_or_else(|| self.alarm.waker());
+        waker.unregister(oper);
+    }
+
+    #[inline]
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    #[inline]
+    fn is_ready(&self) -> bool {
+        !self.is_empty()
+    }
+
+    #[inline]
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        match clock::waker(&self.clock) {
+            Some(waker) => waker.watch(oper, cx),
+            None => {
+                self.alarm.waker().watch(oper, cx);
+                self.arm();
+            }
+        }
+        self.is_ready()
+    }
+
+    #[inline]
+    fn unwatch(&self, oper: Operation) {
+        let waker = clock::waker(&self.clock).unwrap_or_else(|| self.alarm.waker());
+        waker.unwatch(oper);
+    }
+}
@@ -0,0 +1,531 @@
+//! Channel that holds a single value and notifies receivers when it changes.
+//!
+//! The channel always contains the latest value. Each send replaces the value and bumps the
+//! version of the channel, while each receiver remembers the version it has last seen. Sending
+//! never waits for receivers to catch up, and receivers only ever observe the latest value,
+//! skipping over intermediate ones they didn't get to see.
+//!
+//! The value is protected by a read-write lock, so a sender replacing it waits until receivers
+//! are done looking at the old one, including through references returned by `borrow`.
+
+use std::mem;
+use std::ops::Deref;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{RwLock, RwLockReadGuard};
+use std::time::Instant;
+
+use context::Context;
+#[cfg(feature = "debug")]
+use debug::Inspect;
+use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use metrics::Metrics;
+#[cfg(feature = "metrics")]
+use metrics::Stats;
+use select::{Operation, SelectHandle, Selected, Token};
+use waker::SyncWaker;
+
+/// The version of the value a receiver has last seen.
+#[derive(Debug)]
+pub struct Version {
+    /// The version number.
+    seen: AtomicUsize,
+}
+
+impl Clone for Version {
+    fn clone(&self) -> Version {
+        Version {
+            seen: AtomicUsize::new(self.seen.load(Ordering::SeqCst)),
+        }
+    }
+}
+
+/// The result of an attempt to receive a value.
+enum TryRecv<R> {
+    /// A new value was received.
+    Value(R),
+
+    /// The value hasn't changed.
+    Empty,
+
+    /// The value hasn't changed and the channel is disconnected.
+    Disconnected,
+}
+
+/// Inner representation of a watch channel.
+struct Inner<T> {
+    /// The latest value.
+    value: T,
+
+    /// The version of the latest value.
+    version: usize,
+
+    /// Equals `true` when the channel is disconnected.
+    is_disconnected: bool,
+}
+
+/// A reference to the latest value, which keeps the value from being replaced while it is held.
+pub struct Ref<'a, T: 'a> {
+    /// The read lock over the channel.
+    guard: RwLockReadGuard<'a, Inner<T>>,
+}
+
+impl<'a, T> Deref for Ref<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard.value
+    }
+}
+
+/// Watch channel.
+pub struct Channel<T> {
+    /// Inner representation of the channel.
+    inner: RwLock<Inner<T>>,
+
+    /// Clones the value for a receiver.
+    clone: fn(&T) -> T,
+
+    /// The latest version received by any receiver.
+    ///
+    /// This is only written while the channel is locked for reading, so it never goes backwards.
+    received: AtomicUsize,
+
+    /// Receivers waiting for the value to change.
+    receivers: SyncWaker,
+
+    /// Statistics about operations on the channel.
+    metrics: Metrics,
+}
+
+impl<T> Channel<T> {
+    /// Creates a watch channel holding `value`, using `clone` to copy it.
+    pub fn new(value: T, clone: fn(&T) -> T) -> Self {
+        Channel {
+            inner: RwLock::new(Inner {
+                value,
+                version: 0,
+                is_disconnected: false,
+            }),
+            clone,
+            received: AtomicUsize::new(0),
+            receivers: SyncWaker::new(),
+            metrics: Metrics::new(),
+        }
+    }
+
+    /// Locks the channel for reading.
+    ///
+    /// The lock can't be poisoned because no user code runs while it's locked for writing.
+    fn read_lock(&self) -> RwLockReadGuard<Inner<T>> {
+        self.inner.read().unwrap()
+    }
+
+    /// Returns a version marking the current value as seen.
+    pub fn version(&self) -> Version {
+        Version {
+            seen: AtomicUsize::new(self.read_lock().version),
+        }
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub fn receiver<'a>(&'a self, version: &'a Version) -> Receiver<'a, T> {
+        Receiver(self, version)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub fn sender(&self) -> Sender<T> {
+        Sender(self)
+    }
+
+    /// Attempts to reserve a slot for sending a message.
+    ///
+    /// There is always room for the new value, so this always succeeds.
+    fn start_send(&self, _token: &mut Token) -> bool {
+        true
+    }
+
+    /// Replaces the value in the channel, waiting until outstanding borrows are dropped.
+    fn push(&self, msg: T) -> Result<(), T> {
+        let old = {
+            let mut inner = self.inner.write().unwrap();
+
+            if inner.is_disconnected {
+                return Err(msg);
+            }
+
+            inner.version = inner.version.wrapping_add(1);
+            mem::replace(&mut inner.value, msg)
+        };
+        self.metrics.sent(1, || 1);
+
+        // Drop the old value outside the lock.
+        drop(old);
+
+        // Wake all sleeping receivers.
+        self.receivers.notify_all();
+        Ok(())
+    }
+
+    /// Attempts to mark the latest value as seen by `version`, passing it to `f` on success.
+    fn pop<F, R>(&self, version: &Version, f: F) -> TryRecv<R>
+    where
+        F: Fn(&T) -> R,
+    {
+        loop {
+            let inner = self.read_lock();
+            let seen = version.seen.load(Ordering::SeqCst);
+
+            if seen == inner.version {
+                return if inner.is_disconnected {
+                    TryRecv::Disconnected
+                } else {
+                    TryRecv::Empty
+                };
+            }
+
+            // Another thread may be receiving through the same receiver, so make sure only one of
+            // them gets the new value.
+            if version
+                .seen
+                .compare_exchange(seen, inner.version, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+            {
+                self.received.store(inner.version, Ordering::SeqCst);
+                self.metrics.received(1);
+                return TryRecv::Value(f(&inner.value));
+            }
+        }
+    }
+
+    /// Calls `f` on the latest value if `version` hasn't seen it yet, without marking it as seen.
+    pub fn peek_with<F, R>(&self, version: &Version, f: F) -> Result<R, TryRecvError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let inner = self.read_lock();
+
+        if version.seen.load(Ordering::SeqCst) != inner.version {
+            Ok(f(&inner.value))
+        } else if inner.is_disconnected {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives the latest value if `version` hasn't seen it yet and it satisfies `pred`.
+    pub fn try_recv_if<F>(&self, version: &Version, pred: F) -> Result<Option<T>, TryRecvError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        let inner = self.read_lock();
+        let seen = version.seen.load(Ordering::SeqCst);
+
+        if seen == inner.version {
+            return if inner.is_disconnected {
+                Err(TryRecvError::Disconnected)
+            } else {
+                Err(TryRecvError::Empty)
+            };
+        }
+
+        if !pred(&inner.value) {
+            return Ok(None);
+        }
+
+        // If another thread has received the value through the same receiver in the meantime,
+        // there is nothing left to receive.
+        if version
+            .seen
+            .compare_exchange(seen, inner.version, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(TryRecvError::Empty);
+        }
+
+        self.received.store(inner.version, Ordering::SeqCst);
+        self.metrics.received(1);
+        Ok(Some((self.clone)(&inner.value)))
+    }
+
+    /// Returns a reference to the latest value without marking it as seen.
+    ///
+    /// Senders wait until the reference is dropped.
+    pub fn borrow(&self) -> Ref<T> {
+        Ref {
+            guard: self.read_lock(),
+        }
+    }
+
+    /// Attempts to reserve a message for receiving.
+    fn start_recv(&self, version: &Version, token: &mut Token) -> bool {
+        match self.pop(version, self.clone) {
+            TryRecv::Value(msg) => {
+                token.boxed.msg = Box::into_raw(Box::new(msg)) as *mut u8;
+                true
+            }
+            TryRecv::Disconnected => {
+                token.boxed.msg = ptr::null_mut();
+                true
+            }
+            TryRecv::Empty => false,
+        }
+    }
+
+    /// Reads a message from the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if token.boxed.msg.is_null() {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        let msg = Box::from_raw(token.boxed.msg as *mut T);
+        Ok(*msg)
+    }
+
+    /// Writes a message into the channel.
+    pub unsafe fn write(&self, _token: &mut Token, msg: T) -> Result<(), T> {
+        self.push(msg)
+    }
+
+    /// Attempts to send a message into the channel.
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.push(msg).map_err(TrySendError::Disconnected)
+    }
+
+    /// Sends a message into the channel.
+    ///
+    /// Sending only waits for outstanding borrows, so the deadline is ignored.
+    pub fn send(&self, msg: T, _deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        self.push(msg).map_err(SendTimeoutError::Disconnected)
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self, version: &Version) -> Result<T, TryRecvError> {
+        match self.pop(version, self.clone) {
+            TryRecv::Value(msg) => Ok(msg),
+            TryRecv::Empty => Err(TryRecvError::Empty),
+            TryRecv::Disconnected => Err(TryRecvError::Disconnected),
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(
+        &self,
+        version: &Version,
+        deadline: Option<Instant>,
+    ) -> Result<T, RecvTimeoutError> {
+        self.wait(version, deadline, self.clone)
+    }
+
+    /// Waits until the value changes and marks the new value as seen.
+    pub fn changed(
+        &self,
+        version: &Version,
+        deadline: Option<Instant>,
+    ) -> Result<(), RecvTimeoutError> {
+        self.wait(version, deadline, |_| ())
+    }
+
+    /// Blocks until `version` can be marked as having seen a new value, passing it to `f`.
+    fn wait<F, R>(
+        &self,
+        version: &Version,
+        deadline: Option<Instant>,
+        f: F,
+    ) -> Result<R, RecvTimeoutError>
+    where
+        F: Fn(&T) -> R,
+    {
+        let token = &mut Token::default();
+        loop {
+            match self.pop(version, &f) {
+                TryRecv::Value(r) => return Ok(r),
+                TryRecv::Disconnected => return Err(RecvTimeoutError::Disconnected),
+                TryRecv::Empty => {}
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a sender wakes us up.
+                let oper = Operation::hook(token);
+                self.receivers.register(oper, cx);
+
+                // Has the channel become ready just now?
+                if self.has_changed(version) || self.is_disconnected() {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = self.metrics.recv_wait(|| cx.wait_until(deadline));
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.receivers.unregister(oper).unwrap();
+                        // If the channel was disconnected, we still have to check for a new value.
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Returns `true` if the value has changed since `version` has last seen it.
+    pub fn has_changed(&self, version: &Version) -> bool {
+        version.seen.load(Ordering::SeqCst) != self.read_lock().version
+    }
+
+    /// Returns the number of values `version` hasn't seen yet, which is either zero or one.
+    pub fn pending(&self, version: &Version) -> usize {
+        self.has_changed(version) as usize
+    }
+
+    /// Returns the number of values no receiver has received yet, which is either zero or one.
+    pub fn len(&self) -> usize {
+        let inner = self.read_lock();
+        (self.received.load(Ordering::SeqCst) != inner.version) as usize
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        Some(1)
+    }
+
+    /// Disconnects the channel and wakes up all blocked receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        let mut inner = self.inner.write().unwrap();
+
+        if !inner.is_disconnected {
+            inner.is_disconnected = true;
+            self.receivers.disconnect();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.read_lock().is_disconnected
+    }
+
+    /// Returns `true` if some receiver has received the latest value.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if no receiver has received the latest value yet.
+    ///
+    /// The next message replaces the value either way.
+    pub fn is_full(&self) -> bool {
+        self.len() == 1
+    }
+
+    /// Returns a snapshot of statistics about the channel.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> Stats {
+        self.metrics.stats(0, self.receivers.waiting())
+    }
+
+    /// Returns the statistics recorded by the channel.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+#[cfg(feature = "debug")]
+impl<T> Inspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "watch"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn blocked(&self) -> (Vec<Context>, Vec<Context>) {
+        (Vec::new(), self.receivers.contexts())
+    }
+}
+
+/// Receiver handle to a channel.
+pub struct Receiver<'a, T: 'a>(&'a Channel<T>, &'a Version);
+
+/// Sender handle to a channel.
+pub struct Sender<'a, T: 'a>(&'a Channel<T>);
+
+impl<'a, T> SelectHandle for Receiver<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(self.1, token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.0.has_changed(self.1) || self.0.is_disconnected()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.receivers.unwatch(oper);
+    }
+}
+
+impl<'a, T> SelectHandle for Sender<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_send(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    fn unregister(&self, _oper: Operation) {}
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn watch(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    fn unwatch(&self, _oper: Operation) {}
+}
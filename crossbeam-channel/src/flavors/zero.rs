@@ -10,7 +10,12 @@ use std::time::Instant;
 use crossbeam_utils::Backoff;
 
 use context::Context;
+#[cfg(feature = "debug")]
+use debug::Inspect;
 use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use metrics::Metrics;
+#[cfg(feature = "metrics")]
+use metrics::Stats;
 use select::{Operation, SelectHandle, Selected, Token};
 use utils::Spinlock;
 use waker::Waker;
@@ -58,6 +63,16 @@ impl<T> Packet<T> {
         }
     }
 
+    /// Creates a packet on the heap, containing a message that is ready for reading.
+    #[cfg(feature = "async")]
+    fn message_on_heap(msg: T) -> Box<Packet<T>> {
+        Box::new(Packet {
+            on_stack: false,
+            ready: AtomicBool::new(true),
+            msg: UnsafeCell::new(Some(msg)),
+        })
+    }
+
     /// Waits until the packet becomes ready for reading or writing.
     fn wait_ready(&self) {
         let backoff = Backoff::new();
@@ -84,6 +99,9 @@ pub struct Channel<T> {
     /// Inner representation of the channel.
     inner: Spinlock<Inner>,
 
+    /// Statistics about operations on the channel.
+    metrics: Metrics,
+
     /// Indicates that dropping a `Channel<T>` may drop values of type `T`.
     _marker: PhantomData<T>,
 }
@@ -97,6 +115,7 @@ impl<T> Channel<T> {
                 receivers: Waker::new(),
                 is_disconnected: false,
             }),
+            metrics: Metrics::new(),
             _marker: PhantomData,
         }
     }
@@ -111,12 +130,22 @@ impl<T> Channel<T> {
         Sender(self)
     }
 
+    /// Records a message passed from a sender to a receiver.
+    ///
+    /// Every message is passed when an operation pairs up with a waiting one, so this is called
+    /// right after pairing up, by whichever side came second.
+    fn paired(&self) {
+        self.metrics.sent(1, || 0);
+        self.metrics.received(1);
+    }
+
     /// Attempts to reserve a slot for sending a message.
     fn start_send(&self, token: &mut Token) -> bool {
         let mut inner = self.inner.lock();
 
         // If there's a waiting receiver, pair up with it.
         if let Some(operation) = inner.receivers.try_select() {
+            self.paired();
             token.zero = operation.packet;
             true
         } else if inner.is_disconnected {
@@ -146,6 +175,7 @@ impl<T> Channel<T> {
 
         // If there's a waiting sender, pair up with it.
         if let Some(operation) = inner.senders.try_select() {
+            self.paired();
             token.zero = operation.packet;
             true
         } else if inner.is_disconnected {
@@ -189,6 +219,7 @@ impl<T> Channel<T> {
 
         // If there's a waiting receiver, pair up with it.
         if let Some(operation) = inner.receivers.try_select() {
+            self.paired();
             token.zero = operation.packet;
             drop(inner);
             unsafe {
@@ -209,6 +240,7 @@ impl<T> Channel<T> {
 
         // If there's a waiting receiver, pair up with it.
         if let Some(operation) = inner.receivers.try_select() {
+            self.paired();
             token.zero = operation.packet;
             drop(inner);
             unsafe {
@@ -232,7 +264,7 @@ impl<T> Channel<T> {
             drop(inner);
 
             // Block the current thread.
-            let sel = cx.wait_until(deadline);
+            let sel = self.metrics.send_wait(|| cx.wait_until(deadline));
 
             match sel {
                 Selected::Waiting => unreachable!(),
@@ -255,6 +287,52 @@ impl<T> Channel<T> {
         })
     }
 
+    /// Sends a message to a waiting receiver or registers an asynchronous send operation.
+    ///
+    /// If no receiver is waiting, the message is moved into a packet on the heap, which receivers
+    /// can pick up without any further involvement of the sending task.
+    ///
+    /// Returns `true` if the message was sent and `false` if the operation was registered.
+    #[cfg(feature = "async")]
+    pub fn send_or_register(&self, msg: T, oper: Operation, cx: &Context) -> Result<bool, T> {
+        let token = &mut Token::default();
+        let mut inner = self.inner.lock();
+
+        // If there's a waiting receiver, pair up with it.
+        if let Some(operation) = inner.receivers.try_select() {
+            self.paired();
+            token.zero = operation.packet;
+            drop(inner);
+            unsafe {
+                self.write(token, msg).ok().unwrap();
+            }
+            return Ok(true);
+        }
+
+        if inner.is_disconnected {
+            return Err(msg);
+        }
+
+        let packet = Box::into_raw(Packet::<T>::message_on_heap(msg));
+        inner
+            .senders
+            .register_with_packet(oper, packet as usize, cx);
+        inner.receivers.notify();
+        Ok(false)
+    }
+
+    /// Unregisters an asynchronous send operation.
+    ///
+    /// Returns the message back if it hasn't been received in the meantime.
+    #[cfg(feature = "async")]
+    pub fn unregister_message(&self, oper: Operation) -> Option<T> {
+        let operation = self.inner.lock().senders.unregister(oper)?;
+        unsafe {
+            let packet = Box::from_raw(operation.packet as *mut Packet<T>);
+            packet.msg.get().replace(None)
+        }
+    }
+
     /// Attempts to receive a message without blocking.
     pub fn try_recv(&self) -> Result<T, TryRecvError> {
         let token = &mut Token::default();
@@ -262,6 +340,7 @@ impl<T> Channel<T> {
 
         // If there's a waiting sender, pair up with it.
         if let Some(operation) = inner.senders.try_select() {
+            self.paired();
             token.zero = operation.packet;
             drop(inner);
             unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
@@ -279,6 +358,7 @@ impl<T> Channel<T> {
 
         // If there's a waiting sender, pair up with it.
         if let Some(operation) = inner.senders.try_select() {
+            self.paired();
             token.zero = operation.packet;
             drop(inner);
             unsafe {
@@ -301,7 +381,7 @@ impl<T> Channel<T> {
             drop(inner);
 
             // Block the current thread.
-            let sel = cx.wait_until(deadline);
+            let sel = self.metrics.recv_wait(|| cx.wait_until(deadline));
 
             match sel {
                 Selected::Waiting => unreachable!(),
@@ -338,6 +418,11 @@ impl<T> Channel<T> {
         }
     }
 
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.lock().is_disconnected
+    }
+
     /// Returns the current number of messages inside the channel.
     pub fn len(&self) -> usize {
         0
@@ -357,6 +442,36 @@ impl<T> Channel<T> {
     pub fn is_full(&self) -> bool {
         true
     }
+
+    /// Returns a snapshot of statistics about the channel.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> Stats {
+        let inner = self.inner.lock();
+        self.metrics
+            .stats(inner.senders.waiting(), inner.receivers.waiting())
+    }
+
+    /// Returns the statistics recorded by the channel.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+#[cfg(feature = "debug")]
+impl<T> Inspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "zero"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn blocked(&self) -> (Vec<Context>, Vec<Context>) {
+        let inner = self.inner.lock();
+        (inner.senders.contexts(), inner.receivers.contexts())
+    }
 }
 
 /// Receiver handle to a channel.
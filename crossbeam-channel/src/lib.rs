@@ -78,6 +78,37 @@
 //! assert_eq!(r.recv(), Ok("Hi!"));
 //! ```
 //!
+//! The capacity of a channel created by [`bounded`] with a positive capacity can be changed at
+//! runtime with [`set_capacity`]. Sending with [`force_send`] never blocks on a full channel and
+//! evicts the oldest message instead, which keeps only the most recent messages in the channel.
+//!
+//! Receivers of the channels above compete for messages, so each message is received only once.
+//! A channel created with [`broadcast`] instead delivers a clone of every message to every
+//! receiver, overwriting the oldest messages when it is full:
+//!
+//! ```
+//! use crossbeam_channel::broadcast;
+//!
+//! let (s, r1) = broadcast(16);
+//! let r2 = r1.clone();
+//!
+//! s.send("Hi!").unwrap();
+//! assert_eq!(r1.recv(), Ok("Hi!"));
+//! assert_eq!(r2.recv(), Ok("Hi!"));
+//! ```
+//!
+//! Channels created with [`priority_bounded`] and [`priority_unbounded`] deliver messages in order
+//! of the priority given to [`send_with_priority`], and in the order they were sent when
+//! priorities are equal.
+//!
+//! A channel created with [`oneshot`] delivers a single message. Its [`OneshotSender`] is consumed
+//! by sending, which makes it a cheap way to hand back a single result, like a response to a
+//! request.
+//!
+//! A channel created with [`watch`] holds only the latest value. Sending replaces it, and each
+//! receiver waits for a value it hasn't seen yet with [`recv`] or [`changed`], or looks at the
+//! current one with [`borrow`].
+//!
 //! # Sharing channels
 //!
 //! Senders and receivers can be cloned and sent to other threads:
@@ -175,6 +206,10 @@
 //! assert_eq!(r.recv(), Err(RecvError));
 //! ```
 //!
+//! A channel can also be disconnected while its senders and receivers are still alive by calling
+//! [`close`] on any of them. Methods [`sender_count`], [`receiver_count`], and
+//! [`is_disconnected`] tell who is still attached to a channel.
+//!
 //! # Blocking operations
 //!
 //! Send and receive operations come in three flavors:
@@ -212,6 +247,13 @@
 //! assert_eq!(r.recv(), Err(RecvError));
 //! ```
 //!
+//! Messages can also be sent and received in batches with [`send_batch`], [`try_send_batch`],
+//! [`recv_batch`], and [`try_recv_batch`]. Bounded and unbounded channels reserve room for a whole
+//! batch at once, which is cheaper than transferring the messages one by one.
+//!
+//! The next message can be inspected without receiving it using [`peek_with`], and received only
+//! if it satisfies a predicate using [`try_recv_if`].
+//!
 //! # Iteration
 //!
 //! Receivers can be used as iterators. For example, method [`iter`] creates an iterator that
@@ -296,6 +338,22 @@
 //! If you need to select over a dynamically created list of channel operations, use [`Select`]
 //! instead. The [`select!`] macro is just a convenience wrapper around [`Select`].
 //!
+//! Receivers can be adapted with [`map`] and [`filter`], and senders with [`contramap`], to
+//! convert or drop messages as they pass through. The adapters don't spawn any threads and can
+//! be used in [`Select`] and [`select!`] just like the original handles.
+//!
+//! A [`ReceiverSet`] holds any number of receivers and receives from them fairly, as if they were
+//! a single receiver. Receivers can be inserted and removed at any time, and those whose channels
+//! get disconnected are dropped automatically. The set itself can be used in [`Select`] and
+//! [`select!`], which is much cheaper than rebuilding a large [`Select`] for every operation.
+//!
+//! An event loop that keeps polling the same channels can use a [`Selector`] instead, which
+//! registers its operations only once and then repeatedly waits for one of them to become ready.
+//!
+//! Other kinds of events, like a shutdown flag or a readiness bit set by an event loop, can take
+//! part in selection too. Implement [`Source`] for a type that produces them and wrap it in a
+//! [`SourceReceiver`], which is then used like any other receiver.
+//!
 //! # Extra channels
 //!
 //! Three functions can create special kinds of channels, all of which return just a [`Receiver`]
@@ -306,6 +364,22 @@
 //! * [`never`] creates a channel that never delivers messages.
 //!
 //! These channels are very efficient because messages get lazily generated on receive operations.
+//! Threads and tasks blocked on them, as well as blocking operations with a timeout, are woken up
+//! by a hierarchical timer wheel that is shared by the whole process and driven by a single
+//! background thread.
+//!
+//! Functions [`after_with_clock`] and [`tick_with_clock`] create the same kinds of channels, but
+//! measure time with a [`Clock`]. A manual clock only moves forward when advanced, which makes
+//! timeouts in tests deterministic and fast.
+//!
+//! A [`TickBuilder`] configures tick channels further: it sets the delay before the first tick
+//! and the [`MissedTicks`] policy for ticks that are received late. The schedule of a live tick
+//! channel can be restarted with [`reset`] and changed with [`set_period`].
+//!
+//! Function [`timer`] creates a channel like [`after`], along with a [`Timer`] handle that can
+//! postpone or cancel the message. This is handy for idle timeouts, which would otherwise need a
+//! new channel after every bit of activity. Function [`timer_with_clock`] creates one that measures
+//! time with a [`Clock`].
 //!
 //! An example that prints elapsed time every 50 milliseconds for the duration of 1 second:
 //!
@@ -329,50 +403,165 @@
 //! # }
 //! ```
 //!
+//! # Asynchronous operations
+//!
+//! With the `async` feature enabled, channels can also be used from asynchronous tasks.
+//! [`send_async`] and [`recv_async`] return futures that wait for the operation to complete
+//! without blocking the thread, and [`into_stream`] turns a receiver into a `Stream`. The
+//! feature requires Rust 1.36 or newer.
+//!
+//! # Statistics
+//!
+//! With the `metrics` feature enabled, channels count sent and received messages, track their
+//! largest length and the time threads spend blocked on them, and report how many operations are
+//! waiting. A snapshot of these [`Stats`] is returned by [`Sender::stats`] and
+//! [`Receiver::stats`]. Without the feature, nothing is recorded and the statistics cost nothing.
+//!
+//! # Debugging
+//!
+//! Channels can be given a name with [`bounded_named`] and [`unbounded_named`]. The name is only
+//! kept with the `debug` feature enabled, in which case it is returned by [`Sender::name`] and
+//! [`Receiver::name`] and shows up in their `Debug` output.
+//!
+//! With the `debug` feature enabled, all live channels are kept in a global registry. Function
+//! [`live_channels`] returns a [`ChannelInfo`] for each of them, listing its name, flavor, length,
+//! and the threads blocked sending into or receiving from it. This is useful for finding out why
+//! a program hangs.
+//!
+//! With the `deadlock-detection` feature enabled, channels also remember which threads have used
+//! them. Function [`find_deadlocks`] then looks for groups of threads that are blocked on each
+//! other forever, such as two threads sending into zero-capacity channels the other one receives
+//! from. A [`Watchdog`] thread can do this periodically and print or call a custom function with
+//! the names of the channels and the ids of the threads in every [`Deadlock`] it finds. The
+//! feature adds overhead to every operation and is meant for debug builds.
+//!
 //! [`std::sync::mpsc`]: https://doc.rust-lang.org/std/sync/mpsc/index.html
 //! [`unbounded`]: fn.unbounded.html
 //! [`bounded`]: fn.bounded.html
+//! [`broadcast`]: fn.broadcast.html
+//! [`priority_bounded`]: fn.priority_bounded.html
+//! [`priority_unbounded`]: fn.priority_unbounded.html
+//! [`send_with_priority`]: struct.Sender.html#method.send_with_priority
 //! [`after`]: fn.after.html
 //! [`tick`]: fn.tick.html
 //! [`never`]: fn.never.html
+//! [`after_with_clock`]: fn.after_with_clock.html
+//! [`tick_with_clock`]: fn.tick_with_clock.html
+//! [`Clock`]: struct.Clock.html
+//! [`TickBuilder`]: struct.TickBuilder.html
+//! [`MissedTicks`]: enum.MissedTicks.html
+//! [`reset`]: struct.Receiver.html#method.reset
+//! [`set_period`]: struct.Receiver.html#method.set_period
+//! [`timer`]: fn.timer.html
+//! [`timer_with_clock`]: fn.timer_with_clock.html
+//! [`Timer`]: struct.Timer.html
+//! [`oneshot`]: fn.oneshot.html
+//! [`OneshotSender`]: struct.OneshotSender.html
+//! [`watch`]: fn.watch.html
+//! [`changed`]: struct.Receiver.html#method.changed
+//! [`borrow`]: struct.Receiver.html#method.borrow
 //! [`send`]: struct.Sender.html#method.send
 //! [`recv`]: struct.Receiver.html#method.recv
+//! [`send_batch`]: struct.Sender.html#method.send_batch
+//! [`try_send_batch`]: struct.Sender.html#method.try_send_batch
+//! [`recv_batch`]: struct.Receiver.html#method.recv_batch
+//! [`try_recv_batch`]: struct.Receiver.html#method.try_recv_batch
+//! [`peek_with`]: struct.Receiver.html#method.peek_with
+//! [`try_recv_if`]: struct.Receiver.html#method.try_recv_if
+//! [`set_capacity`]: struct.Sender.html#method.set_capacity
+//! [`force_send`]: struct.Sender.html#method.force_send
+//! [`close`]: struct.Sender.html#method.close
+//! [`sender_count`]: struct.Sender.html#method.sender_count
+//! [`receiver_count`]: struct.Sender.html#method.receiver_count
+//! [`is_disconnected`]: struct.Sender.html#method.is_disconnected
 //! [`iter`]: struct.Receiver.html#method.iter
 //! [`try_iter`]: struct.Receiver.html#method.try_iter
+//! [`map`]: struct.Receiver.html#method.map
+//! [`filter`]: struct.Receiver.html#method.filter
+//! [`contramap`]: struct.Sender.html#method.contramap
+//! [`ReceiverSet`]: struct.ReceiverSet.html
 //! [`select!`]: macro.select.html
 //! [`Select`]: struct.Select.html
+//! [`Selector`]: struct.Selector.html
+//! [`Source`]: trait.Source.html
+//! [`SourceReceiver`]: struct.SourceReceiver.html
 //! [`Sender`]: struct.Sender.html
 //! [`Receiver`]: struct.Receiver.html
+//! [`send_async`]: struct.Sender.html#method.send_async
+//! [`recv_async`]: struct.Receiver.html#method.recv_async
+//! [`into_stream`]: struct.Receiver.html#method.into_stream
+//! [`Stats`]: struct.Stats.html
+//! [`Sender::stats`]: struct.Sender.html#method.stats
+//! [`Receiver::stats`]: struct.Receiver.html#method.stats
+//! [`bounded_named`]: fn.bounded_named.html
+//! [`unbounded_named`]: fn.unbounded_named.html
+//! [`Sender::name`]: struct.Sender.html#method.name
+//! [`Receiver::name`]: struct.Receiver.html#method.name
+//! [`live_channels`]: fn.live_channels.html
+//! [`ChannelInfo`]: struct.ChannelInfo.html
+//! [`find_deadlocks`]: fn.find_deadlocks.html
+//! [`Watchdog`]: struct.Watchdog.html
+//! [`Deadlock`]: struct.Deadlock.html
 
 #![warn(missing_docs)]
 #![warn(missing_debug_implementations)]
 
+extern crate crossbeam_epoch as epoch;
 extern crate crossbeam_utils;
+#[cfg(feature = "async")]
+extern crate futures_core;
 
+mod adapter;
 mod channel;
+mod clock;
 mod context;
 mod counter;
+mod deadlock;
+mod debug;
 mod err;
 mod flavors;
+mod metrics;
 mod select;
 mod select_macro;
+mod selector;
+mod set;
+mod source;
 mod utils;
 mod waker;
+mod wheel;
 
 /// Crate internals used by the `select!` macro.
 #[doc(hidden)]
 pub mod internal {
+    pub use select::IntoDeadline;
+    pub use select::Policy;
     pub use select::SelectHandle;
-    pub use select::{select, select_timeout, try_select};
+    pub use select::{select, select_deadline, select_timeout, try_select};
 }
 
-pub use channel::{after, never, tick};
-pub use channel::{bounded, unbounded};
+pub use adapter::{ContramappedSender, FilteredReceiver, MappedReceiver};
+pub use channel::{after, after_with_clock, never, tick, tick_with_clock, timer, timer_with_clock};
+pub use channel::{bounded, broadcast, oneshot, unbounded, watch};
+pub use channel::{bounded_named, unbounded_named};
+pub use channel::{priority_bounded, priority_unbounded};
 pub use channel::{IntoIter, Iter, TryIter};
-pub use channel::{Receiver, Sender};
+pub use channel::{OneshotSender, Receiver, Sender, TickBuilder, Timer, WatchRef, WeakSender};
+#[cfg(feature = "async")]
+pub use channel::{RecvFuture, RecvStream, SendFuture};
+pub use clock::Clock;
+#[cfg(feature = "deadlock-detection")]
+pub use deadlock::{find_deadlocks, Deadlock, Watchdog, WatchdogHandle};
+#[cfg(feature = "debug")]
+pub use debug::{live_channels, ChannelInfo};
+pub use flavors::tick::MissedTicks;
+#[cfg(feature = "metrics")]
+pub use metrics::Stats;
 
 pub use select::{Select, SelectedOperation};
+pub use selector::Selector;
+pub use set::ReceiverSet;
+pub use source::{Notifier, Source, SourceReceiver};
 
+pub use err::{BroadcastRecvError, RecvError, RecvTimeoutError, TryRecvError};
 pub use err::{ReadyTimeoutError, SelectTimeoutError, TryReadyError, TrySelectError};
-pub use err::{RecvError, RecvTimeoutError, TryRecvError};
 pub use err::{SendError, SendTimeoutError, TrySendError};
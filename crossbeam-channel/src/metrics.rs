@@ -0,0 +1,262 @@
+//! Statistics about channel operations.
+//!
+//! Statistics are recorded only with the `metrics` feature. Without it, `Metrics` is an empty type
+//! whose methods do nothing, so that recording compiles down to nothing at all.
+
+#[cfg(feature = "metrics")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "metrics")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "metrics")]
+use utils::Spinlock;
+
+/// A snapshot of statistics about a channel.
+///
+/// Statistics are recorded only with the `metrics` feature enabled. A snapshot is taken with
+/// [`Sender::stats`] or [`Receiver::stats`], and both return the same statistics for the whole
+/// channel.
+///
+/// Channels created by [`after`], [`tick`], [`timer`], and [`never`] don't record statistics, so
+/// their snapshots are always empty.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::bounded;
+///
+/// let (s, r) = bounded(4);
+/// s.send(1).unwrap();
+/// s.send(2).unwrap();
+/// r.recv().unwrap();
+///
+/// let stats = r.stats();
+/// assert_eq!(stats.sends(), 2);
+/// assert_eq!(stats.recvs(), 1);
+/// assert_eq!(stats.max_len(), 2);
+/// assert_eq!(stats.waiting_receivers(), 0);
+/// ```
+///
+/// [`Sender::stats`]: struct.Sender.html#method.stats
+/// [`Receiver::stats`]: struct.Receiver.html#method.stats
+/// [`after`]: fn.after.html
+/// [`tick`]: fn.tick.html
+/// [`timer`]: fn.timer.html
+/// [`never`]: fn.never.html
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of messages sent into the channel.
+    sends: usize,
+
+    /// Number of messages received from the channel.
+    recvs: usize,
+
+    /// Largest number of messages in the channel at the same time.
+    max_len: usize,
+
+    /// Total time spent blocked in send operations.
+    send_wait: Duration,
+
+    /// Total time spent blocked in receive operations.
+    recv_wait: Duration,
+
+    /// Number of send operations waiting at the time of the snapshot.
+    waiting_senders: usize,
+
+    /// Number of receive operations waiting at the time of the snapshot.
+    waiting_receivers: usize,
+}
+
+#[cfg(feature = "metrics")]
+impl Stats {
+    /// Returns the number of messages sent into the channel.
+    ///
+    /// A message sent into a [`broadcast`] channel is counted once, no matter how many receivers
+    /// it is delivered to.
+    ///
+    /// [`broadcast`]: fn.broadcast.html
+    pub fn sends(&self) -> usize {
+        self.sends
+    }
+
+    /// Returns the number of messages received from the channel.
+    ///
+    /// A message sent into a [`broadcast`] channel is counted once for every receiver it is
+    /// delivered to.
+    ///
+    /// [`broadcast`]: fn.broadcast.html
+    pub fn recvs(&self) -> usize {
+        self.recvs
+    }
+
+    /// Returns the largest number of messages that have been in the channel at the same time.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Returns the total time threads spent blocked in send operations.
+    ///
+    /// Time spent blocked in [`Select`] or [`select!`] counts towards the channel of the
+    /// operation that ends up being selected or becoming ready.
+    ///
+    /// [`Select`]: struct.Select.html
+    /// [`select!`]: macro.select.html
+    pub fn send_wait(&self) -> Duration {
+        self.send_wait
+    }
+
+    /// Returns the total time threads spent blocked in receive operations.
+    ///
+    /// Time spent blocked in [`Select`] or [`select!`] counts towards the channel of the
+    /// operation that ends up being selected or becoming ready.
+    ///
+    /// [`Select`]: struct.Select.html
+    /// [`select!`]: macro.select.html
+    pub fn recv_wait(&self) -> Duration {
+        self.recv_wait
+    }
+
+    /// Returns the number of send operations currently waiting for the channel.
+    pub fn waiting_senders(&self) -> usize {
+        self.waiting_senders
+    }
+
+    /// Returns the number of receive operations currently waiting for the channel.
+    pub fn waiting_receivers(&self) -> usize {
+        self.waiting_receivers
+    }
+}
+
+/// Statistics recorded by a channel.
+#[cfg(feature = "metrics")]
+pub struct Metrics {
+    /// The number of sent messages.
+    sends: AtomicUsize,
+
+    /// The number of received messages.
+    recvs: AtomicUsize,
+
+    /// The largest observed length of the channel.
+    max_len: AtomicUsize,
+
+    /// The total time spent blocked in send and receive operations, respectively.
+    wait: Spinlock<(Duration, Duration)>,
+}
+
+#[cfg(feature = "metrics")]
+impl Metrics {
+    /// Creates empty statistics.
+    #[inline]
+    pub fn new() -> Metrics {
+        Metrics {
+            sends: AtomicUsize::new(0),
+            recvs: AtomicUsize::new(0),
+            max_len: AtomicUsize::new(0),
+            wait: Spinlock::new((Duration::from_secs(0), Duration::from_secs(0))),
+        }
+    }
+
+    /// Records `count` sent messages, after which the channel has the length returned by `len`.
+    #[inline]
+    pub fn sent<F: FnOnce() -> usize>(&self, count: usize, len: F) {
+        self.sends.fetch_add(count, Ordering::Relaxed);
+
+        let len = len();
+        let mut max_len = self.max_len.load(Ordering::Relaxed);
+        while len > max_len {
+            match self.max_len.compare_exchange_weak(
+                max_len,
+                len,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(m) => max_len = m,
+            }
+        }
+    }
+
+    /// Records `count` received messages.
+    #[inline]
+    pub fn received(&self, count: usize) {
+        self.recvs.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Calls `f`, which blocks a send operation, and records the time it takes.
+    #[inline]
+    pub fn send_wait<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        let start = Instant::now();
+        let res = f();
+        self.waited_to_send(start.elapsed());
+        res
+    }
+
+    /// Calls `f`, which blocks a receive operation, and records the time it takes.
+    #[inline]
+    pub fn recv_wait<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        let start = Instant::now();
+        let res = f();
+        self.waited_to_recv(start.elapsed());
+        res
+    }
+
+    /// Records `dur` spent blocked in a send operation.
+    #[inline]
+    pub fn waited_to_send(&self, dur: Duration) {
+        self.wait.lock().0 += dur;
+    }
+
+    /// Records `dur` spent blocked in a receive operation.
+    #[inline]
+    pub fn waited_to_recv(&self, dur: Duration) {
+        self.wait.lock().1 += dur;
+    }
+
+    /// Returns a snapshot of the statistics, given the numbers of waiting operations.
+    pub fn stats(&self, waiting_senders: usize, waiting_receivers: usize) -> Stats {
+        let (send_wait, recv_wait) = *self.wait.lock();
+        Stats {
+            sends: self.sends.load(Ordering::Relaxed),
+            recvs: self.recvs.load(Ordering::Relaxed),
+            max_len: self.max_len.load(Ordering::Relaxed),
+            send_wait,
+            recv_wait,
+            waiting_senders,
+            waiting_receivers,
+        }
+    }
+}
+
+/// Statistics recorded by a channel, which are disabled.
+#[cfg(not(feature = "metrics"))]
+pub struct Metrics;
+
+#[cfg(not(feature = "metrics"))]
+impl Metrics {
+    /// Creates empty statistics.
+    #[inline]
+    pub fn new() -> Metrics {
+        Metrics
+    }
+
+    /// Does nothing.
+    #[inline]
+    pub fn sent<F: FnOnce() -> usize>(&self, _count: usize, _len: F) {}
+
+    /// Does nothing.
+    #[inline]
+    pub fn received(&self, _count: usize) {}
+
+    /// Calls `f`.
+    #[inline]
+    pub fn send_wait<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        f()
+    }
+
+    /// Calls `f`.
+    #[inline]
+    pub fn recv_wait<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        f()
+    }
+}
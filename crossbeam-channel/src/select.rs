@@ -1,8 +1,12 @@
 //! Interface to the select mechanism.
 
+use std::any::TypeId;
 use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
+use std::ops::Range;
+use std::ptr;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use crossbeam_utils::Backoff;
@@ -10,7 +14,7 @@ use crossbeam_utils::Backoff;
 use channel::{self, Receiver, Sender};
 use context::Context;
 use err::{ReadyTimeoutError, TryReadyError};
-use err::{RecvError, SendError};
+use err::{RecvError, SendError, TryRecvError};
 use err::{SelectTimeoutError, TrySelectError};
 use flavors;
 use utils;
@@ -18,17 +22,42 @@ use utils;
 /// Temporary data that gets initialized during select or a blocking operation, and is consumed by
 /// `read` or `write`.
 ///
-/// Each field contains data associated with a specific channel flavor.
+/// Each field contains data associated with a specific channel flavor, or with a group of flavors
+/// and adapters that pass messages the same way.
 #[derive(Debug, Default)]
 pub struct Token {
     pub after: flavors::after::AfterToken,
     pub array: flavors::array::ArrayToken,
+    pub boxed: BoxedToken,
     pub list: flavors::list::ListToken,
     pub never: flavors::never::NeverToken,
+    pub oneshot: flavors::oneshot::OneshotToken,
+    pub priority: flavors::priority::PriorityToken,
     pub tick: flavors::tick::TickToken,
     pub zero: flavors::zero::ZeroToken,
 }
 
+/// The token type shared by flavors and adapters that hand received messages over in a box.
+///
+/// These are the broadcast, watch, and priority flavors, filtered receivers, receiver sets, and
+/// sources. An adapter reads the message out of the receiver it wraps before boxing its own, so
+/// they can all use the same field, which keeps `Token` small. That matters because a token is
+/// created and moved around in every select.
+#[derive(Debug)]
+pub struct BoxedToken {
+    /// A boxed message, or null if the channel is disconnected.
+    pub msg: *mut u8,
+}
+
+impl Default for BoxedToken {
+    #[inline]
+    fn default() -> Self {
+        BoxedToken {
+            msg: ptr::null_mut(),
+        }
+    }
+}
+
 /// Identifier associated with an operation by a specific thread on a specific channel.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Operation(usize);
@@ -117,9 +146,18 @@ pub trait SelectHandle {
 
     /// Unregisters an operation for readiness notification.
     fn unwatch(&self, oper: Operation);
+
+    /// Records time spent blocked in select before this operation was selected or became ready.
+    #[cfg(feature = "metrics")]
+    fn waited(&self, _dur: Duration) {}
+
+    /// Returns the address of the handle, looking through references.
+    fn addr(&self) -> *const u8 {
+        self as *const Self as *const u8
+    }
 }
 
-impl<'a, T: SelectHandle> SelectHandle for &'a T {
+impl<'a, T: SelectHandle + ?Sized> SelectHandle for &'a T {
     fn try_select(&self, token: &mut Token) -> bool {
         (**self).try_select(token)
     }
@@ -151,11 +189,328 @@ impl<'a, T: SelectHandle> SelectHandle for &'a T {
     fn unwatch(&self, oper: Operation) {
         (**self).unwatch(oper)
     }
+
+    #[cfg(feature = "metrics")]
+    fn waited(&self, dur: Duration) {
+        (**self).waited(dur)
+    }
+
+    fn addr(&self) -> *const u8 {
+        (**self).addr()
+    }
+}
+
+/// A handle that receive operations in select can be executed on.
+pub trait RecvHandle: SelectHandle {
+    /// The type of received messages.
+    type Msg;
+
+    /// Reads a message after the operation has been selected.
+    ///
+    /// # Safety
+    ///
+    /// The token must come from a successful selection of this handle's operation.
+    unsafe fn read(&self, token: &mut Token) -> Result<Self::Msg, ()>;
+}
+
+impl<'a, T: RecvHandle> RecvHandle for &'a T {
+    type Msg = T::Msg;
+
+    unsafe fn read(&self, token: &mut Token) -> Result<Self::Msg, ()> {
+        (**self).read(token)
+    }
+}
+
+/// A handle that send operations in select can be executed on.
+pub trait SendHandle: SelectHandle {
+    /// The type of messages passed to the handle.
+    type Msg;
+
+    /// The type of messages written into the channel, which are returned if sending fails.
+    type Sent;
+
+    /// Writes a message after the operation has been selected.
+    ///
+    /// # Safety
+    ///
+    /// The token must come from a successful selection of this handle's operation.
+    unsafe fn write(&self, token: &mut Token, msg: Self::Msg) -> Result<(), Self::Sent>;
+}
+
+impl<'a, T: SendHandle> SendHandle for &'a T {
+    type Msg = T::Msg;
+    type Sent = T::Sent;
+
+    unsafe fn write(&self, token: &mut Token, msg: Self::Msg) -> Result<(), Self::Sent> {
+        (**self).write(token, msg)
+    }
+}
+
+/// A sender or a receiver owned by a `Select`.
+///
+/// Operations on owned handles are completed without passing the handle again, so the handle
+/// also erases the type of messages and checks it at runtime.
+trait OwnedHandle: Send + Sync {
+    /// Returns the handle that participates in select.
+    fn handle(&self) -> &SelectHandle;
+
+    /// Returns `true` if this is a receive operation.
+    fn is_recv(&self) -> bool;
+
+    /// Returns the type of messages that are sent or received.
+    fn msg_type(&self) -> TypeId;
+
+    /// Completes the selected operation and returns `true` on success.
+    ///
+    /// `slot` points to an `Option` of the message type. A received message is stored into it,
+    /// while a message to send is taken from it and put back if sending fails.
+    ///
+    /// # Safety
+    ///
+    /// The token must come from a successful selection of this handle's operation, and `slot`
+    /// must have the type returned by `msg_type`.
+    unsafe fn complete(&self, token: &mut Token, slot: *mut u8) -> bool;
+
+    /// Returns the address of the receiver if this is a conditional receive operation.
+    fn peeked(&self) -> Option<*const u8> {
+        None
+    }
+}
+
+/// A receiver owned by a `Select`.
+struct OwnedRecv<R>(R);
+
+impl<R> OwnedHandle for OwnedRecv<R>
+where
+    R: RecvHandle + Send + Sync,
+    R::Msg: 'static,
+{
+    fn handle(&self) -> &SelectHandle {
+        &self.0
+    }
+
+    fn is_recv(&self) -> bool {
+        true
+    }
+
+    fn msg_type(&self) -> TypeId {
+        TypeId::of::<R::Msg>()
+    }
+
+    unsafe fn complete(&self, token: &mut Token, slot: *mut u8) -> bool {
+        match self.0.read(token) {
+            Ok(msg) => {
+                *(slot as *mut Option<R::Msg>) = Some(msg);
+                true
+            }
+            Err(()) => false,
+        }
+    }
+}
+
+/// A sender owned by a `Select`.
+struct OwnedSend<S>(S);
+
+impl<S, T> OwnedHandle for OwnedSend<S>
+where
+    S: SendHandle<Msg = T, Sent = T> + Send + Sync,
+    T: 'static,
+{
+    fn handle(&self) -> &SelectHandle {
+        &self.0
+    }
+
+    fn is_recv(&self) -> bool {
+        false
+    }
+
+    fn msg_type(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    unsafe fn complete(&self, token: &mut Token, slot: *mut u8) -> bool {
+        let slot = &mut *(slot as *mut Option<T>);
+        let msg = slot.take().unwrap();
+        match self.0.write(token, msg) {
+            Ok(()) => true,
+            Err(msg) => {
+                *slot = Some(msg);
+                false
+            }
+        }
+    }
+}
+
+/// A conditional receive operation owned by a `Select`.
+///
+/// Selecting the operation doesn't receive the message, so that it can be inspected first.
+struct Peek<'a, T: 'a>(&'a Receiver<T>);
+
+impl<'a, T> SelectHandle for Peek<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        channel::start_peek(self.0, token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.0.deadline()
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        // Wait for a message as an observer, so that a message left in the channel because it
+        // doesn't satisfy the predicate doesn't use up the wakeup of another receiver.
+        self.0.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.unwatch(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        !self.0.is_empty() || self.0.is_disconnected()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.unwatch(oper);
+    }
+
+    #[cfg(feature = "metrics")]
+    fn waited(&self, dur: Duration) {
+        self.0.waited(dur);
+    }
+}
+
+impl<'a, T: Send + 'static> OwnedHandle for Peek<'a, T> {
+    fn handle(&self) -> &SelectHandle {
+        self
+    }
+
+    fn is_recv(&self) -> bool {
+        true
+    }
+
+    fn msg_type(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    unsafe fn complete(&self, token: &mut Token, slot: *mut u8) -> bool {
+        match channel::read_if(self.0, token, |_| true) {
+            Ok(Some(msg)) => {
+                *(slot as *mut Option<T>) = Some(msg);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn peeked(&self) -> Option<*const u8> {
+        Some(self.0 as *const Receiver<T> as *const u8)
+    }
+}
+
+/// A sender or a receiver participating in a `Select`.
+#[derive(Clone)]
+enum Handle<'a> {
+    /// A handle borrowed by the `Select`.
+    Borrowed(&'a SelectHandle),
+
+    /// A handle owned by the `Select`.
+    Owned(Arc<OwnedHandle + 'a>),
+}
+
+impl<'a> Handle<'a> {
+    /// Returns the handle that participates in select.
+    fn get(&self) -> &SelectHandle {
+        match self {
+            Handle::Borrowed(handle) => *handle,
+            Handle::Owned(owned) => owned.handle(),
+        }
+    }
+}
+
+impl<'a> SelectHandle for Handle<'a> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.get().try_select(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.get().deadline()
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.get().register(oper, cx)
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.get().unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        self.get().accept(token, cx)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.get().is_ready()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.get().watch(oper, cx)
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.get().unwatch(oper)
+    }
+
+    #[cfg(feature = "metrics")]
+    fn waited(&self, dur: Duration) {
+        self.get().waited(dur)
+    }
+
+    fn addr(&self) -> *const u8 {
+        self.get().addr()
+    }
+}
+
+/// Determines how to choose among operations that are ready at the same time.
+#[derive(Clone, Copy, Debug)]
+pub enum Policy<'w> {
+    /// Chooses a random operation.
+    Random,
+
+    /// Chooses a random operation with probability proportional to its weight.
+    ///
+    /// The slice is indexed by operation index, and operations past its end have a weight of 1.
+    Weighted(&'w [u32]),
+
+    /// Chooses the operation that comes first in the list.
+    Biased,
+}
+
+impl<'w> Policy<'w> {
+    /// Reorders the operations so that trying them one by one follows the policy.
+    fn apply<H>(self, handles: &mut [(H, usize, *const u8)]) {
+        match self {
+            Policy::Random => utils::shuffle(handles),
+            Policy::Weighted(weights) => {
+                utils::shuffle_weighted(handles, |&(_, i, _)| weights.get(i).cloned().unwrap_or(1))
+            }
+            Policy::Biased => {}
+        }
+    }
 }
 
 /// Determines when a select operation should time out.
 #[derive(Clone, Copy, Eq, PartialEq)]
-enum Timeout {
+pub enum Timeout {
     /// No blocking.
     Now,
 
@@ -166,13 +521,34 @@ enum Timeout {
     At(Instant),
 }
 
+/// A timeout for the `default` case in `select!`, given either as a duration or as a deadline.
+pub trait IntoDeadline {
+    /// Converts the timeout into a deadline.
+    fn into_deadline(self) -> Instant;
+}
+
+impl IntoDeadline for Duration {
+    #[inline]
+    fn into_deadline(self) -> Instant {
+        Instant::now() + self
+    }
+}
+
+impl IntoDeadline for Instant {
+    #[inline]
+    fn into_deadline(self) -> Instant {
+        self
+    }
+}
+
 /// Runs until one of the operations is selected, potentially blocking the current thread.
 ///
 /// Successful receive operations will have to be followed up by `channel::read()` and successful
 /// send operations by `channel::write()`.
-fn run_select(
-    handles: &mut [(&SelectHandle, usize, *const u8)],
+fn run_select<H: SelectHandle>(
+    handles: &mut [(H, usize, *const u8)],
     timeout: Timeout,
+    policy: Policy,
 ) -> Option<(Token, usize, *const u8)> {
     if handles.is_empty() {
         // Wait until the timeout and return.
@@ -189,21 +565,24 @@ fn run_select(
         }
     }
 
-    // Shuffle the operations for fairness.
-    utils::shuffle(handles);
+    // Order the operations according to the policy, e.g. shuffle them for fairness.
+    policy.apply(handles);
 
     // Create a token, which serves as a temporary variable that gets initialized in this function
-    // and is later used by a call to `channel::read()` or `channel::write()` that completes the
+    // and is later used by a call to `read()` or `write()` that completes the
     // selected operation.
     let mut token = Token::default();
 
     // Try selecting one of the operations without blocking.
-    for &(handle, i, ptr) in handles.iter() {
+    for &(ref handle, i, ptr) in handles.iter() {
         if handle.try_select(&mut token) {
             return Some((token, i, ptr));
         }
     }
 
+    #[cfg(feature = "metrics")]
+    let start = Instant::now();
+
     loop {
         // Prepare for blocking.
         let res = Context::with(|cx| {
@@ -218,9 +597,10 @@ fn run_select(
             // Register all operations.
             for (handle, i, _) in handles.iter_mut() {
                 registered_count += 1;
+                let oper = Operation::hook(handle);
 
                 // If registration returns `false`, that means the operation has just become ready.
-                if handle.register(Operation::hook::<&SelectHandle>(handle), cx) {
+                if handle.register(oper, cx) {
                     // Try aborting select.
                     sel = match cx.try_select(Selected::Aborted) {
                         Ok(()) => {
@@ -247,7 +627,7 @@ fn run_select(
                     Timeout::Never => None,
                     Timeout::At(when) => Some(when),
                 };
-                for &(handle, _, _) in handles.iter() {
+                for &(ref handle, _, _) in handles.iter() {
                     if let Some(x) = handle.deadline() {
                         deadline = deadline.map(|y| x.min(y)).or(Some(x));
                     }
@@ -259,7 +639,8 @@ fn run_select(
 
             // Unregister all registered operations.
             for (handle, _, _) in handles.iter_mut().take(registered_count) {
-                handle.unregister(Operation::hook::<&SelectHandle>(handle));
+                let oper = Operation::hook(handle);
+                handle.unregister(oper);
             }
 
             match sel {
@@ -267,7 +648,7 @@ fn run_select(
                 Selected::Aborted => {
                     // If an operation became ready during registration, try selecting it.
                     if let Some(index_ready) = index_ready {
-                        for &(handle, i, ptr) in handles.iter() {
+                        for &(ref handle, i, ptr) in handles.iter() {
                             if i == index_ready && handle.try_select(&mut token) {
                                 return Some((i, ptr));
                             }
@@ -279,7 +660,7 @@ fn run_select(
                     // Find the selected operation.
                     for (handle, i, ptr) in handles.iter_mut() {
                         // Is this the selected operation?
-                        if sel == Selected::Operation(Operation::hook::<&SelectHandle>(handle)) {
+                        if sel == Selected::Operation(Operation::hook(handle)) {
                             // Try selecting this operation.
                             if handle.accept(&mut token, cx) {
                                 return Some((*i, *ptr));
@@ -294,12 +675,16 @@ fn run_select(
 
         // Return if an operation was selected.
         if let Some((i, ptr)) = res {
+            #[cfg(feature = "metrics")]
+            record_wait(handles, i, start);
             return Some((token, i, ptr));
         }
 
         // Try selecting one of the operations without blocking.
-        for &(handle, i, ptr) in handles.iter() {
+        for &(ref handle, i, ptr) in handles.iter() {
             if handle.try_select(&mut token) {
+                #[cfg(feature = "metrics")]
+                handle.waited(start.elapsed());
                 return Some((token, i, ptr));
             }
         }
@@ -317,7 +702,11 @@ fn run_select(
 }
 
 /// Runs until one of the operations becomes ready, potentially blocking the current thread.
-fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout) -> Option<usize> {
+fn run_ready<H: SelectHandle>(
+    handles: &mut [(H, usize, *const u8)],
+    timeout: Timeout,
+    policy: Policy,
+) -> Option<usize> {
     if handles.is_empty() {
         // Wait until the timeout and return.
         match timeout {
@@ -333,14 +722,17 @@ fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout
         }
     }
 
-    // Shuffle the operations for fairness.
-    utils::shuffle(handles);
+    // Order the operations according to the policy, e.g. shuffle them for fairness.
+    policy.apply(handles);
+
+    #[cfg(feature = "metrics")]
+    let start = Instant::now();
 
     loop {
         let backoff = Backoff::new();
         loop {
             // Check operations for readiness.
-            for &(handle, i, _) in handles.iter() {
+            for &(ref handle, i, _) in handles.iter() {
                 if handle.is_ready() {
                     return Some(i);
                 }
@@ -372,7 +764,7 @@ fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout
             // Begin watching all operations.
             for (handle, _, _) in handles.iter_mut() {
                 registered_count += 1;
-                let oper = Operation::hook::<&SelectHandle>(handle);
+                let oper = Operation::hook(handle);
 
                 // If registration returns `false`, that means the operation has just become ready.
                 if handle.watch(oper, cx) {
@@ -398,7 +790,7 @@ fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout
                     Timeout::Never => None,
                     Timeout::At(when) => Some(when),
                 };
-                for &(handle, _, _) in handles.iter() {
+                for &(ref handle, _, _) in handles.iter() {
                     if let Some(x) = handle.deadline() {
                         deadline = deadline.map(|y| x.min(y)).or(Some(x));
                     }
@@ -410,7 +802,8 @@ fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout
 
             // Unwatch all operations.
             for (handle, _, _) in handles.iter_mut().take(registered_count) {
-                handle.unwatch(Operation::hook::<&SelectHandle>(handle));
+                let oper = Operation::hook(handle);
+                handle.unwatch(oper);
             }
 
             match sel {
@@ -419,7 +812,7 @@ fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout
                 Selected::Disconnected => {}
                 Selected::Operation(_) => {
                     for (handle, i, _) in handles.iter_mut() {
-                        let oper = Operation::hook::<&SelectHandle>(handle);
+                        let oper = Operation::hook(handle);
                         if sel == Selected::Operation(oper) {
                             return Some(*i);
                         }
@@ -431,23 +824,39 @@ fn run_ready(handles: &mut [(&SelectHandle, usize, *const u8)], timeout: Timeout
         });
 
         // Return if an operation became ready.
-        if res.is_some() {
-            return res;
+        if let Some(i) = res {
+            #[cfg(feature = "metrics")]
+            record_wait(handles, i, start);
+            return Some(i);
+        }
+    }
+}
+
+/// Records the time since `start` on the operation with index `i`, which has been selected or has
+/// become ready after blocking.
+#[cfg(feature = "metrics")]
+fn record_wait<H: SelectHandle>(handles: &[(H, usize, *const u8)], i: usize, start: Instant) {
+    for &(ref handle, j, _) in handles {
+        if i == j {
+            handle.waited(start.elapsed());
+            break;
         }
     }
 }
 
 /// Attempts to select one of the operations without blocking.
 #[inline]
-pub fn try_select<'a>(
-    handles: &mut [(&'a SelectHandle, usize, *const u8)],
+pub fn try_select<'a, H: SelectHandle + 'a>(
+    handles: &mut [(H, usize, *const u8)],
+    policy: Policy,
 ) -> Result<SelectedOperation<'a>, TrySelectError> {
-    match run_select(handles, Timeout::Now) {
+    match run_select(handles, Timeout::Now, policy) {
         None => Err(TrySelectError),
         Some((token, index, ptr)) => Ok(SelectedOperation {
             token,
             index,
             ptr,
+            owned: None,
             _marker: PhantomData,
         }),
     }
@@ -455,34 +864,48 @@ pub fn try_select<'a>(
 
 /// Blocks until one of the operations becomes ready and selects it.
 #[inline]
-pub fn select<'a>(handles: &mut [(&'a SelectHandle, usize, *const u8)]) -> SelectedOperation<'a> {
+pub fn select<'a, H: SelectHandle + 'a>(
+    handles: &mut [(H, usize, *const u8)],
+    policy: Policy,
+) -> SelectedOperation<'a> {
     if handles.is_empty() {
         panic!("no operations have been added to `Select`");
     }
 
-    let (token, index, ptr) = run_select(handles, Timeout::Never).unwrap();
+    let (token, index, ptr) = run_select(handles, Timeout::Never, policy).unwrap();
     SelectedOperation {
         token,
         index,
         ptr,
+        owned: None,
         _marker: PhantomData,
     }
 }
 
 /// Blocks for a limited time until one of the operations becomes ready and selects it.
 #[inline]
-pub fn select_timeout<'a>(
-    handles: &mut [(&'a SelectHandle, usize, *const u8)],
+pub fn select_timeout<'a, H: SelectHandle + 'a>(
+    handles: &mut [(H, usize, *const u8)],
     timeout: Duration,
+    policy: Policy,
 ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
-    let timeout = Timeout::At(Instant::now() + timeout);
+    select_deadline(handles, Instant::now() + timeout, policy)
+}
 
-    match run_select(handles, timeout) {
+/// Blocks until a given deadline, or until one of the operations becomes ready and selects it.
+#[inline]
+pub fn select_deadline<'a, H: SelectHandle + 'a>(
+    handles: &mut [(H, usize, *const u8)],
+    deadline: Instant,
+    policy: Policy,
+) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+    match run_select(handles, Timeout::At(deadline), policy) {
         None => Err(SelectTimeoutError),
         Some((token, index, ptr)) => Ok(SelectedOperation {
             token,
             index,
             ptr,
+            owned: None,
             _marker: PhantomData,
         }),
     }
@@ -494,12 +917,22 @@ pub fn select_timeout<'a>(
 /// ready, and finally execute it. If multiple operations are ready at the same time, a random one
 /// among them is selected.
 ///
+/// The random choice can be skewed by giving operations different weights with [`set_weight`].
+/// Alternatively, a `Select` created with [`biased`] always chooses the operation that was added
+/// first.
+///
 /// An operation is considered to be ready if it doesn't have to block. Note that it is ready even
 /// when it will simply return an error because the channel is disconnected.
 ///
 /// The [`select!`] macro is a convenience wrapper around `Select`. However, it cannot select over a
 /// dynamically created list of channel operations.
 ///
+/// Operations are usually added with references to senders and receivers, which must outlive the
+/// `Select`. Alternatively, [`send_owned`], [`recv_owned`], and [`recv_all`] move the handles into
+/// the `Select`, so that cloned handles can be used in a `Select<'static>`. Such operations are
+/// completed with [`SelectedOperation::send_any`] and [`SelectedOperation::recv_any`], which don't
+/// need the handle to be passed again.
+///
 /// Once a list of operations has been built with `Select`, there are two different ways of
 /// proceeding:
 ///
@@ -564,7 +997,40 @@ pub fn select_timeout<'a>(
 /// }
 /// ```
 ///
+/// Use [`recv_all`] to receive messages from a list of receivers that changes over time:
+///
+/// ```
+/// use crossbeam_channel::{Receiver, Select};
+///
+/// fn drain<T: Send + 'static>(mut rs: Vec<Receiver<T>>) -> Vec<T> {
+///     let mut msgs = Vec::new();
+///     while !rs.is_empty() {
+///         // Build a list of operations that owns clones of the receivers.
+///         let mut sel = Select::new();
+///         let opers = sel.recv_all(rs.iter().cloned());
+///
+///         // Receive a message, or forget the receiver if its channel is disconnected.
+///         let oper = sel.select();
+///         let index = oper.index() - opers.start;
+///         match oper.recv_any() {
+///             Ok(msg) => msgs.push(msg),
+///             Err(_) => {
+///                 rs.remove(index);
+///             }
+///         }
+///     }
+///     msgs
+/// }
+/// ```
+///
 /// [`select!`]: macro.select.html
+/// [`send_owned`]: struct.Select.html#method.send_owned
+/// [`recv_owned`]: struct.Select.html#method.recv_owned
+/// [`recv_all`]: struct.Select.html#method.recv_all
+/// [`SelectedOperation::send_any`]: struct.SelectedOperation.html#method.send_any
+/// [`SelectedOperation::recv_any`]: struct.SelectedOperation.html#method.recv_any
+/// [`set_weight`]: struct.Select.html#method.set_weight
+/// [`biased`]: struct.Select.html#method.biased
 /// [`try_select`]: struct.Select.html#method.try_select
 /// [`select`]: struct.Select.html#method.select
 /// [`select_timeout`]: struct.Select.html#method.select_timeout
@@ -573,10 +1039,18 @@ pub fn select_timeout<'a>(
 /// [`ready_timeout`]: struct.Select.html#method.ready_timeout
 pub struct Select<'a> {
     /// A list of senders and receivers participating in selection.
-    handles: Vec<(&'a SelectHandle, usize, *const u8)>,
+    handles: Vec<(Handle<'a>, usize, *const u8)>,
 
     /// The next index to assign to an operation.
     next_index: usize,
+
+    /// `true` if the operation that was added first is chosen among ready operations.
+    biased: bool,
+
+    /// The weights of operations, indexed by operation index.
+    ///
+    /// Operations past the end of the list have a weight of 1.
+    weights: Vec<u32>,
 }
 
 unsafe impl<'a> Send for Select<'a> {}
@@ -599,6 +1073,44 @@ impl<'a> Select<'a> {
         Select {
             handles: Vec::with_capacity(4),
             next_index: 0,
+            biased: false,
+            weights: Vec::new(),
+        }
+    }
+
+    /// Creates an empty list of channel operations for biased selection.
+    ///
+    /// If multiple operations are ready at the same time, a biased `Select` chooses the one that
+    /// was added first rather than a random one. Note that an operation that is always ready will
+    /// starve the operations added after it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// s1.send(1).unwrap();
+    /// s2.send(2).unwrap();
+    ///
+    /// let mut sel = Select::biased();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // Both operations are ready, but the first one is always selected.
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert_eq!(oper.recv(&r1), Ok(1));
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper2);
+    /// assert_eq!(oper.recv(&r2), Ok(2));
+    /// ```
+    pub fn biased() -> Select<'a> {
+        Select {
+            biased: true,
+            ..Select::new()
         }
     }
 
@@ -620,7 +1132,7 @@ impl<'a> Select<'a> {
     pub fn send<T>(&mut self, s: &'a Sender<T>) -> usize {
         let i = self.next_index;
         let ptr = s as *const Sender<_> as *const u8;
-        self.handles.push((s, i, ptr));
+        self.handles.push((Handle::Borrowed(s), i, ptr));
         self.next_index += 1;
         i
     }
@@ -643,45 +1155,272 @@ impl<'a> Select<'a> {
     pub fn recv<T>(&mut self, r: &'a Receiver<T>) -> usize {
         let i = self.next_index;
         let ptr = r as *const Receiver<_> as *const u8;
-        self.handles.push((r, i, ptr));
+        self.handles.push((Handle::Borrowed(r), i, ptr));
         self.next_index += 1;
         i
     }
 
-    /// Removes a previously added operation.
+    /// Adds a send operation on a sender adapter, such as a [`ContramappedSender`].
     ///
-    /// This is useful when an operation is selected because the channel got disconnected and we
-    /// want to try again to select a different operation instead.
+    /// Returns the index of the added operation. The selected operation must be completed with
+    /// [`SelectedOperation::send_to`].
     ///
-    /// If new operations are added after removing some, the indices of removed operations will not
-    /// be reused.
+    /// # Examples
     ///
-    /// # Panics
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
     ///
-    /// An attempt to remove a non-existing or already removed operation will panic.
+    /// let (s, r) = unbounded::<String>();
+    /// let s = s.contramap(|n: i32| n.to_string());
+    ///
+    /// let mut sel = Select::new();
+    /// let index = sel.send_to(&s);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), index);
+    /// oper.send_to(&s, 7).unwrap();
+    /// assert_eq!(r.recv(), Ok("7".to_string()));
+    /// ```
+    ///
+    /// [`ContramappedSender`]: struct.ContramappedSender.html
+    /// [`SelectedOperation::send_to`]: struct.SelectedOperation.html#method.send_to
+    pub fn send_to<S: SendHandle>(&mut self, s: &'a S) -> usize {
+        let i = self.next_index;
+        let ptr = s.addr();
+        self.handles.push((Handle::Borrowed(s), i, ptr));
+        self.next_index += 1;
+        i
+    }
+
+    /// Adds a receive operation on a receiver adapter, such as a [`MappedReceiver`] or a
+    /// [`ReceiverSet`].
+    ///
+    /// Returns the index of the added operation. The selected operation must be completed with
+    /// [`SelectedOperation::recv_from`].
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::thread;
     /// use crossbeam_channel::{unbounded, Select};
     ///
-    /// let (s1, r1) = unbounded::<i32>();
-    /// let (_, r2) = unbounded::<i32>();
+    /// let (s, r) = unbounded::<i32>();
+    /// let r = r.map(|n| n * 2);
+    /// s.send(21).unwrap();
     ///
     /// let mut sel = Select::new();
-    /// let oper1 = sel.recv(&r1);
-    /// let oper2 = sel.recv(&r2);
+    /// let index = sel.recv_from(&r);
     ///
-    /// // Both operations are initially ready, so a random one will be executed.
     /// let oper = sel.select();
-    /// assert_eq!(oper.index(), oper2);
-    /// assert!(oper.recv(&r2).is_err());
-    /// sel.remove(oper2);
+    /// assert_eq!(oper.index(), index);
+    /// assert_eq!(oper.recv_from(&r), Ok(42));
+    /// ```
     ///
-    /// s1.send(10).unwrap();
+    /// [`MappedReceiver`]: struct.MappedReceiver.html
+    /// [`ReceiverSet`]: struct.ReceiverSet.html
+    /// [`SelectedOperation::recv_from`]: struct.SelectedOperation.html#method.recv_from
+    pub fn recv_from<R: RecvHandle>(&mut self, r: &'a R) -> usize {
+        let i = self.next_index;
+        let ptr = r.addr();
+        self.handles.push((Handle::Borrowed(r), i, ptr));
+        self.next_index += 1;
+        i
+    }
+
+    /// Adds a send operation on a sender that is moved into the `Select`.
     ///
-    /// let oper = sel.select();
+    /// Returns the index of the added operation. The selected operation must be completed with
+    /// [`SelectedOperation::send_any`].
+    ///
+    /// Since the sender is owned, it doesn't have to outlive the `Select`. A cloned [`Sender`]
+    /// can be added to a `Select<'static>`, which in turn can be stored and changed freely.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    ///
+    /// let mut sel: Select<'static> = Select::new();
+    /// let index = sel.send_owned(s.clone());
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), index);
+    /// oper.send_any(10).unwrap();
+    /// assert_eq!(r.recv(), Ok(10));
+    /// ```
+    ///
+    /// [`SelectedOperation::send_any`]: struct.SelectedOperation.html#method.send_any
+    /// [`Sender`]: struct.Sender.html
+    pub fn send_owned<S, T>(&mut self, s: S) -> usize
+    where
+        S: SendHandle<Msg = T, Sent = T> + Send + Sync + 'a,
+        T: 'static,
+    {
+        let ptr = s.addr();
+        self.add_owned(Arc::new(OwnedSend(s)), ptr)
+    }
+
+    /// Adds a receive operation on a receiver that is moved into the `Select`.
+    ///
+    /// Returns the index of the added operation. The selected operation must be completed with
+    /// [`SelectedOperation::recv_any`].
+    ///
+    /// Since the receiver is owned, it doesn't have to outlive the `Select`. A cloned [`Receiver`]
+    /// can be added to a `Select<'static>`, which in turn can be stored and changed freely.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    ///
+    /// let mut sel: Select<'static> = Select::new();
+    /// let index = sel.recv_owned(r.clone());
+    ///
+    /// s.send(10).unwrap();
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), index);
+    /// assert_eq!(oper.recv_any(), Ok(10));
+    /// ```
+    ///
+    /// [`SelectedOperation::recv_any`]: struct.SelectedOperation.html#method.recv_any
+    /// [`Receiver`]: struct.Receiver.html
+    pub fn recv_owned<R>(&mut self, r: R) -> usize
+    where
+        R: RecvHandle + Send + Sync + 'a,
+        R::Msg: 'static,
+    {
+        let ptr = r.addr();
+        self.add_owned(Arc::new(OwnedRecv(r)), ptr)
+    }
+
+    /// Adds a receive operation for every receiver in an iterator.
+    ///
+    /// Returns the range of indices of the added operations, which follow the order of the
+    /// iterator. The receivers are owned by the `Select`, as if added with [`recv_owned`], so
+    /// the iterator may yield either references or clones of receivers. The selected operation
+    /// must be completed with [`SelectedOperation::recv_any`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (senders, receivers): (Vec<_>, Vec<_>) = (0..4).map(|_| unbounded::<usize>()).unzip();
+    ///
+    /// let mut sel = Select::new();
+    /// let opers = sel.recv_all(receivers.iter());
+    /// assert_eq!(opers, 0..4);
+    ///
+    /// senders[2].send(2).unwrap();
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index() - opers.start, 2);
+    /// assert_eq!(oper.recv_any::<usize>(), Ok(2));
+    /// ```
+    ///
+    /// [`recv_owned`]: struct.Select.html#method.recv_owned
+    /// [`SelectedOperation::recv_any`]: struct.SelectedOperation.html#method.recv_any
+    pub fn recv_all<I>(&mut self, iter: I) -> Range<usize>
+    where
+        I: IntoIterator,
+        I::Item: RecvHandle + Send + Sync + 'a,
+        <I::Item as RecvHandle>::Msg: 'static,
+    {
+        let start = self.next_index;
+        for r in iter {
+            self.recv_owned(r);
+        }
+        start..self.next_index
+    }
+
+    /// Adds a conditional receive operation.
+    ///
+    /// Returns the index of the added operation. The selected operation must be completed with
+    /// [`SelectedOperation::recv_if`], which receives the message only if it satisfies a
+    /// predicate.
+    ///
+    /// The operation is ready when the receiver has a message or its channel is disconnected, but
+    /// selecting it doesn't receive the message. The message is not locked either, so other
+    /// receivers may take it before the operation is completed. A message that doesn't satisfy the
+    /// predicate stays at the front of the channel and keeps the operation ready, so the operation
+    /// is usually removed afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     s1.send(10).unwrap();
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     s2.send(20).unwrap();
+    /// });
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.peek(&r1);
+    /// sel.peek(&r2);
+    ///
+    /// let msg = loop {
+    ///     let oper = sel.select();
+    ///     let index = oper.index();
+    ///     let r = if index == oper1 { &r1 } else { &r2 };
+    ///     if let Ok(Some(msg)) = oper.recv_if(r, |x| *x > 15) {
+    ///         break msg;
+    ///     }
+    ///     // The message is not suitable, so stop watching this receiver.
+    ///     sel.remove(index);
+    /// };
+    ///
+    /// assert_eq!(msg, 20);
+    /// assert_eq!(r1.try_recv(), Ok(10));
+    /// ```
+    ///
+    /// [`SelectedOperation::recv_if`]: struct.SelectedOperation.html#method.recv_if
+    pub fn peek<T: Send + 'static>(&mut self, r: &'a Receiver<T>) -> usize {
+        self.add_owned(Arc::new(Peek(r)), ptr::null())
+    }
+
+    /// Removes a previously added operation.
+    ///
+    /// This is useful when an operation is selected because the channel got disconnected and we
+    /// want to try again to select a different operation instead.
+    ///
+    /// If new operations are added after removing some, the indices of removed operations will not
+    /// be reused.
+    ///
+    /// # Panics
+    ///
+    /// An attempt to remove a non-existing or already removed operation will panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded::<i32>();
+    /// let (_, r2) = unbounded::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // Both operations are initially ready, so a random one will be executed.
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper2);
+    /// assert!(oper.recv(&r2).is_err());
+    /// sel.remove(oper2);
+    ///
+    /// s1.send(10).unwrap();
+    ///
+    /// let oper = sel.select();
     /// assert_eq!(oper.index(), oper1);
     /// assert_eq!(oper.recv(&r1), Ok(10));
     /// ```
@@ -701,7 +1440,69 @@ impl<'a> Select<'a> {
             .expect("no operation with this index")
             .0;
 
-        self.handles.swap_remove(i);
+        // A biased `Select` depends on the order of operations, while others shuffle them anyway.
+        if self.biased {
+            self.handles.remove(i);
+        } else {
+            self.handles.swap_remove(i);
+        }
+    }
+
+    /// Sets the weight of a previously added operation.
+    ///
+    /// If multiple operations are ready at the same time, each one among them is chosen with
+    /// probability proportional to its weight. Every operation has a weight of 1 by default. An
+    /// operation with a weight of 0 is chosen only if none of the ready operations has a positive
+    /// weight.
+    ///
+    /// Weights have no effect on a `Select` created with [`biased`].
+    ///
+    /// # Panics
+    ///
+    /// An attempt to set the weight of a non-existing or removed operation will panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // When both operations are ready, the first one is chosen three times as often.
+    /// sel.set_weight(oper1, 3);
+    ///
+    /// // The second operation is only chosen if the first one is not ready.
+    /// sel.set_weight(oper2, 0);
+    /// s1.send(1).unwrap();
+    /// s2.send(2).unwrap();
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert_eq!(oper.recv(&r1), Ok(1));
+    /// ```
+    ///
+    /// [`biased`]: struct.Select.html#method.biased
+    pub fn set_weight(&mut self, index: usize, weight: u32) {
+        assert!(
+            index < self.next_index,
+            "index out of bounds; {} >= {}",
+            index,
+            self.next_index,
+        );
+        assert!(
+            self.handles.iter().any(|&(_, i, _)| i == index),
+            "no operation with this index"
+        );
+
+        if self.weights.len() <= index {
+            self.weights.resize(index + 1, 1);
+        }
+        self.weights[index] = weight;
     }
 
     /// Attempts to select one of the operations without blocking.
@@ -747,7 +1548,11 @@ impl<'a> Select<'a> {
     /// }
     /// ```
     pub fn try_select(&mut self) -> Result<SelectedOperation<'a>, TrySelectError> {
-        try_select(&mut self.handles)
+        let res = {
+            let (handles, policy) = self.parts();
+            try_select(handles, policy)
+        };
+        res.map(|oper| self.attach(oper))
     }
 
     /// Blocks until one of the operations becomes ready and selects it.
@@ -797,7 +1602,11 @@ impl<'a> Select<'a> {
     /// }
     /// ```
     pub fn select(&mut self) -> SelectedOperation<'a> {
-        select(&mut self.handles)
+        let oper = {
+            let (handles, policy) = self.parts();
+            select(handles, policy)
+        };
+        self.attach(oper)
     }
 
     /// Blocks for a limited time until one of the operations becomes ready and selects it.
@@ -850,7 +1659,66 @@ impl<'a> Select<'a> {
         &mut self,
         timeout: Duration,
     ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
-        select_timeout(&mut self.handles, timeout)
+        let res = {
+            let (handles, policy) = self.parts();
+            select_timeout(handles, timeout, policy)
+        };
+        res.map(|oper| self.attach(oper))
+    }
+
+    /// Blocks until a given deadline, or until one of the operations becomes ready and selects
+    /// it.
+    ///
+    /// If an operation becomes ready, it is selected and returned. If multiple operations are
+    /// ready at the same time, a random one among them is selected. If none of the operations
+    /// become ready before the deadline, an error is returned.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// [`SelectedOperation::send`]: struct.SelectedOperation.html#method.send
+    /// [`SelectedOperation::recv`]: struct.SelectedOperation.html#method.recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     for i in 0..3 {
+    ///         thread::sleep(Duration::from_millis(400));
+    ///         s.send(i).unwrap();
+    ///     }
+    /// });
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r);
+    ///
+    /// // Only the first two messages arrive within one second.
+    /// let deadline = Instant::now() + Duration::from_secs(1);
+    /// let mut received = Vec::new();
+    /// while let Ok(oper) = sel.select_deadline(deadline) {
+    ///     assert_eq!(oper.index(), oper1);
+    ///     received.push(oper.recv(&r).unwrap());
+    /// }
+    /// assert_eq!(received, [0, 1]);
+    /// ```
+    pub fn select_deadline(
+        &mut self,
+        deadline: Instant,
+    ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        let res = {
+            let (handles, policy) = self.parts();
+            select_deadline(handles, deadline, policy)
+        };
+        res.map(|oper| self.attach(oper))
     }
 
     /// Attempts to find a ready operation without blocking.
@@ -890,7 +1758,8 @@ impl<'a> Select<'a> {
     /// }
     /// ```
     pub fn try_ready(&mut self) -> Result<usize, TryReadyError> {
-        match run_ready(&mut self.handles, Timeout::Now) {
+        let (handles, policy) = self.parts();
+        match run_ready(handles, Timeout::Now, policy) {
             None => Err(TryReadyError),
             Some(index) => Ok(index),
         }
@@ -943,7 +1812,8 @@ impl<'a> Select<'a> {
             panic!("no operations have been added to `Select`");
         }
 
-        run_ready(&mut self.handles, Timeout::Never).unwrap()
+        let (handles, policy) = self.parts();
+        run_ready(handles, Timeout::Never, policy).unwrap()
     }
 
     /// Blocks for a limited time until one of the operations becomes ready.
@@ -987,13 +1857,90 @@ impl<'a> Select<'a> {
     /// }
     /// ```
     pub fn ready_timeout(&mut self, timeout: Duration) -> Result<usize, ReadyTimeoutError> {
-        let timeout = Timeout::At(Instant::now() + timeout);
+        self.ready_deadline(Instant::now() + timeout)
+    }
 
-        match run_ready(&mut self.handles, timeout) {
+    /// Blocks until a given deadline, or until one of the operations becomes ready.
+    ///
+    /// If an operation becomes ready, its index is returned. If multiple operations are ready at
+    /// the same time, a random one among them is chosen. If none of the operations become ready
+    /// before the deadline, an error is returned.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// Note that this method might return with success spuriously, so it's a good idea to double
+    /// check if the operation is really ready.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     s1.send(10).unwrap();
+    /// });
+    /// thread::spawn(move || s2.send(20).unwrap());
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // The second operation will be selected because it becomes ready first.
+    /// let deadline = Instant::now() + Duration::from_millis(500);
+    /// match sel.ready_deadline(deadline) {
+    ///     Err(_) => panic!("should not have timed out"),
+    ///     Ok(i) if i == oper1 => assert_eq!(r1.try_recv(), Ok(10)),
+    ///     Ok(i) if i == oper2 => assert_eq!(r2.try_recv(), Ok(20)),
+    ///     Ok(_) => unreachable!(),
+    /// }
+    /// ```
+    pub fn ready_deadline(&mut self, deadline: Instant) -> Result<usize, ReadyTimeoutError> {
+        let (handles, policy) = self.parts();
+        match run_ready(handles, Timeout::At(deadline), policy) {
             None => Err(ReadyTimeoutError),
             Some(index) => Ok(index),
         }
     }
+
+    /// Returns the list of operations along with the policy for choosing among them.
+    fn parts(&mut self) -> (&mut [(Handle<'a>, usize, *const u8)], Policy) {
+        let policy = if self.biased {
+            Policy::Biased
+        } else if self.weights.is_empty() {
+            Policy::Random
+        } else {
+            Policy::Weighted(&self.weights)
+        };
+        (&mut self.handles, policy)
+    }
+
+    /// Adds an operation on a handle owned by the `Select`.
+    fn add_owned(&mut self, owned: Arc<OwnedHandle + 'a>, ptr: *const u8) -> usize {
+        let i = self.next_index;
+        self.handles.push((Handle::Owned(owned), i, ptr));
+        self.next_index += 1;
+        i
+    }
+
+    /// Hands the owned handle of a selected operation over to the operation.
+    fn attach(&self, mut oper: SelectedOperation<'a>) -> SelectedOperation<'a> {
+        oper.owned = self
+            .handles
+            .iter()
+            .find(|entry| entry.1 == oper.index)
+            .and_then(|entry| match &entry.0 {
+                Handle::Borrowed(_) => None,
+                Handle::Owned(owned) => Some(owned.clone()),
+            });
+        oper
+    }
 }
 
 impl<'a> Clone for Select<'a> {
@@ -1001,6 +1948,8 @@ impl<'a> Clone for Select<'a> {
         Select {
             handles: self.handles.clone(),
             next_index: self.next_index,
+            biased: self.biased,
+            weights: self.weights.clone(),
         }
     }
 }
@@ -1039,6 +1988,9 @@ pub struct SelectedOperation<'a> {
     /// The address of the selected `Sender` or `Receiver`.
     ptr: *const u8,
 
+    /// The selected `Sender` or `Receiver`, if it is owned by the `Select`.
+    owned: Option<Arc<OwnedHandle + 'a>>,
+
     /// Indicates that `Sender`s and `Receiver`s are borrowed.
     _marker: PhantomData<&'a ()>,
 }
@@ -1145,6 +2097,189 @@ impl<'a> SelectedOperation<'a> {
         mem::forget(self);
         res.map_err(|_| RecvError)
     }
+
+    /// Completes the send operation on a sender adapter.
+    ///
+    /// The passed reference must be the same one that was used in [`Select::send_to`] when the
+    /// operation was added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect reference is passed.
+    ///
+    /// [`Select::send_to`]: struct.Select.html#method.send_to
+    pub fn send_to<S: SendHandle>(mut self, s: &S, msg: S::Msg) -> Result<(), SendError<S::Sent>> {
+        assert!(s.addr() == self.ptr, "passed a sender that wasn't selected",);
+        let res = unsafe { s.write(&mut self.token, msg) };
+        mem::forget(self);
+        res.map_err(SendError)
+    }
+
+    /// Completes the receive operation on a receiver adapter.
+    ///
+    /// The passed reference must be the same one that was used in [`Select::recv_from`] when the
+    /// operation was added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect reference is passed.
+    ///
+    /// [`Select::recv_from`]: struct.Select.html#method.recv_from
+    pub fn recv_from<R: RecvHandle>(mut self, r: &R) -> Result<R::Msg, RecvError> {
+        assert!(
+            r.addr() == self.ptr,
+            "passed a receiver that wasn't selected",
+        );
+        let res = unsafe { r.read(&mut self.token) };
+        mem::forget(self);
+        res.map_err(|_| RecvError)
+    }
+
+    /// Completes the conditional receive operation, receiving the message only if it satisfies
+    /// `pred`.
+    ///
+    /// The passed [`Receiver`] reference must be the same one that was used in [`Select::peek`]
+    /// when the operation was added.
+    ///
+    /// Returns `Ok(Some(msg))` if the message was received, or `Ok(None)` if `pred` returned
+    /// `false` and the message was left in the channel. If another receiver has taken the message
+    /// since the operation was selected, this fails with `Empty`, or with `Disconnected` if the
+    /// channel is also empty and disconnected by now.
+    ///
+    /// `pred` is called while other receivers wait for it, just like during
+    /// [`Receiver::try_recv_if`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operation was not added with [`Select::peek`], or if an incorrect
+    /// [`Receiver`] reference is passed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.peek(&r);
+    ///
+    /// assert_eq!(sel.select().recv_if(&r, |x| x % 2 == 0), Ok(None));
+    /// assert_eq!(sel.select().recv_if(&r, |x| x % 2 == 1), Ok(Some(1)));
+    /// assert_eq!(sel.select().recv_if(&r, |x| x % 2 == 0), Ok(Some(2)));
+    /// ```
+    ///
+    /// [`Receiver`]: struct.Receiver.html
+    /// [`Select::peek`]: struct.Select.html#method.peek
+    /// [`Receiver::try_recv_if`]: struct.Receiver.html#method.try_recv_if
+    pub fn recv_if<T, F>(mut self, r: &Receiver<T>, pred: F) -> Result<Option<T>, TryRecvError>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        let ptr = r as *const Receiver<T> as *const u8;
+        assert!(
+            self.owned.take().and_then(|owned| owned.peeked()) == Some(ptr),
+            "passed a receiver that wasn't selected",
+        );
+        let res = unsafe { channel::read_if(r, &mut self.token, pred) };
+        mem::forget(self);
+        res
+    }
+
+    /// Completes the send operation on a sender owned by the `Select`.
+    ///
+    /// The operation must have been added with [`Select::send_owned`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operation was not added with [`Select::send_owned`], or if the message
+    /// has a different type than the sender's messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, Select, SendError};
+    ///
+    /// let (s, r) = bounded::<i32>(0);
+    /// drop(r);
+    ///
+    /// let mut sel = Select::new();
+    /// sel.send_owned(s);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.send_any(10), Err(SendError(10)));
+    /// ```
+    ///
+    /// [`Select::send_owned`]: struct.Select.html#method.send_owned
+    pub fn send_any<T: 'static>(mut self, msg: T) -> Result<(), SendError<T>> {
+        let owned = self.take_owned(false, TypeId::of::<T>());
+        let mut slot = Some(msg);
+        let res =
+            unsafe { owned.complete(&mut self.token, &mut slot as *mut Option<T> as *mut u8) };
+        mem::forget(self);
+        if res {
+            Ok(())
+        } else {
+            Err(SendError(slot.unwrap()))
+        }
+    }
+
+    /// Completes the receive operation on a receiver owned by the `Select`.
+    ///
+    /// The operation must have been added with [`Select::recv_owned`] or [`Select::recv_all`].
+    /// Since the receiver is not passed, the type of messages cannot be inferred from it and may
+    /// need to be specified explicitly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operation was not added with [`Select::recv_owned`] or
+    /// [`Select::recv_all`], or if `T` is a different type than the receiver's messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded::<i32>();
+    /// let (s2, r2) = unbounded::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv_owned(r1);
+    /// sel.recv_owned(r2);
+    ///
+    /// s2.send(20).unwrap();
+    /// let oper = sel.select();
+    /// assert_eq!(oper.recv_any::<i32>(), Ok(20));
+    /// ```
+    ///
+    /// [`Select::recv_owned`]: struct.Select.html#method.recv_owned
+    /// [`Select::recv_all`]: struct.Select.html#method.recv_all
+    pub fn recv_any<T: 'static>(mut self) -> Result<T, RecvError> {
+        let owned = self.take_owned(true, TypeId::of::<T>());
+        let mut slot = None;
+        let res =
+            unsafe { owned.complete(&mut self.token, &mut slot as *mut Option<T> as *mut u8) };
+        mem::forget(self);
+        if res {
+            Ok(slot.unwrap())
+        } else {
+            Err(RecvError)
+        }
+    }
+
+    /// Takes the owned handle of the operation and checks that it matches the expected kind of
+    /// operation and type of messages.
+    fn take_owned(&mut self, is_recv: bool, msg_type: TypeId) -> Arc<OwnedHandle + 'a> {
+        let owned = match self.owned.take() {
+            Some(owned) if owned.is_recv() == is_recv => owned,
+            _ if is_recv => panic!("selected operation is not an owned receive operation"),
+            _ => panic!("selected operation is not an owned send operation"),
+        };
+        assert!(owned.msg_type() == msg_type, "mismatched type of messages");
+        owned
+    }
 }
 
 impl<'a> fmt::Debug for SelectedOperation<'a> {
@@ -33,12 +33,13 @@ macro_rules! crossbeam_channel_delegate {
 /// 2. Code generation
 ///
 /// The parsing stage consists of these subparts:
-/// 1. `@list`: Turns a list of tokens into a list of cases.
+/// 1. `@list`: Turns a list of tokens into a list of cases, preceded by `biased;` if requested.
 /// 2. `@list_errorN`: Diagnoses the syntax error.
 /// 3. `@case`: Parses a single case and verifies its argument list.
 ///
 /// The codegen stage consists of these subparts:
-/// 1. `@init`: Attempts to optimize `select!` away and initializes the list of handles.
+/// 1. `@init`: Attempts to optimize `select!` away, picks the selection policy, and initializes
+///    the list of handles.
 /// 1. `@count`: Counts the listed cases.
 /// 3. `@add`: Adds send/receive operations to the list of handles and starts selection.
 /// 4. `@complete`: Completes the selected send/receive operation.
@@ -48,6 +49,13 @@ macro_rules! crossbeam_channel_delegate {
 #[doc(hidden)]
 #[macro_export(local_inner_macros)]
 macro_rules! crossbeam_channel_internal {
+    // Print an error if `biased;` is followed by no cases.
+    (@list
+        ()
+        (biased;)
+    ) => {
+        crossbeam_channel_delegate!(compile_error("empty `select!` block"))
+    };
     // The list is empty. Now check the arguments of each processed case.
     (@list
         ()
@@ -60,6 +68,17 @@ macro_rules! crossbeam_channel_internal {
             ()
         )
     };
+    // Remember that the cases are selected in order if the list starts with `biased;`.
+    (@list
+        (biased; $($tail:tt)*)
+        ()
+    ) => {
+        crossbeam_channel_internal!(
+            @list
+            ($($tail)*)
+            (biased;)
+        )
+    };
     // If necessary, insert an empty argument list after `default`.
     (@list
         (default => $($tail:tt)*)
@@ -107,6 +126,24 @@ macro_rules! crossbeam_channel_internal {
             "expected `->` after `send` operation, found `=>`"
         ))
     };
+    // Print an error if there is a missing result in a recv_from case.
+    (@list
+        (recv_from($($args:tt)*) => $($tail:tt)*)
+        ($($head:tt)*)
+    ) => {
+        crossbeam_channel_delegate!(compile_error(
+            "expected `->` after `recv_from` case, found `=>`"
+        ))
+    };
+    // Print an error if there is a missing result in a send_to case.
+    (@list
+        (send_to($($args:tt)*) => $($tail:tt)*)
+        ($($head:tt)*)
+    ) => {
+        crossbeam_channel_delegate!(compile_error(
+            "expected `->` after `send_to` operation, found `=>`"
+        ))
+    };
     // Make sure the arrow and the result are not repeated.
     (@list
         ($case:ident $args:tt -> $res:tt -> $($tail:tt)*)
@@ -181,13 +218,19 @@ macro_rules! crossbeam_channel_internal {
     (@list_error1 send $($tail:tt)*) => {
         crossbeam_channel_internal!(@list_error2 send $($tail)*)
     };
+    (@list_error1 recv_from $($tail:tt)*) => {
+        crossbeam_channel_internal!(@list_error2 recv_from $($tail)*)
+    };
+    (@list_error1 send_to $($tail:tt)*) => {
+        crossbeam_channel_internal!(@list_error2 send_to $($tail)*)
+    };
     (@list_error1 default $($tail:tt)*) => {
         crossbeam_channel_internal!(@list_error2 default $($tail)*)
     };
     (@list_error1 $t:tt $($tail:tt)*) => {
         crossbeam_channel_delegate!(compile_error(
             crossbeam_channel_delegate!(concat(
-                "expected one of `recv`, `send`, or `default`, found `",
+                "expected one of `recv`, `send`, `recv_from`, `send_to`, or `default`, found `",
                 crossbeam_channel_delegate!(stringify($t)),
                 "`",
             ))
@@ -252,6 +295,16 @@ macro_rules! crossbeam_channel_internal {
             "expected an expression after `=>`"
         ))
     };
+    (@list_error3 $case:ident($($args:tt)*) $(-> $r:pat)* => recv_from($($a:tt)*) $($tail:tt)*) => {
+        crossbeam_channel_delegate!(compile_error(
+            "expected an expression after `=>`"
+        ))
+    };
+    (@list_error3 $case:ident($($args:tt)*) $(-> $r:pat)* => send_to($($a:tt)*) $($tail:tt)*) => {
+        crossbeam_channel_delegate!(compile_error(
+            "expected an expression after `=>`"
+        ))
+    };
     (@list_error3 $case:ident($($args:tt)*) $(-> $r:pat)* => default($($a:tt)*) $($tail:tt)*) => {
         crossbeam_channel_delegate!(compile_error(
             "expected an expression after `=>`"
@@ -349,6 +402,24 @@ macro_rules! crossbeam_channel_internal {
             ))
         ))
     };
+    (@list_error3 recv_from($($args:tt)*) $t:tt $($tail:tt)*) => {
+        crossbeam_channel_delegate!(compile_error(
+            crossbeam_channel_delegate!(concat(
+                "expected `->`, found `",
+                crossbeam_channel_delegate!(stringify($t)),
+                "`",
+            ))
+        ))
+    };
+    (@list_error3 send_to($($args:tt)*) $t:tt $($tail:tt)*) => {
+        crossbeam_channel_delegate!(compile_error(
+            crossbeam_channel_delegate!(concat(
+                "expected `->`, found `",
+                crossbeam_channel_delegate!(stringify($t)),
+                "`",
+            ))
+        ))
+    };
     (@list_error3 recv $args:tt $($tail:tt)*) => {
         crossbeam_channel_delegate!(compile_error(
             crossbeam_channel_delegate!(concat(
@@ -367,6 +438,24 @@ macro_rules! crossbeam_channel_internal {
             ))
         ))
     };
+    (@list_error3 recv_from $args:tt $($tail:tt)*) => {
+        crossbeam_channel_delegate!(compile_error(
+            crossbeam_channel_delegate!(concat(
+                "expected an argument list after `recv_from`, found `",
+                crossbeam_channel_delegate!(stringify($args)),
+                "`",
+            ))
+        ))
+    };
+    (@list_error3 send_to $args:tt $($tail:tt)*) => {
+        crossbeam_channel_delegate!(compile_error(
+            crossbeam_channel_delegate!(concat(
+                "expected an argument list after `send_to`, found `",
+                crossbeam_channel_delegate!(stringify($args)),
+                "`",
+            ))
+        ))
+    };
     (@list_error3 default $args:tt $($tail:tt)*) => {
         crossbeam_channel_delegate!(compile_error(
             crossbeam_channel_delegate!(concat(
@@ -384,6 +473,19 @@ macro_rules! crossbeam_channel_internal {
         crossbeam_channel_delegate!(compile_error("invalid syntax"))
     };
 
+    // Keep `biased;` in front of the checked cases.
+    (@case
+        (biased; $($tail:tt)*)
+        ()
+        $default:tt
+    ) => {
+        crossbeam_channel_internal!(
+            @case
+            ($($tail)*)
+            (biased;)
+            $default
+        )
+    };
     // Success! All cases were parsed.
     (@case
         ()
@@ -507,6 +609,116 @@ macro_rules! crossbeam_channel_internal {
         ))
     };
 
+    // Check the format of a recv_from case.
+    (@case
+        (recv_from($r:expr) -> $res:pat => $body:tt, $($tail:tt)*)
+        ($($cases:tt)*)
+        $default:tt
+    ) => {
+        crossbeam_channel_internal!(
+            @case
+            ($($tail)*)
+            ($($cases)* recv_from($r) -> $res => $body,)
+            $default
+        )
+    };
+    // Allow trailing comma...
+    (@case
+        (recv_from($r:expr,) -> $res:pat => $body:tt, $($tail:tt)*)
+        ($($cases:tt)*)
+        $default:tt
+    ) => {
+        crossbeam_channel_internal!(
+            @case
+            ($($tail)*)
+            ($($cases)* recv_from($r) -> $res => $body,)
+            $default
+        )
+    };
+    // Print an error if the argument list is invalid.
+    (@case
+        (recv_from($($args:tt)*) -> $res:pat => $body:tt, $($tail:tt)*)
+        ($($cases:tt)*)
+        $default:tt
+    ) => {
+        crossbeam_channel_delegate!(compile_error(
+            crossbeam_channel_delegate!(concat(
+                "invalid argument list in `recv_from(",
+                crossbeam_channel_delegate!(stringify($($args)*)),
+                ")`",
+            ))
+        ))
+    };
+    // Print an error if there is no argument list.
+    (@case
+        (recv_from $t:tt $($tail:tt)*)
+        ($($cases:tt)*)
+        $default:tt
+    ) => {
+        crossbeam_channel_delegate!(compile_error(
+            crossbeam_channel_delegate!(concat(
+                "expected an argument list after `recv_from`, found `",
+                crossbeam_channel_delegate!(stringify($t)),
+                "`",
+            ))
+        ))
+    };
+
+    // Check the format of a send_to case.
+    (@case
+        (send_to($s:expr, $m:expr) -> $res:pat => $body:tt, $($tail:tt)*)
+        ($($cases:tt)*)
+        $default:tt
+    ) => {
+        crossbeam_channel_internal!(
+            @case
+            ($($tail)*)
+            ($($cases)* send_to($s, $m) -> $res => $body,)
+            $default
+        )
+    };
+    // Allow trailing comma...
+    (@case
+        (send_to($s:expr, $m:expr,) -> $res:pat => $body:tt, $($tail:tt)*)
+        ($($cases:tt)*)
+        $default:tt
+    ) => {
+        crossbeam_channel_internal!(
+            @case
+            ($($tail)*)
+            ($($cases)* send_to($s, $m) -> $res => $body,)
+            $default
+        )
+    };
+    // Print an error if the argument list is invalid.
+    (@case
+        (send_to($($args:tt)*) -> $res:pat => $body:tt, $($tail:tt)*)
+        ($($cases:tt)*)
+        $default:tt
+    ) => {
+        crossbeam_channel_delegate!(compile_error(
+            crossbeam_channel_delegate!(concat(
+                "invalid argument list in `send_to(",
+                crossbeam_channel_delegate!(stringify($($args)*)),
+                ")`",
+            ))
+        ))
+    };
+    // Print an error if there is no argument list.
+    (@case
+        (send_to $t:tt $($tail:tt)*)
+        ($($cases:tt)*)
+        $default:tt
+    ) => {
+        crossbeam_channel_delegate!(compile_error(
+            crossbeam_channel_delegate!(concat(
+                "expected an argument list after `send_to`, found `",
+                crossbeam_channel_delegate!(stringify($t)),
+                "`",
+            ))
+        ))
+    };
+
     // Check the format of a default case.
     (@case
         (default() => $body:tt, $($tail:tt)*)
@@ -593,13 +805,37 @@ macro_rules! crossbeam_channel_internal {
     ) => {
         crossbeam_channel_delegate!(compile_error(
             crossbeam_channel_delegate!(concat(
-                "expected one of `recv`, `send`, or `default`, found `",
+                "expected one of `recv`, `send`, `recv_from`, `send_to`, or `default`, found `",
                 crossbeam_channel_delegate!(stringify($case)),
                 "`",
             ))
         ))
     };
 
+    // A single operation doesn't need a selection policy.
+    (@init
+        (biased; $case:ident $args:tt -> $res:pat => $body:tt,)
+        $default:tt
+    ) => {
+        crossbeam_channel_internal!(
+            @init
+            ($case $args -> $res => $body,)
+            $default
+        )
+    };
+    // Select the operations in the order they are listed.
+    (@init
+        (biased; $($cases:tt)*)
+        $default:tt
+    ) => {
+        crossbeam_channel_internal!(
+            @init
+            Biased
+            ($($cases)*)
+            $default
+        )
+    };
+
     // Optimize `select!` into `try_recv()`.
     (@init
         (recv($r:expr) -> $res:pat => $recv_body:tt,)
@@ -643,7 +879,8 @@ macro_rules! crossbeam_channel_internal {
         match $r {
             ref _r => {
                 let _r: &$crate::Receiver<_> = _r;
-                match _r.recv_timeout($timeout) {
+                let _deadline = $crate::internal::IntoDeadline::into_deadline($timeout);
+                match _r.recv_deadline(_deadline) {
                     ::std::result::Result::Err($crate::RecvTimeoutError::Timeout) => {
                         $default_body
                     }
@@ -755,8 +992,21 @@ macro_rules! crossbeam_channel_internal {
     //     }
     // }};
 
+    // Select a random operation among the ready ones.
+    (@init
+        ($($cases:tt)*)
+        $default:tt
+    ) => {
+        crossbeam_channel_internal!(
+            @init
+            Random
+            ($($cases)*)
+            $default
+        )
+    };
     // Create the list of handles and add operations to it.
     (@init
+        $policy:ident
         ($($cases:tt)*)
         $default:tt
     ) => {{
@@ -769,6 +1019,7 @@ macro_rules! crossbeam_channel_internal {
         crossbeam_channel_internal!(
             @add
             _sel
+            $policy
             ($($cases)*)
             $default
             (
@@ -820,13 +1071,14 @@ macro_rules! crossbeam_channel_internal {
     // Run blocking selection.
     (@add
         $sel:ident
+        $policy:ident
         ()
         ()
         $labels:tt
         $cases:tt
     ) => {{
         let _oper: $crate::SelectedOperation<'_> = {
-            let _oper = $crate::internal::select(&mut $sel);
+            let _oper = $crate::internal::select(&mut $sel, $crate::internal::Policy::$policy);
 
             // Erase the lifetime so that `sel` can be dropped early even without NLL.
             #[allow(unsafe_code)]
@@ -843,13 +1095,14 @@ macro_rules! crossbeam_channel_internal {
     // Run non-blocking selection.
     (@add
         $sel:ident
+        $policy:ident
         ()
         (default() => $body:tt,)
         $labels:tt
         $cases:tt
     ) => {{
         let _oper: ::std::option::Option<$crate::SelectedOperation<'_>> = {
-            let _oper = $crate::internal::try_select(&mut $sel);
+            let _oper = $crate::internal::try_select(&mut $sel, $crate::internal::Policy::$policy);
 
             // Erase the lifetime so that `sel` can be dropped early even without NLL.
             #[allow(unsafe_code)]
@@ -874,13 +1127,19 @@ macro_rules! crossbeam_channel_internal {
     // Run selection with a timeout.
     (@add
         $sel:ident
+        $policy:ident
         ()
         (default($timeout:expr) => $body:tt,)
         $labels:tt
         $cases:tt
     ) => {{
         let _oper: ::std::option::Option<$crate::SelectedOperation<'_>> = {
-            let _oper = $crate::internal::select_timeout(&mut $sel, $timeout);
+            let _deadline = $crate::internal::IntoDeadline::into_deadline($timeout);
+            let _oper = $crate::internal::select_deadline(
+                &mut $sel,
+                _deadline,
+                $crate::internal::Policy::$policy,
+            );
 
             // Erase the lifetime so that `sel` can be dropped early even without NLL.
             #[allow(unsafe_code)]
@@ -905,6 +1164,7 @@ macro_rules! crossbeam_channel_internal {
     // Have we used up all labels?
     (@add
         $sel:ident
+        $policy:ident
         $input:tt
         $default:tt
         ()
@@ -915,6 +1175,7 @@ macro_rules! crossbeam_channel_internal {
     // Add a receive operation to `sel`.
     (@add
         $sel:ident
+        $policy:ident
         (recv($r:expr) -> $res:pat => $body:tt, $($tail:tt)*)
         $default:tt
         (($i:tt $var:ident) $($labels:tt)*)
@@ -937,6 +1198,7 @@ macro_rules! crossbeam_channel_internal {
                 crossbeam_channel_internal!(
                     @add
                     $sel
+                    $policy
                     ($($tail)*)
                     $default
                     ($($labels)*)
@@ -948,6 +1210,7 @@ macro_rules! crossbeam_channel_internal {
     // Add a send operation to `sel`.
     (@add
         $sel:ident
+        $policy:ident
         (send($s:expr, $m:expr) -> $res:pat => $body:tt, $($tail:tt)*)
         $default:tt
         (($i:tt $var:ident) $($labels:tt)*)
@@ -970,6 +1233,7 @@ macro_rules! crossbeam_channel_internal {
                 crossbeam_channel_internal!(
                     @add
                     $sel
+                    $policy
                     ($($tail)*)
                     $default
                     ($($labels)*)
@@ -978,6 +1242,72 @@ macro_rules! crossbeam_channel_internal {
             }
         }
     }};
+    // Add a receive operation on an adapter to `sel`.
+    (@add
+        $sel:ident
+        $policy:ident
+        (recv_from($r:expr) -> $res:pat => $body:tt, $($tail:tt)*)
+        $default:tt
+        (($i:tt $var:ident) $($labels:tt)*)
+        ($($cases:tt)*)
+    ) => {{
+        match $r {
+            ref _r => {
+                #[allow(unsafe_code)]
+                let $var = unsafe {
+                    // Erase the lifetime so that `sel` can be dropped early even without NLL.
+                    unsafe fn unbind<'a, T>(x: &T) -> &'a T {
+                        ::std::mem::transmute(x)
+                    }
+                    unbind(_r)
+                };
+                $sel[$i] = ($var, $i, $crate::internal::SelectHandle::addr($var));
+
+                crossbeam_channel_internal!(
+                    @add
+                    $sel
+                    $policy
+                    ($($tail)*)
+                    $default
+                    ($($labels)*)
+                    ($($cases)* [$i] recv_from($var) -> $res => $body,)
+                )
+            }
+        }
+    }};
+    // Add a send operation on an adapter to `sel`.
+    (@add
+        $sel:ident
+        $policy:ident
+        (send_to($s:expr, $m:expr) -> $res:pat => $body:tt, $($tail:tt)*)
+        $default:tt
+        (($i:tt $var:ident) $($labels:tt)*)
+        ($($cases:tt)*)
+    ) => {{
+        match $s {
+            ref _s => {
+                #[allow(unsafe_code)]
+                let $var = unsafe {
+                    // Erase the lifetime so that `sel` can be dropped early even without NLL.
+                    unsafe fn unbind<'a, T>(x: &T) -> &'a T {
+                        ::std::mem::transmute(x)
+                    }
+                    unbind(_s)
+                };
+                $sel[$i] = ($var, $i, $crate::internal::SelectHandle::addr($var));
+
+                crossbeam_channel_internal!(
+                    @add
+                    $sel
+                    $policy
+                    ($($tail)*)
+                    $default
+                    ($($labels)*)
+                    ($($cases)* [$i] send_to($var, $m) -> $res => $body,)
+                )
+            }
+        }
+    }};
 
     // Complete a receive operation.
     (@complete
@@ -1021,6 +1351,48 @@ macro_rules! crossbeam_channel_internal {
             }
         }
     }};
+    // Complete a receive operation on an adapter.
+    (@complete
+        $sel:ident
+        $oper:ident
+        ([$i:tt] recv_from($r:ident) -> $res:pat => $body:tt, $($tail:tt)*)
+    ) => {{
+        if $oper.index() == $i {
+            let _res = $oper.recv_from($r);
+            { $sel };
+
+            let $res = _res;
+            $body
+        } else {
+            crossbeam_channel_internal! {
+                @complete
+                $sel
+                $oper
+                ($($tail)*)
+            }
+        }
+    }};
+    // Complete a send operation on an adapter.
+    (@complete
+        $sel:ident
+        $oper:ident
+        ([$i:tt] send_to($s:ident, $m:expr) -> $res:pat => $body:tt, $($tail:tt)*)
+    ) => {{
+        if $oper.index() == $i {
+            let _res = $oper.send_to($s, $m);
+            { $sel };
+
+            let $res = _res;
+            $body
+        } else {
+            crossbeam_channel_internal! {
+                @complete
+                $sel
+                $oper
+                ($($tail)*)
+            }
+        }
+    }};
     // Panic if we don't identify the selected case, but this should never happen.
     (@complete
         $sel:ident
@@ -1066,17 +1438,25 @@ macro_rules! crossbeam_channel_internal {
 ///
 /// This macro allows you to define a set of channel operations, wait until any one of them becomes
 /// ready, and finally execute it. If multiple operations are ready at the same time, a random one
-/// among them is selected.
+/// among them is selected, unless the block starts with `biased;`, in which case the one listed
+/// first is selected.
 ///
 /// It is also possible to define a `default` case that gets executed if none of the operations are
-/// ready, either right away or for a certain duration of time.
+/// ready, either right away, for a certain duration of time, or until a deadline. The timeout in
+/// `default(timeout)` can be either a `Duration` or an `Instant`.
 ///
 /// An operation is considered to be ready if it doesn't have to block. Note that it is ready even
 /// when it will simply return an error because the channel is disconnected.
 ///
+/// Operations on adapters such as [`MappedReceiver`], [`ReceiverSet`] and [`ContramappedSender`]
+/// are written as `recv_from(r) -> res => body` and `send_to(s, msg) -> res => body`.
+///
 /// The `select` macro is a convenience wrapper around [`Select`]. However, it cannot select over a
 /// dynamically created list of channel operations.
 ///
+/// [`MappedReceiver`]: struct.MappedReceiver.html
+/// [`ReceiverSet`]: struct.ReceiverSet.html
+/// [`ContramappedSender`]: struct.ContramappedSender.html
 /// [`Select`]: struct.Select.html
 ///
 /// # Examples
@@ -1167,6 +1547,60 @@ macro_rules! crossbeam_channel_internal {
 /// # }
 /// ```
 ///
+/// Select over a set of operations until a deadline:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::thread;
+/// use std::time::{Duration, Instant};
+/// use crossbeam_channel::unbounded;
+///
+/// let (s, r) = unbounded();
+///
+/// thread::spawn(move || {
+///     for i in 0..3 {
+///         thread::sleep(Duration::from_millis(400));
+///         s.send(i).unwrap();
+///     }
+/// });
+///
+/// // Only the first two messages arrive within one second.
+/// let deadline = Instant::now() + Duration::from_secs(1);
+/// let mut received = Vec::new();
+/// loop {
+///     select! {
+///         recv(r) -> msg => received.push(msg.unwrap()),
+///         default(deadline) => break,
+///     }
+/// }
+/// assert_eq!(received, [0, 1]);
+/// # }
+/// ```
+///
+/// Prefer one operation over another with `biased;`:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use crossbeam_channel::unbounded;
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+/// s1.send("urgent").unwrap();
+/// s2.send("normal").unwrap();
+///
+/// // Both operations are ready, but the first one is always executed.
+/// select! {
+///     biased;
+///     recv(r1) -> msg => assert_eq!(msg, Ok("urgent")),
+///     recv(r2) -> msg => panic!(),
+/// }
+/// # }
+/// ```
+///
 /// Optionally add a receive operation to `select!` using [`never`]:
 ///
 /// ```
@@ -0,0 +1,462 @@
+//! A long-lived selector that keeps its operations registered.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use channel::{Receiver, Sender};
+use context::Context;
+use err::{ReadyTimeoutError, TryReadyError};
+use select::{Operation, RecvHandle, SelectHandle, SendHandle, Timeout};
+use utils;
+
+/// An operation watched by a selector.
+struct Watched<'a> {
+    /// The sender or receiver.
+    handle: &'a SelectHandle,
+
+    /// The index of the operation.
+    index: usize,
+
+    /// A heap allocation whose address identifies the operation.
+    hook: Box<u8>,
+}
+
+/// Waits until any one of a fixed set of channel operations becomes ready.
+///
+/// A `Selector` is similar to calling [`Select::ready`] in a loop, except that its operations are
+/// registered with their channels only once, when they are added, and stay registered until they
+/// are removed or the selector is dropped. Waiting again is therefore cheap even with many
+/// operations, which makes a selector suitable for event loops that keep polling the same
+/// channels.
+///
+/// Like [`Select::ready`], [`wait`] only reports that an operation is ready and doesn't execute it.
+/// Another thread might make the operation not ready before we try executing it, so it's wise to
+/// use a retry loop. If multiple operations are ready at the same time, they are reported in turn
+/// so that a busy channel cannot starve the others.
+///
+/// Note that channels with registered operations do a little more work on every send or receive,
+/// so a selector that is no longer needed should be dropped.
+///
+/// A selector is bound to the thread that created it and cannot be sent to another thread.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{unbounded, Selector};
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+///
+/// thread::spawn(move || {
+///     for i in 0..5 {
+///         s1.send(i).unwrap();
+///         s2.send(i * 10).unwrap();
+///     }
+/// });
+///
+/// let mut sel = Selector::new();
+/// let oper1 = sel.recv(&r1);
+/// let oper2 = sel.recv(&r2);
+///
+/// let mut sum = 0;
+/// for _ in 0..10 {
+///     loop {
+///         // Wait until a receive operation becomes ready and try executing it.
+///         let res = match sel.wait() {
+///             i if i == oper1 => r1.try_recv(),
+///             i if i == oper2 => r2.try_recv(),
+///             _ => unreachable!(),
+///         };
+///
+///         // If the operation turns out not to be ready, retry.
+///         if let Ok(msg) = res {
+///             sum += msg;
+///             break;
+///         }
+///     }
+/// }
+///
+/// assert_eq!(sum, 110);
+/// ```
+///
+/// [`Select::ready`]: struct.Select.html#method.ready
+/// [`wait`]: struct.Selector.html#method.wait
+pub struct Selector<'a> {
+    /// The watched operations.
+    handles: Vec<Watched<'a>>,
+
+    /// The next index to assign to an operation.
+    next_index: usize,
+
+    /// The position in `handles` the next readiness check starts from.
+    next: usize,
+
+    /// The context the operations are registered with.
+    cx: Context,
+}
+
+impl<'a> Selector<'a> {
+    /// Creates an empty selector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::Selector;
+    ///
+    /// let mut sel = Selector::new();
+    ///
+    /// // The selector is empty, which means no operation can become ready.
+    /// assert!(sel.try_wait().is_err());
+    /// ```
+    pub fn new() -> Selector<'a> {
+        Selector {
+            handles: Vec::with_capacity(4),
+            next_index: 0,
+            next: 0,
+            cx: Context::persistent(),
+        }
+    }
+
+    /// Adds a send operation and registers it for readiness notification.
+    ///
+    /// Returns the index of the added operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, Selector};
+    ///
+    /// let (s, r) = bounded::<i32>(1);
+    ///
+    /// let mut sel = Selector::new();
+    /// let index = sel.send(&s);
+    /// assert_eq!(sel.wait(), index);
+    /// ```
+    pub fn send<T>(&mut self, s: &'a Sender<T>) -> usize {
+        self.add(s)
+    }
+
+    /// Adds a receive operation and registers it for readiness notification.
+    ///
+    /// Returns the index of the added operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Selector};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    ///
+    /// let mut sel = Selector::new();
+    /// let index = sel.recv(&r);
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(sel.wait(), index);
+    /// ```
+    pub fn recv<T>(&mut self, r: &'a Receiver<T>) -> usize {
+        self.add(r)
+    }
+
+    /// Adds a send operation on a sender adapter, such as a [`ContramappedSender`], and registers
+    /// it for readiness notification.
+    ///
+    /// Returns the index of the added operation.
+    ///
+    /// [`ContramappedSender`]: struct.ContramappedSender.html
+    pub fn send_to<S: SendHandle>(&mut self, s: &'a S) -> usize {
+        self.add(s)
+    }
+
+    /// Adds a receive operation on a receiver adapter, such as a [`MappedReceiver`] or a
+    /// [`ReceiverSet`], and registers it for readiness notification.
+    ///
+    /// Returns the index of the added operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Selector};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// let r = r.map(|n| n + 1);
+    ///
+    /// let mut sel = Selector::new();
+    /// let index = sel.recv_from(&r);
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(sel.wait(), index);
+    /// assert_eq!(r.try_recv(), Ok(2));
+    /// ```
+    ///
+    /// [`MappedReceiver`]: struct.MappedReceiver.html
+    /// [`ReceiverSet`]: struct.ReceiverSet.html
+    pub fn recv_from<R: RecvHandle>(&mut self, r: &'a R) -> usize {
+        self.add(r)
+    }
+
+    /// Removes a previously added operation and unregisters it.
+    ///
+    /// If new operations are added after removing some, the indices of removed operations will not
+    /// be reused.
+    ///
+    /// # Panics
+    ///
+    /// An attempt to remove a non-existing or already removed operation will panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Selector};
+    ///
+    /// let (s1, r1) = unbounded::<i32>();
+    /// let (_, r2) = unbounded::<i32>();
+    ///
+    /// let mut sel = Selector::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // The second channel is disconnected, so its operation is always ready.
+    /// assert_eq!(sel.wait(), oper2);
+    /// sel.remove(oper2);
+    ///
+    /// s1.send(10).unwrap();
+    /// assert_eq!(sel.wait(), oper1);
+    /// ```
+    pub fn remove(&mut self, index: usize) {
+        assert!(
+            index < self.next_index,
+            "index out of bounds; {} >= {}",
+            index,
+            self.next_index,
+        );
+
+        let i = self
+            .handles
+            .iter()
+            .position(|w| w.index == index)
+            .expect("no operation with this index");
+
+        let mut watched = self.handles.remove(i);
+        watched.handle.unwatch(Operation::hook(&mut *watched.hook));
+    }
+
+    /// Returns the index of a ready operation without blocking.
+    ///
+    /// If none of the operations are ready, an error is returned.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Selector};
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// let mut sel = Selector::new();
+    /// let oper = sel.recv(&r);
+    /// assert!(sel.try_wait().is_err());
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(sel.try_wait(), Ok(oper));
+    /// assert_eq!(r.try_recv(), Ok(1));
+    /// ```
+    pub fn try_wait(&mut self) -> Result<usize, TryReadyError> {
+        match self.run_wait(Timeout::Now) {
+            None => Err(TryReadyError),
+            Some(index) => Ok(index),
+        }
+    }
+
+    /// Blocks until one of the operations becomes ready and returns its index.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// Note that another thread might make the operation not ready before this method returns, so
+    /// it's a good idea to always double check if the operation is really ready.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no operations have been added to the selector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Selector};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     s1.send(10).unwrap();
+    /// });
+    /// thread::spawn(move || s2.send(20).unwrap());
+    ///
+    /// let mut sel = Selector::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // The second operation will be reported because it becomes ready first.
+    /// match sel.wait() {
+    ///     i if i == oper1 => assert_eq!(r1.try_recv(), Ok(10)),
+    ///     i if i == oper2 => assert_eq!(r2.try_recv(), Ok(20)),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn wait(&mut self) -> usize {
+        if self.handles.is_empty() {
+            panic!("no operations have been added to `Selector`");
+        }
+
+        self.run_wait(Timeout::Never).unwrap()
+    }
+
+    /// Blocks for a limited time until one of the operations becomes ready and returns its index.
+    ///
+    /// If none of the operations become ready for the specified duration, an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Selector};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    ///
+    /// let mut sel = Selector::new();
+    /// sel.recv(&r);
+    /// assert!(sel.wait_timeout(Duration::from_millis(100)).is_err());
+    /// ```
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Result<usize, ReadyTimeoutError> {
+        self.wait_deadline(Instant::now() + timeout)
+    }
+
+    /// Blocks until a given deadline, or until one of the operations becomes ready and returns
+    /// its index.
+    ///
+    /// If none of the operations become ready before the deadline, an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{after, Selector};
+    ///
+    /// let timeout = after(Duration::from_millis(100));
+    ///
+    /// let mut sel = Selector::new();
+    /// let oper = sel.recv(&timeout);
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(1);
+    /// assert_eq!(sel.wait_deadline(deadline), Ok(oper));
+    /// ```
+    pub fn wait_deadline(&mut self, deadline: Instant) -> Result<usize, ReadyTimeoutError> {
+        match self.run_wait(Timeout::At(deadline)) {
+            None => Err(ReadyTimeoutError),
+            Some(index) => Ok(index),
+        }
+    }
+
+    /// Adds an operation and registers it for readiness notification.
+    fn add(&mut self, handle: &'a SelectHandle) -> usize {
+        let index = self.next_index;
+        self.next_index += 1;
+
+        let mut hook = Box::new(0);
+        handle.watch(Operation::hook(&mut *hook), &self.cx);
+        self.handles.push(Watched {
+            handle,
+            index,
+            hook,
+        });
+        index
+    }
+
+    /// Returns the index of a ready operation, starting from where the previous check left off.
+    fn poll(&mut self) -> Option<usize> {
+        let len = self.handles.len();
+        for i in 0..len {
+            let pos = (self.next + i) % len;
+            if self.handles[pos].handle.is_ready() {
+                self.next = pos + 1;
+                return Some(self.handles[pos].index);
+            }
+        }
+        None
+    }
+
+    /// Runs until one of the operations becomes ready, potentially blocking the current thread.
+    fn run_wait(&mut self, timeout: Timeout) -> Option<usize> {
+        if self.handles.is_empty() {
+            // Wait until the timeout and return.
+            match timeout {
+                Timeout::Now => return None,
+                Timeout::Never => {
+                    utils::sleep_until(None);
+                    unreachable!();
+                }
+                Timeout::At(when) => {
+                    utils::sleep_until(Some(when));
+                    return None;
+                }
+            }
+        }
+
+        loop {
+            // Re-arm the context before checking operations for readiness so that a notification
+            // arriving in between is not missed.
+            self.cx.reset();
+
+            if let Some(index) = self.poll() {
+                return Some(index);
+            }
+
+            // Check with each operation for how long we're allowed to block, and compute the
+            // earliest deadline.
+            let mut deadline: Option<Instant> = match timeout {
+                Timeout::Now => return None,
+                Timeout::Never => None,
+                Timeout::At(when) => {
+                    if Instant::now() >= when {
+                        return None;
+                    }
+                    Some(when)
+                }
+            };
+            for watched in self.handles.iter() {
+                if let Some(x) = watched.handle.deadline() {
+                    deadline = deadline.map(|y| x.min(y)).or(Some(x));
+                }
+            }
+
+            // Block the current thread until one of the operations is notified.
+            self.cx.wait_until(deadline);
+        }
+    }
+}
+
+impl<'a> Default for Selector<'a> {
+    fn default() -> Selector<'a> {
+        Selector::new()
+    }
+}
+
+impl<'a> fmt::Debug for Selector<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Selector { .. }")
+    }
+}
+
+impl<'a> Drop for Selector<'a> {
+    fn drop(&mut self) {
+        for watched in self.handles.iter_mut() {
+            watched.handle.unwatch(Operation::hook(&mut *watched.hook));
+        }
+    }
+}
@@ -0,0 +1,388 @@
+//! A set of receivers that acts as a single receiver.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::ptr;
+use std::time::{Duration, Instant};
+
+use channel::{self, Receiver};
+use context::Context;
+use err::{RecvError, RecvTimeoutError, TryRecvError};
+use select::{Operation, RecvHandle, Select, SelectHandle, Token};
+
+/// A set of receivers that messages are received from as if it were a single receiver.
+///
+/// Receivers can be inserted and removed at any time. Each receive operation starts from the
+/// receiver after the one that delivered the previous message, so that a busy channel cannot
+/// starve the others.
+///
+/// A receiver is dropped from the set as soon as a receive operation finds its channel empty and
+/// disconnected. Once the set is empty, receive operations fail as if the channel were
+/// disconnected.
+///
+/// The set can be used in [`Select`] and [`select!`] like a single [`Receiver`] through
+/// `recv_from`, which is much cheaper than adding hundreds of receivers to a new [`Select`] before
+/// every operation.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{unbounded, ReceiverSet};
+///
+/// let mut set = ReceiverSet::new();
+///
+/// for i in 0..10 {
+///     let (s, r) = unbounded();
+///     set.insert(r);
+///
+///     thread::spawn(move || s.send(i).unwrap());
+/// }
+///
+/// // Receive a message from each thread.
+/// let mut sum = 0;
+/// while let Ok(msg) = set.recv() {
+///     sum += msg;
+/// }
+///
+/// assert_eq!(sum, 45);
+/// assert!(set.is_empty());
+/// ```
+///
+/// [`Select`]: struct.Select.html
+/// [`select!`]: macro.select.html
+/// [`Receiver`]: struct.Receiver.html
+pub struct ReceiverSet<T> {
+    /// The receivers in the set, along with their keys.
+    receivers: RefCell<Vec<(usize, Receiver<T>)>>,
+
+    /// The index of the receiver the next receive operation starts from.
+    next: Cell<usize>,
+
+    /// The key assigned to the next inserted receiver.
+    next_key: usize,
+
+    /// Operations watching the receivers for readiness.
+    watchers: RefCell<Vec<Operation>>,
+}
+
+impl<T> ReceiverSet<T> {
+    /// Creates an empty set of receivers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{ReceiverSet, TryRecvError};
+    ///
+    /// let set = ReceiverSet::<i32>::new();
+    /// assert_eq!(set.try_recv(), Err(TryRecvError::Disconnected));
+    /// ```
+    pub fn new() -> ReceiverSet<T> {
+        ReceiverSet {
+            receivers: RefCell::new(Vec::new()),
+            next: Cell::new(0),
+            next_key: 0,
+            watchers: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Inserts a receiver into the set and returns a key identifying it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, ReceiverSet};
+    ///
+    /// let (s, r) = unbounded();
+    /// let mut set = ReceiverSet::new();
+    /// let key = set.insert(r);
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(set.recv(), Ok(1));
+    /// assert!(set.contains(key));
+    /// ```
+    pub fn insert(&mut self, r: Receiver<T>) -> usize {
+        let key = self.next_key;
+        self.next_key += 1;
+        self.receivers.get_mut().push((key, r));
+        key
+    }
+
+    /// Removes a receiver from the set and returns it.
+    ///
+    /// Returns `None` if there is no receiver with the key, either because it was removed or
+    /// because it was dropped after its channel got disconnected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, ReceiverSet};
+    ///
+    /// let (s, r) = unbounded();
+    /// let mut set = ReceiverSet::new();
+    /// let key = set.insert(r);
+    ///
+    /// s.send(1).unwrap();
+    /// let r = set.remove(key).unwrap();
+    /// assert_eq!(r.recv(), Ok(1));
+    /// assert!(set.remove(key).is_none());
+    /// ```
+    pub fn remove(&mut self, key: usize) -> Option<Receiver<T>> {
+        let receivers = self.receivers.get_mut();
+        let index = receivers.iter().position(|&(k, _)| k == key)?;
+        Some(receivers.remove(index).1)
+    }
+
+    /// Returns `true` if the set contains a receiver with the key.
+    pub fn contains(&self, key: usize) -> bool {
+        self.receivers.borrow().iter().any(|&(k, _)| k == key)
+    }
+
+    /// Returns the number of receivers in the set.
+    pub fn len(&self) -> usize {
+        self.receivers.borrow().len()
+    }
+
+    /// Returns `true` if the set contains no receivers.
+    pub fn is_empty(&self) -> bool {
+        self.receivers.borrow().is_empty()
+    }
+
+    /// Attempts to receive a message from any receiver in the set without blocking.
+    ///
+    /// If all receivers are empty, this call fails with [`TryRecvError::Empty`]. If the set is
+    /// empty because all channels are disconnected, this call fails with
+    /// [`TryRecvError::Disconnected`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, ReceiverSet, TryRecvError};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// let mut set = ReceiverSet::new();
+    /// set.insert(r1);
+    /// set.insert(r2);
+    ///
+    /// assert_eq!(set.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// s2.send(2).unwrap();
+    /// assert_eq!(set.try_recv(), Ok(2));
+    ///
+    /// drop(s1);
+    /// drop(s2);
+    /// assert_eq!(set.try_recv(), Err(TryRecvError::Disconnected));
+    /// assert!(set.is_empty());
+    /// ```
+    ///
+    /// [`TryRecvError::Empty`]: enum.TryRecvError.html#variant.Empty
+    /// [`TryRecvError::Disconnected`]: enum.TryRecvError.html#variant.Disconnected
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let token = &mut Token::default();
+        if self.try_select(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Blocks the current thread until a message is received from any receiver in the set.
+    ///
+    /// If all channels are disconnected, or the set is empty, this call fails with [`RecvError`].
+    ///
+    /// [`RecvError`]: struct.RecvError.html
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let mut sel = Select::new();
+        sel.recv_from(self);
+        let oper = sel.select();
+        oper.recv_from(self)
+    }
+
+    /// Waits for a message to be received from any receiver in the set, but only for a limited
+    /// time.
+    ///
+    /// If all channels are disconnected, or the set is empty, this call fails with
+    /// [`RecvTimeoutError::Disconnected`].
+    ///
+    /// [`RecvTimeoutError::Disconnected`]: enum.RecvTimeoutError.html#variant.Disconnected
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_deadline(Instant::now() + timeout)
+    }
+
+    /// Waits for a message to be received from any receiver in the set, but only until a given
+    /// deadline.
+    ///
+    /// If all channels are disconnected, or the set is empty, this call fails with
+    /// [`RecvTimeoutError::Disconnected`].
+    ///
+    /// [`RecvTimeoutError::Disconnected`]: enum.RecvTimeoutError.html#variant.Disconnected
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        let mut sel = Select::new();
+        sel.recv_from(self);
+        match sel.select_deadline(deadline) {
+            Ok(oper) => oper
+                .recv_from(self)
+                .map_err(|_| RecvTimeoutError::Disconnected),
+            Err(_) => Err(RecvTimeoutError::Timeout),
+        }
+    }
+
+    /// Completes a selected receive operation on the receiver at `index`.
+    ///
+    /// Returns `true` and stores the message in the token on success. If the channel is
+    /// disconnected, the receiver is dropped from the set and `false` is returned.
+    unsafe fn complete(
+        &self,
+        receivers: &mut Vec<(usize, Receiver<T>)>,
+        index: usize,
+        token: &mut Token,
+    ) -> bool {
+        match channel::read(&receivers[index].1, token) {
+            Ok(msg) => {
+                token.boxed.msg = Box::into_raw(Box::new(msg)) as *mut u8;
+                self.next.set(index + 1);
+                true
+            }
+            Err(()) => {
+                // Operations may stay registered for readiness across receive operations, so
+                // unwatch the receiver before dropping it.
+                let (_, r) = receivers.remove(index);
+                for &oper in self.watchers.borrow().iter() {
+                    r.unwatch(oper);
+                }
+                false
+            }
+        }
+    }
+}
+
+impl<T> Default for ReceiverSet<T> {
+    fn default() -> ReceiverSet<T> {
+        ReceiverSet::new()
+    }
+}
+
+impl<T> fmt::Debug for ReceiverSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ReceiverSet { .. }")
+    }
+}
+
+impl<T> SelectHandle for ReceiverSet<T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        let mut receivers = self.receivers.borrow_mut();
+
+        // Try every receiver once, starting from where the previous operation left off.
+        let mut index = self.next.get();
+        let mut tried = 0;
+        while tried < receivers.len() {
+            if index >= receivers.len() {
+                index = 0;
+            }
+            if receivers[index].1.try_select(token) {
+                if unsafe { self.complete(&mut receivers, index, token) } {
+                    return true;
+                }
+                // The receiver was dropped, so `index` now refers to the next one.
+                continue;
+            }
+            index += 1;
+            tried += 1;
+        }
+
+        if receivers.is_empty() {
+            token.boxed.msg = ptr::null_mut();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.receivers
+            .borrow()
+            .iter()
+            .filter_map(|entry| entry.1.deadline())
+            .min()
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        let receivers = self.receivers.borrow();
+        let mut ready = receivers.is_empty();
+        for entry in receivers.iter() {
+            ready |= entry.1.register(oper, cx);
+        }
+        ready
+    }
+
+    fn unregister(&self, oper: Operation) {
+        for entry in self.receivers.borrow().iter() {
+            entry.1.unregister(oper);
+        }
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        // All receivers were registered with the same operation, so find the one that selected
+        // it. A zero-capacity channel provides a packet that must be received, while the other
+        // channels simply have a message ready.
+        let packet = cx.has_packet();
+        let mut receivers = self.receivers.borrow_mut();
+
+        let mut index = 0;
+        while index < receivers.len() {
+            if channel::expects_packet(&receivers[index].1) == packet
+                && receivers[index].1.accept(token, cx)
+            {
+                if unsafe { self.complete(&mut receivers, index, token) } {
+                    return true;
+                }
+                continue;
+            }
+            index += 1;
+        }
+
+        if receivers.is_empty() {
+            token.boxed.msg = ptr::null_mut();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        let receivers = self.receivers.borrow();
+        receivers.is_empty() || receivers.iter().any(|entry| entry.1.is_ready())
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.watchers.borrow_mut().push(oper);
+
+        let receivers = self.receivers.borrow();
+        let mut ready = receivers.is_empty();
+        for entry in receivers.iter() {
+            ready |= entry.1.watch(oper, cx);
+        }
+        ready
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.watchers.borrow_mut().retain(|&o| o != oper);
+
+        for entry in self.receivers.borrow().iter() {
+            entry.1.unwatch(oper);
+        }
+    }
+}
+
+impl<T> RecvHandle for ReceiverSet<T> {
+    type Msg = T;
+
+    unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if token.boxed.msg.is_null() {
+            Err(())
+        } else {
+            Ok(*Box::from_raw(token.boxed.msg as *mut T))
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! User-defined sources of messages that can be used in select.
+
+use std::fmt;
+use std::ptr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use context::Context;
+use err::{RecvError, RecvTimeoutError, TryRecvError};
+use select::{Operation, RecvHandle, Select, SelectHandle, Token};
+use waker::SyncWaker;
+
+/// A source of messages that can be received from alongside channels.
+///
+/// Implementing this trait makes any type that produces messages, such as a shutdown flag or a
+/// readiness bit set by an event loop, usable in [`Select`] and [`select!`] once it is wrapped in
+/// a [`SourceReceiver`].
+///
+/// A source keeps a [`Notifier`] and must call [`Notifier::notify`] after every change that may
+/// make it ready, including disconnection. Threads blocked on the source are woken up by the
+/// notification and then call [`try_recv`] again.
+///
+/// # Examples
+///
+/// A flag that can be raised once to shut down a worker:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+/// use std::thread;
+/// use crossbeam_channel::{unbounded, Notifier, Source, SourceReceiver, TryRecvError};
+///
+/// #[derive(Default)]
+/// struct Shutdown {
+///     flag: AtomicBool,
+///     notifier: Notifier,
+/// }
+///
+/// impl Shutdown {
+///     fn raise(&self) {
+///         self.flag.store(true, Ordering::SeqCst);
+///         self.notifier.notify();
+///     }
+/// }
+///
+/// impl Source for Shutdown {
+///     type Msg = ();
+///
+///     fn try_recv(&self) -> Result<(), TryRecvError> {
+///         if self.is_ready() {
+///             Ok(())
+///         } else {
+///             Err(TryRecvError::Empty)
+///         }
+///     }
+///
+///     fn is_ready(&self) -> bool {
+///         self.flag.load(Ordering::SeqCst)
+///     }
+///
+///     fn notifier(&self) -> &Notifier {
+///         &self.notifier
+///     }
+/// }
+///
+/// let shutdown = Arc::new(Shutdown::default());
+/// let (s, r) = unbounded::<i32>();
+///
+/// let worker = {
+///     let shutdown = SourceReceiver::new(shutdown.clone());
+///     thread::spawn(move || {
+///         let mut sum = 0;
+///         loop {
+///             select! {
+///                 recv(r) -> msg => sum += msg.unwrap(),
+///                 recv_from(shutdown) -> _ => return sum,
+///             }
+///         }
+///     })
+/// };
+///
+/// s.send(1).unwrap();
+/// shutdown.raise();
+/// assert!(worker.join().unwrap() <= 1);
+/// # }
+/// ```
+///
+/// [`Select`]: struct.Select.html
+/// [`select!`]: macro.select.html
+/// [`SourceReceiver`]: struct.SourceReceiver.html
+/// [`Notifier`]: struct.Notifier.html
+/// [`Notifier::notify`]: struct.Notifier.html#method.notify
+/// [`try_recv`]: trait.Source.html#tymethod.try_recv
+pub trait Source {
+    /// The type of received messages.
+    type Msg;
+
+    /// Attempts to receive a message without blocking.
+    ///
+    /// Returns [`TryRecvError::Empty`] if the source is not ready, and
+    /// [`TryRecvError::Disconnected`] if it will never produce messages again.
+    ///
+    /// [`TryRecvError::Empty`]: enum.TryRecvError.html#variant.Empty
+    /// [`TryRecvError::Disconnected`]: enum.TryRecvError.html#variant.Disconnected
+    fn try_recv(&self) -> Result<Self::Msg, TryRecvError>;
+
+    /// Returns `true` if [`try_recv`] would not return [`TryRecvError::Empty`].
+    ///
+    /// [`try_recv`]: trait.Source.html#tymethod.try_recv
+    /// [`TryRecvError::Empty`]: enum.TryRecvError.html#variant.Empty
+    fn is_ready(&self) -> bool;
+
+    /// Returns the notifier that wakes up threads waiting for the source.
+    fn notifier(&self) -> &Notifier;
+
+    /// Returns the instant at which the source becomes ready on its own, if there is one.
+    ///
+    /// Sources that depend on time rather than on notifications return the instant so that
+    /// blocked threads wake up in time to receive from them.
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+}
+
+impl<S: Source + ?Sized> Source for Arc<S> {
+    type Msg = S::Msg;
+
+    fn try_recv(&self) -> Result<S::Msg, TryRecvError> {
+        (**self).try_recv()
+    }
+
+    fn is_ready(&self) -> bool {
+        (**self).is_ready()
+    }
+
+    fn notifier(&self) -> &Notifier {
+        (**self).notifier()
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        (**self).deadline()
+    }
+}
+
+/// Wakes up threads waiting for a [`Source`].
+///
+/// [`Source`]: trait.Source.html
+pub struct Notifier {
+    /// Threads waiting for the source to become ready.
+    waker: SyncWaker,
+}
+
+impl Notifier {
+    /// Creates a new notifier.
+    pub fn new() -> Notifier {
+        Notifier {
+            waker: SyncWaker::new(),
+        }
+    }
+
+    /// Wakes up all threads waiting for the source, so that they check it again.
+    pub fn notify(&self) {
+        self.waker.notify_all();
+    }
+}
+
+impl Default for Notifier {
+    fn default() -> Notifier {
+        Notifier::new()
+    }
+}
+
+impl fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Notifier { .. }")
+    }
+}
+
+/// A receiver that receives messages from a user-defined [`Source`].
+///
+/// It can be used in [`Select`] and [`select!`] just like a [`Receiver`], through `recv_from`.
+///
+/// See [`Source`] for an example.
+///
+/// [`Source`]: trait.Source.html
+/// [`Select`]: struct.Select.html
+/// [`select!`]: macro.select.html
+/// [`Receiver`]: struct.Receiver.html
+pub struct SourceReceiver<S> {
+    /// The source messages are received from.
+    source: S,
+}
+
+impl<S: Source> SourceReceiver<S> {
+    /// Creates a receiver for a source.
+    pub fn new(source: S) -> SourceReceiver<S> {
+        SourceReceiver { source }
+    }
+
+    /// Attempts to receive a message from the source without blocking.
+    pub fn try_recv(&self) -> Result<S::Msg, TryRecvError> {
+        let token = &mut Token::default();
+        if self.try_select(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Blocks the current thread until a message is received from the source.
+    ///
+    /// If the source is disconnected, this call fails with [`RecvError`].
+    ///
+    /// [`RecvError`]: struct.RecvError.html
+    pub fn recv(&self) -> Result<S::Msg, RecvError> {
+        let mut sel = Select::new();
+        sel.recv_from(self);
+        let oper = sel.select();
+        oper.recv_from(self)
+    }
+
+    /// Waits for a message to be received from the source, but only for a limited time.
+    ///
+    /// If the source is disconnected, this call fails with [`RecvTimeoutError::Disconnected`].
+    ///
+    /// [`RecvTimeoutError::Disconnected`]: enum.RecvTimeoutError.html#variant.Disconnected
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<S::Msg, RecvTimeoutError> {
+        self.recv_deadline(Instant::now() + timeout)
+    }
+
+    /// Waits for a message to be received from the source, but only until a given deadline.
+    ///
+    /// If the source is disconnected, this call fails with [`RecvTimeoutError::Disconnected`].
+    ///
+    /// [`RecvTimeoutError::Disconnected`]: enum.RecvTimeoutError.html#variant.Disconnected
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<S::Msg, RecvTimeoutError> {
+        let mut sel = Select::new();
+        sel.recv_from(self);
+        match sel.select_deadline(deadline) {
+            Ok(oper) => oper
+                .recv_from(self)
+                .map_err(|_| RecvTimeoutError::Disconnected),
+            Err(_) => Err(RecvTimeoutError::Timeout),
+        }
+    }
+
+    /// Returns a reference to the source.
+    pub fn get_ref(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns the source.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<S> fmt::Debug for SourceReceiver<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("SourceReceiver { .. }")
+    }
+}
+
+impl<S: Source> SelectHandle for SourceReceiver<S> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        match self.source.try_recv() {
+            Ok(msg) => {
+                token.boxed.msg = Box::into_raw(Box::new(msg)) as *mut u8;
+                true
+            }
+            Err(TryRecvError::Disconnected) => {
+                token.boxed.msg = ptr::null_mut();
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.source.deadline()
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        cx.untrack();
+        self.source.notifier().waker.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.source.notifier().waker.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.source.is_ready()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        cx.untrack();
+        self.source.notifier().waker.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.source.notifier().waker.unwatch(oper);
+    }
+}
+
+impl<S: Source> RecvHandle for SourceReceiver<S> {
+    type Msg = S::Msg;
+
+    unsafe fn read(&self, token: &mut Token) -> Result<S::Msg, ()> {
+        if token.boxed.msg.is_null() {
+            Err(())
+        } else {
+            Ok(*Box::from_raw(token.boxed.msg as *mut S::Msg))
+        }
+    }
+}
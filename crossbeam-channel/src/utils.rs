@@ -1,14 +1,40 @@
 //! Miscellaneous utilities.
 
+#[cfg(debug_assertions)]
+use std::cell::RefCell;
 use std::cell::{Cell, UnsafeCell};
 use std::num::Wrapping;
 use std::ops::{Deref, DerefMut};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use crossbeam_utils::Backoff;
 
+thread_local! {
+    /// The state of the random number generator used for shuffling.
+    static RNG: Cell<Wrapping<u32>> = Cell::new(Wrapping(1406868647));
+}
+
+#[cfg(debug_assertions)]
+thread_local! {
+    /// Addresses of the queue heads the current thread has locked to inspect a message.
+    static LOCKED_HEADS: RefCell<Vec<usize>> = RefCell::new(Vec::new());
+}
+
+/// Returns the next random number from the generator.
+fn next_random(rng: &Cell<Wrapping<u32>>) -> u32 {
+    // This is the 32-bit variant of Xorshift.
+    //
+    // Source: https://en.wikipedia.org/wiki/Xorshift
+    let mut x = rng.get();
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    rng.set(x);
+    x.0
+}
+
 /// Randomly shuffles a slice.
 pub fn shuffle<T>(v: &mut [T]) {
     let len = v.len();
@@ -16,22 +42,9 @@ pub fn shuffle<T>(v: &mut [T]) {
         return;
     }
 
-    thread_local! {
-        static RNG: Cell<Wrapping<u32>> = Cell::new(Wrapping(1406868647));
-    }
-
     let _ = RNG.try_with(|rng| {
         for i in 1..len {
-            // This is the 32-bit variant of Xorshift.
-            //
-            // Source: https://en.wikipedia.org/wiki/Xorshift
-            let mut x = rng.get();
-            x ^= x << 13;
-            x ^= x >> 17;
-            x ^= x << 5;
-            rng.set(x);
-
-            let x = x.0;
+            let x = next_random(rng);
             let n = i + 1;
 
             // This is a fast alternative to `let j = x % n`.
@@ -45,6 +58,46 @@ pub fn shuffle<T>(v: &mut [T]) {
     });
 }
 
+/// Randomly shuffles a slice so that elements with greater weights tend to come first.
+///
+/// The first element is chosen with probability proportional to its weight, then the second one
+/// among the remaining elements, and so on. Elements with zero weight are shuffled to the end.
+pub fn shuffle_weighted<T, F>(v: &mut [T], weight: F)
+where
+    F: Fn(&T) -> u32,
+{
+    let mut total: u64 = v.iter().map(|x| u64::from(weight(x))).sum();
+
+    let _ = RNG.try_with(|rng| {
+        for i in 0..v.len() {
+            if total == 0 {
+                break;
+            }
+
+            // Pick a random point in the total weight of the remaining elements.
+            let x = next_random(rng);
+            let mut r = ((u128::from(x) * u128::from(total)) >> 32) as u64;
+
+            // Find the element the point belongs to and move it forward.
+            let mut j = i;
+            loop {
+                let w = u64::from(weight(&v[j]));
+                if r < w {
+                    total -= w;
+                    break;
+                }
+                r -= w;
+                j += 1;
+            }
+            v.swap(i, j);
+        }
+    });
+
+    // Shuffle the elements with zero weight.
+    let start = v.iter().position(|x| weight(x) == 0).unwrap_or(v.len());
+    shuffle(&mut v[start..]);
+}
+
 /// Sleeps until the deadline, or forever if the deadline isn't specified.
 pub fn sleep_until(deadline: Option<Instant>) {
     loop {
@@ -61,12 +114,57 @@ pub fn sleep_until(deadline: Option<Instant>) {
     }
 }
 
+/// Records that the current thread has locked or unlocked `head` to inspect a message.
+///
+/// Locked heads are only tracked in debug builds, where they are checked by `check_head_wait`.
+#[cfg(debug_assertions)]
+pub fn set_head_locked(head: &AtomicUsize, locked: bool) {
+    let addr = head as *const AtomicUsize as usize;
+    let _ = LOCKED_HEADS.try_with(|heads| {
+        let mut heads = heads.borrow_mut();
+        if locked {
+            heads.push(addr);
+        } else if let Some(i) = heads.iter().rposition(|&a| a == addr) {
+            heads.swap_remove(i);
+        }
+    });
+}
+
+/// Records that the current thread has locked or unlocked `head` to inspect a message.
+#[cfg(not(debug_assertions))]
+#[inline]
+pub fn set_head_locked(_head: &AtomicUsize, _locked: bool) {}
+
+/// Panics if the current thread is about to wait for `head` to be unlocked while holding it locked.
+///
+/// This happens when the closure inspecting a message receives from the same channel, which would
+/// otherwise deadlock. The check is only done in debug builds.
+#[cfg(debug_assertions)]
+pub fn check_head_wait(head: &AtomicUsize) {
+    let addr = head as *const AtomicUsize as usize;
+    let held = LOCKED_HEADS
+        .try_with(|heads| heads.borrow().contains(&addr))
+        .unwrap_or(false);
+    debug_assert!(
+        !held,
+        "receiving from a channel while inspecting one of its messages would deadlock"
+    );
+}
+
+/// Panics if the current thread is about to wait for `head` to be unlocked while holding it locked.
+#[cfg(not(debug_assertions))]
+#[inline]
+pub fn check_head_wait(_head: &AtomicUsize) {}
+
 /// A simple spinlock.
 pub struct Spinlock<T> {
     flag: AtomicBool,
     value: UnsafeCell<T>,
 }
 
+unsafe impl<T: Send> Send for Spinlock<T> {}
+unsafe impl<T: Send> Sync for Spinlock<T> {}
+
 impl<T> Spinlock<T> {
     /// Returns a new spinlock initialized with `value`.
     pub fn new(value: T) -> Spinlock<T> {
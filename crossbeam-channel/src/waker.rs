@@ -83,7 +83,7 @@ impl Waker {
 
             for i in 0..self.selectors.len() {
                 // Does the entry belong to a different thread?
-                if self.selectors[i].cx.thread_id() != thread_id {
+                if self.selectors[i].cx.thread_id() != Some(thread_id) {
                     // Try selecting this operation.
                     let sel = Selected::Operation(self.selectors[i].oper);
                     let res = self.selectors[i].cx.try_select(sel);
@@ -106,6 +106,37 @@ impl Waker {
         entry
     }
 
+    /// Selects the operations of all other threads and wakes them up.
+    #[inline]
+    pub fn try_select_all(&mut self) {
+        if !self.selectors.is_empty() {
+            let thread_id = current_thread_id();
+            let mut i = 0;
+
+            while i < self.selectors.len() {
+                // Does the entry belong to a different thread?
+                if self.selectors[i].cx.thread_id() != Some(thread_id) {
+                    // Try selecting this operation.
+                    let sel = Selected::Operation(self.selectors[i].oper);
+                    let res = self.selectors[i].cx.try_select(sel);
+
+                    if res.is_ok() {
+                        // Provide the packet.
+                        self.selectors[i].cx.store_packet(self.selectors[i].packet);
+                        // Wake the thread up.
+                        self.selectors[i].cx.unpark();
+
+                        // Remove the entry from the queue.
+                        self.selectors.remove(i);
+                        continue;
+                    }
+                }
+
+                i += 1;
+            }
+        }
+    }
+
     /// Returns `true` if there is an entry which can be selected by the current thread.
     #[inline]
     pub fn can_select(&self) -> bool {
@@ -115,7 +146,7 @@ impl Waker {
             let thread_id = current_thread_id();
 
             self.selectors.iter().any(|entry| {
-                entry.cx.thread_id() != thread_id && entry.cx.selected() == Selected::Waiting
+                entry.cx.thread_id() != Some(thread_id) && entry.cx.selected() == Selected::Waiting
             })
         }
     }
@@ -139,11 +170,14 @@ impl Waker {
     /// Notifies all operations waiting to be ready.
     #[inline]
     pub fn notify(&mut self) {
-        for entry in self.observers.drain(..) {
+        self.observers.retain(|entry| {
             if entry.cx.try_select(Selected::Operation(entry.oper)).is_ok() {
                 entry.cx.unpark();
             }
-        }
+
+            // Persistent operations stay registered until they are unwatched.
+            entry.cx.is_persistent()
+        });
     }
 
     /// Notifies all registered operations that the channel is disconnected.
@@ -162,6 +196,22 @@ impl Waker {
 
         self.notify();
     }
+
+    /// Returns the number of registered operations.
+    #[inline]
+    pub fn waiting(&self) -> usize {
+        self.selectors.len() + self.observers.len()
+    }
+
+    /// Returns the contexts of registered operations.
+    #[cfg(feature = "debug")]
+    pub fn contexts(&self) -> Vec<Context> {
+        self.selectors
+            .iter()
+            .chain(self.observers.iter())
+            .map(|entry| entry.cx.clone())
+            .collect()
+    }
 }
 
 impl Drop for Waker {
@@ -230,6 +280,39 @@ impl SyncWaker {
         }
     }
 
+    /// Attempts to find up to `n` threads (not the current one), select their operations, and
+    /// wake them up.
+    #[inline]
+    pub fn notify_many(&self, n: usize) {
+        if !self.is_empty.load(Ordering::SeqCst) {
+            let mut inner = self.inner.lock();
+            for _ in 0..n {
+                if inner.try_select().is_none() {
+                    break;
+                }
+            }
+            inner.notify();
+            self.is_empty.store(
+                inner.selectors.is_empty() && inner.observers.is_empty(),
+                Ordering::SeqCst,
+            );
+        }
+    }
+
+    /// Selects the operations of all threads (except the current one) and wakes them up.
+    #[inline]
+    pub fn notify_all(&self) {
+        if !self.is_empty.load(Ordering::SeqCst) {
+            let mut inner = self.inner.lock();
+            inner.try_select_all();
+            inner.notify();
+            self.is_empty.store(
+                inner.selectors.is_empty() && inner.observers.is_empty(),
+                Ordering::SeqCst,
+            );
+        }
+    }
+
     /// Registers an operation waiting to be ready.
     #[inline]
     pub fn watch(&self, oper: Operation, cx: &Context) {
@@ -252,6 +335,12 @@ impl SyncWaker {
         );
     }
 
+    /// Returns `true` if no operations are registered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.is_empty.load(Ordering::SeqCst)
+    }
+
     /// Notifies all threads that the channel is disconnected.
     #[inline]
     pub fn disconnect(&self) {
@@ -262,6 +351,18 @@ impl SyncWaker {
             Ordering::SeqCst,
         );
     }
+
+    /// Returns the number of registered operations.
+    #[inline]
+    pub fn waiting(&self) -> usize {
+        self.inner.lock().waiting()
+    }
+
+    /// Returns the contexts of registered operations.
+    #[cfg(feature = "debug")]
+    pub fn contexts(&self) -> Vec<Context> {
+        self.inner.lock().contexts()
+    }
 }
 
 impl Drop for SyncWaker {
@@ -273,7 +374,7 @@ impl Drop for SyncWaker {
 
 /// Returns the id of the current thread.
 #[inline]
-fn current_thread_id() -> ThreadId {
+pub fn current_thread_id() -> ThreadId {
     thread_local! {
         /// Cached thread-local id.
         static THREAD_ID: ThreadId = thread::current().id();
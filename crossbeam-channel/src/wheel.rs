@@ -0,0 +1,543 @@
+//! A hierarchical timer wheel shared by timer channels and blocking operations with a timeout.
+//!
+//! Time is divided into ticks of one millisecond. The wheel has six levels of 64 slots each: a slot
+//! on the lowest level spans a single tick, and a slot on every other level spans all slots of the
+//! level below it. A timer is put into the lowest level on which its expiration falls into a
+//! different slot than the current tick, and cascades down to lower levels as time goes by. Ticks
+//! only decide which slot a timer goes into, and a timer fires at its exact deadline.
+//!
+//! A single background thread owns and drives the wheel. Scheduling a timer pushes it onto a
+//! lock-free stack that the thread drains, and cancelling a timer only takes its callback out, so
+//! both take constant time and never wait for the thread. Once cancelled timers make up half of the
+//! wheel, the thread is woken up to take them out of their slots.
+//!
+//! A panicking callback doesn't affect other timers. If the thread dies anyway, its timers are
+//! handed over to a new thread, and if no thread can be spawned, timers fire right away rather
+//! than never.
+
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use utils::Spinlock;
+use waker::SyncWaker;
+
+/// Number of levels in the wheel.
+const LEVELS: usize = 6;
+
+/// Number of bits needed to index a slot within a level.
+const SLOT_BITS: usize = 6;
+
+/// Number of slots in each level.
+const SLOTS: usize = 1 << SLOT_BITS;
+
+/// Number of ticks covered by the whole wheel.
+const MAX_TICKS: u64 = 1 << (SLOT_BITS * LEVELS);
+
+/// Number of cancelled timers below which the wheel is never compacted.
+const COMPACT_THRESHOLD: usize = 64;
+
+/// A callback invoked when a timer expires.
+pub type Callback = Box<Fn() + Send>;
+
+/// A scheduled timer.
+struct Timer {
+    /// The instant at which the timer expires.
+    deadline: Instant,
+
+    /// The callback, or `None` once the timer has fired or been cancelled.
+    callback: Spinlock<Option<Callback>>,
+}
+
+impl Timer {
+    /// Returns `true` if the timer has neither fired nor been cancelled.
+    fn is_pending(&self) -> bool {
+        self.callback.lock().is_some()
+    }
+
+    /// Invokes the callback, unless the timer has already fired or been cancelled.
+    fn fire(&self) {
+        let callback = self.callback.lock().take();
+        if let Some(callback) = callback {
+            // A panicking callback must not take other timers down with it.
+            let _ = panic::catch_unwind(AssertUnwindSafe(move || (*callback)()));
+        }
+    }
+}
+
+/// Identifies a scheduled timer.
+#[derive(Clone)]
+pub struct Key(Arc<Timer>);
+
+impl Key {
+    /// Returns `true` if the timer has neither fired nor been cancelled.
+    pub fn is_pending(&self) -> bool {
+        self.0.is_pending()
+    }
+}
+
+/// A timer waiting to be put into the wheel.
+struct Node {
+    /// The timer.
+    timer: Arc<Timer>,
+
+    /// The next node in the stack.
+    next: *mut Node,
+}
+
+/// The state of the wheel, owned by the driver thread.
+struct Inner {
+    /// Timers in each slot of each level.
+    slots: Vec<Vec<Arc<Timer>>>,
+
+    /// A bit for each slot of each level, set if the slot is occupied.
+    occupied: [u64; LEVELS],
+
+    /// Timers expiring during ticks that have already been processed.
+    due: Vec<Arc<Timer>>,
+
+    /// The instant at which tick zero begins.
+    start: Instant,
+
+    /// The last tick that has been processed.
+    elapsed: u64,
+
+    /// Number of timers in the slots and in `due`.
+    len: usize,
+}
+
+/// The part of the wheel shared by all threads.
+struct Wheel {
+    /// Timers scheduled since the driver thread last looked, as a lock-free stack.
+    pending: AtomicPtr<Node>,
+
+    /// The driver thread, or `None` if it isn't running.
+    driver: Spinlock<Option<Thread>>,
+
+    /// Number of timers in the wheel, as last seen by the driver thread.
+    len: AtomicUsize,
+
+    /// Number of timers cancelled since the wheel was last compacted.
+    cancelled: AtomicUsize,
+}
+
+impl Inner {
+    /// Creates an empty wheel.
+    fn new() -> Inner {
+        Inner {
+            slots: (0..LEVELS * SLOTS).map(|_| Vec::new()).collect(),
+            occupied: [0; LEVELS],
+            due: Vec::new(),
+            start: Instant::now(),
+            elapsed: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the tick `instant` falls into.
+    fn tick(&self, instant: Instant) -> u64 {
+        if instant <= self.start {
+            0
+        } else {
+            // Ticks beyond the end of the wheel all go to the highest level anyway.
+            let tick = nanos(instant - self.start) / 1_000_000;
+            tick.min(self.elapsed + MAX_TICKS)
+        }
+    }
+
+    /// Returns the instant at which `tick` begins.
+    fn instant(&self, tick: u64) -> Instant {
+        self.start + Duration::new(tick / 1000, (tick % 1000) as u32 * 1_000_000)
+    }
+
+    /// Returns the slot, counting slots of all levels, a timer expiring at `tick` belongs to.
+    fn slot_for(&self, tick: u64) -> usize {
+        // Find the highest bit in which the tick differs from the current one. Timers that don't
+        // fit into the wheel go to the highest level and get rescheduled when their slot comes up.
+        let mut significant = (self.elapsed ^ tick) | (SLOTS as u64 - 1);
+        if significant >= MAX_TICKS {
+            significant = MAX_TICKS - 1;
+        }
+        let level = (63 - significant.leading_zeros() as usize) / SLOT_BITS;
+        let slot = (tick >> (level * SLOT_BITS)) as usize % SLOTS;
+        level * SLOTS + slot
+    }
+
+    /// Puts a timer into the slot it belongs to, or drops it if it has been cancelled.
+    fn insert(&mut self, timer: Arc<Timer>) {
+        if !timer.is_pending() {
+            return;
+        }
+
+        self.len += 1;
+        let tick = self.tick(timer.deadline);
+        if tick <= self.elapsed {
+            self.due.push(timer);
+        } else {
+            let slot = self.slot_for(tick);
+            self.slots[slot].push(timer);
+            self.occupied[slot / SLOTS] |= 1 << (slot % SLOTS);
+        }
+    }
+
+    /// Returns the next occupied slot and the tick at which it comes up.
+    fn next_expiration(&self) -> Option<(usize, u64)> {
+        // Timers on a lower level always expire before those on a higher level.
+        for level in 0..LEVELS {
+            let occupied = self.occupied[level];
+            if occupied == 0 {
+                continue;
+            }
+
+            let slot_range = 1u64 << (level * SLOT_BITS);
+            let level_range = slot_range << SLOT_BITS;
+            let current = (self.elapsed / slot_range) % SLOTS as u64;
+
+            // Find the first occupied slot starting from the current one.
+            let offset = u64::from(occupied.rotate_right(current as u32).trailing_zeros());
+            let slot = (current + offset) % SLOTS as u64;
+
+            let mut tick = (self.elapsed & !(level_range - 1)) + slot * slot_range;
+            if tick <= self.elapsed {
+                tick += level_range;
+            }
+            return Some((level * SLOTS + slot as usize, tick));
+        }
+        None
+    }
+
+    /// Processes all ticks up to the one `now` falls into, moving timers to lower levels.
+    fn advance(&mut self, now: Instant) {
+        let now = self.tick(now);
+
+        while let Some((slot, tick)) = self.next_expiration() {
+            if tick > now {
+                break;
+            }
+            self.elapsed = tick;
+
+            // Take all timers out of the slot, and put them back into the wheel one level lower.
+            let timers = mem::replace(&mut self.slots[slot], Vec::new());
+            self.occupied[slot / SLOTS] &= !(1 << (slot % SLOTS));
+            self.len -= timers.len();
+            for timer in timers {
+                self.insert(timer);
+            }
+        }
+
+        self.elapsed = self.elapsed.max(now);
+    }
+
+    /// Takes the timers that have expired by `now` out of the wheel.
+    fn expired(&mut self, now: Instant) -> Vec<Arc<Timer>> {
+        let before = self.due.len();
+        let (expired, due) = mem::replace(&mut self.due, Vec::new())
+            .into_iter()
+            .filter(|timer| timer.is_pending())
+            .partition(|timer| timer.deadline <= now);
+        self.due = due;
+        self.len = self.len - before + self.due.len();
+        expired
+    }
+
+    /// Takes the timers that have been cancelled out of the wheel.
+    fn compact(&mut self) {
+        self.due.retain(|timer| timer.is_pending());
+        let mut len = self.due.len();
+
+        for (slot, timers) in self.slots.iter_mut().enumerate() {
+            if timers.is_empty() {
+                continue;
+            }
+            timers.retain(|timer| timer.is_pending());
+            if timers.is_empty() {
+                // Give the memory back, since the slot might not be used again for a long time.
+                *timers = Vec::new();
+                self.occupied[slot / SLOTS] &= !(1 << (slot % SLOTS));
+            }
+            len += timers.len();
+        }
+
+        self.len = len;
+    }
+
+    /// Returns the instant the driver thread needs to wake up at, or `None` if there are no timers.
+    fn wakeup(&self) -> Option<Instant> {
+        let due = self.due.iter().map(|timer| timer.deadline).min();
+        let next = self.next_expiration().map(|(_, tick)| self.instant(tick));
+        match (due, next) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Takes all timers out of the wheel.
+    fn drain(&mut self) -> Vec<Arc<Timer>> {
+        let mut timers = mem::replace(&mut self.due, Vec::new());
+        for slot in &mut self.slots {
+            timers.append(slot);
+        }
+        self.occupied = [0; LEVELS];
+        self.len = 0;
+        timers
+    }
+}
+
+impl Wheel {
+    /// Pushes a timer onto the stack of pending timers.
+    ///
+    /// Returns `true` if the stack was empty.
+    fn push(&self, timer: Arc<Timer>) -> bool {
+        let node = Box::into_raw(Box::new(Node {
+            timer,
+            next: ptr::null_mut(),
+        }));
+
+        let mut head = self.pending.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*node).next = head };
+            match self.pending.compare_exchange_weak(
+                head,
+                node,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return head.is_null(),
+                Err(h) => head = h,
+            }
+        }
+    }
+
+    /// Takes all timers off the stack of pending timers.
+    fn take_pending(&self) -> Vec<Arc<Timer>> {
+        let mut timers = Vec::new();
+        let mut node = self.pending.swap(ptr::null_mut(), Ordering::Acquire);
+        while !node.is_null() {
+            let n = unsafe { Box::from_raw(node) };
+            node = n.next;
+            timers.push(n.timer);
+        }
+        timers
+    }
+
+    /// Makes sure the driver thread looks at the pending timers, starting it if needed.
+    fn wake(&'static self) {
+        {
+            let mut driver = self.driver.lock();
+            if let Some(thread) = &*driver {
+                thread.unpark();
+                return;
+            }
+            if self.pending.load(Ordering::Acquire).is_null() {
+                return;
+            }
+
+            let spawned = thread::Builder::new()
+                .name("crossbeam-channel-timer".to_string())
+                .spawn(move || self.run());
+            if let Ok(handle) = spawned {
+                *driver = Some(handle.thread().clone());
+                return;
+            }
+        }
+
+        // No thread can be spawned, so fire the timers right away instead of never.
+        for timer in self.take_pending() {
+            timer.fire();
+        }
+    }
+
+    /// Runs the driver thread.
+    fn run(&'static self) {
+        let mut driver = Driver {
+            wheel: self,
+            inner: Inner::new(),
+        };
+
+        loop {
+            for timer in self.take_pending() {
+                driver.inner.insert(timer);
+            }
+
+            // Timers cancelled from now on are counted towards the next compaction.
+            if self.cancelled.load(Ordering::Relaxed) >= COMPACT_THRESHOLD {
+                self.cancelled.store(0, Ordering::Relaxed);
+                driver.inner.compact();
+            }
+
+            let now = Instant::now();
+            driver.inner.advance(now);
+            for timer in driver.inner.expired(now) {
+                timer.fire();
+            }
+            self.len.store(driver.inner.len, Ordering::Relaxed);
+
+            // Sleep until the next timer expires. Scheduling a timer unparks the thread.
+            match driver.inner.wakeup() {
+                None => thread::park(),
+                Some(at) => {
+                    let now = Instant::now();
+                    if at > now {
+                        thread::park_timeout(at - now);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The driver thread's hold on the wheel.
+struct Driver {
+    /// The shared part of the wheel.
+    wheel: &'static Wheel,
+
+    /// The state of the wheel.
+    inner: Inner,
+}
+
+impl Drop for Driver {
+    fn drop(&mut self) {
+        // The driver thread is dying, so hand its timers over to a new one.
+        for timer in self.inner.drain() {
+            self.wheel.push(timer);
+        }
+        self.wheel.len.store(0, Ordering::Relaxed);
+        *self.wheel.driver.lock() = None;
+        self.wheel.wake();
+    }
+}
+
+/// Returns the number of nanoseconds in a duration, saturating at `u64::MAX`.
+fn nanos(dur: Duration) -> u64 {
+    dur.as_secs()
+        .saturating_mul(1_000_000_000)
+        .saturating_add(u64::from(dur.subsec_nanos()))
+}
+
+/// The global wheel.
+static WHEEL: AtomicPtr<Wheel> = AtomicPtr::new(ptr::null_mut());
+
+/// Returns the global wheel, creating it if needed.
+fn wheel() -> &'static Wheel {
+    let mut ptr = WHEEL.load(Ordering::Acquire);
+
+    if ptr.is_null() {
+        let wheel = Box::into_raw(Box::new(Wheel {
+            pending: AtomicPtr::new(ptr::null_mut()),
+            driver: Spinlock::new(None),
+            len: AtomicUsize::new(0),
+            cancelled: AtomicUsize::new(0),
+        }));
+
+        match WHEEL.compare_exchange(ptr, wheel, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => ptr = wheel,
+            Err(current) => {
+                // Another thread has created the wheel first.
+                drop(unsafe { Box::from_raw(wheel) });
+                ptr = current;
+            }
+        }
+    }
+
+    unsafe { &*ptr }
+}
+
+/// Schedules `callback` to be invoked on the driver thread once `deadline` is reached.
+pub fn schedule(deadline: Instant, callback: Callback) -> Key {
+    let timer = Arc::new(Timer {
+        deadline,
+        callback: Spinlock::new(Some(callback)),
+    });
+
+    // The driver thread drains the whole stack, so it only needs waking when the stack was empty.
+    let wheel = wheel();
+    if wheel.push(timer.clone()) {
+        wheel.wake();
+    }
+
+    Key(timer)
+}
+
+/// Cancels a scheduled timer.
+///
+/// Returns `false` if the timer has already fired or been cancelled.
+pub fn cancel(key: &Key) -> bool {
+    let callback = key.0.callback.lock().take();
+    if callback.is_none() {
+        return false;
+    }
+    drop(callback);
+
+    // Once cancelled timers make up half of the wheel, wake the driver thread to take them out.
+    let wheel = wheel();
+    let cancelled = wheel.cancelled.fetch_add(1, Ordering::Relaxed) + 1;
+    if cancelled >= COMPACT_THRESHOLD && cancelled >= wheel.len.load(Ordering::Relaxed) / 2 {
+        if let Some(thread) = &*wheel.driver.lock() {
+            thread.unpark();
+        }
+    }
+    true
+}
+
+/// Wakes up operations waiting on a timer channel once its next message is due.
+pub struct Alarm {
+    /// Operations waiting for the message.
+    waker: Arc<SyncWaker>,
+
+    /// The instant the alarm is set for, and its timer in the wheel.
+    armed: Spinlock<Option<(Instant, Key)>>,
+}
+
+impl Alarm {
+    /// Creates an alarm that is not set.
+    pub fn new() -> Alarm {
+        Alarm {
+            waker: Arc::new(SyncWaker::new()),
+            armed: Spinlock::new(None),
+        }
+    }
+
+    /// Returns the waker notified when the alarm goes off.
+    pub fn waker(&self) -> &SyncWaker {
+        &self.waker
+    }
+
+    /// Sets the alarm to go off at `at`, replacing the previous setting.
+    ///
+    /// Setting the alarm to the same instant again has no effect, unless it has gone off early
+    /// because the timer thread couldn't be started.
+    pub fn arm(&self, at: Instant) {
+        if let Some((t, key)) = &*self.armed.lock() {
+            if *t == at && (key.0.is_pending() || at <= Instant::now()) {
+                return;
+            }
+        }
+
+        // The timer might fire right away, so schedule it without holding the lock.
+        let waker = self.waker.clone();
+        let key = schedule(at, Box::new(move || waker.notify_all()));
+
+        let old = mem::replace(&mut *self.armed.lock(), Some((at, key)));
+        if let Some((_, key)) = old {
+            cancel(&key);
+        }
+    }
+
+    /// Unsets the alarm.
+    pub fn disarm(&self) {
+        let old = self.armed.lock().take();
+        if let Some((_, key)) = old {
+            cancel(&key);
+        }
+    }
+}
+
+impl Drop for Alarm {
+    fn drop(&mut self) {
+        self.disarm();
+    }
+}
@@ -0,0 +1,278 @@
+//! Tests for sender and receiver adapters.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, unbounded, Select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn map() {
+    let (s, r) = unbounded::<i32>();
+    let r = r.map(|n| n.to_string());
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    s.send(3).unwrap();
+    assert_eq!(r.try_recv(), Ok("1".to_string()));
+    assert_eq!(r.recv(), Ok("2".to_string()));
+    assert_eq!(r.recv_timeout(ms(100)), Ok("3".to_string()));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+
+    drop(s);
+    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn map_clone() {
+    let (s, r) = unbounded::<i32>();
+    let r1 = r.map(|n| n * 10);
+    let r2 = r1.clone();
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r1.recv(), Ok(10));
+    assert_eq!(r2.recv(), Ok(20));
+    assert!(r1.get_ref().same_channel(r2.get_ref()));
+    assert_eq!(format!("{:?}", r1), "MappedReceiver { .. }");
+}
+
+#[test]
+fn filter() {
+    let (s, r) = unbounded::<i32>();
+    let r = r.filter(|n| n % 3 == 0);
+
+    for i in 0..10 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.try_recv(), Ok(0));
+    assert_eq!(r.recv(), Ok(3));
+    assert_eq!(r.recv_timeout(ms(100)), Ok(6));
+    assert_eq!(r.try_recv(), Ok(9));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert!(r.get_ref().is_empty());
+
+    s.send(1).unwrap();
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+    assert!(r.get_ref().is_empty());
+
+    drop(s);
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn contramap() {
+    let (s, r) = bounded::<String>(1);
+    let s = s.contramap(|n: i32| n.to_string());
+
+    assert_eq!(s.try_send(1), Ok(()));
+    assert_eq!(s.try_send(2), Err(TrySendError::Full("2".to_string())));
+    assert_eq!(r.recv(), Ok("1".to_string()));
+    assert_eq!(s.send_timeout(3, ms(100)), Ok(()));
+    assert_eq!(r.recv(), Ok("3".to_string()));
+
+    drop(r);
+    assert_eq!(s.send(4), Err(SendError("4".to_string())));
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = unbounded::<&str>();
+    let (s2, r2) = unbounded::<i32>();
+    let (s3, r3) = bounded::<i32>(0);
+    let r1 = r1.map(|s| s.len());
+    let r2 = r2.filter(|n| *n > 0);
+    let s3 = s3.contramap(|n: usize| n as i32);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s2.send(-1).unwrap();
+            thread::sleep(ms(100));
+            s1.send("hello").unwrap();
+            thread::sleep(ms(100));
+            s2.send(1).unwrap();
+            thread::sleep(ms(100));
+            assert_eq!(r3.recv(), Ok(7));
+        });
+
+        let mut sel = Select::new();
+        let oper1 = sel.recv_from(&r1);
+        let oper2 = sel.recv_from(&r2);
+
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv_from(&r1), Ok(5));
+
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper2);
+        assert_eq!(oper.recv_from(&r2), Ok(1));
+
+        let mut sel = Select::new();
+        let oper3 = sel.send_to(&s3);
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper3);
+        assert_eq!(oper.send_to(&s3, 7), Ok(()));
+    })
+    .unwrap();
+}
+
+#[test]
+fn select_macro() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+    let r1 = r1.map(|n| n * 2);
+    let r2 = r2.filter(|n| *n > 0);
+
+    s1.send(1).unwrap();
+    select! {
+        recv_from(r1) -> msg => assert_eq!(msg, Ok(2)),
+        recv_from(r2) -> _ => panic!(),
+    }
+
+    s2.send(-1).unwrap();
+    select! {
+        recv_from(r1) -> _ => panic!(),
+        recv_from(r2) -> _ => panic!(),
+        default(ms(100)) => {}
+    }
+    assert!(r2.get_ref().is_empty());
+
+    s2.send(-2).unwrap();
+    select! {
+        recv_from(r2) -> _ => panic!(),
+        default => {}
+    }
+    s2.send(2).unwrap();
+    select! {
+        recv_from(&&r2) -> msg => assert_eq!(msg, Ok(2)),
+    }
+
+    drop(s1);
+    select! {
+        recv_from(r1) -> msg => assert_eq!(msg, Err(RecvError)),
+        recv_from(r2) -> _ => panic!(),
+    }
+}
+
+#[test]
+fn select_macro_send() {
+    let (s, r) = bounded::<String>(1);
+    let s = s.contramap(|n: i32| n.to_string());
+
+    select! {
+        send_to(s, 1) -> res => assert_eq!(res, Ok(())),
+        default => panic!(),
+    }
+    select! {
+        send_to(s, 2) -> _ => panic!(),
+        default => {}
+    }
+    assert_eq!(r.recv(), Ok("1".to_string()));
+
+    drop(r);
+    select! {
+        send_to(s, 3) -> res => assert_eq!(res, Err(SendError("3".to_string()))),
+    }
+}
+
+#[test]
+fn filter_zero_capacity() {
+    let (s, r) = bounded::<i32>(0);
+    let r = r.filter(|n| n % 2 == 0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..9 {
+                s.send(i).unwrap();
+            }
+        });
+
+        for i in 0..5 {
+            select! {
+                recv_from(r) -> msg => assert_eq!(msg, Ok(i * 2)),
+            }
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn filter_timeout() {
+    let (s, r) = unbounded::<i32>();
+    let r = r.filter(|n| *n > 0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for _ in 0..10 {
+                thread::sleep(ms(20));
+                s.send(0).unwrap();
+            }
+        });
+
+        let start = Instant::now();
+        select! {
+            recv_from(r) -> _ => panic!(),
+            default(ms(300)) => {}
+        }
+        assert!(start.elapsed() >= ms(300));
+    })
+    .unwrap();
+}
+
+#[test]
+fn stress() {
+    const COUNT: usize = 10_000;
+
+    let (s1, r1) = bounded::<usize>(5);
+    let (s2, r2) = unbounded::<usize>();
+    let s1 = s1.contramap(|n: usize| n * 2);
+    let r1 = r1.map(|n| n / 2);
+    let r2 = r2.filter(|n| n % 2 == 0);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                select! {
+                    send_to(s1, i) -> res => res.unwrap(),
+                }
+                s2.send(i).unwrap();
+            }
+        });
+
+        let mut sum1 = 0;
+        let mut sum2 = 0;
+        let mut received1 = 0;
+        while received1 < COUNT {
+            select! {
+                recv_from(r1) -> msg => {
+                    sum1 += msg.unwrap();
+                    received1 += 1;
+                }
+                recv_from(r2) -> msg => {
+                    if let Ok(msg) = msg {
+                        sum2 += msg;
+                    }
+                }
+            }
+        }
+        while let Ok(msg) = r2.recv() {
+            sum2 += msg;
+        }
+
+        assert_eq!(sum1, (0..COUNT).sum::<usize>());
+        assert_eq!(sum2, (0..COUNT).filter(|n| n % 2 == 0).sum::<usize>());
+    })
+    .unwrap();
+}
@@ -0,0 +1,538 @@
+//! Tests for batch send and receive operations.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate rand;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, broadcast, priority_unbounded, unbounded};
+use crossbeam_channel::{RecvError, SendError, TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+use rand::{thread_rng, Rng};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn array_smoke() {
+    let (s, r) = bounded(4);
+    let mut buf = Vec::new();
+
+    assert_eq!(s.try_send_batch(vec![1, 2, 3]), Ok(()));
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Ok(3));
+    assert_eq!(buf, [1, 2, 3]);
+
+    assert_eq!(s.send_batch(vec![4, 5]), Ok(()));
+    assert_eq!(r.recv_batch(&mut buf, 1), Ok(1));
+    assert_eq!(r.recv_batch(&mut buf, 1), Ok(1));
+    assert_eq!(buf, [1, 2, 3, 4, 5]);
+
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn list_smoke() {
+    let (s, r) = unbounded();
+    let mut buf = Vec::new();
+
+    assert_eq!(s.try_send_batch(vec![1, 2, 3]), Ok(()));
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Ok(3));
+    assert_eq!(buf, [1, 2, 3]);
+
+    assert_eq!(s.send_batch(vec![4, 5]), Ok(()));
+    assert_eq!(r.recv_batch(&mut buf, 1), Ok(1));
+    assert_eq!(r.recv_batch(&mut buf, 1), Ok(1));
+    assert_eq!(buf, [1, 2, 3, 4, 5]);
+
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn priority_smoke() {
+    let (s, r) = priority_unbounded();
+    let mut buf = Vec::new();
+
+    assert_eq!(s.try_send_batch(vec![1, 2, 3]), Ok(()));
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Ok(3));
+    assert_eq!(buf, [1, 2, 3]);
+
+    assert_eq!(s.send_batch(vec![4, 5]), Ok(()));
+    assert_eq!(r.recv_batch(&mut buf, 1), Ok(1));
+    assert_eq!(r.recv_batch(&mut buf, 1), Ok(1));
+    assert_eq!(buf, [1, 2, 3, 4, 5]);
+
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn broadcast_smoke() {
+    let (s, r) = broadcast(100);
+    let mut buf = Vec::new();
+
+    assert_eq!(s.try_send_batch(vec![1, 2, 3]), Ok(()));
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Ok(3));
+    assert_eq!(buf, [1, 2, 3]);
+
+    assert_eq!(s.send_batch(vec![4, 5]), Ok(()));
+    assert_eq!(r.recv_batch(&mut buf, 1), Ok(1));
+    assert_eq!(r.recv_batch(&mut buf, 1), Ok(1));
+    assert_eq!(buf, [1, 2, 3, 4, 5]);
+
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn array_empty_batch() {
+    let (s, r) = bounded(4);
+    let mut buf = Vec::new();
+
+    assert_eq!(s.try_send_batch(vec![]), Ok(()));
+    assert_eq!(s.send_batch(vec![]), Ok(()));
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Err(TryRecvError::Empty));
+
+    s.send(1).unwrap();
+    assert_eq!(r.try_recv_batch(&mut buf, 0), Ok(0));
+    assert_eq!(r.recv_batch(&mut buf, 0), Ok(0));
+    assert!(buf.is_empty());
+    assert_eq!(r.try_recv(), Ok(1));
+}
+
+#[test]
+fn list_empty_batch() {
+    let (s, r) = unbounded();
+    let mut buf = Vec::new();
+
+    assert_eq!(s.try_send_batch(vec![]), Ok(()));
+    assert_eq!(s.send_batch(vec![]), Ok(()));
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Err(TryRecvError::Empty));
+
+    s.send(1).unwrap();
+    assert_eq!(r.try_recv_batch(&mut buf, 0), Ok(0));
+    assert_eq!(r.recv_batch(&mut buf, 0), Ok(0));
+    assert!(buf.is_empty());
+    assert_eq!(r.try_recv(), Ok(1));
+}
+
+#[test]
+fn priority_empty_batch() {
+    let (s, r) = priority_unbounded();
+    let mut buf = Vec::new();
+
+    assert_eq!(s.try_send_batch(vec![]), Ok(()));
+    assert_eq!(s.send_batch(vec![]), Ok(()));
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Err(TryRecvError::Empty));
+
+    s.send(1).unwrap();
+    assert_eq!(r.try_recv_batch(&mut buf, 0), Ok(0));
+    assert_eq!(r.recv_batch(&mut buf, 0), Ok(0));
+    assert!(buf.is_empty());
+    assert_eq!(r.try_recv(), Ok(1));
+}
+
+#[test]
+fn broadcast_empty_batch() {
+    let (s, r) = broadcast(100);
+    let mut buf = Vec::new();
+
+    assert_eq!(s.try_send_batch(vec![]), Ok(()));
+    assert_eq!(s.send_batch(vec![]), Ok(()));
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Err(TryRecvError::Empty));
+
+    s.send(1).unwrap();
+    assert_eq!(r.try_recv_batch(&mut buf, 0), Ok(0));
+    assert_eq!(r.recv_batch(&mut buf, 0), Ok(0));
+    assert!(buf.is_empty());
+    assert_eq!(r.try_recv(), Ok(1));
+}
+
+#[test]
+fn try_send_batch_full() {
+    let (s, r) = bounded(3);
+    let mut buf = Vec::new();
+
+    assert_eq!(s.try_send_batch(1..3), Ok(()));
+    assert_eq!(s.try_send_batch(3..6), Err(TrySendError::Full(vec![4, 5])));
+    assert_eq!(s.try_send_batch(vec![6]), Err(TrySendError::Full(vec![6])));
+
+    assert_eq!(r.try_recv_batch(&mut buf, 2), Ok(2));
+    assert_eq!(s.try_send_batch(6..10), Err(TrySendError::Full(vec![8, 9])));
+
+    assert_eq!(r.try_recv_batch(&mut buf, 10), Ok(3));
+    assert_eq!(buf, [1, 2, 3, 6, 7]);
+}
+
+#[test]
+fn wraparound() {
+    let (s, r) = bounded(5);
+    let mut buf = Vec::new();
+
+    for i in 0..100 {
+        s.send_batch(i * 3..i * 3 + 3).unwrap();
+        assert_eq!(r.try_recv_batch(&mut buf, 2), Ok(2));
+        assert_eq!(r.try_recv_batch(&mut buf, 2), Ok(1));
+    }
+    assert_eq!(buf, (0..300).collect::<Vec<_>>());
+}
+
+#[test]
+fn block_boundaries() {
+    let (s, r) = unbounded();
+    let mut buf = Vec::new();
+
+    for i in 0..50 {
+        s.send_batch(i * 17..i * 17 + 17).unwrap();
+        while r.try_recv_batch(&mut buf, 13).is_ok() {}
+    }
+    assert_eq!(buf, (0..850).collect::<Vec<_>>());
+
+    s.send_batch(0..1000).unwrap();
+    assert_eq!(r.len(), 1000);
+    buf.clear();
+    while r.try_recv_batch(&mut buf, 1000).is_ok() {}
+    assert_eq!(buf, (0..1000).collect::<Vec<_>>());
+}
+
+#[test]
+fn array_recv_disconnected() {
+    let (s, r) = bounded(4);
+    let mut buf = Vec::new();
+
+    s.send_batch(vec![1, 2]).unwrap();
+    drop(s);
+
+    assert_eq!(r.recv_batch(&mut buf, 10), Ok(2));
+    assert_eq!(r.recv_batch(&mut buf, 10), Err(RecvError));
+    assert_eq!(
+        r.try_recv_batch(&mut buf, 10),
+        Err(TryRecvError::Disconnected)
+    );
+}
+
+#[test]
+fn list_recv_disconnected() {
+    let (s, r) = unbounded();
+    let mut buf = Vec::new();
+
+    s.send_batch(vec![1, 2]).unwrap();
+    drop(s);
+
+    assert_eq!(r.recv_batch(&mut buf, 10), Ok(2));
+    assert_eq!(r.recv_batch(&mut buf, 10), Err(RecvError));
+    assert_eq!(
+        r.try_recv_batch(&mut buf, 10),
+        Err(TryRecvError::Disconnected)
+    );
+}
+
+#[test]
+fn priority_recv_disconnected() {
+    let (s, r) = priority_unbounded();
+    let mut buf = Vec::new();
+
+    s.send_batch(vec![1, 2]).unwrap();
+    drop(s);
+
+    assert_eq!(r.recv_batch(&mut buf, 10), Ok(2));
+    assert_eq!(r.recv_batch(&mut buf, 10), Err(RecvError));
+    assert_eq!(
+        r.try_recv_batch(&mut buf, 10),
+        Err(TryRecvError::Disconnected)
+    );
+}
+
+#[test]
+fn broadcast_recv_disconnected() {
+    let (s, r) = broadcast(100);
+    let mut buf = Vec::new();
+
+    s.send_batch(vec![1, 2]).unwrap();
+    drop(s);
+
+    assert_eq!(r.recv_batch(&mut buf, 10), Ok(2));
+    assert_eq!(r.recv_batch(&mut buf, 10), Err(RecvError));
+    assert_eq!(
+        r.try_recv_batch(&mut buf, 10),
+        Err(TryRecvError::Disconnected)
+    );
+}
+
+#[test]
+fn array_send_disconnected() {
+    let (s, r) = bounded(4);
+    drop(r);
+    assert_eq!(s.send_batch(vec![1, 2]), Err(SendError(vec![1, 2])));
+    assert_eq!(
+        s.try_send_batch(vec![3]),
+        Err(TrySendError::Disconnected(vec![3]))
+    );
+}
+
+#[test]
+fn list_send_disconnected() {
+    let (s, r) = unbounded();
+    drop(r);
+    assert_eq!(s.send_batch(vec![1, 2]), Err(SendError(vec![1, 2])));
+    assert_eq!(
+        s.try_send_batch(vec![3]),
+        Err(TrySendError::Disconnected(vec![3]))
+    );
+}
+
+#[test]
+fn priority_send_disconnected() {
+    let (s, r) = priority_unbounded();
+    drop(r);
+    assert_eq!(s.send_batch(vec![1, 2]), Err(SendError(vec![1, 2])));
+    assert_eq!(
+        s.try_send_batch(vec![3]),
+        Err(TrySendError::Disconnected(vec![3]))
+    );
+}
+
+#[test]
+fn broadcast_send_disconnected() {
+    let (s, r) = broadcast(100);
+    drop(r);
+    assert_eq!(s.send_batch(vec![1, 2]), Err(SendError(vec![1, 2])));
+    assert_eq!(
+        s.try_send_batch(vec![3]),
+        Err(TrySendError::Disconnected(vec![3]))
+    );
+}
+
+#[test]
+fn send_batch_blocks() {
+    let (s, r) = bounded(2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            assert_eq!(r.iter().take(5).collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+            thread::sleep(ms(500));
+            drop(r);
+        });
+        scope.spawn(|_| {
+            assert_eq!(s.send_batch(1..6), Ok(()));
+            assert_eq!(s.send_batch(6..10), Err(SendError(vec![8, 9])));
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn array_recv_batch_blocks() {
+    let (s, r) = bounded(4);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let mut buf = Vec::new();
+            assert_eq!(r.recv_batch(&mut buf, 10), Ok(3));
+            assert_eq!(buf, [1, 2, 3]);
+            assert_eq!(r.recv_batch(&mut buf, 10), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.send_batch(1..4).unwrap();
+            thread::sleep(ms(500));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn list_recv_batch_blocks() {
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let mut buf = Vec::new();
+            assert_eq!(r.recv_batch(&mut buf, 10), Ok(3));
+            assert_eq!(buf, [1, 2, 3]);
+            assert_eq!(r.recv_batch(&mut buf, 10), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.send_batch(1..4).unwrap();
+            thread::sleep(ms(500));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn priority_recv_batch_blocks() {
+    let (s, r) = priority_unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let mut buf = Vec::new();
+            assert_eq!(r.recv_batch(&mut buf, 10), Ok(3));
+            assert_eq!(buf, [1, 2, 3]);
+            assert_eq!(r.recv_batch(&mut buf, 10), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.send_batch(1..4).unwrap();
+            thread::sleep(ms(500));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn broadcast_recv_batch_blocks() {
+    let (s, r) = broadcast(100);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let mut buf = Vec::new();
+            assert_eq!(r.recv_batch(&mut buf, 10), Ok(3));
+            assert_eq!(buf, [1, 2, 3]);
+            assert_eq!(r.recv_batch(&mut buf, 10), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.send_batch(1..4).unwrap();
+            thread::sleep(ms(500));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn wakes_multiple_receivers() {
+    const THREADS: usize = 4;
+
+    for (s, r) in vec![bounded(THREADS), unbounded()] {
+        scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    let mut buf = Vec::new();
+                    assert_eq!(r.recv_batch(&mut buf, 1), Ok(1));
+                });
+            }
+            scope.spawn(|_| {
+                thread::sleep(ms(500));
+                s.send_batch(0..THREADS).unwrap();
+            });
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    for (s, r) in vec![bounded::<usize>(3), bounded(100), unbounded()] {
+        let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+        let senders = (0..THREADS).map(|_| s.clone()).collect::<Vec<_>>();
+        drop(s);
+
+        scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    let mut rng = thread_rng();
+                    let mut buf = Vec::new();
+                    while let Ok(n) = r.recv_batch(&mut buf, rng.gen_range(1, 50)) {
+                        assert_eq!(n, buf.len());
+                        for i in buf.drain(..) {
+                            v[i].fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                });
+            }
+            for s in senders {
+                scope.spawn(move |_| {
+                    let mut rng = thread_rng();
+                    let mut i = 0;
+                    while i < COUNT {
+                        let n = rng.gen_range(1, 50).min(COUNT - i);
+                        s.send_batch(i..i + n).unwrap();
+                        i += n;
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        for c in v {
+            assert_eq!(c.load(Ordering::SeqCst), THREADS);
+        }
+    }
+}
+
+#[test]
+fn order() {
+    const COUNT: usize = 10_000;
+
+    // Broadcast channels are left out because a slow receiver would miss messages.
+    for (s, r) in vec![bounded(4), bounded(100), unbounded(), priority_unbounded()] {
+        scope(|scope| {
+            scope.spawn(|_| {
+                let mut rng = thread_rng();
+                let mut buf = Vec::new();
+                while buf.len() < COUNT {
+                    r.recv_batch(&mut buf, rng.gen_range(1, 50)).unwrap();
+                }
+                assert_eq!(buf, (0..COUNT).collect::<Vec<_>>());
+            });
+            scope.spawn(|_| {
+                let mut rng = thread_rng();
+                let mut i = 0;
+                while i < COUNT {
+                    let n = rng.gen_range(1, 50).min(COUNT - i);
+                    s.send_batch(i..i + n).unwrap();
+                    i += n;
+                }
+            });
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    for &cap in &[Some(7), None] {
+        DROPS.store(0, Ordering::SeqCst);
+        let (s, r) = match cap {
+            Some(cap) => bounded(cap),
+            None => unbounded(),
+        };
+
+        let _ = s.try_send_batch((0..10).map(|_| DropCounter));
+        if cap.is_some() {
+            assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+        }
+
+        let mut buf = Vec::new();
+        r.try_recv_batch(&mut buf, 4).unwrap();
+        drop(buf);
+        assert_eq!(DROPS.load(Ordering::SeqCst), cap.map_or(4, |_| 7));
+
+        drop(s);
+        drop(r);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 10);
+    }
+}
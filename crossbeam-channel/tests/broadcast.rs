@@ -0,0 +1,382 @@
+//! Tests for the broadcast channel flavor.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{broadcast, Select};
+use crossbeam_channel::{BroadcastRecvError, RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{SendError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = broadcast(1);
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+
+    s.send(8).unwrap();
+    assert_eq!(r.recv(), Ok(8));
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    broadcast::<i32>(0);
+}
+
+#[test]
+fn capacity() {
+    for i in 1..10 {
+        let (s, r) = broadcast::<()>(i);
+        assert_eq!(s.capacity(), Some(i));
+        assert_eq!(r.capacity(), Some(i));
+    }
+}
+
+#[test]
+fn every_receiver_gets_every_message() {
+    let (s, r1) = broadcast(4);
+    let r2 = r1.clone();
+
+    for i in 0..4 {
+        s.send(i).unwrap();
+    }
+
+    for i in 0..4 {
+        assert_eq!(r1.recv(), Ok(i));
+    }
+    for i in 0..4 {
+        assert_eq!(r2.recv(), Ok(i));
+    }
+
+    assert_eq!(r1.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r2.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn clone_starts_at_same_position() {
+    let (s, r1) = broadcast(4);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r1.recv(), Ok(1));
+
+    let r2 = r1.clone();
+    assert_eq!(r1.recv(), Ok(2));
+    assert_eq!(r2.recv(), Ok(2));
+}
+
+#[test]
+fn len_empty_full() {
+    let (s, r1) = broadcast(2);
+    let r2 = r1.clone();
+
+    assert_eq!(s.len(), 0);
+    assert_eq!(s.is_empty(), true);
+    assert_eq!(s.is_full(), false);
+
+    s.send(()).unwrap();
+    s.send(()).unwrap();
+    assert_eq!(s.len(), 2);
+    assert_eq!(s.is_full(), true);
+
+    r1.recv().unwrap();
+    assert_eq!(r1.len(), 1);
+    assert_eq!(r1.is_full(), false);
+    assert_eq!(r2.len(), 2);
+    assert_eq!(r2.is_full(), true);
+
+    // Sending into a full channel doesn't block.
+    s.send(()).unwrap();
+    assert_eq!(s.len(), 2);
+    assert_eq!(r1.len(), 2);
+    assert_eq!(r2.len(), 2);
+}
+
+#[test]
+fn lagged() {
+    let (s, r) = broadcast(3);
+
+    for i in 0..10 {
+        assert_eq!(s.try_send(i), Ok(()));
+    }
+
+    assert_eq!(r.try_recv_lagged(), Err(BroadcastRecvError::Lagged(7)));
+    assert_eq!(r.try_recv_lagged(), Ok(7));
+
+    // The lag is reported once, and then receiving resumes from the oldest message.
+    for i in 10..14 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.try_recv_lagged(), Err(BroadcastRecvError::Lagged(3)));
+    assert_eq!(r.try_recv_lagged(), Ok(11));
+    assert_eq!(r.try_recv_lagged(), Ok(12));
+    assert_eq!(r.try_recv_lagged(), Ok(13));
+    assert_eq!(r.try_recv_lagged(), Err(BroadcastRecvError::Empty));
+
+    drop(s);
+    assert_eq!(r.try_recv_lagged(), Err(BroadcastRecvError::Disconnected));
+}
+
+#[test]
+fn skip_lagged() {
+    let (s, r) = broadcast(3);
+
+    // All receive methods skip over missed messages in the same way.
+    for i in 0..5 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.try_recv(), Ok(2));
+
+    for i in 5..10 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.recv(), Ok(7));
+
+    for i in 10..15 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.recv_timeout(ms(100)), Ok(12));
+
+    for i in 15..20 {
+        s.send(i).unwrap();
+    }
+    let deadline = Instant::now() + ms(100);
+    assert_eq!(r.recv_deadline(deadline), Ok(17));
+    assert_eq!(r.recv_deadline(deadline), Ok(18));
+    assert_eq!(r.try_recv(), Ok(19));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+
+    for i in 20..26 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [23, 24, 25]);
+}
+
+#[test]
+fn recv() {
+    let (s, r) = broadcast(10);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv(), Ok(7));
+            thread::sleep(ms(1000));
+            assert_eq!(r.recv(), Ok(8));
+            assert_eq!(r.recv(), Ok(9));
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.send(7).unwrap();
+            s.send(8).unwrap();
+            s.send(9).unwrap();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn send_after_disconnect() {
+    let (s, r) = broadcast(100);
+
+    s.send(1).unwrap();
+    drop(r);
+
+    assert_eq!(s.send(2), Err(SendError(2)));
+    assert_eq!(s.try_send(3), Err(TrySendError::Disconnected(3)));
+}
+
+#[test]
+fn recv_after_disconnect() {
+    let (s, r) = broadcast(100);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    drop(s);
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn disconnect_wakes_receivers() {
+    let (s, r1) = broadcast::<()>(1);
+    let r2 = r1.clone();
+
+    scope(|scope| {
+        scope.spawn(move |_| assert_eq!(r1.recv(), Err(RecvError)));
+        scope.spawn(move |_| assert_eq!(r2.recv(), Err(RecvError)));
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn send_wakes_all_receivers() {
+    const THREADS: usize = 8;
+
+    let (s, r) = broadcast(1);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let r = r.clone();
+            scope.spawn(move |_| assert_eq!(r.recv(), Ok(1)));
+        }
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.send(1).unwrap();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn spmc() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = broadcast(COUNT);
+    let receivers: Vec<_> = (0..THREADS).map(|_| r.clone()).collect();
+    drop(r);
+
+    scope(|scope| {
+        for r in receivers {
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    assert_eq!(r.recv(), Ok(i));
+                }
+                assert_eq!(r.recv(), Err(RecvError));
+            });
+        }
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn shared_receiver() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = broadcast(COUNT);
+    let seen = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                // Threads sharing the same receiver compete for messages.
+                while r.recv().is_ok() {
+                    seen.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+    })
+    .unwrap();
+
+    assert_eq!(seen.load(Ordering::SeqCst), COUNT);
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = broadcast(1);
+    let (s2, r2) = broadcast::<i32>(1);
+    let r3 = r1.clone();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s1.send(1).unwrap();
+            drop(s2);
+        });
+
+        select! {
+            recv(r1) -> v => assert_eq!(v, Ok(1)),
+            recv(r2) -> _ => panic!(),
+        }
+        select! {
+            recv(r3) -> v => assert_eq!(v, Ok(1)),
+        }
+        select! {
+            recv(r2) -> v => assert_eq!(v, Err(RecvError)),
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn select_send() {
+    let (s, r) = broadcast(1);
+
+    // Sending never blocks, so the send operation is always ready.
+    for i in 0..3 {
+        let mut sel = Select::new();
+        sel.send(&s);
+        let oper = sel.select();
+        oper.send(&s, i).unwrap();
+    }
+
+    assert_eq!(r.try_recv_lagged(), Err(BroadcastRecvError::Lagged(2)));
+    assert_eq!(r.try_recv(), Ok(2));
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Clone for DropCounter {
+        fn clone(&self) -> DropCounter {
+            DropCounter
+        }
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = broadcast(5);
+
+    for _ in 0..8 {
+        s.send(DropCounter).unwrap();
+    }
+    // Three messages have been overwritten.
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+
+    drop(r.recv().unwrap());
+    // The received message is a clone of the one still in the channel.
+    assert_eq!(DROPS.load(Ordering::SeqCst), 4);
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 9);
+}
@@ -0,0 +1,349 @@
+//! Tests for timer channels driven by a manual clock.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{after_with_clock, bounded, tick_with_clock, timer_with_clock, unbounded};
+use crossbeam_channel::{Clock, RecvTimeoutError, Select, SendTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+#[test]
+fn manual() {
+    let clock = Clock::manual();
+    assert!(clock.is_manual());
+
+    let start = clock.now();
+    thread::sleep(ms(10));
+    assert_eq!(clock.now(), start);
+
+    clock.advance(secs(1));
+    assert_eq!(clock.now(), start + secs(1));
+    assert_eq!(clock.clone().now(), start + secs(1));
+}
+
+#[test]
+#[should_panic(expected = "the system clock can't be advanced")]
+fn advance_system() {
+    let clock = Clock::system();
+    assert!(!clock.is_manual());
+    clock.advance(secs(1));
+}
+
+#[test]
+fn wait_for_waiters() {
+    let clock = Clock::manual();
+    let r1 = after_with_clock(secs(1), &clock);
+    let r2 = tick_with_clock(secs(1), &clock);
+    let (s, r) = bounded::<()>(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            r1.recv().unwrap();
+        });
+        scope.spawn(|_| {
+            select! {
+                recv(r2) -> msg => assert!(msg.is_ok()),
+                recv(r) -> _ => panic!(),
+            }
+        });
+
+        clock.wait_for_waiters(2);
+        clock.advance(secs(1));
+    })
+    .unwrap();
+    drop(s);
+}
+
+#[test]
+#[should_panic(expected = "the system clock has no waiters")]
+fn wait_for_waiters_system() {
+    Clock::system().wait_for_waiters(0);
+}
+
+#[test]
+fn after() {
+    let clock = Clock::manual();
+    let start = clock.now();
+    let r = after_with_clock(secs(10), &clock);
+
+    assert!(r.is_empty());
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    clock.advance(secs(9));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    clock.advance(secs(5));
+    assert_eq!(r.len(), 1);
+    assert_eq!(r.peek_with(|t| *t), Ok(start + secs(10)));
+    assert_eq!(r.try_recv(), Ok(start + secs(10)));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn tick() {
+    let clock = Clock::manual();
+    let start = clock.now();
+    let r = tick_with_clock(secs(1), &clock);
+
+    for i in 1..6 {
+        assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+        clock.advance(secs(1));
+        assert_eq!(r.try_recv(), Ok(start + secs(i)));
+    }
+
+    // Missed ticks are not delivered.
+    clock.advance(secs(10));
+    assert_eq!(r.try_recv(), Ok(start + secs(6)));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    clock.advance(secs(1));
+    assert_eq!(r.try_recv(), Ok(start + secs(16)));
+}
+
+#[test]
+fn timer() {
+    let clock = Clock::manual();
+    let start = clock.now();
+    let (t, r) = timer_with_clock(secs(10), &clock);
+
+    clock.advance(secs(5));
+    assert_eq!(t.remaining(), Some(secs(5)));
+    t.reset(secs(10));
+    assert_eq!(t.remaining(), Some(secs(10)));
+
+    clock.advance(secs(9));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    // A reset that makes the message due wakes up blocked receivers.
+    scope(|scope| {
+        scope.spawn(|_| {
+            assert_eq!(r.recv(), Ok(start + secs(14)));
+        });
+
+        clock.wait_for_waiters(1);
+        t.reset(secs(0));
+    })
+    .unwrap();
+    assert_eq!(t.remaining(), None);
+}
+
+#[test]
+fn recv() {
+    let clock = Clock::manual();
+    let start = clock.now();
+    let r = after_with_clock(secs(3600), &clock);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let real = Instant::now();
+            assert_eq!(r.recv(), Ok(start + secs(3600)));
+            assert!(real.elapsed() >= ms(400));
+        });
+
+        thread::sleep(ms(500));
+        clock.advance(secs(1800));
+        thread::sleep(ms(100));
+        clock.advance(secs(1800));
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_timeout() {
+    let clock = Clock::manual();
+    let r = after_with_clock(secs(1), &clock);
+
+    // Timeouts of ordinary operations are still measured in real time.
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            clock.advance(secs(1));
+        });
+        assert!(r.recv_timeout(secs(10)).is_ok());
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_timeout_with_clock() {
+    let clock = Clock::manual();
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            clock.advance(secs(60));
+            thread::sleep(ms(500));
+            s.send(1).unwrap();
+        });
+
+        assert_eq!(
+            r.recv_timeout_with_clock(secs(60), &clock),
+            Err(RecvTimeoutError::Timeout)
+        );
+        assert_eq!(r.recv_timeout_with_clock(secs(60), &clock), Ok(1));
+    })
+    .unwrap();
+
+    // A message that is already available is received even if the timeout is zero.
+    s.send(2).unwrap();
+    assert_eq!(r.recv_timeout_with_clock(ms(0), &clock), Ok(2));
+
+    drop(s);
+    assert_eq!(
+        r.recv_timeout_with_clock(secs(60), &clock),
+        Err(RecvTimeoutError::Disconnected)
+    );
+
+    // The system clock falls back to real time.
+    let (_s, r) = bounded::<i32>(1);
+    assert_eq!(
+        r.recv_timeout_with_clock(ms(100), &Clock::system()),
+        Err(RecvTimeoutError::Timeout)
+    );
+}
+
+#[test]
+fn send_timeout_with_clock() {
+    let clock = Clock::manual();
+    let (s, r) = bounded(1);
+    s.send(1).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            clock.advance(secs(60));
+            thread::sleep(ms(500));
+            assert_eq!(r.recv(), Ok(1));
+        });
+
+        assert_eq!(
+            s.send_timeout_with_clock(2, secs(60), &clock),
+            Err(SendTimeoutError::Timeout(2))
+        );
+        assert_eq!(s.send_timeout_with_clock(3, secs(60), &clock), Ok(()));
+    })
+    .unwrap();
+
+    assert_eq!(r.recv(), Ok(3));
+    drop(r);
+    assert_eq!(
+        s.send_timeout_with_clock(4, secs(60), &clock),
+        Err(SendTimeoutError::Disconnected(4))
+    );
+}
+
+#[test]
+fn select() {
+    let clock = Clock::manual();
+    let (_s, r) = unbounded::<i32>();
+    let timeout = after_with_clock(secs(5), &clock);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let mut sel = Select::new();
+            let oper1 = sel.recv(&r);
+            let oper2 = sel.recv(&timeout);
+
+            let oper = sel.select();
+            match oper.index() {
+                i if i == oper1 => panic!(),
+                i if i == oper2 => assert!(oper.recv(&timeout).is_ok()),
+                _ => unreachable!(),
+            }
+        });
+
+        // Advancing the clock without reaching the deadline doesn't complete the selection.
+        thread::sleep(ms(200));
+        clock.advance(secs(4));
+        thread::sleep(ms(200));
+        clock.advance(secs(1));
+    })
+    .unwrap();
+}
+
+#[test]
+fn select_macro() {
+    let clock = Clock::manual();
+    let ticker = tick_with_clock(secs(1), &clock);
+    let timeout = after_with_clock(ms(3500), &clock);
+    let ticks = AtomicUsize::new(0);
+
+    scope(|scope| {
+        scope.spawn(|_| loop {
+            select! {
+                recv(ticker) -> _ => {
+                    ticks.fetch_add(1, Ordering::SeqCst);
+                }
+                recv(timeout) -> _ => break,
+            }
+        });
+
+        for _ in 0..7 {
+            thread::sleep(ms(100));
+            clock.advance(ms(500));
+        }
+    })
+    .unwrap();
+
+    assert_eq!(ticks.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn ready() {
+    let clock = Clock::manual();
+    let r = after_with_clock(secs(1), &clock);
+
+    let mut sel = Select::new();
+    sel.recv(&r);
+    assert!(sel.try_ready().is_err());
+    assert!(sel.ready_timeout(ms(100)).is_err());
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            clock.advance(secs(1));
+        });
+        assert_eq!(sel.ready(), 0);
+    })
+    .unwrap();
+}
+
+#[test]
+fn many_waiters() {
+    const THREADS: usize = 8;
+
+    let clock = Clock::manual();
+    let r = tick_with_clock(secs(1), &clock);
+    let hits = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                r.recv().unwrap();
+                hits.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+
+        while hits.load(Ordering::SeqCst) < THREADS {
+            thread::sleep(ms(10));
+            clock.advance(secs(1));
+        }
+    })
+    .unwrap();
+}
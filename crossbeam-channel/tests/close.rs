@@ -0,0 +1,523 @@
+//! Tests for sender/receiver counts and closing channels.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, broadcast, never, priority_bounded, tick, unbounded};
+use crossbeam_channel::{RecvError, SendError, TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn zero_counts() {
+    let (s, r) = bounded::<i32>(0);
+    assert_eq!(s.sender_count(), 1);
+    assert_eq!(s.receiver_count(), 1);
+    assert_eq!(r.sender_count(), 1);
+    assert_eq!(r.receiver_count(), 1);
+
+    let s2 = s.clone();
+    let r2 = r.clone();
+    let r3 = r.clone();
+    assert_eq!(s2.sender_count(), 2);
+    assert_eq!(r3.sender_count(), 2);
+    assert_eq!(s.receiver_count(), 3);
+    assert_eq!(r2.receiver_count(), 3);
+
+    drop(s);
+    drop(r2);
+    assert_eq!(r.sender_count(), 1);
+    assert_eq!(s2.receiver_count(), 2);
+
+    drop(r);
+    drop(r3);
+    assert_eq!(s2.sender_count(), 1);
+    assert_eq!(s2.receiver_count(), 0);
+}
+
+#[test]
+fn array_counts() {
+    let (s, r) = bounded::<i32>(10);
+    assert_eq!(s.sender_count(), 1);
+    assert_eq!(s.receiver_count(), 1);
+    assert_eq!(r.sender_count(), 1);
+    assert_eq!(r.receiver_count(), 1);
+
+    let s2 = s.clone();
+    let r2 = r.clone();
+    let r3 = r.clone();
+    assert_eq!(s2.sender_count(), 2);
+    assert_eq!(r3.sender_count(), 2);
+    assert_eq!(s.receiver_count(), 3);
+    assert_eq!(r2.receiver_count(), 3);
+
+    drop(s);
+    drop(r2);
+    assert_eq!(r.sender_count(), 1);
+    assert_eq!(s2.receiver_count(), 2);
+
+    drop(r);
+    drop(r3);
+    assert_eq!(s2.sender_count(), 1);
+    assert_eq!(s2.receiver_count(), 0);
+}
+
+#[test]
+fn list_counts() {
+    let (s, r) = unbounded::<i32>();
+    assert_eq!(s.sender_count(), 1);
+    assert_eq!(s.receiver_count(), 1);
+    assert_eq!(r.sender_count(), 1);
+    assert_eq!(r.receiver_count(), 1);
+
+    let s2 = s.clone();
+    let r2 = r.clone();
+    let r3 = r.clone();
+    assert_eq!(s2.sender_count(), 2);
+    assert_eq!(r3.sender_count(), 2);
+    assert_eq!(s.receiver_count(), 3);
+    assert_eq!(r2.receiver_count(), 3);
+
+    drop(s);
+    drop(r2);
+    assert_eq!(r.sender_count(), 1);
+    assert_eq!(s2.receiver_count(), 2);
+
+    drop(r);
+    drop(r3);
+    assert_eq!(s2.sender_count(), 1);
+    assert_eq!(s2.receiver_count(), 0);
+}
+
+#[test]
+fn priority_counts() {
+    let (s, r) = priority_bounded::<i32>(10);
+    assert_eq!(s.sender_count(), 1);
+    assert_eq!(s.receiver_count(), 1);
+    assert_eq!(r.sender_count(), 1);
+    assert_eq!(r.receiver_count(), 1);
+
+    let s2 = s.clone();
+    let r2 = r.clone();
+    let r3 = r.clone();
+    assert_eq!(s2.sender_count(), 2);
+    assert_eq!(r3.sender_count(), 2);
+    assert_eq!(s.receiver_count(), 3);
+    assert_eq!(r2.receiver_count(), 3);
+
+    drop(s);
+    drop(r2);
+    assert_eq!(r.sender_count(), 1);
+    assert_eq!(s2.receiver_count(), 2);
+
+    drop(r);
+    drop(r3);
+    assert_eq!(s2.sender_count(), 1);
+    assert_eq!(s2.receiver_count(), 0);
+}
+
+#[test]
+fn broadcast_counts() {
+    let (s, r) = broadcast::<i32>(10);
+    assert_eq!(s.sender_count(), 1);
+    assert_eq!(s.receiver_count(), 1);
+    assert_eq!(r.sender_count(), 1);
+    assert_eq!(r.receiver_count(), 1);
+
+    let s2 = s.clone();
+    let r2 = r.clone();
+    let r3 = r.clone();
+    assert_eq!(s2.sender_count(), 2);
+    assert_eq!(r3.sender_count(), 2);
+    assert_eq!(s.receiver_count(), 3);
+    assert_eq!(r2.receiver_count(), 3);
+
+    drop(s);
+    drop(r2);
+    assert_eq!(r.sender_count(), 1);
+    assert_eq!(s2.receiver_count(), 2);
+
+    drop(r);
+    drop(r3);
+    assert_eq!(s2.sender_count(), 1);
+    assert_eq!(s2.receiver_count(), 0);
+}
+
+#[test]
+fn timer_counts() {
+    let r = after(ms(50));
+    let r2 = r.clone();
+    assert_eq!(r.sender_count(), 0);
+    assert_eq!(r.receiver_count(), 2);
+    drop(r2);
+    assert_eq!(r.receiver_count(), 1);
+
+    let r = tick(ms(50));
+    assert_eq!(r.sender_count(), 0);
+    assert_eq!(r.receiver_count(), 1);
+
+    let r = never::<i32>();
+    assert_eq!(r.sender_count(), 0);
+    assert_eq!(r.receiver_count(), 1);
+}
+
+#[test]
+fn zero_is_disconnected() {
+    let (s, r) = bounded::<i32>(0);
+    assert!(!s.is_disconnected());
+    assert!(!r.is_disconnected());
+
+    let s2 = s.clone();
+    drop(s);
+    assert!(!r.is_disconnected());
+
+    drop(s2);
+    assert!(r.is_disconnected());
+
+    let (s, r) = bounded::<i32>(0);
+    drop(r);
+    assert!(s.is_disconnected());
+}
+
+#[test]
+fn array_is_disconnected() {
+    let (s, r) = bounded::<i32>(10);
+    assert!(!s.is_disconnected());
+    assert!(!r.is_disconnected());
+
+    let s2 = s.clone();
+    drop(s);
+    assert!(!r.is_disconnected());
+
+    drop(s2);
+    assert!(r.is_disconnected());
+
+    let (s, r) = bounded::<i32>(10);
+    drop(r);
+    assert!(s.is_disconnected());
+}
+
+#[test]
+fn list_is_disconnected() {
+    let (s, r) = unbounded::<i32>();
+    assert!(!s.is_disconnected());
+    assert!(!r.is_disconnected());
+
+    let s2 = s.clone();
+    drop(s);
+    assert!(!r.is_disconnected());
+
+    drop(s2);
+    assert!(r.is_disconnected());
+
+    let (s, r) = unbounded::<i32>();
+    drop(r);
+    assert!(s.is_disconnected());
+}
+
+#[test]
+fn priority_is_disconnected() {
+    let (s, r) = priority_bounded::<i32>(10);
+    assert!(!s.is_disconnected());
+    assert!(!r.is_disconnected());
+
+    let s2 = s.clone();
+    drop(s);
+    assert!(!r.is_disconnected());
+
+    drop(s2);
+    assert!(r.is_disconnected());
+
+    let (s, r) = priority_bounded::<i32>(10);
+    drop(r);
+    assert!(s.is_disconnected());
+}
+
+#[test]
+fn broadcast_is_disconnected() {
+    let (s, r) = broadcast::<i32>(10);
+    assert!(!s.is_disconnected());
+    assert!(!r.is_disconnected());
+
+    let s2 = s.clone();
+    drop(s);
+    assert!(!r.is_disconnected());
+
+    drop(s2);
+    assert!(r.is_disconnected());
+
+    let (s, r) = broadcast::<i32>(10);
+    drop(r);
+    assert!(s.is_disconnected());
+}
+
+#[test]
+fn timer_is_disconnected() {
+    assert!(!after(ms(0)).is_disconnected());
+    assert!(!tick(ms(50)).is_disconnected());
+    assert!(!never::<i32>().is_disconnected());
+}
+
+#[test]
+fn array_close_sender() {
+    let (s, r) = bounded(10);
+    let s2 = s.clone();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    assert!(s.close());
+    assert!(!s.close());
+    assert!(s.is_disconnected());
+    assert!(r.is_disconnected());
+
+    assert_eq!(s2.try_send(3), Err(TrySendError::Disconnected(3)));
+    assert_eq!(s2.send(4), Err(SendError(4)));
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn list_close_sender() {
+    let (s, r) = unbounded();
+    let s2 = s.clone();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    assert!(s.close());
+    assert!(!s.close());
+    assert!(s.is_disconnected());
+    assert!(r.is_disconnected());
+
+    assert_eq!(s2.try_send(3), Err(TrySendError::Disconnected(3)));
+    assert_eq!(s2.send(4), Err(SendError(4)));
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn priority_close_sender() {
+    let (s, r) = priority_bounded(10);
+    let s2 = s.clone();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    assert!(s.close());
+    assert!(!s.close());
+    assert!(s.is_disconnected());
+    assert!(r.is_disconnected());
+
+    assert_eq!(s2.try_send(3), Err(TrySendError::Disconnected(3)));
+    assert_eq!(s2.send(4), Err(SendError(4)));
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn broadcast_close_sender() {
+    let (s, r) = broadcast(10);
+    let s2 = s.clone();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    assert!(s.close());
+    assert!(!s.close());
+    assert!(s.is_disconnected());
+    assert!(r.is_disconnected());
+
+    assert_eq!(s2.try_send(3), Err(TrySendError::Disconnected(3)));
+    assert_eq!(s2.send(4), Err(SendError(4)));
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn array_close_receiver() {
+    let (s, r) = bounded(10);
+    let r2 = r.clone();
+    s.send(1).unwrap();
+
+    assert!(r2.close());
+    assert!(!r.close());
+    assert!(s.is_disconnected());
+
+    assert_eq!(s.send(2), Err(SendError(2)));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn list_close_receiver() {
+    let (s, r) = unbounded();
+    let r2 = r.clone();
+    s.send(1).unwrap();
+
+    assert!(r2.close());
+    assert!(!r.close());
+    assert!(s.is_disconnected());
+
+    assert_eq!(s.send(2), Err(SendError(2)));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn priority_close_receiver() {
+    let (s, r) = priority_bounded(10);
+    let r2 = r.clone();
+    s.send(1).unwrap();
+
+    assert!(r2.close());
+    assert!(!r.close());
+    assert!(s.is_disconnected());
+
+    assert_eq!(s.send(2), Err(SendError(2)));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn broadcast_close_receiver() {
+    let (s, r) = broadcast(10);
+    let r2 = r.clone();
+    s.send(1).unwrap();
+
+    assert!(r2.close());
+    assert!(!r.close());
+    assert!(s.is_disconnected());
+
+    assert_eq!(s.send(2), Err(SendError(2)));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn close_timers() {
+    assert!(!after(ms(0)).close());
+    assert!(!tick(ms(50)).close());
+    assert!(!never::<i32>().close());
+}
+
+#[test]
+fn zero_close_wakes_receivers() {
+    let (s, r) = bounded::<i32>(0);
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.recv(), Err(RecvError)));
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.close();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn array_close_wakes_receivers() {
+    let (s, r) = bounded::<i32>(10);
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.recv(), Err(RecvError)));
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.close();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn list_close_wakes_receivers() {
+    let (s, r) = unbounded::<i32>();
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.recv(), Err(RecvError)));
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.close();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn priority_close_wakes_receivers() {
+    let (s, r) = priority_bounded::<i32>(10);
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.recv(), Err(RecvError)));
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.close();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn broadcast_close_wakes_receivers() {
+    let (s, r) = broadcast::<i32>(10);
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.recv(), Err(RecvError)));
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.close();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn close_wakes_senders() {
+    for &cap in &[0, 1] {
+        let (s, r) = bounded(cap);
+        if cap > 0 {
+            s.send(1).unwrap();
+        }
+
+        scope(|scope| {
+            scope.spawn(|_| assert_eq!(s.send(2), Err(SendError(2))));
+            scope.spawn(|_| {
+                thread::sleep(ms(500));
+                r.close();
+            });
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn close_select() {
+    let (s1, r1) = unbounded::<i32>();
+    let (_s2, r2) = bounded::<i32>(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            select! {
+                recv(r1) -> _ => panic!(),
+                recv(r2) -> v => assert_eq!(v, Err(RecvError)),
+            }
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            r2.close();
+        });
+    })
+    .unwrap();
+
+    drop(s1);
+}
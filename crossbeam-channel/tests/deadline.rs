@@ -0,0 +1,233 @@
+//! Tests for operations with a deadline.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{after, bounded, never, tick, unbounded, Select};
+use crossbeam_channel::{RecvTimeoutError, SendTimeoutError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn recv_deadline() {
+    let (s, r) = bounded::<i32>(1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            s.send(7).unwrap();
+        });
+
+        let start = Instant::now();
+        assert_eq!(
+            r.recv_deadline(start + ms(500)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        assert!(start.elapsed() >= ms(500));
+        assert_eq!(r.recv_deadline(start + ms(2000)), Ok(7));
+        assert_eq!(
+            r.recv_deadline(start + ms(3000)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    })
+    .unwrap();
+}
+
+#[test]
+fn send_deadline() {
+    let (s, r) = bounded(1);
+    s.send(1).unwrap();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Ok(3));
+        });
+
+        let start = Instant::now();
+        assert_eq!(
+            s.send_deadline(2, start + ms(500)),
+            Err(SendTimeoutError::Timeout(2))
+        );
+        assert_eq!(s.send_deadline(3, start + ms(2000)), Ok(()));
+        thread::sleep(ms(500));
+        assert_eq!(
+            s.send_deadline(4, start + ms(3000)),
+            Err(SendTimeoutError::Disconnected(4))
+        );
+    })
+    .unwrap();
+}
+
+#[test]
+fn past_deadline() {
+    let (s, r) = bounded(1);
+    let past = Instant::now();
+    thread::sleep(ms(10));
+
+    assert_eq!(s.send_deadline(1, past), Ok(()));
+    assert_eq!(s.send_deadline(2, past), Err(SendTimeoutError::Timeout(2)));
+    assert_eq!(r.recv_deadline(past), Ok(1));
+    assert_eq!(r.recv_deadline(past), Err(RecvTimeoutError::Timeout));
+
+    let mut sel = Select::new();
+    sel.recv(&r);
+    assert!(sel.select_deadline(past).is_err());
+    assert!(sel.ready_deadline(past).is_err());
+}
+
+#[test]
+fn shared_deadline() {
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..5 {
+                thread::sleep(ms(300));
+                if s.send(i).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let start = Instant::now();
+        let deadline = start + ms(1000);
+        let mut v = Vec::new();
+        while let Ok(i) = r.recv_deadline(deadline) {
+            v.push(i);
+        }
+
+        assert_eq!(v, [0, 1, 2]);
+        assert!(start.elapsed() < ms(1250));
+    })
+    .unwrap();
+}
+
+#[test]
+fn timer_flavors() {
+    let start = Instant::now();
+
+    let r = after(ms(500));
+    assert_eq!(
+        r.recv_deadline(start + ms(250)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    assert!(r.recv_deadline(start + ms(1000)).unwrap() >= start + ms(500));
+
+    let r = tick(ms(200));
+    assert!(r.recv_deadline(start + ms(2000)).is_ok());
+
+    let r = never::<i32>();
+    assert_eq!(
+        r.recv_deadline(Instant::now() + ms(100)),
+        Err(RecvTimeoutError::Timeout)
+    );
+}
+
+#[test]
+fn select_deadline() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(1000));
+            s2.send(2).unwrap();
+        });
+
+        let start = Instant::now();
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r1);
+        let oper2 = sel.recv(&r2);
+
+        assert!(sel.select_deadline(start + ms(500)).is_err());
+        assert!(start.elapsed() >= ms(500));
+
+        let oper = sel.select_deadline(start + ms(2000)).unwrap();
+        match oper.index() {
+            i if i == oper1 => panic!(),
+            i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(2)),
+            _ => unreachable!(),
+        }
+    })
+    .unwrap();
+
+    drop(s1);
+}
+
+#[test]
+fn ready_deadline() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(1000));
+            s2.send(2).unwrap();
+        });
+
+        let start = Instant::now();
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r1);
+        let oper2 = sel.recv(&r2);
+
+        assert!(sel.ready_deadline(start + ms(500)).is_err());
+        match sel.ready_deadline(start + ms(2000)) {
+            Ok(i) if i == oper1 => panic!(),
+            Ok(i) if i == oper2 => assert_eq!(r2.try_recv(), Ok(2)),
+            res => panic!("{:?}", res),
+        }
+    })
+    .unwrap();
+
+    drop(s1);
+}
+
+#[test]
+fn select_macro() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+
+    let start = Instant::now();
+    select! {
+        recv(r1) -> _ => panic!(),
+        default(start + ms(500)) => {}
+    }
+    assert!(start.elapsed() >= ms(500));
+
+    // A single receive operation takes a different path in the macro.
+    let start = Instant::now();
+    select! {
+        recv(r2) -> _ => panic!(),
+        default(start + ms(500)) => {}
+    }
+    assert!(start.elapsed() >= ms(500));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s2.send(2).unwrap();
+        });
+
+        select! {
+            recv(r1) -> _ => panic!(),
+            recv(r2) -> msg => assert_eq!(msg, Ok(2)),
+            default(Instant::now() + ms(2000)) => panic!(),
+        }
+    })
+    .unwrap();
+
+    // Durations still work.
+    select! {
+        send(s1, 1) -> res => assert_eq!(res, Ok(())),
+        default(ms(1000)) => panic!(),
+    }
+}
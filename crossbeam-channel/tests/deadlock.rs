@@ -0,0 +1,330 @@
+//! Tests for deadlock detection.
+
+#![cfg(feature = "deadlock-detection")]
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::mpsc;
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+
+use crossbeam_channel::{bounded_named, find_deadlocks, unbounded_named, Deadlock, Watchdog};
+use crossbeam_channel::{Receiver, Sender};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Returns the deadlock thread `id` is part of, if there is one.
+///
+/// Tests run in parallel, so deadlocks of other tests are ignored.
+fn deadlock_of(id: ThreadId) -> Option<Deadlock> {
+    find_deadlocks()
+        .into_iter()
+        .find(|d| d.threads().contains(&id))
+}
+
+/// Waits until thread `id` is found in a deadlock and returns it.
+///
+/// Threads take a moment to block, so the search is repeated for up to five seconds.
+fn wait_for_deadlock(id: ThreadId) -> Deadlock {
+    for _ in 0..500 {
+        if let Some(deadlock) = deadlock_of(id) {
+            return deadlock;
+        }
+        thread::sleep(ms(10));
+    }
+    panic!("no deadlock found");
+}
+
+/// Waits until thread `id` is no longer found in a deadlock.
+fn wait_for_no_deadlock(id: ThreadId) {
+    for _ in 0..500 {
+        if deadlock_of(id).is_none() {
+            return;
+        }
+        thread::sleep(ms(10));
+    }
+    panic!("deadlock still found");
+}
+
+/// Creates two zero-capacity channels and spawns two threads that deadlock on them.
+///
+/// Each thread first completes one operation on each channel, and then both send at the same
+/// time. Returns the ids of the threads.
+fn cycle(
+    scope: &crossbeam_utils::thread::Scope,
+    a: (Sender<i32>, Receiver<i32>),
+    b: (Sender<i32>, Receiver<i32>),
+) -> (ThreadId, ThreadId) {
+    let ((s1, r1), (s2, r2)) = (a, b);
+
+    let t1 = scope.spawn(move |_| {
+        s1.send(1).unwrap();
+        r2.recv().unwrap();
+        s1.send(1).unwrap();
+    });
+    let t2 = scope.spawn(move |_| {
+        r1.recv().unwrap();
+        s2.send(2).unwrap();
+        s2.send(2).unwrap();
+    });
+
+    (t1.thread().id(), t2.thread().id())
+}
+
+#[test]
+fn smoke() {
+    let a = bounded_named("smoke-a", 0);
+    let b = bounded_named("smoke-b", 0);
+    let (ra, rb) = (a.1.clone(), b.1.clone());
+
+    scope(|scope| {
+        let (t1, t2) = cycle(scope, a, b);
+
+        let deadlock = wait_for_deadlock(t1);
+        assert_eq!(deadlock.threads().len(), 2);
+        assert!(deadlock.threads().contains(&t2));
+
+        let mut names = deadlock
+            .channels()
+            .iter()
+            .map(|c| c.name().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["smoke-a", "smoke-b"]);
+
+        // Break the deadlock.
+        ra.recv().unwrap();
+        rb.recv().unwrap();
+        wait_for_no_deadlock(t1);
+        wait_for_no_deadlock(t2);
+    })
+    .unwrap();
+}
+
+#[test]
+fn display() {
+    let a = bounded_named("display-a", 0);
+    let b = bounded_named("display-b", 0);
+    let (ra, rb) = (a.1.clone(), b.1.clone());
+
+    scope(|scope| {
+        let (t1, _) = cycle(scope, a, b);
+
+        let report = wait_for_deadlock(t1).to_string();
+        assert!(report.starts_with("deadlock between threads"));
+        assert!(report.contains(&format!("{:?}", t1)));
+        assert!(report.contains("\"display-a\" (zero, len 0)"));
+        assert!(report.contains("\"display-b\" (zero, len 0)"));
+
+        ra.recv().unwrap();
+        rb.recv().unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn no_deadlock() {
+    let (s, r) = bounded_named("no-deadlock", 0);
+
+    scope(|scope| {
+        let t = scope.spawn(|_| {
+            for i in 0..3 {
+                s.send(i).unwrap();
+            }
+        });
+
+        // The receiving side has been used by a thread that is not blocked.
+        assert_eq!(r.recv(), Ok(0));
+        thread::sleep(ms(200));
+        assert!(deadlock_of(t.thread().id()).is_none());
+
+        assert_eq!(r.recv(), Ok(1));
+        assert_eq!(r.recv(), Ok(2));
+    })
+    .unwrap();
+}
+
+#[test]
+fn unknown_counterpart() {
+    let (s, r) = unbounded_named::<i32>("unknown-counterpart");
+
+    scope(|scope| {
+        // Nobody has used the sending side yet.
+        let t = scope.spawn(|_| r.recv().unwrap());
+        thread::sleep(ms(200));
+        assert!(deadlock_of(t.thread().id()).is_none());
+
+        s.send(1).unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn exited_thread() {
+    let a = bounded_named("exited-thread-a", 0);
+    let b = bounded_named("exited-thread-b", 0);
+    let (ra, rb) = (a.1.clone(), b.1.clone());
+
+    scope(|scope| {
+        // A thread that has used the receiving side and exited can't receive anymore.
+        scope
+            .spawn(|_| assert!(ra.try_recv().is_err()))
+            .join()
+            .unwrap();
+
+        let (t1, t2) = cycle(scope, a, b);
+        let deadlock = wait_for_deadlock(t1);
+        assert!(deadlock.threads().contains(&t2));
+
+        ra.recv().unwrap();
+        rb.recv().unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn dropped_handle() {
+    let a = bounded_named("dropped-handle-a", 0);
+    let b = bounded_named("dropped-handle-b", 0);
+    let (ra, rb) = (a.1.clone(), b.1.clone());
+    let r = a.1.clone();
+    let (done_s, done_r) = mpsc::channel::<()>();
+
+    scope(|scope| {
+        // A thread that has used the receiving side and dropped its receiver can't receive
+        // anymore, even though it keeps running.
+        let (used_s, used_r) = mpsc::channel();
+        scope.spawn(move |_| {
+            assert!(r.try_recv().is_err());
+            drop(r);
+            used_s.send(()).unwrap();
+            done_r.recv().unwrap();
+        });
+        used_r.recv().unwrap();
+
+        let (t1, t2) = cycle(scope, a, b);
+        let deadlock = wait_for_deadlock(t1);
+        assert!(deadlock.threads().contains(&t2));
+
+        ra.recv().unwrap();
+        rb.recv().unwrap();
+        done_s.send(()).unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn timeout() {
+    let (s1, r1) = bounded_named("timeout-a", 0);
+    let (s2, r2) = bounded_named("timeout-b", 0);
+
+    scope(|scope| {
+        let t1 = scope.spawn(|_| {
+            s1.send(1).unwrap();
+            r2.recv().unwrap();
+            assert!(s1.send_timeout(1, ms(500)).is_err());
+            r2.recv().unwrap();
+        });
+        let t2 = scope.spawn(|_| {
+            r1.recv().unwrap();
+            s2.send(2).unwrap();
+            s2.send(2).unwrap();
+        });
+        thread::sleep(ms(200));
+
+        // The first thread will wake up on its own, so the second one isn't stuck either.
+        assert!(deadlock_of(t1.thread().id()).is_none());
+        assert!(deadlock_of(t2.thread().id()).is_none());
+    })
+    .unwrap();
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = bounded_named::<i32>("select-a", 0);
+    let (s2, r2) = bounded_named::<i32>("select-b", 0);
+    let (s3, r3) = unbounded_named::<i32>("select-c");
+    let (s4, r4) = unbounded_named::<i32>("select-d");
+    let (ra, rb) = (r1.clone(), r2.clone());
+
+    // The main thread uses the sending side of the third channel.
+    s3.send(0).unwrap();
+    assert_eq!(r3.recv(), Ok(0));
+
+    scope(|scope| {
+        let t1 = scope.spawn(move |_| {
+            s1.send(1).unwrap();
+            r2.recv().unwrap();
+            select! {
+                send(s1, 1) -> res => res.unwrap(),
+                recv(r3) -> _ => panic!(),
+            }
+        });
+        let t2 = scope.spawn(move |_| {
+            r1.recv().unwrap();
+            s2.send(2).unwrap();
+            s4.send(4).unwrap();
+            s2.send(2).unwrap();
+        });
+        let (t1, t2) = (t1.thread().id(), t2.thread().id());
+
+        assert_eq!(r4.recv(), Ok(4));
+        thread::sleep(ms(200));
+
+        // The first thread can still receive a message sent by the main thread.
+        assert!(deadlock_of(t1).is_none());
+        assert!(deadlock_of(t2).is_none());
+
+        // Once the main thread is blocked too, all three threads are deadlocked.
+        scope.spawn(move |_| {
+            let deadlock = wait_for_deadlock(t1);
+            assert_eq!(deadlock.threads().len(), 3);
+            assert!(deadlock.threads().contains(&t2));
+
+            ra.recv().unwrap();
+            rb.recv().unwrap();
+        });
+        assert!(r4.recv().is_err());
+    })
+    .unwrap();
+}
+
+#[test]
+fn watchdog() {
+    let a = bounded_named("watchdog-a", 0);
+    let b = bounded_named("watchdog-b", 0);
+    let (ra, rb) = (a.1.clone(), b.1.clone());
+    let (reports_s, reports_r) = mpsc::channel();
+
+    let watchdog = Watchdog::new(ms(50))
+        .on_deadlock(move |d| reports_s.send(d.clone()).unwrap())
+        .spawn();
+
+    scope(|scope| {
+        let (t1, t2) = cycle(scope, a, b);
+
+        // Wait for the deadlock to be reported.
+        let deadlock = loop {
+            let d = reports_r.recv_timeout(ms(5000)).unwrap();
+            if d.threads().contains(&t1) {
+                break d;
+            }
+        };
+        assert!(deadlock.threads().contains(&t2));
+
+        // The same deadlock is not reported twice.
+        thread::sleep(ms(300));
+        assert!(reports_r.try_iter().all(|d| !d.threads().contains(&t1)));
+
+        ra.recv().unwrap();
+        rb.recv().unwrap();
+    })
+    .unwrap();
+
+    watchdog.stop();
+}
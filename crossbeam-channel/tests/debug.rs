@@ -0,0 +1,133 @@
+//! Tests for the registry of live channels.
+
+#![cfg(feature = "debug")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::Select;
+use crossbeam_channel::{bounded_named, live_channels, unbounded, unbounded_named, ChannelInfo};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Returns information about the live channel named `name`.
+fn find(name: &str) -> Option<ChannelInfo> {
+    live_channels().into_iter().find(|c| c.name() == Some(name))
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = bounded_named("smoke", 4);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    let info = find("smoke").unwrap();
+    assert_eq!(info.flavor(), "array");
+    assert_eq!(info.len(), 2);
+    assert!(!info.is_empty());
+    assert!(info.blocked_senders().is_empty());
+    assert!(info.blocked_receivers().is_empty());
+
+    drop(s);
+    assert!(find("smoke").is_some());
+    drop(r);
+    assert!(find("smoke").is_none());
+}
+
+#[test]
+fn flavors() {
+    let (_s1, _r1) = bounded_named::<i32>("flavors-array", 1);
+    let (_s2, _r2) = bounded_named::<i32>("flavors-zero", 0);
+    let (_s3, _r3) = unbounded_named::<i32>("flavors-list");
+
+    assert_eq!(find("flavors-array").unwrap().flavor(), "array");
+    assert_eq!(find("flavors-zero").unwrap().flavor(), "zero");
+    assert_eq!(find("flavors-list").unwrap().flavor(), "list");
+}
+
+#[test]
+fn unnamed() {
+    // This is the only test that creates unnamed channels.
+    let (s, r) = unbounded();
+    s.send_batch(0..3).unwrap();
+
+    let info = live_channels()
+        .into_iter()
+        .find(|c| c.name().is_none())
+        .unwrap();
+    assert_eq!(info.flavor(), "list");
+    assert_eq!(info.len(), 3);
+    assert!(info.to_string().starts_with("<unnamed> (list, len 3)"));
+
+    drop((s, r));
+    assert!(live_channels().iter().all(|c| c.name().is_some()));
+}
+
+#[test]
+fn blocked_sender() {
+    let (s, r) = bounded_named("blocked-sender", 1);
+    s.send(0).unwrap();
+
+    scope(|scope| {
+        let t = scope.spawn(|_| s.send(1).unwrap());
+        thread::sleep(ms(100));
+
+        let info = find("blocked-sender").unwrap();
+        assert_eq!(info.blocked_senders(), &[t.thread().id()]);
+        assert!(info.blocked_receivers().is_empty());
+
+        r.recv().unwrap();
+        r.recv().unwrap();
+    })
+    .unwrap();
+
+    let info = find("blocked-sender").unwrap();
+    assert!(info.blocked_senders().is_empty());
+}
+
+#[test]
+fn blocked_receivers() {
+    let (s, r) = bounded_named::<i32>("blocked-receivers", 0);
+
+    scope(|scope| {
+        let t1 = scope.spawn(|_| r.recv().unwrap());
+        let t2 = scope.spawn(|_| {
+            let mut sel = Select::new();
+            let index = sel.recv(&r);
+            let oper = sel.select();
+            assert_eq!(oper.index(), index);
+            oper.recv(&r).unwrap();
+        });
+        thread::sleep(ms(100));
+
+        let info = find("blocked-receivers").unwrap();
+        assert_eq!(info.blocked_receivers().len(), 2);
+        assert!(info.blocked_receivers().contains(&t1.thread().id()));
+        assert!(info.blocked_receivers().contains(&t2.thread().id()));
+
+        s.send(1).unwrap();
+        s.send(2).unwrap();
+    })
+    .unwrap();
+
+    let info = find("blocked-receivers").unwrap();
+    assert!(info.blocked_receivers().is_empty());
+}
+
+#[test]
+fn display() {
+    let (s, _r) = bounded_named("display", 2);
+    s.send(1).unwrap();
+
+    let info = find("display").unwrap();
+    assert_eq!(
+        info.to_string(),
+        "\"display\" (array, len 1): blocked senders [], blocked receivers []"
+    );
+}
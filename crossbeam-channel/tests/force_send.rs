@@ -0,0 +1,288 @@
+//! Tests for sending with eviction of the oldest message.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, broadcast, priority_bounded, priority_unbounded, unbounded};
+use crossbeam_channel::{Select, TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = bounded(1);
+    assert_eq!(s.force_send(1), Ok(None));
+    assert_eq!(s.force_send(2), Ok(Some(1)));
+    assert_eq!(s.force_send(3), Ok(Some(2)));
+    assert_eq!(r.len(), 1);
+    assert_eq!(r.try_recv(), Ok(3));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn wraparound() {
+    let (s, r) = bounded(3);
+
+    for i in 0..3 {
+        assert_eq!(s.force_send(i), Ok(None));
+    }
+    for i in 3..100 {
+        assert_eq!(s.force_send(i), Ok(Some(i - 3)));
+        assert!(s.is_full());
+
+        if i % 7 == 0 {
+            assert_eq!(r.recv(), Ok(i - 2));
+            assert_eq!(s.force_send(i), Ok(None));
+            assert_eq!(r.try_iter().collect::<Vec<_>>(), [i - 1, i, i]);
+            for j in i - 2..i + 1 {
+                s.send(j).unwrap();
+            }
+        }
+    }
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [97, 98, 99]);
+}
+
+#[test]
+fn lowered_capacity() {
+    let (s, r) = bounded(4);
+    for i in 0..4 {
+        s.send(i).unwrap();
+    }
+    assert!(s.set_capacity(2));
+
+    assert_eq!(s.force_send(4), Ok(Some(0)));
+    assert_eq!(r.len(), 4);
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+
+    assert_eq!(s.force_send(5), Ok(Some(3)));
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [4, 5]);
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = bounded(1);
+    s.send(1).unwrap();
+    drop(r);
+    assert_eq!(s.force_send(2), Err(TrySendError::Disconnected(2)));
+
+    let (s, r) = priority_bounded(1);
+    drop(r);
+    assert_eq!(s.force_send(1), Err(TrySendError::Disconnected(1)));
+
+    let (s, r) = bounded(0);
+    drop(r);
+    assert_eq!(s.force_send(1), Err(TrySendError::Disconnected(1)));
+
+    let (s, r) = unbounded();
+    drop(r);
+    assert_eq!(s.force_send(1), Err(TrySendError::Disconnected(1)));
+}
+
+#[test]
+fn priority() {
+    let (s, r) = priority_bounded(3);
+    s.send_with_priority("a", 2).unwrap();
+    s.send_with_priority("b", 1).unwrap();
+    s.send_with_priority("c", 1).unwrap();
+
+    // The oldest of the messages with the lowest priority is evicted.
+    assert_eq!(s.force_send("d"), Ok(Some("b")));
+    assert_eq!(s.force_send("e"), Ok(Some("d")));
+    assert_eq!(s.force_send("f"), Ok(Some("e")));
+    assert_eq!(r.recv(), Ok("a"));
+    assert_eq!(s.force_send("g"), Ok(None));
+    assert_eq!(s.force_send("h"), Ok(Some("f")));
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), ["c", "g", "h"]);
+
+    let (s, r) = priority_unbounded();
+    for i in 0..10 {
+        assert_eq!(s.force_send(i), Ok(None));
+    }
+    assert_eq!(r.len(), 10);
+}
+
+#[test]
+fn other_flavors() {
+    let (s, r) = unbounded();
+    for i in 0..10 {
+        assert_eq!(s.force_send(i), Ok(None));
+    }
+    assert_eq!(r.len(), 10);
+
+    let (s, _r) = broadcast(2);
+    for i in 0..10 {
+        assert_eq!(s.force_send(i), Ok(None));
+    }
+}
+
+#[test]
+fn zero_capacity() {
+    let (s, r) = bounded(0);
+    assert_eq!(s.force_send(1), Err(TrySendError::Full(1)));
+
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.recv(), Ok(2)));
+
+        loop {
+            match s.force_send(2) {
+                Ok(None) => break,
+                Err(TrySendError::Full(2)) => thread::sleep(ms(10)),
+                res => panic!("{:?}", res),
+            }
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn wakes_receiver() {
+    let (s, r) = bounded(1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Ok(3));
+        });
+
+        thread::sleep(ms(500));
+        assert_eq!(s.force_send(1), Ok(None));
+        thread::sleep(ms(500));
+        assert_eq!(s.force_send(2), Ok(None));
+        assert_eq!(s.force_send(3), Ok(Some(2)));
+    })
+    .unwrap();
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = bounded::<i32>(1);
+    let (s2, r2) = bounded(1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let mut sel = Select::new();
+            sel.recv(&r1);
+            let oper2 = sel.recv(&r2);
+
+            let oper = sel.select();
+            assert_eq!(oper.index(), oper2);
+            assert_eq!(oper.recv(&r2), Ok(1));
+        });
+
+        thread::sleep(ms(500));
+        assert_eq!(s2.force_send(1), Ok(None));
+    })
+    .unwrap();
+
+    drop(s1);
+    assert_eq!(s2.force_send(2), Ok(None));
+    assert_eq!(s2.force_send(3), Ok(Some(2)));
+
+    let mut sel = Select::new();
+    sel.recv(&r2);
+    let oper = sel.select();
+    assert_eq!(oper.recv(&r2), Ok(3));
+}
+
+#[test]
+fn waits_for_peek() {
+    let (s, r) = bounded(2);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            r.peek_with(|x| {
+                thread::sleep(ms(500));
+                assert_eq!(*x, 1);
+            })
+            .unwrap();
+        });
+
+        thread::sleep(ms(100));
+        let start = Instant::now();
+        assert_eq!(s.force_send(3), Ok(Some(1)));
+        assert!(start.elapsed() >= ms(200));
+    })
+    .unwrap();
+
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [2, 3]);
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    for &cap in &[1, 3, 100] {
+        let (s, r) = bounded::<usize>(cap);
+        let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+        let senders = (0..THREADS).map(|_| s.clone()).collect::<Vec<_>>();
+        drop(s);
+
+        scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    while let Ok(i) = r.recv() {
+                        v[i].fetch_add(1, Ordering::SeqCst);
+                    }
+                });
+            }
+            for s in senders {
+                let v = &v;
+                scope.spawn(move |_| {
+                    for i in 0..COUNT {
+                        if let Some(j) = s.force_send(i).unwrap() {
+                            v[j].fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        for c in v {
+            assert_eq!(c.load(Ordering::SeqCst), THREADS);
+        }
+    }
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = bounded(2);
+    for _ in 0..2 {
+        s.force_send(DropCounter).unwrap();
+    }
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+
+    for i in 0..5 {
+        let old = s.force_send(DropCounter).unwrap();
+        assert!(old.is_some());
+        drop(old);
+        assert_eq!(DROPS.load(Ordering::SeqCst), i + 1);
+    }
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 7);
+}
@@ -0,0 +1,247 @@
+//! Tests for futures and streams.
+
+#![cfg(feature = "async")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate futures_core;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{after, bounded, never, tick, unbounded};
+use crossbeam_channel::{RecvError, SendError};
+use crossbeam_utils::thread::scope;
+use futures_core::Stream;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn waker() -> Waker {
+    Arc::new(ThreadWaker(thread::current())).into()
+}
+
+fn block_on<F: Future>(mut fut: F) -> F::Output {
+    let waker = waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = unsafe { Pin::new_unchecked(&mut fut) };
+
+    loop {
+        if let Poll::Ready(res) = fut.as_mut().poll(&mut cx) {
+            return res;
+        }
+        thread::park();
+    }
+}
+
+fn poll_once<F: Future + Unpin>(fut: &mut F) -> Poll<F::Output> {
+    let waker = waker();
+    let mut cx = Context::from_waker(&waker);
+    Pin::new(fut).poll(&mut cx)
+}
+
+fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+    let waker = waker();
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(item) = Pin::new(&mut *stream).poll_next(&mut cx) {
+            return item;
+        }
+        thread::park();
+    }
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    assert_eq!(block_on(s.send_async(7)), Ok(()));
+    assert_eq!(block_on(r.recv_async()), Ok(7));
+    assert_eq!(poll_once(&mut r.recv_async()), Poll::Pending);
+}
+
+#[test]
+fn recv() {
+    for cap in 0..3 {
+        let (s, r) = bounded(cap);
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                assert_eq!(block_on(r.recv_async()), Ok(7));
+                assert_eq!(block_on(r.recv_async()), Ok(8));
+                assert_eq!(block_on(r.recv_async()), Err(RecvError));
+            });
+            scope.spawn(move |_| {
+                thread::sleep(ms(500));
+                s.send(7).unwrap();
+                thread::sleep(ms(100));
+                s.send(8).unwrap();
+            });
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn send() {
+    for cap in 0..3 {
+        let (s, r) = bounded(cap);
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                for i in 0..5 {
+                    assert_eq!(block_on(s.send_async(i)), Ok(()));
+                }
+                thread::sleep(ms(500));
+                assert_eq!(block_on(s.send_async(5)), Err(SendError(5)));
+            });
+            scope.spawn(move |_| {
+                thread::sleep(ms(500));
+                for i in 0..5 {
+                    assert_eq!(r.recv(), Ok(i));
+                }
+            });
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn async_to_async() {
+    const COUNT: usize = 1000;
+
+    for cap in 0..3 {
+        let (s, r) = bounded(cap);
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    assert_eq!(block_on(r.recv_async()), Ok(i));
+                }
+            });
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    assert_eq!(block_on(s.send_async(i)), Ok(()));
+                }
+            });
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn cancel_send() {
+    let (s, r) = bounded(0);
+
+    let mut fut = s.send_async(1);
+    assert_eq!(poll_once(&mut fut), Poll::Pending);
+    drop(fut);
+
+    assert!(r.try_recv().is_err());
+
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.recv(), Ok(2)));
+        scope.spawn(|_| assert_eq!(block_on(s.send_async(2)), Ok(())));
+    })
+    .unwrap();
+}
+
+#[test]
+fn cancel_recv() {
+    for cap in 0..3 {
+        let (s, r) = bounded(cap);
+
+        let mut fut = r.recv_async();
+        assert_eq!(poll_once(&mut fut), Poll::Pending);
+        drop(fut);
+
+        scope(|scope| {
+            scope.spawn(|_| assert_eq!(r.recv(), Ok(1)));
+            scope.spawn(|_| s.send(1).unwrap());
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn zero_delivered_before_poll() {
+    let (s, r) = bounded(0);
+
+    let mut fut = s.send_async(1);
+    assert_eq!(poll_once(&mut fut), Poll::Pending);
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(poll_once(&mut fut), Poll::Ready(Ok(())));
+}
+
+#[test]
+fn stream() {
+    const COUNT: usize = 100;
+
+    let (s, r) = bounded(1);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            let mut stream = r.into_stream();
+            for i in 0..COUNT {
+                assert_eq!(next(&mut stream), Some(i));
+            }
+            assert_eq!(next(&mut stream), None);
+        });
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn timers() {
+    let start = Instant::now();
+    assert!(block_on(after(ms(100)).recv_async()).is_ok());
+    assert!(start.elapsed() >= ms(100));
+
+    let r = tick(ms(50));
+    for _ in 0..3 {
+        assert!(block_on(r.recv_async()).is_ok());
+    }
+    assert!(start.elapsed() >= ms(250));
+
+    assert_eq!(poll_once(&mut never::<i32>().recv_async()), Poll::Pending);
+}
+
+#[test]
+fn panicking_waker() {
+    struct PanickingWaker;
+
+    impl Wake for PanickingWaker {
+        fn wake(self: Arc<Self>) {
+            panic!("wake");
+        }
+    }
+
+    // The timer thread survives a waker that panics when woken up.
+    let waker = Arc::new(PanickingWaker).into();
+    let mut cx = Context::from_waker(&waker);
+    let r = after(ms(10));
+    assert_eq!(Pin::new(&mut r.recv_async()).poll(&mut cx), Poll::Pending);
+    thread::sleep(ms(100));
+
+    let start = Instant::now();
+    assert!(block_on(after(ms(50)).recv_async()).is_ok());
+    assert!(start.elapsed() >= ms(50));
+}
@@ -0,0 +1,292 @@
+//! Tests for channel statistics.
+
+#![cfg(feature = "metrics")]
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, broadcast, never, oneshot, tick, unbounded, watch};
+use crossbeam_channel::{priority_bounded, Select, Stats};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = bounded(4);
+    assert_eq!(s.stats(), Stats::default());
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    s.send(3).unwrap();
+    r.recv().unwrap();
+    r.try_recv().unwrap();
+    s.send(4).unwrap();
+
+    let stats = s.stats();
+    assert_eq!(stats, r.stats());
+    assert_eq!(stats.sends(), 4);
+    assert_eq!(stats.recvs(), 2);
+    assert_eq!(stats.max_len(), 3);
+    assert_eq!(stats.send_wait(), ms(0));
+    assert_eq!(stats.recv_wait(), ms(0));
+    assert_eq!(stats.waiting_senders(), 0);
+    assert_eq!(stats.waiting_receivers(), 0);
+}
+
+#[test]
+fn unbounded_batch() {
+    let (s, r) = unbounded();
+    s.send_batch(0..10).unwrap();
+    s.send(10).unwrap();
+
+    let mut buf = Vec::new();
+    assert_eq!(r.recv_batch(&mut buf, 4), Ok(4));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(4)));
+    assert_eq!(r.try_recv_if(|_| false), Ok(None));
+
+    let stats = r.stats();
+    assert_eq!(stats.sends(), 11);
+    assert_eq!(stats.recvs(), 5);
+    assert_eq!(stats.max_len(), 11);
+}
+
+#[test]
+fn force_send() {
+    let (s, r) = bounded(2);
+    for i in 0..5 {
+        s.force_send(i).unwrap();
+    }
+    assert_eq!(r.recv(), Ok(3));
+
+    let stats = r.stats();
+    assert_eq!(stats.sends(), 5);
+    assert_eq!(stats.recvs(), 1);
+    assert_eq!(stats.max_len(), 2);
+}
+
+#[test]
+fn zero() {
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..3 {
+                s.send(i).unwrap();
+            }
+        });
+
+        thread::sleep(ms(100));
+        assert_eq!(r.try_recv(), Ok(0));
+        assert_eq!(r.recv(), Ok(1));
+
+        select! {
+            recv(r) -> msg => assert_eq!(msg, Ok(2)),
+        }
+    })
+    .unwrap();
+
+    let stats = r.stats();
+    assert_eq!(stats.sends(), 3);
+    assert_eq!(stats.recvs(), 3);
+    assert_eq!(stats.max_len(), 0);
+    assert!(stats.send_wait() >= ms(50));
+}
+
+#[test]
+fn waiting() {
+    let (s, r) = bounded(1);
+    s.send(0).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(1).unwrap());
+        scope.spawn(|_| s.send(2).unwrap());
+
+        thread::sleep(ms(200));
+        let stats = r.stats();
+        assert_eq!(stats.waiting_senders(), 2);
+        assert_eq!(stats.waiting_receivers(), 0);
+
+        for _ in 0..3 {
+            r.recv().unwrap();
+        }
+    })
+    .unwrap();
+
+    let stats = r.stats();
+    assert_eq!(stats.waiting_senders(), 0);
+    assert!(stats.send_wait() >= ms(200));
+
+    let (s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| r.recv().unwrap());
+        scope.spawn(|_| {
+            let mut sel = Select::new();
+            let index = sel.recv(&r);
+            let oper = sel.select();
+            assert_eq!(oper.index(), index);
+            oper.recv(&r).unwrap();
+        });
+
+        thread::sleep(ms(200));
+        assert_eq!(s.stats().waiting_receivers(), 2);
+
+        s.send(1).unwrap();
+        s.send(2).unwrap();
+    })
+    .unwrap();
+
+    let stats = s.stats();
+    assert_eq!(stats.waiting_receivers(), 0);
+    assert_eq!(stats.recvs(), 2);
+    assert!(stats.recv_wait() >= ms(200));
+}
+
+#[test]
+fn select_wait() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(1);
+    let (_s3, r3) = unbounded::<i32>();
+    s2.send(0).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(200));
+            s1.send(1).unwrap();
+        });
+
+        // Only the selected operation's channel is charged for the wait.
+        select! {
+            recv(r1) -> msg => assert_eq!(msg, Ok(1)),
+            recv(r3) -> _ => panic!(),
+        }
+    })
+    .unwrap();
+
+    assert!(r1.stats().recv_wait() >= ms(150));
+    assert_eq!(r3.stats().recv_wait(), Duration::from_secs(0));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(200));
+            r2.recv().unwrap();
+        });
+
+        select! {
+            send(s2, 1) -> res => res.unwrap(),
+            recv(r3) -> _ => panic!(),
+        }
+    })
+    .unwrap();
+
+    assert!(s2.stats().send_wait() >= ms(150));
+    assert_eq!(s2.stats().recv_wait(), Duration::from_secs(0));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(200));
+            s1.send(2).unwrap();
+        });
+
+        let mut sel = Select::new();
+        sel.recv(&r1);
+        sel.recv(&r3);
+        sel.ready();
+    })
+    .unwrap();
+
+    assert!(r1.stats().recv_wait() >= ms(300));
+}
+
+#[test]
+fn broadcast_receivers() {
+    let (s, r1) = broadcast(4);
+    let r2 = r1.clone();
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r1.recv(), Ok(1));
+    assert_eq!(r2.recv(), Ok(1));
+    assert_eq!(r2.recv(), Ok(2));
+
+    let stats = s.stats();
+    assert_eq!(stats.sends(), 2);
+    assert_eq!(stats.recvs(), 3);
+    assert_eq!(stats.max_len(), 2);
+}
+
+#[test]
+fn other_flavors() {
+    let (s, r) = priority_bounded(4);
+    s.send_with_priority(1, 1).unwrap();
+    s.send_with_priority(2, 2).unwrap();
+    assert_eq!(r.recv(), Ok(2));
+    let stats = r.stats();
+    assert_eq!((stats.sends(), stats.recvs(), stats.max_len()), (2, 1, 2));
+
+    let (s, r) = watch(0);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.recv(), Ok(2));
+    let stats = r.stats();
+    assert_eq!((stats.sends(), stats.recvs(), stats.max_len()), (2, 1, 1));
+
+    let (s, r) = oneshot();
+    s.send(1).unwrap();
+    assert_eq!(r.recv(), Ok(1));
+    let stats = r.stats();
+    assert_eq!((stats.sends(), stats.recvs(), stats.max_len()), (1, 1, 1));
+}
+
+#[test]
+fn time_channels() {
+    let r = after(ms(0));
+    r.recv().unwrap();
+    assert_eq!(r.stats(), Stats::default());
+
+    let r = tick(ms(10));
+    r.recv().unwrap();
+    assert_eq!(r.stats(), Stats::default());
+
+    assert_eq!(never::<i32>().stats(), Stats::default());
+}
+
+#[test]
+fn stress() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 10_000;
+
+    for cap in vec![0, 1, 100] {
+        let (s, r) = bounded(cap);
+
+        scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    for i in 0..COUNT {
+                        s.send(i).unwrap();
+                    }
+                });
+                scope.spawn(|_| {
+                    for _ in 0..COUNT {
+                        r.recv().unwrap();
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let stats = r.stats();
+        assert_eq!(stats.sends(), THREADS * COUNT);
+        assert_eq!(stats.recvs(), THREADS * COUNT);
+        assert!(stats.max_len() <= cap);
+        assert_eq!(stats.waiting_senders(), 0);
+        assert_eq!(stats.waiting_receivers(), 0);
+    }
+}
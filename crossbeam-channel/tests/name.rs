@@ -0,0 +1,58 @@
+//! Tests for named channels.
+
+#![cfg(feature = "debug")]
+
+extern crate crossbeam_channel;
+
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, bounded_named, unbounded, unbounded_named};
+
+#[test]
+fn named() {
+    let (s, r) = bounded_named::<i32>("jobs", 4);
+    assert_eq!(s.name(), Some("jobs"));
+    assert_eq!(r.name(), Some("jobs"));
+    assert_eq!(s.capacity(), Some(4));
+
+    let (s, r) = bounded_named::<i32>("rendezvous", 0);
+    assert_eq!(s.name(), Some("rendezvous"));
+    assert_eq!(r.name(), Some("rendezvous"));
+
+    let (s, r) = unbounded_named::<i32>("events");
+    assert_eq!(s.name(), Some("events"));
+    assert_eq!(r.name(), Some("events"));
+    assert_eq!(s.capacity(), None);
+}
+
+#[test]
+fn unnamed() {
+    let (s, r) = bounded::<i32>(4);
+    assert_eq!(s.name(), None);
+    assert_eq!(r.name(), None);
+
+    let (s, r) = unbounded::<i32>();
+    assert_eq!(s.name(), None);
+    assert_eq!(r.name(), None);
+
+    assert_eq!(after(Duration::from_millis(0)).name(), None);
+}
+
+#[test]
+fn clones() {
+    let (s, r) = unbounded_named::<i32>("events");
+    assert_eq!(s.clone().name(), Some("events"));
+    assert_eq!(r.clone().name(), Some("events"));
+    assert_eq!(s.downgrade().upgrade().unwrap().name(), Some("events"));
+}
+
+#[test]
+fn debug() {
+    let (s, r) = bounded_named::<i32>("jobs", 1);
+    assert_eq!(format!("{:?}", s), "Sender { name: \"jobs\", .. }");
+    assert_eq!(format!("{:?}", r), "Receiver { name: \"jobs\", .. }");
+
+    let (s, r) = bounded::<i32>(1);
+    assert_eq!(format!("{:?}", s), "Sender { .. }");
+    assert_eq!(format!("{:?}", r), "Receiver { .. }");
+}
@@ -0,0 +1,273 @@
+//! Tests for the oneshot channel flavor.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{oneshot, unbounded, Select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = oneshot();
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert!(r.is_empty());
+    assert!(!r.is_disconnected());
+
+    s.send(7).unwrap();
+    assert_eq!(r.len(), 1);
+    assert!(r.is_full());
+    assert!(r.is_disconnected());
+
+    assert_eq!(r.try_recv(), Ok(7));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn capacity() {
+    let (_s, r) = oneshot::<()>();
+    assert_eq!(r.capacity(), Some(1));
+    assert!(!r.set_capacity(2));
+}
+
+#[test]
+fn counts() {
+    let (s, r) = oneshot::<i32>();
+    assert_eq!(r.sender_count(), 1);
+    assert_eq!(r.receiver_count(), 1);
+
+    let r2 = r.clone();
+    assert!(r.same_channel(&r2));
+    assert_eq!(r.receiver_count(), 2);
+
+    drop(s);
+    assert_eq!(r.sender_count(), 0);
+}
+
+#[test]
+fn sender_dropped() {
+    let (s, r) = oneshot::<i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+        thread::sleep(ms(500));
+        drop(s);
+    })
+    .unwrap();
+}
+
+#[test]
+fn receivers_dropped() {
+    let (s, r) = oneshot();
+    let r2 = r.clone();
+    drop(r);
+    assert!(!s.is_disconnected());
+    drop(r2);
+    assert!(s.is_disconnected());
+    assert_eq!(s.send(1), Err(SendError(1)));
+}
+
+#[test]
+fn close() {
+    let (s, r) = oneshot();
+    assert!(r.close());
+    assert!(!r.close());
+    assert_eq!(s.send(1), Err(SendError(1)));
+    assert_eq!(r.recv(), Err(RecvError));
+
+    let (s, r) = oneshot();
+    s.send(1).unwrap();
+    assert!(!r.close());
+    assert_eq!(r.recv(), Ok(1));
+}
+
+#[test]
+fn recv() {
+    let (s, r) = oneshot();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.send(7).unwrap();
+        });
+        assert_eq!(r.recv(), Ok(7));
+        assert_eq!(r.recv(), Err(RecvError));
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_timeout() {
+    let (s, r) = oneshot();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            s.send(7).unwrap();
+        });
+        assert_eq!(r.recv_timeout(ms(500)), Err(RecvTimeoutError::Timeout));
+        assert_eq!(r.recv_timeout(ms(1000)), Ok(7));
+        assert_eq!(
+            r.recv_timeout(ms(1000)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    })
+    .unwrap();
+}
+
+#[test]
+fn many_receivers() {
+    const THREADS: usize = 8;
+
+    let (s, r) = oneshot();
+    let hits = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| match r.recv() {
+                Ok(7) => {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                }
+                Ok(_) => unreachable!(),
+                Err(RecvError) => {}
+            });
+        }
+        thread::sleep(ms(500));
+        s.send(7).unwrap();
+    })
+    .unwrap();
+
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn peek() {
+    let (s, r) = oneshot();
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Empty));
+
+    s.send(5).unwrap();
+    assert_eq!(r.peek_with(|x| *x), Ok(5));
+    assert_eq!(r.try_recv_if(|x| *x == 4), Ok(None));
+    assert_eq!(r.len(), 1);
+    assert_eq!(r.try_recv_if(|x| *x == 5), Ok(Some(5)));
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = oneshot::<i32>();
+    let (s2, r2) = oneshot();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s2.send(2).unwrap();
+            drop(s1);
+        });
+
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r1);
+        let oper2 = sel.recv(&r2);
+        let oper = sel.select();
+        match oper.index() {
+            i if i == oper1 => panic!(),
+            i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(2)),
+            _ => unreachable!(),
+        }
+    })
+    .unwrap();
+
+    select! {
+        recv(r1) -> msg => assert_eq!(msg, Err(RecvError)),
+        default(ms(1000)) => panic!(),
+    }
+}
+
+#[test]
+fn select_with_other_flavors() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = oneshot();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s2.send("done").unwrap();
+        });
+
+        select! {
+            recv(r1) -> _ => panic!(),
+            recv(r2) -> msg => assert_eq!(msg, Ok("done")),
+        }
+    })
+    .unwrap();
+
+    drop(s1);
+}
+
+#[test]
+fn stress() {
+    const COUNT: usize = 10_000;
+
+    let (s, r) = unbounded();
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                let (reply, response) = oneshot();
+                s.send((i, reply)).unwrap();
+                assert_eq!(response.recv(), Ok(i * 2));
+            }
+            drop(s);
+        });
+        scope.spawn(|_| {
+            for (i, reply) in r.iter() {
+                reply.send(i * 2).unwrap();
+            }
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = oneshot();
+    s.send(DropCounter).unwrap();
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    let (s, r) = oneshot();
+    drop(r);
+    drop(s.send(DropCounter));
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+
+    let (s, r) = oneshot();
+    s.send(DropCounter).unwrap();
+    drop(r.recv().unwrap());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,799 @@
+//! Tests for peeking and conditional receive operations.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate rand;
+
+use std::panic;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{after, bounded, broadcast, never, priority_unbounded, tick, unbounded};
+use crossbeam_channel::{Select, TryRecvError};
+use crossbeam_utils::thread::scope;
+use rand::{thread_rng, Rng};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn array_smoke() {
+    let (s, r) = bounded(3);
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Empty));
+    assert_eq!(r.try_recv_if(|_| true), Err(TryRecvError::Empty));
+
+    s.send(1).unwrap();
+    assert_eq!(r.peek_with(|x| *x), Ok(1));
+    assert_eq!(r.peek_with(|x| *x + 1), Ok(2));
+    assert_eq!(r.len(), 1);
+
+    assert_eq!(r.try_recv_if(|x| *x == 2), Ok(None));
+    assert_eq!(r.try_recv_if(|x| *x == 1), Ok(Some(1)));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn list_smoke() {
+    let (s, r) = unbounded();
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Empty));
+    assert_eq!(r.try_recv_if(|_| true), Err(TryRecvError::Empty));
+
+    s.send(1).unwrap();
+    assert_eq!(r.peek_with(|x| *x), Ok(1));
+    assert_eq!(r.peek_with(|x| *x + 1), Ok(2));
+    assert_eq!(r.len(), 1);
+
+    assert_eq!(r.try_recv_if(|x| *x == 2), Ok(None));
+    assert_eq!(r.try_recv_if(|x| *x == 1), Ok(Some(1)));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn priority_smoke() {
+    let (s, r) = priority_unbounded();
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Empty));
+    assert_eq!(r.try_recv_if(|_| true), Err(TryRecvError::Empty));
+
+    s.send(1).unwrap();
+    assert_eq!(r.peek_with(|x| *x), Ok(1));
+    assert_eq!(r.peek_with(|x| *x + 1), Ok(2));
+    assert_eq!(r.len(), 1);
+
+    assert_eq!(r.try_recv_if(|x| *x == 2), Ok(None));
+    assert_eq!(r.try_recv_if(|x| *x == 1), Ok(Some(1)));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn broadcast_smoke() {
+    let (s, r) = broadcast(100);
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Empty));
+    assert_eq!(r.try_recv_if(|_| true), Err(TryRecvError::Empty));
+
+    s.send(1).unwrap();
+    assert_eq!(r.peek_with(|x| *x), Ok(1));
+    assert_eq!(r.peek_with(|x| *x + 1), Ok(2));
+    assert_eq!(r.len(), 1);
+
+    assert_eq!(r.try_recv_if(|x| *x == 2), Ok(None));
+    assert_eq!(r.try_recv_if(|x| *x == 1), Ok(Some(1)));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn array_rejected_message_stays_first() {
+    let (s, r) = bounded(3);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    for _ in 0..10 {
+        assert_eq!(r.try_recv_if(|_| false), Ok(None));
+    }
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(2)));
+}
+
+#[test]
+fn list_rejected_message_stays_first() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    for _ in 0..10 {
+        assert_eq!(r.try_recv_if(|_| false), Ok(None));
+    }
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(2)));
+}
+
+#[test]
+fn priority_rejected_message_stays_first() {
+    let (s, r) = priority_unbounded();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    for _ in 0..10 {
+        assert_eq!(r.try_recv_if(|_| false), Ok(None));
+    }
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(2)));
+}
+
+#[test]
+fn broadcast_rejected_message_stays_first() {
+    let (s, r) = broadcast(100);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    for _ in 0..10 {
+        assert_eq!(r.try_recv_if(|_| false), Ok(None));
+    }
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(2)));
+}
+
+#[test]
+fn zero_disconnected() {
+    let (s, r) = bounded::<usize>(0);
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Empty));
+    drop(s);
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Disconnected));
+    assert_eq!(r.try_recv_if(|_| true), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn array_disconnected() {
+    let (s, r) = bounded(3);
+    s.send(1).unwrap();
+    drop(s);
+
+    assert_eq!(r.peek_with(|x| *x), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(1)));
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Disconnected));
+    assert_eq!(r.try_recv_if(|_| true), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn list_disconnected() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+    drop(s);
+
+    assert_eq!(r.peek_with(|x| *x), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(1)));
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Disconnected));
+    assert_eq!(r.try_recv_if(|_| true), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn priority_disconnected() {
+    let (s, r) = priority_unbounded();
+    s.send(1).unwrap();
+    drop(s);
+
+    assert_eq!(r.peek_with(|x| *x), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(1)));
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Disconnected));
+    assert_eq!(r.try_recv_if(|_| true), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn broadcast_disconnected() {
+    let (s, r) = broadcast(100);
+    s.send(1).unwrap();
+    drop(s);
+
+    assert_eq!(r.peek_with(|x| *x), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(1)));
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Disconnected));
+    assert_eq!(r.try_recv_if(|_| true), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn priority() {
+    let (s, r) = priority_unbounded();
+    s.send_with_priority("a", 1).unwrap();
+    s.send_with_priority("b", 5).unwrap();
+    s.send_with_priority("c", 5).unwrap();
+
+    assert_eq!(r.peek_with(|x| *x), Ok("b"));
+    assert_eq!(r.try_recv_if(|x| *x == "c"), Ok(None));
+    assert_eq!(r.try_recv_if(|x| *x == "b"), Ok(Some("b")));
+    assert_eq!(r.peek_with(|x| *x), Ok("c"));
+}
+
+#[test]
+fn broadcast_receivers() {
+    let (s, r1) = broadcast(2);
+    let r2 = r1.clone();
+
+    s.send(1).unwrap();
+    assert_eq!(r1.try_recv_if(|x| *x == 1), Ok(Some(1)));
+    assert_eq!(r2.peek_with(|x| *x), Ok(1));
+    assert_eq!(r1.peek_with(|x| *x), Err(TryRecvError::Empty));
+
+    s.send(2).unwrap();
+    s.send(3).unwrap();
+    // `r2` has fallen behind and skips over the first message.
+    assert_eq!(r2.peek_with(|x| *x), Ok(2));
+    assert_eq!(r2.try_recv_if(|x| *x == 3), Ok(None));
+    assert_eq!(r2.try_recv_if(|x| *x == 2), Ok(Some(2)));
+    assert_eq!(r1.try_recv_if(|x| *x == 2), Ok(Some(2)));
+}
+
+#[test]
+fn timers() {
+    let r = after(ms(100));
+    assert_eq!(r.peek_with(|_| ()), Err(TryRecvError::Empty));
+    thread::sleep(ms(200));
+
+    let when = r.peek_with(|when| *when).unwrap();
+    assert_eq!(r.try_recv_if(|_| false), Ok(None));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(when)));
+    assert_eq!(r.peek_with(|_| ()), Err(TryRecvError::Empty));
+
+    let start = Instant::now();
+    let r = tick(ms(100));
+    thread::sleep(ms(150));
+    let when = r.peek_with(|when| *when).unwrap();
+    assert!(when > start);
+    assert_eq!(r.try_recv_if(|when| *when > Instant::now()), Ok(None));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(when)));
+
+    let r = never::<usize>();
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Empty));
+    assert_eq!(r.try_recv_if(|_| true), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn wraparound() {
+    let (s, r) = bounded(3);
+
+    for i in 0..100 {
+        s.send(i).unwrap();
+        s.send(i).unwrap();
+        assert_eq!(r.try_recv_if(|_| false), Ok(None));
+        assert_eq!(r.try_recv_if(|x| *x == i), Ok(Some(i)));
+        assert_eq!(r.peek_with(|x| *x), Ok(i));
+        assert_eq!(r.recv(), Ok(i));
+    }
+    assert!(r.is_empty());
+}
+
+#[test]
+fn block_boundaries() {
+    let (s, r) = unbounded();
+
+    for i in 0..1000 {
+        s.send(i).unwrap();
+    }
+    for i in 0..1000 {
+        assert_eq!(r.peek_with(|x| *x), Ok(i));
+        assert_eq!(r.try_recv_if(|_| false), Ok(None));
+        if i % 2 == 0 {
+            assert_eq!(r.try_recv_if(|_| true), Ok(Some(i)));
+        } else {
+            assert_eq!(r.recv(), Ok(i));
+        }
+        assert_eq!(r.len(), 999 - i);
+    }
+
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(7)));
+    assert_eq!(r.try_recv_if(|_| true), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn array_panic_unlocks() {
+    let (s, r) = bounded(3);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let _ = r.peek_with(|_| panic!("peek"));
+    }));
+    assert!(res.is_err());
+
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let _ = r.try_recv_if(|_| panic!("try_recv_if"));
+    }));
+    assert!(res.is_err());
+
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(2)));
+    s.send(3).unwrap();
+    assert_eq!(r.recv(), Ok(3));
+}
+
+#[test]
+fn list_panic_unlocks() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let _ = r.peek_with(|_| panic!("peek"));
+    }));
+    assert!(res.is_err());
+
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let _ = r.try_recv_if(|_| panic!("try_recv_if"));
+    }));
+    assert!(res.is_err());
+
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(2)));
+    s.send(3).unwrap();
+    assert_eq!(r.recv(), Ok(3));
+}
+
+#[test]
+fn priority_panic_unlocks() {
+    let (s, r) = priority_unbounded();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let _ = r.peek_with(|_| panic!("peek"));
+    }));
+    assert!(res.is_err());
+
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let _ = r.try_recv_if(|_| panic!("try_recv_if"));
+    }));
+    assert!(res.is_err());
+
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(2)));
+    s.send(3).unwrap();
+    assert_eq!(r.recv(), Ok(3));
+}
+
+#[test]
+fn broadcast_panic_unlocks() {
+    let (s, r) = broadcast(100);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let _ = r.peek_with(|_| panic!("peek"));
+    }));
+    assert!(res.is_err());
+
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let _ = r.try_recv_if(|_| panic!("try_recv_if"));
+    }));
+    assert!(res.is_err());
+
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv_if(|_| true), Ok(Some(2)));
+    s.send(3).unwrap();
+    assert_eq!(r.recv(), Ok(3));
+}
+
+#[test]
+fn other_receivers_wait() {
+    for (s, r) in vec![bounded(10), unbounded()] {
+        s.send(1).unwrap();
+        s.send(2).unwrap();
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                let msg = r.try_recv_if(|x| {
+                    thread::sleep(ms(500));
+                    *x == 1
+                });
+                assert_eq!(msg, Ok(Some(1)));
+            });
+            scope.spawn(|_| {
+                thread::sleep(ms(100));
+                assert_eq!(r.recv(), Ok(2));
+            });
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn senders_not_blocked() {
+    for (s, r) in vec![bounded(10), unbounded()] {
+        s.send(0).unwrap();
+
+        r.peek_with(|_| {
+            for i in 1..5 {
+                s.send(i).unwrap();
+            }
+        })
+        .unwrap();
+
+        assert_eq!(r.try_iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    }
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    for (s, r) in vec![
+        bounded::<usize>(3),
+        bounded(100),
+        unbounded(),
+        priority_unbounded(),
+    ] {
+        let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+        let senders = (0..THREADS).map(|_| s.clone()).collect::<Vec<_>>();
+        drop(s);
+
+        scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    let mut rng = thread_rng();
+                    loop {
+                        let res = match rng.gen_range(0, 4) {
+                            0 => r.try_recv_if(|_| rng.gen()),
+                            1 => r.peek_with(|_| None),
+                            2 => {
+                                let mut sel = Select::new();
+                                sel.peek(&r);
+                                sel.select().recv_if(&r, |_| rng.gen())
+                            }
+                            _ => r.recv().map(Some).map_err(|_| TryRecvError::Disconnected),
+                        };
+                        match res {
+                            Ok(Some(i)) => {
+                                v[i].fetch_add(1, Ordering::SeqCst);
+                            }
+                            Ok(None) | Err(TryRecvError::Empty) => {}
+                            Err(_) => break,
+                        }
+                    }
+                });
+            }
+            for s in senders {
+                scope.spawn(move |_| {
+                    for i in 0..COUNT {
+                        s.send(i).unwrap();
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        for c in v {
+            assert_eq!(c.load(Ordering::SeqCst), THREADS);
+        }
+    }
+}
+
+#[test]
+fn select_ready() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = bounded(1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            s1.send(1).unwrap();
+            thread::sleep(ms(500));
+            s2.send(2).unwrap();
+        });
+
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r1);
+        sel.recv(&r2);
+
+        let msg = loop {
+            let index = sel.ready();
+            let r = if index == oper1 { &r1 } else { &r2 };
+            if let Ok(Some(msg)) = r.try_recv_if(|x| *x == 2) {
+                break msg;
+            }
+            sel.remove(index);
+        };
+
+        assert_eq!(msg, 2);
+        assert_eq!(r1.try_recv(), Ok(1));
+    })
+    .unwrap();
+}
+
+#[test]
+fn array_select_peek() {
+    let (s, r) = bounded(3);
+    let mut sel = Select::new();
+    let oper = sel.peek(&r);
+    assert!(sel.try_select().is_err());
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    for _ in 0..10 {
+        let selected = sel.select();
+        assert_eq!(selected.index(), oper);
+        assert_eq!(selected.recv_if(&r, |x| *x == 2), Ok(None));
+    }
+    assert_eq!(sel.select().recv_if(&r, |x| *x == 1), Ok(Some(1)));
+    assert_eq!(r.peek_with(|x| *x), Ok(2));
+    assert_eq!(sel.select().recv_if(&r, |_| true), Ok(Some(2)));
+
+    drop(s);
+    assert_eq!(
+        sel.select().recv_if(&r, |_| true),
+        Err(TryRecvError::Disconnected)
+    );
+}
+
+#[test]
+fn list_select_peek() {
+    let (s, r) = unbounded();
+    let mut sel = Select::new();
+    let oper = sel.peek(&r);
+    assert!(sel.try_select().is_err());
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    for _ in 0..10 {
+        let selected = sel.select();
+        assert_eq!(selected.index(), oper);
+        assert_eq!(selected.recv_if(&r, |x| *x == 2), Ok(None));
+    }
+    assert_eq!(sel.select().recv_if(&r, |x| *x == 1), Ok(Some(1)));
+    assert_eq!(r.peek_with(|x| *x), Ok(2));
+    assert_eq!(sel.select().recv_if(&r, |_| true), Ok(Some(2)));
+
+    drop(s);
+    assert_eq!(
+        sel.select().recv_if(&r, |_| true),
+        Err(TryRecvError::Disconnected)
+    );
+}
+
+#[test]
+fn priority_select_peek() {
+    let (s, r) = priority_unbounded();
+    let mut sel = Select::new();
+    let oper = sel.peek(&r);
+    assert!(sel.try_select().is_err());
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    for _ in 0..10 {
+        let selected = sel.select();
+        assert_eq!(selected.index(), oper);
+        assert_eq!(selected.recv_if(&r, |x| *x == 2), Ok(None));
+    }
+    assert_eq!(sel.select().recv_if(&r, |x| *x == 1), Ok(Some(1)));
+    assert_eq!(r.peek_with(|x| *x), Ok(2));
+    assert_eq!(sel.select().recv_if(&r, |_| true), Ok(Some(2)));
+
+    drop(s);
+    assert_eq!(
+        sel.select().recv_if(&r, |_| true),
+        Err(TryRecvError::Disconnected)
+    );
+}
+
+#[test]
+fn broadcast_select_peek() {
+    let (s, r) = broadcast(100);
+    let mut sel = Select::new();
+    let oper = sel.peek(&r);
+    assert!(sel.try_select().is_err());
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    for _ in 0..10 {
+        let selected = sel.select();
+        assert_eq!(selected.index(), oper);
+        assert_eq!(selected.recv_if(&r, |x| *x == 2), Ok(None));
+    }
+    assert_eq!(sel.select().recv_if(&r, |x| *x == 1), Ok(Some(1)));
+    assert_eq!(r.peek_with(|x| *x), Ok(2));
+    assert_eq!(sel.select().recv_if(&r, |_| true), Ok(Some(2)));
+
+    drop(s);
+    assert_eq!(
+        sel.select().recv_if(&r, |_| true),
+        Err(TryRecvError::Disconnected)
+    );
+}
+
+#[test]
+fn array_receive_while_selected() {
+    let (s, r) = bounded(3);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    s.send(3).unwrap();
+
+    let mut sel = Select::new();
+    sel.peek(&r);
+
+    // Selecting the operation doesn't keep other receivers from taking the message.
+    let oper = sel.select();
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.try_recv(), Ok(1)));
+    })
+    .unwrap();
+    assert_eq!(oper.recv_if(&r, |_| true), Err(TryRecvError::Empty));
+
+    // Not even on the thread that selected it.
+    let oper = sel.select();
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(oper.recv_if(&r, |_| true), Err(TryRecvError::Empty));
+
+    let oper = sel.select();
+    assert_eq!(r.try_recv(), Ok(3));
+    drop(s);
+    assert_eq!(oper.recv_if(&r, |_| true), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn list_receive_while_selected() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    s.send(3).unwrap();
+
+    let mut sel = Select::new();
+    sel.peek(&r);
+
+    // Selecting the operation doesn't keep other receivers from taking the message.
+    let oper = sel.select();
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.try_recv(), Ok(1)));
+    })
+    .unwrap();
+    assert_eq!(oper.recv_if(&r, |_| true), Err(TryRecvError::Empty));
+
+    // Not even on the thread that selected it.
+    let oper = sel.select();
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(oper.recv_if(&r, |_| true), Err(TryRecvError::Empty));
+
+    let oper = sel.select();
+    assert_eq!(r.try_recv(), Ok(3));
+    drop(s);
+    assert_eq!(oper.recv_if(&r, |_| true), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn select_peek_recv_any() {
+    let (s1, r1) = bounded::<usize>(3);
+    let (s2, r2) = unbounded::<usize>();
+    let mut sel = Select::new();
+    let oper1 = sel.peek(&r1);
+    let oper2 = sel.peek(&r2);
+
+    s1.send(1).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv_any::<usize>(), Ok(1));
+
+    s2.send(2).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv_any::<usize>(), Ok(2));
+    assert!(sel.try_select().is_err());
+}
+
+#[test]
+fn select_peek_blocking() {
+    let (s1, r1) = bounded(1);
+    let (s2, r2) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s1.send(1).unwrap();
+            thread::sleep(ms(100));
+            s2.send(2).unwrap();
+        });
+
+        let mut sel = Select::new();
+        let oper1 = sel.peek(&r1);
+        let oper2 = sel.peek(&r2);
+
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv_if(&r1, |_| false), Ok(None));
+        sel.remove(oper1);
+
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper2);
+        assert_eq!(oper.recv_if(&r2, |x| *x == 2), Ok(Some(2)));
+        assert_eq!(r1.try_recv(), Ok(1));
+    })
+    .unwrap();
+
+    let r = after(ms(100));
+    let mut sel = Select::new();
+    sel.peek(&r);
+    let start = Instant::now();
+    let when = sel.select().recv_if(&r, |_| true).unwrap().unwrap();
+    assert!(when >= start);
+    assert!(Instant::now() - start >= ms(100));
+}
+
+#[test]
+fn select_peek_wakes_receivers() {
+    let (s, r) = bounded(1);
+
+    scope(|scope| {
+        // This receiver rejects every message, which must not keep the other one from waking up.
+        scope.spawn(|_| {
+            let mut sel = Select::new();
+            sel.peek(&r);
+            while let Ok(None) = sel.select().recv_if(&r, |_| false) {}
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            s.send(1).unwrap();
+        });
+
+        assert_eq!(r.recv(), Ok(1));
+    })
+    .unwrap();
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn reentrant_receive_panics() {
+    for (s, r) in vec![bounded(3), unbounded()] {
+        s.send(1).unwrap();
+
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let _ = r.peek_with(|_| r.try_recv());
+        }));
+        assert!(res.is_err());
+
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let _ = r.try_recv_if(|_| r.try_recv_if(|_| true).is_ok());
+        }));
+        assert!(res.is_err());
+
+        assert_eq!(r.try_recv(), Ok(1));
+    }
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    for &cap in &[Some(3), None] {
+        DROPS.store(0, Ordering::SeqCst);
+        let (s, r) = match cap {
+            Some(cap) => bounded(cap),
+            None => unbounded(),
+        };
+
+        for _ in 0..3 {
+            s.send(DropCounter).unwrap();
+        }
+
+        r.peek_with(|_| ()).unwrap();
+        assert_eq!(r.try_recv_if(|_| false), Ok(None));
+        assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+
+        drop(r.try_recv_if(|_| true).unwrap());
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+        drop(s);
+        drop(r);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+    }
+}
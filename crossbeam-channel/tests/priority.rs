@@ -0,0 +1,321 @@
+//! Tests for the priority channel flavor.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate rand;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{priority_bounded, priority_unbounded, Select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
+use crossbeam_utils::thread::scope;
+use rand::{thread_rng, Rng};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = priority_bounded(1);
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+
+    s.send_with_priority(8, 3).unwrap();
+    assert_eq!(r.recv(), Ok(8));
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    priority_bounded::<i32>(0);
+}
+
+#[test]
+fn capacity() {
+    for i in 1..10 {
+        let (s, r) = priority_bounded::<()>(i);
+        assert_eq!(s.capacity(), Some(i));
+        assert_eq!(r.capacity(), Some(i));
+    }
+
+    let (s, r) = priority_unbounded::<()>();
+    assert_eq!(s.capacity(), None);
+    assert_eq!(r.capacity(), None);
+}
+
+#[test]
+fn order() {
+    let (s, r) = priority_unbounded();
+
+    s.send_with_priority("a", 1).unwrap();
+    s.send_with_priority("b", 0).unwrap();
+    s.send_with_priority("c", 2).unwrap();
+    s.send_with_priority("d", 1).unwrap();
+    s.send("e").unwrap();
+    s.send_with_priority("f", 2).unwrap();
+
+    let v: Vec<_> = r.try_iter().collect();
+    assert_eq!(v, ["c", "f", "a", "d", "b", "e"]);
+}
+
+#[test]
+fn fifo_within_priority() {
+    const COUNT: usize = 1000;
+
+    let (s, r) = priority_unbounded();
+    let mut rng = thread_rng();
+
+    for i in 0..COUNT {
+        let p = rng.gen_range(0, 4);
+        s.send_with_priority((p, i), p).unwrap();
+    }
+
+    let mut last = None;
+    for (p, i) in r.try_iter() {
+        if let Some((lp, li)) = last {
+            assert!(p < lp || (p == lp && i > li));
+        }
+        last = Some((p, i));
+    }
+}
+
+#[test]
+fn len_empty_full() {
+    let (s, r) = priority_bounded(2);
+
+    assert_eq!(s.len(), 0);
+    assert_eq!(s.is_empty(), true);
+    assert_eq!(s.is_full(), false);
+
+    s.send_with_priority((), 1).unwrap();
+    s.send(()).unwrap();
+
+    assert_eq!(r.len(), 2);
+    assert_eq!(r.is_empty(), false);
+    assert_eq!(r.is_full(), true);
+
+    r.recv().unwrap();
+    assert_eq!(s.len(), 1);
+    assert_eq!(s.is_full(), false);
+}
+
+#[test]
+fn try_send() {
+    let (s, r) = priority_bounded(1);
+
+    assert_eq!(s.try_send(1), Ok(()));
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(r.recv(), Ok(1));
+
+    drop(r);
+    assert_eq!(s.try_send(3), Err(TrySendError::Disconnected(3)));
+}
+
+#[test]
+fn send_timeout() {
+    let (s, r) = priority_bounded(1);
+
+    s.send(1).unwrap();
+    assert_eq!(
+        s.send_timeout(2, ms(100)),
+        Err(SendTimeoutError::Timeout(2))
+    );
+
+    drop(r);
+    assert_eq!(
+        s.send_timeout(3, ms(100)),
+        Err(SendTimeoutError::Disconnected(3))
+    );
+    assert_eq!(s.send_with_priority(4, 1), Err(SendError(4)));
+}
+
+#[test]
+fn try_send_with_priority() {
+    let (s, r) = priority_bounded(2);
+
+    assert_eq!(s.try_send_with_priority(1, 0), Ok(()));
+    assert_eq!(s.try_send_with_priority(2, 3), Ok(()));
+    assert_eq!(s.try_send_with_priority(3, 7), Err(TrySendError::Full(3)));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(s.try_send_with_priority(3, 7), Ok(()));
+    assert_eq!(r.recv(), Ok(3));
+    assert_eq!(r.recv(), Ok(1));
+
+    drop(r);
+    assert_eq!(
+        s.try_send_with_priority(4, 1),
+        Err(TrySendError::Disconnected(4))
+    );
+}
+
+#[test]
+fn send_timeout_with_priority() {
+    let (s, r) = priority_bounded(2);
+
+    s.send(1).unwrap();
+    s.send_timeout_with_priority(2, 5, ms(100)).unwrap();
+    assert_eq!(
+        s.send_timeout_with_priority(3, 9, ms(100)),
+        Err(SendTimeoutError::Timeout(3))
+    );
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            assert_eq!(r.recv(), Ok(2));
+        });
+        assert_eq!(s.send_timeout_with_priority(3, 9, ms(1000)), Ok(()));
+    })
+    .unwrap();
+    assert_eq!(r.recv(), Ok(3));
+    assert_eq!(r.recv(), Ok(1));
+
+    drop(r);
+    assert_eq!(
+        s.send_timeout_with_priority(4, 1, ms(100)),
+        Err(SendTimeoutError::Disconnected(4))
+    );
+}
+
+#[test]
+fn send_blocks_when_full() {
+    let (s, r) = priority_bounded(1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            s.send_with_priority(1, 0).unwrap();
+            s.send_with_priority(2, 9).unwrap();
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Ok(2));
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_after_disconnect() {
+    let (s, r) = priority_unbounded();
+
+    s.send_with_priority(1, 0).unwrap();
+    s.send_with_priority(2, 1).unwrap();
+    drop(s);
+
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn disconnect_wakes_receiver() {
+    let (s, r) = priority_unbounded::<()>();
+
+    scope(|scope| {
+        scope.spawn(move |_| assert_eq!(r.recv(), Err(RecvError)));
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = priority_bounded::<usize>(3);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let n = r.recv().unwrap();
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    s.send_with_priority(i, (i % 3) as u32).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = priority_bounded(1);
+    let (s2, r2) = priority_unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s1.send_with_priority(1, 2).unwrap();
+        });
+
+        select! {
+            recv(r1) -> v => assert_eq!(v, Ok(1)),
+            recv(r2) -> _ => panic!(),
+        }
+    })
+    .unwrap();
+
+    select! {
+        send(s1, 2) -> res => res.unwrap(),
+    }
+    s2.send_with_priority(3, 1).unwrap();
+
+    let mut sel = Select::new();
+    sel.send(&s1);
+    assert!(sel.try_select().is_err());
+
+    assert_eq!(r1.recv(), Ok(2));
+    assert_eq!(r2.recv(), Ok(3));
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = priority_unbounded();
+
+    for i in 0..10 {
+        s.send_with_priority(DropCounter, i).unwrap();
+    }
+    drop(r.recv().unwrap());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 10);
+}
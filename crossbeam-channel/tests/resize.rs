@@ -0,0 +1,395 @@
+//! Tests for changing the capacity of bounded channels.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate rand;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Barrier;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, broadcast, never, priority_unbounded, unbounded, Select};
+use crossbeam_channel::{RecvError, SendError, TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+use rand::{thread_rng, Rng};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = bounded(2);
+    assert!(s.set_capacity(5));
+    assert_eq!(s.capacity(), Some(5));
+    assert_eq!(r.capacity(), Some(5));
+
+    for i in 0..5 {
+        s.try_send(i).unwrap();
+    }
+    assert_eq!(s.try_send(5), Err(TrySendError::Full(5)));
+    assert!(s.is_full());
+    assert_eq!(r.len(), 5);
+
+    for i in 0..5 {
+        assert_eq!(r.try_recv(), Ok(i));
+    }
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    let (s, _r) = bounded::<i32>(1);
+    s.set_capacity(0);
+}
+
+#[test]
+fn unsupported() {
+    let (s, r) = unbounded::<i32>();
+    assert!(!s.set_capacity(1));
+    assert!(!r.set_capacity(1));
+    assert_eq!(s.capacity(), None);
+
+    let (s, r) = bounded::<i32>(0);
+    assert!(!s.set_capacity(1));
+    assert!(!r.set_capacity(1));
+    assert_eq!(s.capacity(), Some(0));
+
+    let (s, _r) = priority_unbounded::<i32>();
+    assert!(!s.set_capacity(1));
+
+    let (s, _r) = broadcast::<i32>(2);
+    assert!(!s.set_capacity(1));
+    assert_eq!(s.capacity(), Some(2));
+
+    assert!(!never::<i32>().set_capacity(1));
+}
+
+#[test]
+fn grow_preserves_order() {
+    let (s, r) = bounded(3);
+
+    // Move the head and tail away from the start of the buffer.
+    for i in 0..5 {
+        s.send(i).unwrap();
+        assert_eq!(r.recv(), Ok(i));
+    }
+    for i in 0..3 {
+        s.send(i).unwrap();
+    }
+
+    assert!(r.set_capacity(7));
+    for i in 3..7 {
+        s.try_send(i).unwrap();
+    }
+    assert_eq!(s.try_send(7), Err(TrySendError::Full(7)));
+
+    for i in 0..7 {
+        assert_eq!(r.recv(), Ok(i));
+    }
+    assert!(r.is_empty());
+}
+
+#[test]
+fn shrink_keeps_messages() {
+    let (s, r) = bounded(4);
+    for i in 0..4 {
+        s.send(i).unwrap();
+    }
+
+    assert!(s.set_capacity(2));
+    assert_eq!(r.len(), 4);
+    assert!(s.is_full());
+    assert_eq!(s.try_send(4), Err(TrySendError::Full(4)));
+
+    assert_eq!(r.recv(), Ok(0));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(s.try_send(4), Err(TrySendError::Full(4)));
+
+    assert_eq!(r.recv(), Ok(2));
+    s.try_send(4).unwrap();
+    assert_eq!(s.try_send(5), Err(TrySendError::Full(5)));
+
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [3, 4]);
+}
+
+#[test]
+fn shrink_then_grow() {
+    let (s, r) = bounded(8);
+    for i in 0..8 {
+        s.send(i).unwrap();
+    }
+
+    assert!(s.set_capacity(1));
+    assert!(s.set_capacity(8));
+    assert_eq!(s.try_send(8), Err(TrySendError::Full(8)));
+
+    assert!(s.set_capacity(10));
+    s.send(8).unwrap();
+    s.send(9).unwrap();
+    assert_eq!(
+        r.try_iter().collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn grow_wakes_senders() {
+    let (s, r) = bounded(1);
+    s.send(0).unwrap();
+
+    scope(|scope| {
+        for i in 1..4 {
+            let s = s.clone();
+            scope.spawn(move |_| s.send(i).unwrap());
+        }
+
+        thread::sleep(ms(500));
+        assert_eq!(r.len(), 1);
+        assert!(r.set_capacity(4));
+    })
+    .unwrap();
+
+    assert_eq!(r.len(), 4);
+    let mut v = r.try_iter().collect::<Vec<_>>();
+    v.sort();
+    assert_eq!(v, [0, 1, 2, 3]);
+}
+
+#[test]
+fn shrink_blocks_senders() {
+    let (s, r) = bounded(3);
+    s.send(0).unwrap();
+    s.send(1).unwrap();
+    assert!(s.set_capacity(1));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            s.send(2).unwrap();
+        });
+
+        thread::sleep(ms(500));
+        assert_eq!(r.recv(), Ok(0));
+        thread::sleep(ms(500));
+        assert_eq!(r.len(), 1);
+        assert_eq!(r.recv(), Ok(1));
+        assert_eq!(r.recv(), Ok(2));
+    })
+    .unwrap();
+}
+
+#[test]
+fn shrink_concurrent_senders() {
+    const THREADS: usize = 8;
+    const CAP: usize = 16;
+
+    for _ in 0..200 {
+        let (s, r) = bounded(64);
+        for i in 0..CAP - 1 {
+            s.send(i).unwrap();
+        }
+        assert!(s.set_capacity(CAP));
+
+        // Only one message fits, no matter how many senders race for it while the old queue
+        // still holds messages.
+        let barrier = Barrier::new(THREADS);
+        scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    barrier.wait();
+                    for i in 0..10 {
+                        let _ = s.try_send(i);
+                        assert!(r.len() <= r.capacity().unwrap());
+                    }
+                });
+            }
+        })
+        .unwrap();
+        assert_eq!(r.len(), CAP);
+
+        // Receiving from the old queue makes room for exactly as many new messages.
+        for _ in 0..4 {
+            r.recv().unwrap();
+        }
+        scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    for i in 0..10 {
+                        let _ = s.try_send(i);
+                    }
+                });
+            }
+        })
+        .unwrap();
+        assert_eq!(r.len(), CAP);
+    }
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = bounded(2);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    drop(s);
+
+    assert!(r.set_capacity(10));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv(), Err(RecvError));
+
+    let (s, r) = bounded(1);
+    drop(r);
+    assert!(s.set_capacity(4));
+    assert_eq!(s.send(1), Err(SendError(1)));
+}
+
+#[test]
+fn disconnect_while_resizing() {
+    for _ in 0..1000 {
+        let (s, r) = bounded(1);
+
+        scope(|scope| {
+            scope.spawn(|_| s.set_capacity(2));
+            scope.spawn(move |_| drop(r));
+        })
+        .unwrap();
+
+        assert_eq!(s.send(1), Err(SendError(1)));
+        assert!(s.is_disconnected());
+    }
+}
+
+#[test]
+fn peek_across_resize() {
+    let (s, r) = bounded(2);
+    s.send(1).unwrap();
+
+    let msg = r.peek_with(|x| {
+        s.send(2).unwrap();
+        *x
+    });
+    assert_eq!(msg, Ok(1));
+
+    assert!(s.set_capacity(3));
+    s.send(3).unwrap();
+    assert_eq!(r.try_recv_if(|x| *x == 1), Ok(Some(1)));
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [2, 3]);
+}
+
+#[test]
+fn pending_operations() {
+    let (s, r) = bounded(2);
+    s.send(1).unwrap();
+
+    // Reserve a slot for receiving and another one for sending, then change the capacity before
+    // completing either operation.
+    let mut sel = Select::new();
+    sel.recv(&r);
+    let recv = sel.select();
+    let mut sel = Select::new();
+    sel.send(&s);
+    let send = sel.select();
+
+    assert!(s.set_capacity(4));
+    assert!(r.set_capacity(1));
+    assert_eq!(recv.recv(&r), Ok(1));
+    assert_eq!(send.send(&s, 2), Ok(()));
+
+    assert_eq!(s.try_send(3), Err(TrySendError::Full(3)));
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [2]);
+    s.send(3).unwrap();
+    assert_eq!(r.recv(), Ok(3));
+}
+
+#[test]
+fn force_send_across_resize() {
+    let (s, r) = bounded(2);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    assert!(s.set_capacity(3));
+    assert_eq!(s.force_send(3), Ok(None));
+    assert_eq!(s.force_send(4), Ok(Some(1)));
+
+    assert!(s.set_capacity(1));
+    assert_eq!(s.force_send(5), Ok(Some(2)));
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [3, 4, 5]);
+
+    assert_eq!(s.force_send(6), Ok(None));
+    assert_eq!(s.force_send(7), Ok(Some(6)));
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [7]);
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded::<usize>(1);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+    let senders = (0..THREADS).map(|_| s.clone()).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let n = r.recv().unwrap();
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for s in senders {
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+
+        let mut rng = thread_rng();
+        for _ in 0..200 {
+            s.set_capacity(rng.gen_range(1, 64));
+            thread::yield_now();
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = bounded(2);
+    s.send(DropCounter).unwrap();
+    s.send(DropCounter).unwrap();
+
+    assert!(s.set_capacity(5));
+    assert!(s.set_capacity(20));
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+
+    for _ in 0..3 {
+        s.send(DropCounter).unwrap();
+    }
+    drop(r.recv().unwrap());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 5);
+}
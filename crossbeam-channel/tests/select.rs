@@ -1210,6 +1210,250 @@ fn fairness2() {
     .unwrap();
 }
 
+#[test]
+fn biased() {
+    const COUNT: usize = 1000;
+
+    let (s1, r1) = unbounded::<()>();
+    let (s2, r2) = unbounded::<()>();
+    let (s3, r3) = unbounded::<()>();
+
+    for _ in 0..COUNT {
+        s2.send(()).unwrap();
+        s3.send(()).unwrap();
+    }
+
+    let mut sel = Select::biased();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+    let oper3 = sel.recv(&r3);
+
+    // The second operation always wins until its channel is empty.
+    for _ in 0..COUNT {
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper2);
+        oper.recv(&r2).unwrap();
+    }
+    assert_eq!(sel.ready(), oper3);
+
+    // Removing an operation doesn't change the order of the others.
+    s1.send(()).unwrap();
+    s2.send(()).unwrap();
+    sel.remove(oper1);
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.index(), oper2);
+    oper.recv(&r2).unwrap();
+
+    let oper = sel.select_timeout(ms(100)).unwrap();
+    assert_eq!(oper.index(), oper3);
+    oper.recv(&r3).unwrap();
+}
+
+#[test]
+fn weights() {
+    const COUNT: usize = 10_000;
+
+    let (s1, r1) = unbounded::<()>();
+    let (s2, r2) = unbounded::<()>();
+    let (s3, r3) = unbounded::<()>();
+
+    for _ in 0..COUNT {
+        s1.send(()).unwrap();
+        s2.send(()).unwrap();
+        s3.send(()).unwrap();
+    }
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+    let oper3 = sel.recv(&r3);
+    sel.set_weight(oper1, 3);
+    sel.set_weight(oper3, 0);
+
+    let mut hits = [0usize; 3];
+    for _ in 0..COUNT {
+        let oper = sel.select();
+        match oper.index() {
+            i if i == oper1 => {
+                oper.recv(&r1).unwrap();
+                hits[0] += 1;
+            }
+            i if i == oper2 => {
+                oper.recv(&r2).unwrap();
+                hits[1] += 1;
+            }
+            i if i == oper3 => {
+                oper.recv(&r3).unwrap();
+                hits[2] += 1;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // The first operation is chosen about three times as often as the second one, and the third
+    // one is never chosen while the others are ready.
+    assert!(hits[0] >= COUNT * 3 / 4 * 9 / 10);
+    assert!(hits[1] >= COUNT / 4 * 9 / 10);
+    assert_eq!(hits[2], 0);
+
+    // Once the other channels are empty, the third operation is chosen.
+    while r1.try_recv().is_ok() {}
+    while r2.try_recv().is_ok() {}
+    assert_eq!(sel.ready(), oper3);
+}
+
+#[test]
+#[should_panic(expected = "no operation with this index")]
+fn weight_of_removed() {
+    let (_s, r) = unbounded::<()>();
+
+    let mut sel = Select::new();
+    let oper = sel.recv(&r);
+    sel.remove(oper);
+    sel.set_weight(oper, 2);
+}
+
+#[test]
+fn owned() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(1);
+
+    let mut sel: Select<'static> = Select::new();
+    let oper1 = sel.recv_owned(r1.clone());
+    let oper2 = sel.send_owned(s2.clone());
+    drop(r1);
+
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.send_any(1), Ok(()));
+    assert!(sel.try_select().is_err());
+
+    s1.send(2).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv_any(), Ok(2));
+
+    // Removing an operation drops its handle.
+    sel.remove(oper2);
+    drop(s2);
+    assert_eq!(r2.recv(), Ok(1));
+    assert!(r2.recv().is_err());
+
+    drop(s1);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert!(oper.recv_any::<i32>().is_err());
+}
+
+#[test]
+fn recv_all() {
+    let (s, r) = unbounded::<usize>();
+    let mut senders = Vec::new();
+    let mut receivers = Vec::new();
+    for _ in 0..4 {
+        let (s, r) = unbounded::<usize>();
+        senders.push(s);
+        receivers.push(r);
+    }
+
+    let mut sel = Select::new();
+    let oper = sel.recv(&r);
+    let opers = sel.recv_all(&receivers);
+    assert_eq!(opers, 1..5);
+    let opers2 = sel.recv_all(receivers.iter().cloned());
+    assert_eq!(opers2, 5..9);
+
+    for (i, s) in senders.iter().enumerate() {
+        s.send(i).unwrap();
+        let oper = sel.select();
+        assert!(opers.contains(&oper.index()) || opers2.contains(&oper.index()));
+        let index = if opers.contains(&oper.index()) {
+            oper.index() - opers.start
+        } else {
+            oper.index() - opers2.start
+        };
+        assert_eq!(index, i);
+        assert_eq!(oper.recv_any(), Ok(i));
+    }
+
+    // Borrowed receivers added with `recv_all` can also be passed explicitly.
+    senders[3].send(3).unwrap();
+    sel.remove(opers2.start + 3);
+    let sel_oper = sel.select();
+    assert_eq!(sel_oper.index(), opers.start + 3);
+    assert_eq!(sel_oper.recv(&receivers[3]), Ok(3));
+
+    s.send(10).unwrap();
+    let sel_oper = sel.select();
+    assert_eq!(sel_oper.index(), oper);
+    assert_eq!(sel_oper.recv(&r), Ok(10));
+}
+
+#[test]
+fn owned_dynamic() {
+    const COUNT: usize = 100;
+
+    let mut receivers = Vec::new();
+    scope(|scope| {
+        for i in 0..4 {
+            let (s, r) = bounded::<usize>(0);
+            receivers.push(r);
+            scope.spawn(move |_| {
+                for j in 0..COUNT {
+                    s.send(i * COUNT + j).unwrap();
+                }
+            });
+        }
+
+        // The list of receivers shrinks as channels get disconnected.
+        let mut sum = 0;
+        let mut sel = Select::new();
+        let mut opers = sel.recv_all(receivers.iter().cloned());
+        while !receivers.is_empty() {
+            let oper = sel.select();
+            let index = oper.index() - opers.start;
+            match oper.recv_any::<usize>() {
+                Ok(msg) => sum += msg,
+                Err(_) => {
+                    receivers.remove(index);
+                    sel = Select::new();
+                    opers = sel.recv_all(receivers.iter().cloned());
+                }
+            }
+        }
+        assert_eq!(sum, (0..4 * COUNT).sum::<usize>());
+    })
+    .unwrap();
+}
+
+#[test]
+fn owned_clone() {
+    const THREADS: usize = 20;
+
+    let (s, r) = bounded::<usize>(0);
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv_owned(r.clone());
+    let oper2 = sel.send_owned(s.clone());
+    drop((s, r));
+
+    // Clones of the `Select` share the owned handles.
+    scope(|scope| {
+        for i in 0..THREADS {
+            let mut sel = sel.clone();
+            scope.spawn(move |_| {
+                let oper = sel.select();
+                match oper.index() {
+                    ix if ix == oper1 => assert_ne!(oper.recv_any(), Ok(i)),
+                    ix if ix == oper2 => assert!(oper.send_any(i).is_ok()),
+                    _ => unreachable!(),
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
 #[test]
 fn sync_and_clone() {
     const THREADS: usize = 20;
@@ -877,6 +877,57 @@ fn fairness2() {
     .unwrap();
 }
 
+#[test]
+fn biased() {
+    const COUNT: usize = 1000;
+
+    let (s1, r1) = unbounded::<()>();
+    let (s2, r2) = unbounded::<()>();
+
+    for _ in 0..COUNT {
+        s1.send(()).unwrap();
+        s2.send(()).unwrap();
+    }
+
+    let mut hits = [0usize; 2];
+    for _ in 0..COUNT * 2 {
+        select! {
+            biased;
+            recv(r1) -> _ => hits[0] += 1,
+            recv(r2) -> _ => hits[1] += 1,
+        }
+        if hits[0] < COUNT {
+            assert_eq!(hits[1], 0);
+        }
+    }
+    assert_eq!(hits, [COUNT, COUNT]);
+
+    s2.send(()).unwrap();
+    select! {
+        biased;
+        recv(r1) -> _ => panic!(),
+        recv(r2) -> res => assert_eq!(res, Ok(())),
+        default => panic!(),
+    }
+    select! {
+        biased;
+        recv(r1) -> _ => panic!(),
+        recv(r2) -> _ => panic!(),
+        default(ms(100)) => {}
+    }
+
+    select! {
+        biased;
+        recv(after(ms(100))) -> _ => {}
+    }
+    select! {
+        biased;
+        send(s1, ()) -> res => assert!(res.is_ok()),
+        recv(r1) -> _ => panic!(),
+    }
+    assert_eq!(r1.try_recv(), Ok(()));
+}
+
 #[test]
 fn fairness_recv() {
     const COUNT: usize = 10_000;
@@ -0,0 +1,274 @@
+//! Tests for long-lived selectors.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{after, bounded, tick, unbounded, ReceiverSet, Selector};
+use crossbeam_channel::{RecvError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+
+    let mut sel = Selector::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+    assert!(sel.try_wait().is_err());
+
+    s1.send(1).unwrap();
+    assert_eq!(sel.wait(), oper1);
+    assert_eq!(r1.try_recv(), Ok(1));
+
+    s2.send(2).unwrap();
+    assert_eq!(sel.wait(), oper2);
+    assert_eq!(r2.try_recv(), Ok(2));
+
+    assert!(sel.wait_timeout(ms(100)).is_err());
+}
+
+#[test]
+fn send() {
+    let (s, r) = bounded::<i32>(1);
+
+    let mut sel = Selector::new();
+    let oper = sel.send(&s);
+    assert_eq!(sel.wait(), oper);
+    s.try_send(1).unwrap();
+    assert!(sel.try_wait().is_err());
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            assert_eq!(r.recv(), Ok(1));
+        });
+
+        assert_eq!(sel.wait(), oper);
+        assert_eq!(s.try_send(2), Ok(()));
+    })
+    .unwrap();
+}
+
+#[test]
+fn remove() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+
+    let mut sel = Selector::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+
+    drop(s2);
+    assert_eq!(sel.wait(), oper2);
+    assert_eq!(r2.try_recv(), Err(TryRecvError::Disconnected));
+    sel.remove(oper2);
+    assert!(sel.try_wait().is_err());
+
+    s1.send(1).unwrap();
+    assert_eq!(sel.wait(), oper1);
+    sel.remove(oper1);
+    assert!(sel.try_wait().is_err());
+
+    // Indices of removed operations are not reused.
+    let oper3 = sel.recv(&r1);
+    assert_eq!(oper3, 2);
+    assert_eq!(sel.wait(), oper3);
+}
+
+#[test]
+#[should_panic(expected = "no operation with this index")]
+fn remove_twice() {
+    let (_s, r) = unbounded::<i32>();
+
+    let mut sel = Selector::new();
+    let oper = sel.recv(&r);
+    sel.remove(oper);
+    sel.remove(oper);
+}
+
+#[test]
+fn blocking() {
+    const COUNT: usize = 1000;
+
+    let (s1, r1) = unbounded();
+    let (s2, r2) = bounded(1);
+    let (s3, r3) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                match i % 3 {
+                    0 => s1.send(i).unwrap(),
+                    1 => s2.send(i).unwrap(),
+                    _ => s3.send(i).unwrap(),
+                }
+            }
+        });
+
+        let mut sel = Selector::new();
+        let oper1 = sel.recv(&r1);
+        let oper2 = sel.recv(&r2);
+        let oper3 = sel.recv(&r3);
+
+        let mut sum = 0;
+        let mut received = 0;
+        while received < COUNT {
+            let res = match sel.wait() {
+                i if i == oper1 => r1.try_recv(),
+                i if i == oper2 => r2.try_recv(),
+                i if i == oper3 => r3.try_recv(),
+                _ => unreachable!(),
+            };
+            if let Ok(msg) = res {
+                sum += msg;
+                received += 1;
+            }
+        }
+        assert_eq!(sum, (0..COUNT).sum::<usize>());
+    })
+    .unwrap();
+}
+
+#[test]
+fn fairness() {
+    const COUNT: usize = 10_000;
+
+    let (s1, r1) = unbounded::<()>();
+    let (s2, r2) = unbounded::<()>();
+    for _ in 0..COUNT {
+        s1.send(()).unwrap();
+        s2.send(()).unwrap();
+    }
+
+    let mut sel = Selector::new();
+    let oper1 = sel.recv(&r1);
+    sel.recv(&r2);
+
+    let mut hits = [0usize; 2];
+    for _ in 0..COUNT {
+        if sel.wait() == oper1 {
+            hits[0] += 1;
+        } else {
+            hits[1] += 1;
+        }
+    }
+    assert_eq!(hits, [COUNT / 2, COUNT / 2]);
+}
+
+#[test]
+fn timers() {
+    let start = Instant::now();
+    let r1 = after(ms(300));
+    let r2 = tick(ms(50));
+
+    let mut sel = Selector::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+
+    // The ticker keeps waking up the selector without being added again.
+    let mut ticks = 0;
+    loop {
+        match sel.wait() {
+            i if i == oper1 => break,
+            i if i == oper2 => {
+                if r2.try_recv().is_ok() {
+                    ticks += 1;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+    assert!(start.elapsed() >= ms(300));
+    assert!(ticks >= 4);
+}
+
+#[test]
+fn timeout() {
+    let (s, r) = unbounded::<i32>();
+
+    let mut sel = Selector::new();
+    let oper = sel.recv(&r);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(300));
+            s.send(1).unwrap();
+        });
+
+        let start = Instant::now();
+        assert!(sel.wait_timeout(ms(100)).is_err());
+        assert!(sel.wait_deadline(start + ms(200)).is_err());
+        assert!(start.elapsed() >= ms(200));
+        assert_eq!(sel.wait_timeout(ms(1000)), Ok(oper));
+    })
+    .unwrap();
+}
+
+#[test]
+fn empty() {
+    let mut sel = Selector::new();
+    assert!(sel.try_wait().is_err());
+
+    let start = Instant::now();
+    assert!(sel.wait_timeout(ms(100)).is_err());
+    assert!(start.elapsed() >= ms(100));
+}
+
+#[test]
+fn receiver_set() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+    let mut set = ReceiverSet::new();
+    set.insert(r1);
+    set.insert(r2);
+
+    let mut sel = Selector::new();
+    let oper = sel.recv_from(&set);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s1.send(1).unwrap();
+            drop(s1);
+            thread::sleep(ms(100));
+            s2.send(2).unwrap();
+            drop(s2);
+        });
+
+        // Disconnected receivers are dropped from the set while the selector is watching it.
+        let mut msgs = Vec::new();
+        loop {
+            assert_eq!(sel.wait(), oper);
+            match set.try_recv() {
+                Ok(msg) => msgs.push(msg),
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        assert_eq!(msgs, [1, 2]);
+        assert!(set.is_empty());
+    })
+    .unwrap();
+}
+
+#[test]
+fn drop_channels() {
+    let (s, r) = unbounded::<i32>();
+
+    let mut sel = Selector::new();
+    sel.recv(&r);
+    sel.send(&s);
+    drop(sel);
+
+    // The channel is destroyed without any operations left registered.
+    drop(s);
+    assert_eq!(r.recv(), Err(RecvError));
+}
@@ -0,0 +1,260 @@
+//! Tests for receiver sets.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{after, bounded, never, unbounded, ReceiverSet, Select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = bounded(1);
+    let mut set = ReceiverSet::new();
+    let k1 = set.insert(r1);
+    let k2 = set.insert(r2);
+
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(k1));
+    assert!(set.contains(k2));
+    assert_eq!(set.try_recv(), Err(TryRecvError::Empty));
+
+    s1.send(1).unwrap();
+    assert_eq!(set.try_recv(), Ok(1));
+    s2.send(2).unwrap();
+    assert_eq!(set.recv(), Ok(2));
+    assert_eq!(set.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+
+    let r1 = set.remove(k1).unwrap();
+    assert!(!set.contains(k1));
+    assert!(set.remove(k1).is_none());
+    s1.send(3).unwrap();
+    assert_eq!(set.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r1.try_recv(), Ok(3));
+}
+
+#[test]
+fn empty() {
+    let set = ReceiverSet::<i32>::new();
+    assert!(set.is_empty());
+    assert_eq!(set.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(set.recv(), Err(RecvError));
+    assert_eq!(
+        set.recv_timeout(ms(100)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
+
+#[test]
+fn round_robin() {
+    const COUNT: usize = 5;
+
+    let mut senders = Vec::new();
+    let mut set = ReceiverSet::new();
+    for _ in 0..COUNT {
+        let (s, r) = unbounded();
+        senders.push(s);
+        set.insert(r);
+    }
+
+    // Every channel is full of messages, yet each gets its turn.
+    for (i, s) in senders.iter().enumerate() {
+        for _ in 0..10 {
+            s.send(i).unwrap();
+        }
+    }
+    for _ in 0..10 {
+        let mut msgs = (0..COUNT).map(|_| set.recv().unwrap()).collect::<Vec<_>>();
+        msgs.sort();
+        assert_eq!(msgs, (0..COUNT).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn drops_disconnected() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let mut set = ReceiverSet::new();
+    let k1 = set.insert(r1);
+    let k2 = set.insert(r2);
+
+    s1.send(1).unwrap();
+    drop(s1);
+
+    // Pending messages are still received.
+    assert_eq!(set.try_recv(), Ok(1));
+    assert_eq!(set.try_recv(), Err(TryRecvError::Empty));
+    assert!(!set.contains(k1));
+    assert!(set.contains(k2));
+
+    drop(s2);
+    assert_eq!(set.recv(), Err(RecvError));
+    assert!(set.is_empty());
+}
+
+#[test]
+fn recv() {
+    let mut set = ReceiverSet::new();
+    let mut senders = Vec::new();
+    for _ in 0..10 {
+        let (s, r) = bounded(0);
+        senders.push(s);
+        set.insert(r);
+    }
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for (i, s) in senders.into_iter().enumerate().rev() {
+                thread::sleep(ms(10));
+                s.send(i).unwrap();
+            }
+        });
+
+        let mut msgs = Vec::new();
+        while let Ok(msg) = set.recv() {
+            msgs.push(msg);
+        }
+        assert_eq!(msgs, (0..10).rev().collect::<Vec<_>>());
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_timeout() {
+    let (s, r) = bounded(0);
+    let mut set = ReceiverSet::new();
+    set.insert(r);
+    set.insert(never());
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(200));
+            s.send(7).unwrap();
+        });
+
+        let start = Instant::now();
+        assert_eq!(set.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+        assert_eq!(set.recv_timeout(ms(1000)), Ok(7));
+        assert!(start.elapsed() >= ms(200));
+    })
+    .unwrap();
+}
+
+#[test]
+fn timers() {
+    let start = Instant::now();
+    let mut set = ReceiverSet::new();
+    set.insert(after(ms(200)));
+    set.insert(after(ms(100)));
+
+    let fired = set.recv().unwrap();
+    assert!(fired >= start + ms(100));
+    assert!(fired < start + ms(200));
+    assert!(set.recv().unwrap() >= start + ms(200));
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(0);
+    let (s3, r3) = unbounded::<i32>();
+    let mut set = ReceiverSet::new();
+    set.insert(r1);
+    set.insert(r2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s2.send(2).unwrap();
+            thread::sleep(ms(100));
+            s3.send(3).unwrap();
+            thread::sleep(ms(100));
+            s1.send(1).unwrap();
+        });
+
+        let mut sel = Select::new();
+        let oper1 = sel.recv_from(&set);
+        let oper2 = sel.recv(&r3);
+
+        for &(index, msg) in &[(oper1, 2), (oper2, 3), (oper1, 1)] {
+            let oper = sel.select();
+            assert_eq!(oper.index(), index);
+            if index == oper1 {
+                assert_eq!(oper.recv_from(&set), Ok(msg));
+            } else {
+                assert_eq!(oper.recv(&r3), Ok(msg));
+            }
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn select_macro() {
+    let (s1, r1) = unbounded::<i32>();
+    let (_s2, r2) = unbounded::<i32>();
+    let mut set = ReceiverSet::new();
+    set.insert(r1);
+
+    select! {
+        recv_from(set) -> _ => panic!(),
+        recv(r2) -> _ => panic!(),
+        default(ms(100)) => {}
+    }
+
+    s1.send(1).unwrap();
+    select! {
+        recv_from(set) -> msg => assert_eq!(msg, Ok(1)),
+        recv(r2) -> _ => panic!(),
+    }
+
+    drop(s1);
+    select! {
+        recv_from(set) -> msg => assert_eq!(msg, Err(RecvError)),
+        recv(r2) -> _ => panic!(),
+    }
+    assert!(set.is_empty());
+}
+
+#[test]
+fn stress_mixed() {
+    const SENDERS: usize = 100;
+    const COUNT: usize = 100;
+
+    let mut set = ReceiverSet::new();
+    let mut senders = Vec::new();
+    for i in 0..SENDERS {
+        let (s, r) = if i % 2 == 0 { bounded(0) } else { unbounded() };
+        senders.push(s);
+        set.insert(r);
+    }
+
+    scope(|scope| {
+        for s in senders {
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+
+        let mut sum = 0;
+        let mut received = 0;
+        while let Ok(msg) = set.recv() {
+            sum += msg;
+            received += 1;
+        }
+        assert_eq!(received, SENDERS * COUNT);
+        assert_eq!(sum, SENDERS * (0..COUNT).sum::<usize>());
+    })
+    .unwrap();
+}
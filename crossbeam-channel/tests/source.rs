@@ -0,0 +1,299 @@
+//! Tests for user-defined sources.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{unbounded, Notifier, Select, Selector, Source, SourceReceiver};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// A flag that is ready once raised.
+#[derive(Default)]
+struct Flag {
+    raised: AtomicBool,
+    notifier: Notifier,
+}
+
+impl Flag {
+    fn raise(&self) {
+        self.raised.store(true, Ordering::SeqCst);
+        self.notifier.notify();
+    }
+}
+
+impl Source for Flag {
+    type Msg = ();
+
+    fn try_recv(&self) -> Result<(), TryRecvError> {
+        if self.is_ready() {
+            Ok(())
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        self.raised.load(Ordering::SeqCst)
+    }
+
+    fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+}
+
+/// A queue of messages that can be closed.
+#[derive(Default)]
+struct Queue {
+    state: Mutex<(VecDeque<i32>, bool)>,
+    notifier: Notifier,
+}
+
+impl Queue {
+    fn push(&self, msg: i32) {
+        self.state.lock().unwrap().0.push_back(msg);
+        self.notifier.notify();
+    }
+
+    fn close(&self) {
+        self.state.lock().unwrap().1 = true;
+        self.notifier.notify();
+    }
+}
+
+impl Source for Queue {
+    type Msg = i32;
+
+    fn try_recv(&self) -> Result<i32, TryRecvError> {
+        let mut state = self.state.lock().unwrap();
+        match state.0.pop_front() {
+            Some(msg) => Ok(msg),
+            None if state.1 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        !state.0.is_empty() || state.1
+    }
+
+    fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+}
+
+/// A source that becomes ready at a fixed instant.
+struct Alarm {
+    when: Instant,
+    notifier: Notifier,
+}
+
+impl Source for Alarm {
+    type Msg = Instant;
+
+    fn try_recv(&self) -> Result<Instant, TryRecvError> {
+        if self.is_ready() {
+            Ok(self.when)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        Instant::now() >= self.when
+    }
+
+    fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        Some(self.when)
+    }
+}
+
+#[test]
+fn smoke() {
+    let r = SourceReceiver::new(Flag::default());
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+
+    r.get_ref().raise();
+    assert_eq!(r.try_recv(), Ok(()));
+    assert_eq!(r.recv(), Ok(()));
+    assert!(r.into_inner().is_ready());
+}
+
+#[test]
+fn recv() {
+    let queue = Arc::new(Queue::default());
+    let r = SourceReceiver::new(queue.clone());
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            queue.push(7);
+            thread::sleep(ms(100));
+            queue.push(8);
+            thread::sleep(ms(100));
+            queue.close();
+        });
+
+        assert_eq!(r.recv(), Ok(7));
+        assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+        assert_eq!(r.recv_timeout(ms(1000)), Ok(8));
+        assert_eq!(r.recv(), Err(RecvError));
+        assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    })
+    .unwrap();
+}
+
+#[test]
+fn deadline() {
+    let start = Instant::now();
+    let r = SourceReceiver::new(Alarm {
+        when: start + ms(100),
+        notifier: Notifier::new(),
+    });
+
+    // Nobody notifies the alarm, so the deadline alone has to wake up the thread.
+    assert_eq!(r.recv(), Ok(start + ms(100)));
+    assert!(start.elapsed() >= ms(100));
+}
+
+#[test]
+fn select_macro() {
+    let flag = Arc::new(Flag::default());
+    let shutdown = SourceReceiver::new(flag.clone());
+    let (s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            s.send(1).unwrap();
+            thread::sleep(ms(100));
+            flag.raise();
+        });
+
+        let mut msgs = Vec::new();
+        loop {
+            select! {
+                recv(r) -> msg => msgs.push(msg.unwrap()),
+                recv_from(shutdown) -> msg => {
+                    assert_eq!(msg, Ok(()));
+                    break;
+                }
+            }
+        }
+        assert_eq!(msgs, [1]);
+    })
+    .unwrap();
+}
+
+#[test]
+fn select() {
+    let queue = Arc::new(Queue::default());
+    let r1 = SourceReceiver::new(queue.clone());
+    let (s2, r2) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            queue.push(1);
+            thread::sleep(ms(100));
+            s2.send(2).unwrap();
+            thread::sleep(ms(100));
+            queue.close();
+        });
+
+        let mut sel = Select::new();
+        let oper1 = sel.recv_from(&r1);
+        let oper2 = sel.recv(&r2);
+
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv_from(&r1), Ok(1));
+
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper2);
+        assert_eq!(oper.recv(&r2), Ok(2));
+
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv_from(&r1), Err(RecvError));
+    })
+    .unwrap();
+}
+
+#[test]
+fn selector() {
+    let flag = Arc::new(Flag::default());
+    let r = SourceReceiver::new(flag.clone());
+
+    let mut sel = Selector::new();
+    let oper = sel.recv_from(&r);
+    assert!(sel.try_wait().is_err());
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            flag.raise();
+        });
+
+        assert_eq!(sel.wait(), oper);
+        assert_eq!(r.try_recv(), Ok(()));
+    })
+    .unwrap();
+}
+
+#[test]
+fn stress() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 1000;
+
+    let queue = Arc::new(Queue::default());
+    let r = SourceReceiver::new(queue.clone());
+
+    scope(|scope| {
+        let senders = (0..THREADS)
+            .map(|_| {
+                scope.spawn(|_| {
+                    for i in 0..COUNT {
+                        queue.push(i as i32);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let receivers = (0..THREADS)
+            .map(|_| {
+                scope.spawn(|_| {
+                    let mut received = 0;
+                    while r.recv().is_ok() {
+                        received += 1;
+                    }
+                    received
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for h in senders {
+            h.join().unwrap();
+        }
+        queue.close();
+
+        let total: usize = receivers.into_iter().map(|h| h.join().unwrap()).sum();
+        assert_eq!(total, THREADS * COUNT);
+    })
+    .unwrap();
+}
@@ -0,0 +1,231 @@
+//! Tests for tick channels configured with a builder.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{after, bounded, tick, TryRecvError};
+use crossbeam_channel::{Clock, MissedTicks, Select, TickBuilder};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+#[test]
+fn default_policy() {
+    assert_eq!(MissedTicks::default(), MissedTicks::Delay);
+
+    let clock = Clock::manual();
+    let start = clock.now();
+    let r = TickBuilder::new(secs(1)).clock(&clock).build();
+
+    clock.advance(secs(1));
+    assert_eq!(r.try_recv(), Ok(start + secs(1)));
+
+    clock.advance(ms(3500));
+    assert_eq!(r.try_recv(), Ok(start + secs(2)));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    // The next tick is a full period after the late one was received.
+    clock.advance(ms(999));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    clock.advance(ms(1));
+    assert_eq!(r.try_recv(), Ok(start + ms(5500)));
+}
+
+#[test]
+fn burst() {
+    let clock = Clock::manual();
+    let start = clock.now();
+    let r = TickBuilder::new(secs(1))
+        .missed_ticks(MissedTicks::Burst)
+        .clock(&clock)
+        .build();
+
+    clock.advance(ms(3500));
+    for i in 1..4 {
+        assert_eq!(r.try_recv(), Ok(start + secs(i)));
+    }
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    clock.advance(ms(500));
+    assert_eq!(r.try_recv(), Ok(start + secs(4)));
+}
+
+#[test]
+fn skip() {
+    let clock = Clock::manual();
+    let start = clock.now();
+    let r = TickBuilder::new(secs(1))
+        .missed_ticks(MissedTicks::Skip)
+        .clock(&clock)
+        .build();
+
+    clock.advance(ms(3500));
+    assert_eq!(r.try_recv(), Ok(start + secs(1)));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    // The next tick stays on the original schedule.
+    clock.advance(ms(500));
+    assert_eq!(r.try_recv(), Ok(start + secs(4)));
+
+    // A tick that is exactly on time doesn't skip the next one.
+    clock.advance(secs(1));
+    assert_eq!(r.try_recv(), Ok(start + secs(5)));
+    clock.advance(secs(1));
+    assert_eq!(r.try_recv(), Ok(start + secs(6)));
+}
+
+#[test]
+fn initial_delay() {
+    let clock = Clock::manual();
+    let start = clock.now();
+    let r = TickBuilder::new(secs(10))
+        .initial_delay(secs(1))
+        .clock(&clock)
+        .build();
+
+    clock.advance(secs(1));
+    assert_eq!(r.try_recv(), Ok(start + secs(1)));
+
+    clock.advance(secs(9));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    clock.advance(secs(1));
+    assert_eq!(r.try_recv(), Ok(start + secs(11)));
+
+    // A zero delay delivers the first tick immediately.
+    let r = TickBuilder::new(secs(10))
+        .initial_delay(secs(0))
+        .clock(&clock)
+        .build();
+    assert_eq!(r.try_recv(), Ok(clock.now()));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn system_clock() {
+    let start = Instant::now();
+    let r = TickBuilder::new(ms(100)).initial_delay(ms(0)).build();
+
+    assert!(r.recv().unwrap() < start + ms(50));
+    assert!(r.recv().unwrap() >= start + ms(100));
+    assert!(start.elapsed() >= ms(100));
+}
+
+#[test]
+fn reset() {
+    let clock = Clock::manual();
+    let start = clock.now();
+    let r = TickBuilder::new(secs(10)).clock(&clock).build();
+
+    clock.advance(secs(10));
+    assert_eq!(r.len(), 1);
+
+    // A tick that is already due is discarded.
+    assert!(r.reset());
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    clock.advance(secs(10));
+    assert_eq!(r.try_recv(), Ok(start + secs(20)));
+}
+
+#[test]
+fn set_period() {
+    let clock = Clock::manual();
+    let start = clock.now();
+    let r = TickBuilder::new(secs(1)).clock(&clock).build();
+
+    // A longer period doesn't delay a tick that is already scheduled.
+    assert!(r.set_period(secs(10)));
+    clock.advance(secs(1));
+    assert_eq!(r.try_recv(), Ok(start + secs(1)));
+    clock.advance(secs(9));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    clock.advance(secs(1));
+    assert_eq!(r.try_recv(), Ok(start + secs(11)));
+
+    // A shorter period brings the next tick forward.
+    assert!(r.set_period(secs(2)));
+    clock.advance(secs(2));
+    assert_eq!(r.try_recv(), Ok(start + secs(13)));
+}
+
+#[test]
+fn set_period_wakes_receiver() {
+    let r = tick(secs(3600));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let start = Instant::now();
+            assert!(r.recv().is_ok());
+            assert!(start.elapsed() < secs(10));
+        });
+
+        thread::sleep(ms(500));
+        assert!(r.set_period(ms(100)));
+    })
+    .unwrap();
+}
+
+#[test]
+fn reset_wakes_select() {
+    let clock = Clock::manual();
+    let r = TickBuilder::new(secs(10)).clock(&clock).build();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let mut sel = Select::new();
+            sel.recv(&r);
+            let oper = sel.select();
+            assert!(oper.recv(&r).is_ok());
+        });
+
+        // The blocked selection sees the new schedule after a reset.
+        thread::sleep(ms(200));
+        clock.advance(secs(9));
+        assert!(r.reset());
+        thread::sleep(ms(200));
+        clock.advance(secs(9));
+        thread::sleep(ms(200));
+        clock.advance(secs(1));
+    })
+    .unwrap();
+}
+
+#[test]
+fn select_macro() {
+    let clock = Clock::manual();
+    let r = TickBuilder::new(secs(1))
+        .missed_ticks(MissedTicks::Burst)
+        .clock(&clock)
+        .build();
+
+    clock.advance(secs(3));
+    let mut ticks = 0;
+    loop {
+        select! {
+            recv(r) -> _ => ticks += 1,
+            default => break,
+        }
+    }
+    assert_eq!(ticks, 3);
+}
+
+#[test]
+fn unsupported_flavors() {
+    let (_s, r) = bounded::<i32>(1);
+    assert!(!r.reset());
+    assert!(!r.set_period(secs(1)));
+
+    let r = after(secs(1));
+    assert!(!r.reset());
+    assert!(!r.set_period(secs(1)));
+}
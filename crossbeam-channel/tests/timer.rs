@@ -0,0 +1,258 @@
+//! Tests for the timer channel flavor.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate rand;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{timer, unbounded, RecvTimeoutError, Select, TryRecvError};
+use crossbeam_utils::thread::scope;
+use rand::{thread_rng, Rng};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn fire() {
+    let start = Instant::now();
+    let (_t, r) = timer(ms(50));
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    thread::sleep(ms(100));
+
+    let fired = r.try_recv().unwrap();
+    assert!(fired - start >= ms(50));
+    assert!(fired < Instant::now());
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn recv() {
+    let start = Instant::now();
+    let (_t, r) = timer(ms(50));
+
+    let fired = r.recv().unwrap();
+    assert!(fired - start >= ms(50));
+    assert!(start.elapsed() >= ms(50));
+    assert!(start.elapsed() < ms(1000));
+}
+
+#[test]
+fn len_empty_full() {
+    let (t, r) = timer(ms(50));
+
+    assert_eq!(r.capacity(), Some(1));
+    assert_eq!(r.len(), 0);
+    assert_eq!(r.is_empty(), true);
+    assert_eq!(r.is_full(), false);
+
+    thread::sleep(ms(100));
+
+    assert_eq!(r.len(), 1);
+    assert_eq!(r.is_empty(), false);
+    assert_eq!(r.is_full(), true);
+    assert_eq!(t.remaining(), Some(ms(0)));
+
+    r.try_recv().unwrap();
+
+    assert_eq!(r.len(), 0);
+    assert_eq!(r.is_empty(), true);
+    assert_eq!(t.remaining(), None);
+}
+
+#[test]
+fn reset() {
+    let start = Instant::now();
+    let (t, r) = timer(ms(100));
+
+    for _ in 0..5 {
+        thread::sleep(ms(50));
+        t.reset(ms(100));
+    }
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    let fired = r.recv().unwrap();
+    assert!(fired - start >= ms(350));
+
+    // An expired timer can be rearmed.
+    t.reset(ms(50));
+    assert!(t.remaining().unwrap() <= ms(50));
+    assert!(r.recv().unwrap() - fired >= ms(50));
+}
+
+#[test]
+fn reset_discards_message() {
+    let (t, r) = timer(ms(10));
+    thread::sleep(ms(50));
+    assert_eq!(r.len(), 1);
+
+    t.reset(ms(1000));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert!(t.remaining().unwrap() > ms(500));
+}
+
+#[test]
+fn reset_wakes_receiver() {
+    let (t, r) = timer(Duration::from_secs(3600));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let start = Instant::now();
+            assert!(r.recv().is_ok());
+            assert!(start.elapsed() >= ms(150));
+            assert!(start.elapsed() < ms(2000));
+        });
+
+        thread::sleep(ms(100));
+        t.reset(ms(100));
+    })
+    .unwrap();
+}
+
+#[test]
+fn cancel() {
+    let (t, r) = timer(ms(50));
+    t.cancel();
+
+    assert_eq!(t.remaining(), None);
+    assert_eq!(r.recv_timeout(ms(200)), Err(RecvTimeoutError::Timeout));
+
+    // A cancelled timer can be rearmed.
+    t.reset(ms(50));
+    assert!(r.recv_timeout(ms(1000)).is_ok());
+
+    // Cancelling discards a message that has been delivered.
+    t.reset(ms(0));
+    thread::sleep(ms(10));
+    t.cancel();
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn dropped_receiver() {
+    let (t, r) = timer(ms(50));
+    let r2 = r.clone();
+    assert_eq!(r.receiver_count(), 2);
+    assert!(r.same_channel(&r2));
+
+    drop(r);
+    assert!(t.remaining().is_some());
+
+    drop(r2);
+    assert_eq!(t.remaining(), None);
+    t.reset(ms(10));
+    assert_eq!(t.remaining(), None);
+}
+
+#[test]
+fn select() {
+    let (s, r) = unbounded::<i32>();
+    let (t, timeout) = timer(ms(500));
+    let hits = AtomicUsize::new(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..5 {
+                thread::sleep(ms(100));
+                s.send(i).unwrap();
+            }
+        });
+
+        let start = Instant::now();
+        loop {
+            let mut sel = Select::new();
+            let oper1 = sel.recv(&r);
+            let oper2 = sel.recv(&timeout);
+            let oper = sel.select();
+            match oper.index() {
+                i if i == oper1 => {
+                    oper.recv(&r).unwrap();
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    t.reset(ms(300));
+                }
+                i if i == oper2 => {
+                    oper.recv(&timeout).unwrap();
+                    break;
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        assert!(start.elapsed() >= ms(800));
+    })
+    .unwrap();
+
+    assert_eq!(hits.load(Ordering::SeqCst), 5);
+}
+
+#[test]
+fn select_macro() {
+    let (_s, r) = unbounded::<i32>();
+    let (_t, timeout) = timer(ms(100));
+
+    let start = Instant::now();
+    select! {
+        recv(r) -> _ => panic!(),
+        recv(timeout) -> msg => assert!(msg.is_ok()),
+    }
+    assert!(start.elapsed() >= ms(100));
+}
+
+#[test]
+fn many_timers() {
+    const COUNT: usize = 1000;
+
+    let mut rng = thread_rng();
+    let timers = (0..COUNT)
+        .map(|_| timer(ms(rng.gen_range(0, 300))))
+        .collect::<Vec<_>>();
+
+    // Cancel every third timer and postpone every other one.
+    for (i, &(ref t, _)) in timers.iter().enumerate() {
+        match i % 3 {
+            0 => t.cancel(),
+            1 => t.reset(ms(rng.gen_range(0, 300))),
+            _ => {}
+        }
+    }
+
+    let start = Instant::now();
+    for (i, &(_, ref r)) in timers.iter().enumerate() {
+        if i % 3 == 0 {
+            assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+        } else {
+            assert!(r.recv().is_ok());
+        }
+    }
+    assert!(start.elapsed() < ms(2000));
+}
+
+#[test]
+fn stress_reset() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 1000;
+
+    let (t, r) = timer(ms(100));
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    t.reset(ms(50));
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert!(r.recv_timeout(ms(1000)).is_ok());
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
@@ -0,0 +1,300 @@
+//! Tests for the watch channel flavor.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{unbounded, watch, Select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = watch(1);
+    assert_eq!(*r.borrow().unwrap(), 1);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert!(r.is_empty());
+
+    s.send(2).unwrap();
+    assert_eq!(r.len(), 1);
+    assert!(r.is_full());
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(*r.borrow().unwrap(), 2);
+}
+
+#[test]
+fn capacity() {
+    let (s, r) = watch(0);
+    assert_eq!(s.capacity(), Some(1));
+    assert_eq!(r.capacity(), Some(1));
+    assert!(!s.set_capacity(2));
+}
+
+#[test]
+fn len() {
+    let (s, r1) = watch(0);
+    let r2 = r1.clone();
+    assert_eq!(s.len(), 0);
+    assert!(s.is_empty());
+    assert!(r1.is_empty());
+
+    s.send(1).unwrap();
+    assert_eq!(s.len(), 1);
+    assert!(s.is_full());
+    assert_eq!(r1.len(), 1);
+
+    assert_eq!(r1.recv(), Ok(1));
+    assert_eq!(s.len(), 0);
+    assert!(s.is_empty());
+    assert!(r1.is_empty());
+    assert_eq!(r2.len(), 1);
+
+    r2.changed().unwrap();
+    assert!(s.is_empty());
+    assert!(r2.is_empty());
+}
+
+#[test]
+fn latest_value() {
+    let (s, r) = watch(0);
+    for i in 1..10 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.recv(), Ok(9));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    assert_eq!(s.force_send(10), Ok(None));
+    assert_eq!(r.recv(), Ok(10));
+}
+
+#[test]
+fn independent_receivers() {
+    let (s, r1) = watch("a");
+    s.send("b").unwrap();
+    let r2 = r1.clone();
+
+    assert_eq!(r1.try_recv(), Ok("b"));
+    assert_eq!(r2.try_recv(), Ok("b"));
+
+    let r3 = r1.clone();
+    assert_eq!(r3.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(*r3.borrow().unwrap(), "b");
+
+    s.send("c").unwrap();
+    assert_eq!(r1.try_recv(), Ok("c"));
+    assert_eq!(r2.try_recv(), Ok("c"));
+    assert_eq!(r3.try_recv(), Ok("c"));
+}
+
+#[test]
+fn changed() {
+    let (s, r) = watch(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.send(1).unwrap();
+        });
+        assert_eq!(r.changed(), Ok(()));
+        assert_eq!(*r.borrow().unwrap(), 1);
+        assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    })
+    .unwrap();
+
+    drop(s);
+    assert_eq!(r.changed(), Err(RecvError));
+}
+
+#[test]
+fn changed_other_flavors() {
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.send(1).unwrap();
+        });
+        assert_eq!(r.changed(), Ok(()));
+        assert_eq!(r.len(), 1);
+    })
+    .unwrap();
+
+    drop(s);
+    assert_eq!(r.changed(), Ok(()));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.changed(), Err(RecvError));
+    assert!(r.borrow().is_none());
+}
+
+#[test]
+fn recv_timeout() {
+    let (s, r) = watch(0);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            s.send(1).unwrap();
+        });
+        assert_eq!(r.recv_timeout(ms(500)), Err(RecvTimeoutError::Timeout));
+        assert_eq!(r.recv_timeout(ms(1000)), Ok(1));
+        assert_eq!(
+            r.recv_timeout(ms(1000)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    })
+    .unwrap();
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = watch(0);
+    s.send(1).unwrap();
+    drop(s);
+    assert!(r.is_disconnected());
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(*r.borrow().unwrap(), 1);
+
+    let (s, r) = watch(0);
+    drop(r);
+    assert!(s.is_disconnected());
+    assert_eq!(s.send(1), Err(SendError(1)));
+}
+
+#[test]
+fn peek() {
+    let (s, r) = watch(0);
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Empty));
+
+    s.send(5).unwrap();
+    assert_eq!(r.peek_with(|x| *x), Ok(5));
+    assert_eq!(r.try_recv_if(|x| *x == 4), Ok(None));
+    assert_eq!(r.len(), 1);
+    assert_eq!(r.try_recv_if(|x| *x == 5), Ok(Some(5)));
+    assert_eq!(r.peek_with(|x| *x), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn borrow_blocks_senders() {
+    let (s, r) = watch(0);
+
+    scope(|scope| {
+        let value = r.borrow().unwrap();
+        scope.spawn(|_| {
+            let start = Instant::now();
+            s.send(1).unwrap();
+            assert!(start.elapsed() >= ms(200));
+        });
+        thread::sleep(ms(500));
+        assert_eq!(*value, 0);
+    })
+    .unwrap();
+
+    assert_eq!(r.recv(), Ok(1));
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = watch(0);
+    let (s2, r2) = watch(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s2.send(2).unwrap();
+        });
+
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r1);
+        let oper2 = sel.recv(&r2);
+        let oper = sel.select();
+        match oper.index() {
+            i if i == oper1 => panic!(),
+            i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(2)),
+            _ => unreachable!(),
+        }
+    })
+    .unwrap();
+
+    select! {
+        send(s1, 1) -> res => assert_eq!(res, Ok(())),
+        recv(r2) -> _ => panic!(),
+    }
+    select! {
+        recv(r1) -> msg => assert_eq!(msg, Ok(1)),
+        recv(r2) -> _ => panic!(),
+        default(ms(1000)) => panic!(),
+    }
+}
+
+#[test]
+fn stress() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = watch(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let r = r.clone();
+            scope.spawn(move |_| {
+                let mut last = 0;
+                while let Ok(i) = r.recv() {
+                    assert!(i > last);
+                    last = i;
+                }
+                assert_eq!(last, COUNT);
+            });
+        }
+        drop(r);
+
+        for i in 1..COUNT + 1 {
+            s.send(i).unwrap();
+        }
+        drop(s);
+    })
+    .unwrap();
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Clone for DropCounter {
+        fn clone(&self) -> DropCounter {
+            DropCounter
+        }
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = watch(DropCounter);
+    for i in 0..5 {
+        s.send(DropCounter).unwrap();
+        assert_eq!(DROPS.load(Ordering::SeqCst), i + 1);
+    }
+
+    drop(r.recv().unwrap());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 6);
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 7);
+}
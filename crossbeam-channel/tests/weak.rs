@@ -0,0 +1,421 @@
+//! Tests for weak senders.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::RecvError;
+use crossbeam_channel::{bounded, broadcast, priority_unbounded, unbounded};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn zero_smoke() {
+    let (s, r) = bounded(0);
+    let w = s.downgrade();
+    assert_eq!(s.sender_count(), 1);
+
+    let s2 = w.upgrade().unwrap();
+    assert_eq!(s.sender_count(), 2);
+    assert!(s2.same_channel(&s));
+
+    scope(|scope| {
+        scope.spawn(move |_| s2.send(1).unwrap());
+        assert_eq!(r.recv(), Ok(1));
+    })
+    .unwrap();
+}
+
+#[test]
+fn array_smoke() {
+    let (s, r) = bounded::<i32>(10);
+    let w = s.downgrade();
+    assert_eq!(s.sender_count(), 1);
+
+    let s2 = w.upgrade().unwrap();
+    assert_eq!(s.sender_count(), 2);
+    assert!(s2.same_channel(&s));
+
+    s2.send(1).unwrap();
+    assert_eq!(r.recv(), Ok(1));
+}
+
+#[test]
+fn list_smoke() {
+    let (s, r) = unbounded::<i32>();
+    let w = s.downgrade();
+    assert_eq!(s.sender_count(), 1);
+
+    let s2 = w.upgrade().unwrap();
+    assert_eq!(s.sender_count(), 2);
+    assert!(s2.same_channel(&s));
+
+    s2.send(1).unwrap();
+    assert_eq!(r.recv(), Ok(1));
+}
+
+#[test]
+fn priority_smoke() {
+    let (s, r) = priority_unbounded::<i32>();
+    let w = s.downgrade();
+    assert_eq!(s.sender_count(), 1);
+
+    let s2 = w.upgrade().unwrap();
+    assert_eq!(s.sender_count(), 2);
+    assert!(s2.same_channel(&s));
+
+    s2.send(1).unwrap();
+    assert_eq!(r.recv(), Ok(1));
+}
+
+#[test]
+fn broadcast_smoke() {
+    let (s, r) = broadcast::<i32>(10);
+    let w = s.downgrade();
+    assert_eq!(s.sender_count(), 1);
+
+    let s2 = w.upgrade().unwrap();
+    assert_eq!(s.sender_count(), 2);
+    assert!(s2.same_channel(&s));
+
+    s2.send(1).unwrap();
+    assert_eq!(r.recv(), Ok(1));
+}
+
+#[test]
+fn zero_does_not_keep_channel_connected() {
+    let (s, r) = bounded::<i32>(0);
+    let w = s.downgrade();
+    let w2 = w.clone();
+
+    drop(s);
+    assert!(r.is_disconnected());
+    assert_eq!(r.recv(), Err(RecvError));
+
+    assert!(w.upgrade().is_none());
+    assert!(w2.upgrade().is_none());
+}
+
+#[test]
+fn array_does_not_keep_channel_connected() {
+    let (s, r) = bounded::<i32>(10);
+    let w = s.downgrade();
+    let w2 = w.clone();
+
+    drop(s);
+    assert!(r.is_disconnected());
+    assert_eq!(r.recv(), Err(RecvError));
+
+    assert!(w.upgrade().is_none());
+    assert!(w2.upgrade().is_none());
+}
+
+#[test]
+fn list_does_not_keep_channel_connected() {
+    let (s, r) = unbounded::<i32>();
+    let w = s.downgrade();
+    let w2 = w.clone();
+
+    drop(s);
+    assert!(r.is_disconnected());
+    assert_eq!(r.recv(), Err(RecvError));
+
+    assert!(w.upgrade().is_none());
+    assert!(w2.upgrade().is_none());
+}
+
+#[test]
+fn priority_does_not_keep_channel_connected() {
+    let (s, r) = priority_unbounded::<i32>();
+    let w = s.downgrade();
+    let w2 = w.clone();
+
+    drop(s);
+    assert!(r.is_disconnected());
+    assert_eq!(r.recv(), Err(RecvError));
+
+    assert!(w.upgrade().is_none());
+    assert!(w2.upgrade().is_none());
+}
+
+#[test]
+fn broadcast_does_not_keep_channel_connected() {
+    let (s, r) = broadcast::<i32>(10);
+    let w = s.downgrade();
+    let w2 = w.clone();
+
+    drop(s);
+    assert!(r.is_disconnected());
+    assert_eq!(r.recv(), Err(RecvError));
+
+    assert!(w.upgrade().is_none());
+    assert!(w2.upgrade().is_none());
+}
+
+#[test]
+fn zero_upgrade_after_receivers_dropped() {
+    let (s, r) = bounded::<i32>(0);
+    let w = s.downgrade();
+    drop(r);
+
+    assert!(w.upgrade().is_none());
+    assert_eq!(s.sender_count(), 1);
+}
+
+#[test]
+fn array_upgrade_after_receivers_dropped() {
+    let (s, r) = bounded::<i32>(10);
+    let w = s.downgrade();
+    drop(r);
+
+    assert!(w.upgrade().is_none());
+    assert_eq!(s.sender_count(), 1);
+}
+
+#[test]
+fn list_upgrade_after_receivers_dropped() {
+    let (s, r) = unbounded::<i32>();
+    let w = s.downgrade();
+    drop(r);
+
+    assert!(w.upgrade().is_none());
+    assert_eq!(s.sender_count(), 1);
+}
+
+#[test]
+fn priority_upgrade_after_receivers_dropped() {
+    let (s, r) = priority_unbounded::<i32>();
+    let w = s.downgrade();
+    drop(r);
+
+    assert!(w.upgrade().is_none());
+    assert_eq!(s.sender_count(), 1);
+}
+
+#[test]
+fn broadcast_upgrade_after_receivers_dropped() {
+    let (s, r) = broadcast::<i32>(10);
+    let w = s.downgrade();
+    drop(r);
+
+    assert!(w.upgrade().is_none());
+    assert_eq!(s.sender_count(), 1);
+}
+
+#[test]
+fn zero_upgrade_after_close() {
+    let (s, _r) = bounded::<i32>(0);
+    let w = s.downgrade();
+    s.close();
+
+    assert!(w.upgrade().is_none());
+    assert_eq!(s.sender_count(), 1);
+}
+
+#[test]
+fn array_upgrade_after_close() {
+    let (s, _r) = bounded::<i32>(10);
+    let w = s.downgrade();
+    s.close();
+
+    assert!(w.upgrade().is_none());
+    assert_eq!(s.sender_count(), 1);
+}
+
+#[test]
+fn list_upgrade_after_close() {
+    let (s, _r) = unbounded::<i32>();
+    let w = s.downgrade();
+    s.close();
+
+    assert!(w.upgrade().is_none());
+    assert_eq!(s.sender_count(), 1);
+}
+
+#[test]
+fn priority_upgrade_after_close() {
+    let (s, _r) = priority_unbounded::<i32>();
+    let w = s.downgrade();
+    s.close();
+
+    assert!(w.upgrade().is_none());
+    assert_eq!(s.sender_count(), 1);
+}
+
+#[test]
+fn broadcast_upgrade_after_close() {
+    let (s, _r) = broadcast::<i32>(10);
+    let w = s.downgrade();
+    s.close();
+
+    assert!(w.upgrade().is_none());
+    assert_eq!(s.sender_count(), 1);
+}
+
+#[test]
+fn zero_outlives_channel() {
+    let (s, r) = bounded::<i32>(0);
+    let w = s.downgrade();
+    drop(s);
+    drop(r);
+
+    assert!(w.upgrade().is_none());
+    let w2 = w.clone();
+    drop(w);
+    assert!(w2.upgrade().is_none());
+}
+
+#[test]
+fn array_outlives_channel() {
+    let (s, r) = bounded::<i32>(10);
+    let w = s.downgrade();
+    drop(s);
+    drop(r);
+
+    assert!(w.upgrade().is_none());
+    let w2 = w.clone();
+    drop(w);
+    assert!(w2.upgrade().is_none());
+}
+
+#[test]
+fn list_outlives_channel() {
+    let (s, r) = unbounded::<i32>();
+    let w = s.downgrade();
+    drop(s);
+    drop(r);
+
+    assert!(w.upgrade().is_none());
+    let w2 = w.clone();
+    drop(w);
+    assert!(w2.upgrade().is_none());
+}
+
+#[test]
+fn priority_outlives_channel() {
+    let (s, r) = priority_unbounded::<i32>();
+    let w = s.downgrade();
+    drop(s);
+    drop(r);
+
+    assert!(w.upgrade().is_none());
+    let w2 = w.clone();
+    drop(w);
+    assert!(w2.upgrade().is_none());
+}
+
+#[test]
+fn broadcast_outlives_channel() {
+    let (s, r) = broadcast::<i32>(10);
+    let w = s.downgrade();
+    drop(s);
+    drop(r);
+
+    assert!(w.upgrade().is_none());
+    let w2 = w.clone();
+    drop(w);
+    assert!(w2.upgrade().is_none());
+}
+
+#[test]
+fn upgraded_sender_keeps_channel_connected() {
+    let (s, r) = unbounded();
+    let w = s.downgrade();
+
+    let s2 = w.upgrade().unwrap();
+    drop(s);
+    assert!(!r.is_disconnected());
+
+    s2.send(1).unwrap();
+    drop(s2);
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn disconnect_wakes_receiver() {
+    let (s, r) = bounded::<()>(0);
+    let w = s.downgrade();
+
+    scope(|scope| {
+        scope.spawn(move |_| assert_eq!(r.recv(), Err(RecvError)));
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            drop(s);
+        });
+    })
+    .unwrap();
+
+    assert!(w.upgrade().is_none());
+}
+
+#[test]
+fn race() {
+    const COUNT: usize = 1000;
+    const THREADS: usize = 4;
+
+    for _ in 0..COUNT / 100 {
+        let (s, r) = unbounded::<usize>();
+        let w = s.downgrade();
+        let sent = AtomicUsize::new(0);
+
+        scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    for i in 0..COUNT {
+                        match w.upgrade() {
+                            Some(s) => {
+                                s.send(i).unwrap();
+                                sent.fetch_add(1, Ordering::SeqCst);
+                            }
+                            None => break,
+                        }
+                    }
+                });
+            }
+            scope.spawn(move |_| {
+                thread::sleep(ms(1));
+                drop(s);
+            });
+        })
+        .unwrap();
+
+        assert!(w.upgrade().is_none());
+        assert_eq!(r.try_iter().count(), sent.load(Ordering::SeqCst));
+    }
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = unbounded();
+    let w = s.downgrade();
+
+    for _ in 0..10 {
+        s.send(DropCounter).unwrap();
+    }
+    drop(s);
+    drop(r);
+
+    // Messages are dropped once the last reference of any kind is gone.
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+    drop(w);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 10);
+}
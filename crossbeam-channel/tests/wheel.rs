@@ -0,0 +1,166 @@
+//! Tests for timed channels and operations backed by the shared timer wheel.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate rand;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{after, bounded, tick, timer, RecvTimeoutError, Select};
+use crossbeam_utils::thread::scope;
+use rand::{thread_rng, Rng};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn cascade() {
+    // Durations around the boundaries between the levels of the wheel.
+    let durations = [1, 63, 64, 65, 127, 128, 300, 4095, 4096, 4100];
+
+    let start = Instant::now();
+    let rs = durations.iter().map(|&d| after(ms(d))).collect::<Vec<_>>();
+
+    for (r, &d) in rs.iter().zip(durations.iter()) {
+        let fired = r.recv().unwrap();
+        assert!(fired >= start + ms(d));
+        assert!(Instant::now() - fired < ms(500));
+    }
+}
+
+#[test]
+fn far_future() {
+    let r = after(Duration::from_secs(10 * 24 * 60 * 60));
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+
+    let (t, r) = timer(Duration::from_secs(10 * 24 * 60 * 60));
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+    t.reset(ms(50));
+    assert!(r.recv_timeout(ms(1000)).is_ok());
+}
+
+#[test]
+fn very_far_future() {
+    // Deadlines this far away overflow a count of nanoseconds.
+    let far = Duration::from_secs(1 << 40);
+
+    for _ in 0..10 {
+        let r1 = after(far);
+        let r2 = after(ms(10));
+        select! {
+            recv(r1) -> _ => panic!(),
+            recv(r2) -> msg => assert!(msg.is_ok()),
+        }
+    }
+
+    let r = after(far);
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+    assert!(after(ms(10)).recv_timeout(ms(1000)).is_ok());
+}
+
+#[test]
+fn select_many() {
+    const COUNT: u64 = 10_000;
+
+    let start = Instant::now();
+    let rs = (0..COUNT)
+        .map(|i| after(ms(if i < 3 { 100 } else { 1000 + i % 500 })))
+        .collect::<Vec<_>>();
+
+    let mut sel = Select::new();
+    for r in &rs {
+        sel.recv(r);
+    }
+
+    // The earliest timers fire first.
+    for _ in 0..3 {
+        let oper = sel.select();
+        let index = oper.index();
+        let fired = oper.recv(&rs[index]).unwrap();
+        assert!(index < 3);
+        assert!(fired >= start + ms(100));
+        assert!(fired < start + ms(1000));
+        sel.remove(index);
+    }
+}
+
+#[test]
+fn many_timeouts() {
+    const THREADS: usize = 50;
+    const COUNT: usize = 20;
+
+    let (_s, r) = bounded::<i32>(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                let mut rng = thread_rng();
+                for _ in 0..COUNT {
+                    let timeout = ms(rng.gen_range(0, 20));
+                    let start = Instant::now();
+                    assert_eq!(r.recv_timeout(timeout), Err(RecvTimeoutError::Timeout));
+                    assert!(start.elapsed() >= timeout);
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn cancel_many() {
+    const COUNT: usize = 10_000;
+
+    // Every blocked receive arms a timer, which is cancelled when the channel is dropped.
+    let start = Instant::now();
+    for _ in 0..COUNT {
+        let r = after(Duration::from_secs(3600));
+        assert_eq!(r.recv_timeout(ms(0)), Err(RecvTimeoutError::Timeout));
+    }
+    for _ in 0..100 {
+        let r = after(Duration::from_secs(3600));
+        assert_eq!(r.recv_timeout(ms(1)), Err(RecvTimeoutError::Timeout));
+    }
+    assert!(start.elapsed() < ms(5000));
+
+    // The wheel still works afterwards.
+    assert!(after(ms(10)).recv_timeout(ms(1000)).is_ok());
+}
+
+#[test]
+fn tick_many_receivers() {
+    const THREADS: usize = 10;
+
+    let r = tick(ms(20));
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..5 {
+                    assert!(r.recv_timeout(ms(5000)).is_ok());
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn sleep_accuracy() {
+    let mut rng = thread_rng();
+
+    for _ in 0..20 {
+        let dur = ms(rng.gen_range(1, 100));
+        let start = Instant::now();
+        let r = after(dur);
+        thread::yield_now();
+
+        let fired = r.recv().unwrap();
+        assert!(fired >= start + dur);
+        assert!(start.elapsed() >= dur);
+        assert!(start.elapsed() < dur + ms(500));
+    }
+}